@@ -0,0 +1,66 @@
+// 在其他程序中嵌入更新引擎的示例
+//
+// 运行：
+//   CF_API_TOKEN=... CF_ZONE_ID=... CF_ROOT_DOMAIN=example.com CF_SUBDOMAINS=home,nas \
+//     cargo run --example embed
+//
+// 示例会在 ./embed-data 中保存配置，立即检查一次，打印各域名的活动事件，
+// 并在 http://127.0.0.1:3001 提供Web管理界面，按 Ctrl+C 退出。
+
+use cloudflare_auto::services::cloudflare::CloudflareConfig;
+use cloudflare_auto::{CloudflareAutoBuilder, NotificationChannel};
+use std::env;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt().with_env_filter("info").init();
+
+    let mut builder = CloudflareAutoBuilder::new()
+        .data_dir("embed-data")
+        .cloudflare(CloudflareConfig {
+            api_key: env::var("CF_API_TOKEN")?,
+            zone_id: env::var("CF_ZONE_ID")?,
+            root_domain: env::var("CF_ROOT_DOMAIN")?,
+            auth_type: Default::default(),
+            auth_email: None,
+            timeouts: Default::default(),
+            proxy_url: env::var("CF_PROXY_URL").ok(),
+            ca_cert_path: env::var("CF_CA_CERT").ok(),
+        })
+        .subdomains(env::var("CF_SUBDOMAINS").unwrap_or_default().split(',').map(str::trim).filter(|s| !s.is_empty()))
+        .check_interval(Duration::from_secs(300))
+        .burst(Duration::from_secs(30), Duration::from_secs(600))
+        // 宿主程序自己执行首次检查
+        .startup_check(false, Duration::ZERO)
+        .web("127.0.0.1:3001".parse()?);
+    if let Ok(url) = env::var("STATUS_WEBHOOK_URL") {
+        builder = builder.notification(NotificationChannel::StatusWebhook(url));
+    }
+    let engine = builder.build().await?;
+
+    // 各域名的同步、确认、警告和错误事件
+    let mut events = engine.events();
+    tokio::spawn(async move {
+        while let Ok(event) = events.recv().await {
+            println!("[{:?}] {}: {}", event.kind, event.domain, event.message);
+        }
+    });
+
+    // 在后台运行定时检查和Web服务，Ctrl+C 时停止
+    let runner = engine.clone();
+    let running = tokio::spawn(async move { runner.run().await });
+    let stopper = engine.clone();
+    tokio::spawn(async move {
+        let _ = tokio::signal::ctrl_c().await;
+        stopper.shutdown();
+    });
+
+    match engine.trigger().await {
+        Ok(updated) => println!("首次检查完成，更新了记录: {}", updated),
+        Err(e) => eprintln!("首次检查失败: {}", e),
+    }
+    println!("监控状态: {:?}", engine.status().await?);
+
+    running.await?
+}
@@ -0,0 +1,102 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderMap, StatusCode},
+    Json,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::warn;
+use super::handlers::ApiResponse;
+use super::state::AppState;
+use crate::services::api_tokens::{TokenDenial, TOKEN_PREFIX};
+
+type Rejection = (StatusCode, Json<ApiResponse<()>>);
+
+fn reject(status: StatusCode, message: &str) -> Rejection {
+    (status, Json(ApiResponse::<()> {
+        success: false,
+        data: None,
+        message: Some(message.to_string()),
+    }))
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// 以常量时间比较密钥：先对两边计算HMAC-SHA256摘要，再由 verify_slice 比较，
+/// 耗时与密钥内容和长度都无关。所有令牌和密码的校验都应使用它
+pub fn secret_matches(provided: Option<&str>, expected: &str) -> bool {
+    let digest = |value: &str| {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(b"cloudflare-auto").expect("HMAC接受任意长度的密钥");
+        mac.update(value.as_bytes());
+        mac
+    };
+    let Some(provided) = provided else {
+        return false;
+    };
+    let expected = digest(expected).finalize().into_bytes();
+    digest(provided).verify_slice(&expected).is_ok()
+}
+
+/// 校验管理员令牌，令牌来自环境变量 ADMIN_TOKEN，未设置时管理接口一律拒绝
+pub fn check_admin(headers: &HeaderMap) -> Result<(), Rejection> {
+    let expected = match std::env::var("ADMIN_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => return Err(reject(StatusCode::FORBIDDEN, "管理接口未启用，请设置 ADMIN_TOKEN 环境变量")),
+    };
+
+    if secret_matches(bearer(headers), &expected) {
+        Ok(())
+    } else {
+        warn!("🔒 管理接口鉴权失败");
+        Err(reject(StatusCode::UNAUTHORIZED, "管理员令牌无效"))
+    }
+}
+
+/// 管理接口的鉴权：接受 ADMIN_TOKEN，或范围包含本次请求且未过期的API令牌
+pub struct AdminAuth;
+
+#[async_trait]
+impl FromRequestParts<AppState> for AdminAuth {
+    type Rejection = Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let token = match bearer(&parts.headers) {
+            Some(token) if token.starts_with(TOKEN_PREFIX) => token,
+            _ => return check_admin(&parts.headers).map(|_| AdminAuth),
+        };
+
+        // 嵌套路由中的路径已去掉 /api/v1（或 /api）前缀
+        let method = parts.method.as_str();
+        let path = parts.uri.path();
+        match state.config_service.authorize_api_token(token, method, path) {
+            Ok(_) => Ok(AdminAuth),
+            Err(denial) => {
+                warn!("🔒 API令牌鉴权失败: {} {} ({:?})", method, path, denial);
+                Err(match denial {
+                    TokenDenial::Unknown => reject(StatusCode::UNAUTHORIZED, "API令牌无效"),
+                    TokenDenial::Expired => reject(StatusCode::UNAUTHORIZED, "API令牌已过期"),
+                    TokenDenial::OutOfScope => reject(StatusCode::FORBIDDEN, "API令牌无权调用该接口"),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_matches() {
+        assert!(secret_matches(Some("s3cret"), "s3cret"));
+        assert!(!secret_matches(Some("s3cre"), "s3cret"));
+        assert!(!secret_matches(Some("s3cret "), "s3cret"));
+        assert!(!secret_matches(None, "s3cret"));
+    }
+}
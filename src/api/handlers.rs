@@ -1,16 +1,90 @@
-use axum::{extract::State, Json, response::IntoResponse};
+use axum::{extract::{ConnectInfo, Path, Query, State}, http::{header, HeaderMap, StatusCode}, Json, response::{IntoResponse, Response}};
+use base64::Engine;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr};
 use tracing::{info, warn, error};
-use crate::services::config_service::ConfigService;
-use crate::config::database::{Database, DnsUpdateRecord};
+use super::auth::{check_admin, secret_matches, AdminAuth};
+use crate::services::config_service::{normalize_subdomain, CheckRun, ConfigService, DomainDiff, DomainStatus, IpConsistency, ReconcileState, UpdatePlan, UpdateReport, UpdateSummary, SOFT_DELETE_RETENTION_DAYS};
+use crate::services::cloudflare::{self, CloudflareConfig, ConnectionReport, HttpTimeouts, DnsRecord, PatchDnsRecordRequest, UpdateDnsRecordRequest};
+use crate::services::fault_injection::{FaultKind, FaultStatus};
+use crate::services::flapping::FlapState;
+use crate::services::domain_settings::ImportReport;
+use crate::services::drift::DriftReport;
+use crate::services::dyndns::{self, ReturnCode};
+use crate::services::acme::{self, AcmeRequest};
+use crate::services::backup::{BackupService, BackupSettings};
+use crate::services::backup_hostname;
+use crate::services::bark;
+use crate::services::chatops::{self, ChatCommand};
+use crate::services::dingtalk;
+use crate::services::discord;
+use crate::services::gotify;
+use crate::services::metrics;
+use crate::services::mqtt;
+use crate::services::monitor_service::{MonitorHandle, MonitorStatus};
+use crate::services::notify_webhook::{self, MAX_NOTIFY_WEBHOOKS};
+use crate::services::ntfy;
+use crate::services::operations;
+use crate::services::pushplus;
+use crate::services::serverchan;
+use crate::services::slack;
+use crate::services::status::ServiceStatus;
+use crate::services::telegram;
+use crate::services::wecom;
+use crate::config::database::{default_burst_duration, ApiToken, BackupHostname, BarkSettings, DingTalkSettings, GotifySettings, MqttSettings, NotifyWebhook, NtfySettings, SlackSettings, TelegramSettings, ChangeDetection, IpSource, default_flap_threshold, default_flap_window_hours, default_update_parallelism, default_http_connect_timeout, default_http_request_timeout, default_delegated_prefix_len, AppConfig, AuthType, DnsUpdateRecord, DomainState, LanHost, Operation, PauseState, RecordOwnership, StatusEvent, TxtRecordChange};
+use crate::utils::debug_capture::{self, CaptureStatus};
+use crate::utils::address_selection::AddressSelection;
+use crate::utils::network::{self, NetworkInterface, PrefixRule};
+use crate::utils::{ip_command, prefix_delegation, router_ipv4, stun};
+use crate::utils::time_format::{default_timezone, parse_timezone, DEFAULT_LOCALE};
+use crate::utils::i18n::{self, DEFAULT_LANGUAGE};
 
 #[derive(Debug, Deserialize)]
 pub struct TestConfigRequest {
     pub api_key: String,
     pub zone_id: String,
     pub root_domain: String,
+    #[serde(default)]
+    pub auth_type: AuthType,
+    #[serde(default)]
+    pub auth_email: Option<String>,
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
 }
 
+impl TestConfigRequest {
+    fn to_cloudflare_config(&self) -> CloudflareConfig {
+        CloudflareConfig {
+            api_key: self.api_key.clone(),
+            zone_id: self.zone_id.clone(),
+            root_domain: self.root_domain.clone(),
+            auth_type: self.auth_type,
+            auth_email: self.auth_email.clone(),
+            timeouts: HttpTimeouts::default(),
+            proxy_url: self.proxy_url.clone().filter(|url| !url.trim().is_empty()),
+            ca_cert_path: self.ca_cert_path.clone().filter(|path| !path.trim().is_empty()),
+        }
+    }
+}
+
+/// 突发检查允许的最小间隔（秒）
+const MIN_BURST_INTERVAL: u64 = 10;
+/// 同时处理的域名数上限，避免触发Cloudflare的请求频率限制
+const MAX_UPDATE_PARALLELISM: u32 = 20;
+/// 连接超时和请求总超时的上限（秒）
+const MAX_HTTP_CONNECT_TIMEOUT: u64 = 60;
+const MAX_HTTP_REQUEST_TIMEOUT: u64 = 300;
+/// 外部IP检测服务和STUN服务器列表的长度上限，每个服务失败时都要等待超时
+const MAX_EXTERNAL_IP_SERVICES: usize = 10;
+/// 配置状态接口无需鉴权，返回前隐藏这些密钥（JSON Pointer），只附带 <字段>_configured 表示是否已设置
+const REDACTED_SECRETS: &[&str] = &["/telegram/bot_token", "/dingtalk/secret", "/serverchan_key", "/pushplus_token", "/mqtt/password"];
+/// 页面在已设置的密钥输入框中显示的占位符，保存时原样回传表示沿用已保存的值
+const HIDDEN_SECRET: &str = "********";
+
 #[derive(Debug, Deserialize)]
 pub struct SaveConfigRequest {
     pub api_key: String,
@@ -18,6 +92,403 @@ pub struct SaveConfigRequest {
     pub root_domain: String,
     pub selected_subdomains: Vec<String>,
     pub check_interval: u64,
+    #[serde(default)]
+    pub auth_type: AuthType,
+    #[serde(default)]
+    pub auth_email: Option<String>,
+    // 以下为可选设置，未提供时沿用已保存的值
+    #[serde(default)]
+    pub display_timezone: Option<String>,
+    #[serde(default)]
+    pub display_locale: Option<String>,
+    #[serde(default)]
+    pub startup_check: Option<bool>,
+    #[serde(default)]
+    pub startup_delay: Option<u64>,
+    #[serde(default)]
+    pub verify_external_ip: Option<bool>,
+    #[serde(default)]
+    pub prefix_rules: Option<Vec<PrefixRule>>,
+    #[serde(default)]
+    pub status_webhook_url: Option<String>,
+    #[serde(default)]
+    pub domain_proxied: Option<BTreeMap<String, bool>>,
+    #[serde(default)]
+    pub uptime_kuma_push_url: Option<String>,
+    #[serde(default)]
+    pub record_comment: Option<String>,
+    #[serde(default)]
+    pub cleanup_duplicates: Option<bool>,
+    #[serde(default)]
+    pub burst_interval: Option<u64>,
+    #[serde(default)]
+    pub burst_duration: Option<u64>,
+    #[serde(default)]
+    pub round_robin: Option<bool>,
+    #[serde(default)]
+    pub ownership_enabled: Option<bool>,
+    #[serde(default)]
+    pub proxied_default: Option<bool>,
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    #[serde(default)]
+    pub https_records: Option<bool>,
+    #[serde(default)]
+    pub flap_threshold: Option<u32>,
+    #[serde(default)]
+    pub flap_window_hours: Option<u64>,
+    #[serde(default)]
+    pub reconcile_interval: Option<u64>,
+    #[serde(default)]
+    pub ui_language: Option<String>,
+    #[serde(default)]
+    pub verify_propagation: Option<bool>,
+    #[serde(default)]
+    pub change_detection: Option<ChangeDetection>,
+    #[serde(default)]
+    pub backup_hostname: Option<BackupHostname>,
+    #[serde(default)]
+    pub update_parallelism: Option<u32>,
+    #[serde(default)]
+    pub http_connect_timeout: Option<u64>,
+    #[serde(default)]
+    pub http_request_timeout: Option<u64>,
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    #[serde(default)]
+    pub ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub interface: Option<String>,
+    #[serde(default)]
+    pub address_selection: Option<AddressSelection>,
+    #[serde(default)]
+    pub ip_source: Option<IpSource>,
+    #[serde(default)]
+    pub external_ip_services: Option<Vec<String>>,
+    #[serde(default)]
+    pub stun_servers: Option<Vec<String>>,
+    #[serde(default)]
+    pub delegated_prefix_len: Option<u8>,
+    #[serde(default)]
+    pub multi_address: Option<bool>,
+    #[serde(default)]
+    pub allowed_prefix: Option<String>,
+    #[serde(default)]
+    pub mqtt: Option<MqttSettings>,
+    #[serde(default)]
+    pub telegram: Option<TelegramSettings>,
+    #[serde(default)]
+    pub discord_webhook_url: Option<String>,
+    #[serde(default)]
+    pub slack: Option<SlackSettings>,
+    #[serde(default)]
+    pub notify_webhooks: Option<Vec<NotifyWebhook>>,
+    #[serde(default)]
+    pub ntfy: Option<NtfySettings>,
+    #[serde(default)]
+    pub gotify: Option<GotifySettings>,
+    #[serde(default)]
+    pub wecom_webhook_url: Option<String>,
+    #[serde(default)]
+    pub dingtalk: Option<DingTalkSettings>,
+    #[serde(default)]
+    pub bark: Option<BarkSettings>,
+    #[serde(default)]
+    pub serverchan_key: Option<String>,
+    #[serde(default)]
+    pub pushplus_token: Option<String>,
+}
+
+impl SaveConfigRequest {
+    /// 把页面回传的密钥占位符换回已保存的值，之后的校验和合并按正常输入处理
+    fn restore_hidden_secrets(&mut self, existing: Option<&AppConfig>) {
+        if let Some(telegram) = self.telegram.as_mut().filter(|t| t.bot_token == HIDDEN_SECRET) {
+            telegram.bot_token = existing
+                .and_then(|c| c.telegram.as_ref())
+                .map(|t| t.bot_token.clone())
+                .unwrap_or_default();
+        }
+        if let Some(dingtalk) = self.dingtalk.as_mut().filter(|d| d.secret.as_deref() == Some(HIDDEN_SECRET)) {
+            dingtalk.secret = existing.and_then(|c| c.dingtalk.as_ref()).and_then(|d| d.secret.clone());
+        }
+        if let Some(mqtt) = self.mqtt.as_mut().filter(|m| m.password.as_deref() == Some(HIDDEN_SECRET)) {
+            mqtt.password = existing.and_then(|c| c.mqtt.as_ref()).and_then(|m| m.password.clone());
+        }
+        if self.serverchan_key.as_deref() == Some(HIDDEN_SECRET) {
+            self.serverchan_key = None;
+        }
+        if self.pushplus_token.as_deref() == Some(HIDDEN_SECRET) {
+            self.pushplus_token = None;
+        }
+    }
+
+    fn into_app_config(self, existing: Option<AppConfig>) -> AppConfig {
+        let display_timezone = self.display_timezone
+            .or_else(|| existing.as_ref().map(|c| c.display_timezone.clone()))
+            .unwrap_or_else(default_timezone);
+        let display_locale = self.display_locale
+            .or_else(|| existing.as_ref().map(|c| c.display_locale.clone()))
+            .unwrap_or_else(|| DEFAULT_LOCALE.to_string());
+        let startup_check = self.startup_check
+            .or_else(|| existing.as_ref().map(|c| c.startup_check))
+            .unwrap_or(true);
+        let startup_delay = self.startup_delay
+            .or_else(|| existing.as_ref().map(|c| c.startup_delay))
+            .unwrap_or(0);
+        let verify_external_ip = self.verify_external_ip
+            .or_else(|| existing.as_ref().map(|c| c.verify_external_ip))
+            .unwrap_or(false);
+        let prefix_rules = self.prefix_rules
+            .or_else(|| existing.as_ref().map(|c| c.prefix_rules.clone()))
+            .unwrap_or_default();
+        // 传入空字符串表示关闭推送
+        let status_webhook_url = optional_text(
+            self.status_webhook_url,
+            existing.as_ref().and_then(|c| c.status_webhook_url.clone()),
+        );
+        let uptime_kuma_push_url = optional_text(
+            self.uptime_kuma_push_url,
+            existing.as_ref().and_then(|c| c.uptime_kuma_push_url.clone()),
+        );
+        let record_comment = optional_text(
+            self.record_comment,
+            existing.as_ref().and_then(|c| c.record_comment.clone()),
+        );
+        let cleanup_duplicates = self.cleanup_duplicates
+            .or_else(|| existing.as_ref().map(|c| c.cleanup_duplicates))
+            .unwrap_or(false);
+        let burst_interval = self.burst_interval
+            .or_else(|| existing.as_ref().map(|c| c.burst_interval))
+            .unwrap_or(0);
+        let burst_duration = self.burst_duration
+            .or_else(|| existing.as_ref().map(|c| c.burst_duration))
+            .unwrap_or_else(default_burst_duration);
+        let round_robin = self.round_robin
+            .or_else(|| existing.as_ref().map(|c| c.round_robin))
+            .unwrap_or(false);
+        let ownership_enabled = self.ownership_enabled
+            .or_else(|| existing.as_ref().map(|c| c.ownership_enabled))
+            .unwrap_or(false);
+        let proxied_default = self.proxied_default
+            .or_else(|| existing.as_ref().map(|c| c.proxied_default))
+            .unwrap_or(false);
+        let dry_run = self.dry_run
+            .or_else(|| existing.as_ref().map(|c| c.dry_run))
+            .unwrap_or(false);
+        let https_records = self.https_records
+            .or_else(|| existing.as_ref().map(|c| c.https_records))
+            .unwrap_or(false);
+        let flap_threshold = self.flap_threshold
+            .or_else(|| existing.as_ref().map(|c| c.flap_threshold))
+            .unwrap_or_else(default_flap_threshold);
+        let flap_window_hours = self.flap_window_hours
+            .or_else(|| existing.as_ref().map(|c| c.flap_window_hours))
+            .unwrap_or_else(default_flap_window_hours);
+        let reconcile_interval = self.reconcile_interval
+            .or_else(|| existing.as_ref().map(|c| c.reconcile_interval))
+            .unwrap_or(0);
+        let ui_language = self.ui_language
+            .or_else(|| existing.as_ref().map(|c| c.ui_language.clone()))
+            .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+        let verify_propagation = self.verify_propagation
+            .or_else(|| existing.as_ref().map(|c| c.verify_propagation))
+            .unwrap_or(false);
+        let change_detection = self.change_detection
+            .or_else(|| existing.as_ref().map(|c| c.change_detection))
+            .unwrap_or_default();
+        // 传入的域名为空表示不再发布备用主机名
+        let backup_hostname = match self.backup_hostname {
+            Some(backup) => Some(backup).filter(|b| !b.name.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.backup_hostname.clone()),
+        };
+        let update_parallelism = self.update_parallelism
+            .or_else(|| existing.as_ref().map(|c| c.update_parallelism))
+            .unwrap_or_else(default_update_parallelism);
+        let http_connect_timeout = self.http_connect_timeout
+            .or_else(|| existing.as_ref().map(|c| c.http_connect_timeout))
+            .unwrap_or_else(default_http_connect_timeout);
+        let http_request_timeout = self.http_request_timeout
+            .or_else(|| existing.as_ref().map(|c| c.http_request_timeout))
+            .unwrap_or_else(default_http_request_timeout);
+        let proxy_url = optional_text(
+            self.proxy_url,
+            existing.as_ref().and_then(|c| c.proxy_url.clone()),
+        );
+        let ca_cert_path = optional_text(
+            self.ca_cert_path,
+            existing.as_ref().and_then(|c| c.ca_cert_path.clone()),
+        );
+        // 传入空字符串表示不再限定网卡
+        let interface = optional_text(
+            self.interface,
+            existing.as_ref().and_then(|c| c.interface.clone()),
+        );
+        let address_selection = self.address_selection
+            .or_else(|| existing.as_ref().map(|c| c.address_selection.clone()))
+            .unwrap_or_default();
+        let ip_source = self.ip_source
+            .or_else(|| existing.as_ref().map(|c| c.ip_source))
+            .unwrap_or_default();
+        let external_ip_services = self.external_ip_services
+            .or_else(|| existing.as_ref().map(|c| c.external_ip_services.clone()))
+            .unwrap_or_default();
+        let stun_servers = self.stun_servers
+            .or_else(|| existing.as_ref().map(|c| c.stun_servers.clone()))
+            .unwrap_or_default();
+        let delegated_prefix_len = self.delegated_prefix_len
+            .or_else(|| existing.as_ref().map(|c| c.delegated_prefix_len))
+            .unwrap_or_else(default_delegated_prefix_len);
+        let multi_address = self.multi_address
+            .or_else(|| existing.as_ref().map(|c| c.multi_address))
+            .unwrap_or(false);
+        // 传入空字符串表示不再限制前缀
+        let allowed_prefix = optional_text(
+            self.allowed_prefix,
+            existing.as_ref().and_then(|c| c.allowed_prefix.clone()),
+        );
+        // 传入的代理地址为空表示不再发布到MQTT
+        let mqtt = match self.mqtt {
+            Some(mqtt) => Some(mqtt).filter(|m| !m.broker.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.mqtt.clone()),
+        };
+        // 传入的机器人令牌为空表示关闭Telegram通知
+        let telegram = match self.telegram {
+            Some(telegram) => Some(telegram).filter(|v| !v.bot_token.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.telegram.clone()),
+        };
+        let discord_webhook_url = optional_text(
+            self.discord_webhook_url,
+            existing.as_ref().and_then(|c| c.discord_webhook_url.clone()),
+        );
+        // 传入的Webhook地址为空表示关闭Slack通知
+        let slack = match self.slack {
+            Some(slack) => Some(slack).filter(|v| !v.webhook_url.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.slack.clone()),
+        };
+        let notify_webhooks = self.notify_webhooks
+            .or_else(|| existing.as_ref().map(|c| c.notify_webhooks.clone()))
+            .unwrap_or_default();
+        // 传入的主题为空表示关闭ntfy推送
+        let ntfy = match self.ntfy {
+            Some(ntfy) => Some(ntfy).filter(|v| !v.topic.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.ntfy.clone()),
+        };
+        // 传入的服务器地址为空表示关闭Gotify推送
+        let gotify = match self.gotify {
+            Some(gotify) => Some(gotify).filter(|v| !v.server.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.gotify.clone()),
+        };
+        let wecom_webhook_url = optional_text(
+            self.wecom_webhook_url,
+            existing.as_ref().and_then(|c| c.wecom_webhook_url.clone()),
+        );
+        // 传入的Webhook地址为空表示关闭钉钉通知
+        let dingtalk = match self.dingtalk {
+            Some(dingtalk) => Some(dingtalk).filter(|v| !v.webhook_url.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.dingtalk.clone()),
+        };
+        // 传入的设备Key为空表示关闭Bark推送
+        let bark = match self.bark {
+            Some(bark) => Some(bark).filter(|v| !v.device_key.trim().is_empty()),
+            None => existing.as_ref().and_then(|c| c.bark.clone()),
+        };
+        let serverchan_key = optional_text(
+            self.serverchan_key,
+            existing.as_ref().and_then(|c| c.serverchan_key.clone()),
+        );
+        let pushplus_token = optional_text(
+            self.pushplus_token,
+            existing.as_ref().and_then(|c| c.pushplus_token.clone()),
+        );
+        let domain_proxied = self.domain_proxied
+            .or_else(|| existing.as_ref().map(|c| c.domain_proxied.clone()))
+            .unwrap_or_default();
+
+        AppConfig {
+            cloudflare_api_key: self.api_key,
+            cloudflare_zone_id: self.zone_id,
+            root_domain: self.root_domain,
+            selected_subdomains: self.selected_subdomains,
+            check_interval: self.check_interval,
+            last_ip: None,
+            auth_type: self.auth_type,
+            auth_email: self.auth_email.filter(|email| !email.is_empty()),
+            display_timezone,
+            display_locale,
+            startup_check,
+            startup_delay,
+            verify_external_ip,
+            prefix_rules,
+            status_webhook_url,
+            domain_proxied,
+            uptime_kuma_push_url,
+            record_comment,
+            cleanup_duplicates,
+            burst_interval,
+            burst_duration,
+            round_robin,
+            ownership_enabled,
+            proxied_default,
+            dry_run,
+            https_records,
+            flap_threshold,
+            flap_window_hours,
+            reconcile_interval,
+            ui_language,
+            verify_propagation,
+            change_detection,
+            backup_hostname,
+            update_parallelism,
+            http_connect_timeout,
+            http_request_timeout,
+            proxy_url,
+            ca_cert_path,
+            interface,
+            address_selection,
+            ip_source,
+            external_ip_services,
+            stun_servers,
+            delegated_prefix_len,
+            host_suffixes: existing.as_ref().map(|c| c.host_suffixes.clone()).unwrap_or_default(),
+            multi_address,
+            allowed_prefix,
+            mqtt,
+            telegram,
+            discord_webhook_url,
+            slack,
+            notify_webhooks,
+            ntfy,
+            gotify,
+            wecom_webhook_url,
+            dingtalk,
+            bark,
+            serverchan_key,
+            pushplus_token,
+        }
+    }
+}
+
+/// 合并可选的文本配置：未传入时沿用已有配置，传入空字符串表示清除
+fn optional_text(value: Option<String>, existing: Option<String>) -> Option<String> {
+    match value {
+        Some(url) => Some(url.trim().to_string()).filter(|url| !url.is_empty()),
+        None => existing,
+    }
+}
+
+/// 序列化配置并隐藏 REDACTED_SECRETS 中的密钥
+fn redacted_config(config: &AppConfig) -> serde_json::Value {
+    let mut value = serde_json::to_value(config).unwrap();
+    for pointer in REDACTED_SECRETS {
+        let (parent, key) = pointer.rsplit_once('/').unwrap();
+        let Some(serde_json::Value::Object(object)) = value.pointer_mut(parent) else {
+            continue;
+        };
+        let configured = object.get(key).is_some_and(|v| !v.is_null());
+        object.insert(key.to_string(), serde_json::Value::Null);
+        object.insert(format!("{}_configured", key), serde_json::Value::Bool(configured));
+    }
+    value
 }
 
 #[derive(Debug, Serialize)]
@@ -37,6 +508,10 @@ pub struct DomainListResponse {
 pub struct ConfigStatus {
     pub configured: bool,
     pub current_config: Option<serde_json::Value>,
+    pub effective_check_interval: Option<u64>, // 当前生效的检查间隔（突发检查期间小于配置值）
+    pub dry_run: bool, // 是否处于演练模式（配置项或环境变量 DRY_RUN）
+    pub pause: Option<PauseState>, // 定时检查的暂停状态，未暂停时为null
+    pub flapping: Option<FlapState>, // IP抖动状态，未抖动时为null
 }
 
 pub async fn test_config(
@@ -45,26 +520,32 @@ pub async fn test_config(
 ) -> impl IntoResponse {
     info!("🧪 收到配置测试请求，域名: {}", payload.root_domain);
     
-    match service.test_config(&payload.api_key, &payload.zone_id, &payload.root_domain).await {
-        Ok(true) => {
+    match service.test_config(payload.to_cloudflare_config()).await {
+        Ok(report) if report.success => {
             info!("✅ 配置测试成功，域名: {}", payload.root_domain);
-            Json(ApiResponse::<()> {
+            let message = if report.messages.is_empty() {
+                "配置测试成功".to_string()
+            } else {
+                format!("配置测试成功（{}）", report.messages.join("；"))
+            };
+            Json(ApiResponse {
                 success: true,
-                data: None,
-                message: Some("配置测试成功".to_string()),
+                data: Some(report),
+                message: Some(message),
             })
         },
-        Ok(false) => {
-            warn!("⚠️ 配置测试失败，域名: {}", payload.root_domain);
-            Json(ApiResponse::<()> {
+        Ok(report) => {
+            warn!("⚠️ 配置测试失败，域名: {} - {:?}", payload.root_domain, report.messages);
+            let message = format!("配置测试失败: {}", report.messages.join("；"));
+            Json(ApiResponse {
                 success: false,
-                data: None,
-                message: Some("配置测试失败".to_string()),
+                data: Some(report),
+                message: Some(message),
             })
         },
         Err(e) => {
             error!("❌ 配置测试错误，域名: {} - {}", payload.root_domain, e);
-            Json(ApiResponse::<()> {
+            Json(ApiResponse::<ConnectionReport> {
                 success: false,
                 data: None,
                 message: Some(format!("配置测试错误: {}", e)),
@@ -77,9 +558,9 @@ pub async fn get_domain_list(
     State(service): State<ConfigService>,
     Json(payload): Json<TestConfigRequest>,
 ) -> impl IntoResponse {
-    match service.get_domain_list(&payload.api_key, &payload.zone_id, &payload.root_domain).await {
+    match service.get_domain_list(payload.to_cloudflare_config()).await {
         Ok(domains) => {
-            let current_ip = service.get_current_ipv6().ok();
+            let current_ip = service.get_current_ipv6().await.ok();
             Json(ApiResponse {
                 success: true,
                 data: Some(DomainListResponse { domains, current_ip }),
@@ -94,23 +575,173 @@ pub async fn get_domain_list(
     }
 }
 
+/// 校验配置保存请求，返回第一个不合法的设置；existing 和 lan_hosts 用于需要结合已有配置判断的项
+fn validate(payload: &SaveConfigRequest, existing: Option<&AppConfig>, lan_hosts: &[LanHost]) -> Result<(), String> {
+    if let Some(tz) = &payload.display_timezone {
+        parse_timezone(tz).map_err(|e| e.to_string())?;
+    }
+    if let Some(lang) = &payload.ui_language {
+        i18n::normalize_language(lang).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(settings) = payload.mqtt.as_ref().filter(|m| !m.broker.trim().is_empty()) {
+        mqtt::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(url) = payload.discord_webhook_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        discord::validate(url).map_err(|e| e.to_string())?;
+    }
+    if let Some(webhooks) = &payload.notify_webhooks {
+        if webhooks.len() > MAX_NOTIFY_WEBHOOKS {
+            return Err(format!("事件Webhook最多 {} 个", MAX_NOTIFY_WEBHOOKS));
+        }
+        for webhook in webhooks {
+            notify_webhook::validate(webhook).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(settings) = payload.ntfy.as_ref().filter(|n| !n.topic.trim().is_empty()) {
+        ntfy::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(settings) = payload.gotify.as_ref().filter(|g| !g.server.trim().is_empty()) {
+        gotify::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(url) = payload.wecom_webhook_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        wecom::validate(url).map_err(|e| e.to_string())?;
+    }
+    if let Some(settings) = payload.dingtalk.as_ref().filter(|d| !d.webhook_url.trim().is_empty()) {
+        dingtalk::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(settings) = payload.bark.as_ref().filter(|b| !b.device_key.trim().is_empty()) {
+        bark::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(key) = payload.serverchan_key.as_deref().map(str::trim).filter(|k| !k.is_empty()) {
+        serverchan::validate(key).map_err(|e| e.to_string())?;
+    }
+    if let Some(token) = payload.pushplus_token.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        pushplus::validate(token).map_err(|e| e.to_string())?;
+    }
+    if let Some(settings) = payload.slack.as_ref().filter(|s| !s.webhook_url.trim().is_empty()) {
+        slack::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(settings) = payload.telegram.as_ref().filter(|t| !t.bot_token.trim().is_empty()) {
+        telegram::validate(settings).map_err(|e| e.to_string())?;
+    }
+    if let Some(backup) = payload.backup_hostname.as_ref().filter(|b| !b.name.trim().is_empty()) {
+        backup_hostname::validate(backup).map_err(|e| e.to_string())?;
+    }
+
+    if let Some(prefix) = payload.allowed_prefix.as_deref().map(str::trim).filter(|prefix| !prefix.is_empty()) {
+        prefix
+            .parse::<ipnetwork::Ipv6Network>()
+            .map_err(|e| format!("允许前缀 {} 无效: {}", prefix, e))?;
+    }
+    for rule in payload.prefix_rules.iter().flatten() {
+        rule.network().map_err(|e| e.to_string())?;
+    }
+    if let Some(parallelism) = payload.update_parallelism.filter(|p| !(1..=MAX_UPDATE_PARALLELISM).contains(p)) {
+        return Err(format!("同时处理的域名数 {} 无效，须为 1-{}", parallelism, MAX_UPDATE_PARALLELISM));
+    }
+    cloudflare::validate_credentials(payload.auth_type, &payload.api_key, payload.auth_email.as_deref())
+        .map_err(|e| e.to_string())?;
+    if let Some(url) = payload.proxy_url.as_deref().filter(|url| !url.trim().is_empty()) {
+        cloudflare::parse_proxy(url).map_err(|e| e.to_string())?;
+    }
+
+    if payload.ip_source == Some(IpSource::Command) && ip_command::configured_command().is_none() {
+        return Err("IP来源为自定义命令时需要设置 IP_COMMAND 环境变量".to_string());
+    }
+    if payload.ip_source == Some(IpSource::DynDns) && dyndns::credentials().is_none() {
+        return Err("IP来源为路由器推送时需要设置 DYNDNS_USERNAME 和 DYNDNS_PASSWORD 环境变量".to_string());
+    }
+    // 发布所有地址需要读取本机网卡上的全部地址，轮询模式下同名的其他记录属于其他主机，不能删除
+    if payload.multi_address == Some(true) {
+        let ip_source = payload.ip_source.or_else(|| existing.map(|c| c.ip_source)).unwrap_or_default();
+        let round_robin = payload.round_robin.or_else(|| existing.map(|c| c.round_robin)).unwrap_or(false);
+        if ip_source != IpSource::Local {
+            return Err("发布所有地址只支持本机网卡地址作为IP来源".to_string());
+        }
+        if round_robin {
+            return Err("发布所有地址与轮询模式不能同时开启".to_string());
+        }
+    }
+    if let Some(name) = payload.interface.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+        let known = network::list_interfaces().is_ok_and(|list| list.iter().any(|iface| iface.name == name));
+        if !known {
+            return Err(format!("找不到网卡 {}", name));
+        }
+    }
+    if let Some(services) = &payload.external_ip_services {
+        if services.len() > MAX_EXTERNAL_IP_SERVICES {
+            return Err(format!("外部IP检测服务最多 {} 个", MAX_EXTERNAL_IP_SERVICES));
+        }
+        for url in services {
+            network::validate_ip_service(url).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(servers) = &payload.stun_servers {
+        if servers.len() > MAX_EXTERNAL_IP_SERVICES {
+            return Err(format!("STUN服务器最多 {} 个", MAX_EXTERNAL_IP_SERVICES));
+        }
+        for server in servers {
+            stun::parse_server(server).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(prefix_len) = payload.delegated_prefix_len {
+        prefix_delegation::validate_prefix_len(prefix_len).map_err(|e| e.to_string())?;
+        // 修改委派前缀长度时，已有的局域网主机后缀需要仍然有效
+        for host in lan_hosts {
+            prefix_delegation::parse_host_suffix(&host.suffix, prefix_len)
+                .map_err(|e| format!("子域名 {} 的主机后缀无效: {}", host.subdomain, e))?;
+        }
+    }
+    if let Some(selection) = &payload.address_selection {
+        selection.validate().map_err(|e| e.to_string())?;
+    }
+
+    if let Some(path) = payload.ca_cert_path.as_deref().filter(|path| !path.trim().is_empty()) {
+        cloudflare::load_ca_certificates(path).map_err(|e| e.to_string())?;
+    }
+    if let Some(timeout) = payload.http_connect_timeout.filter(|t| !(1..=MAX_HTTP_CONNECT_TIMEOUT).contains(t)) {
+        return Err(format!("连接超时 {} 秒无效，须为 1-{} 秒", timeout, MAX_HTTP_CONNECT_TIMEOUT));
+    }
+    if let Some(timeout) = payload.http_request_timeout.filter(|t| !(1..=MAX_HTTP_REQUEST_TIMEOUT).contains(t)) {
+        return Err(format!("请求超时 {} 秒无效，须为 1-{} 秒", timeout, MAX_HTTP_REQUEST_TIMEOUT));
+    }
+    if let (Some(connect), Some(request)) = (payload.http_connect_timeout, payload.http_request_timeout) {
+        if connect > request {
+            return Err("连接超时不能大于请求总超时".to_string());
+        }
+    }
+    if let Some(interval) = payload.burst_interval.filter(|i| *i > 0 && *i < MIN_BURST_INTERVAL) {
+        return Err(format!("突发检查间隔 {} 秒过短，最少 {} 秒", interval, MIN_BURST_INTERVAL));
+    }
+    Ok(())
+}
+
 pub async fn save_config(
     State(service): State<ConfigService>,
-    Json(payload): Json<SaveConfigRequest>,
+    Json(mut payload): Json<SaveConfigRequest>,
 ) -> impl IntoResponse {
+    let existing = service.load_configuration().ok();
+    payload.restore_hidden_secrets(existing.as_ref());
     info!("💾 收到配置保存请求，域名: {}，子域名数量: {}", 
           payload.root_domain, payload.selected_subdomains.len());
     
-    match service.save_configuration_and_update(
-        payload.api_key,
-        payload.zone_id,
-        payload.root_domain.clone(),
-        payload.selected_subdomains.clone(),
-        payload.check_interval,
-    ).await {
+    let root_domain = payload.root_domain.clone();
+    let check_interval = payload.check_interval;
+
+    let lan_hosts = service.list_lan_hosts().unwrap_or_default();
+    if let Err(e) = validate(&payload, existing.as_ref(), &lan_hosts) {
+        return Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(format!("配置保存失败: {}", e)),
+        });
+    }
+    
+    match service.save_configuration_and_update(payload.into_app_config(existing)).await {
         Ok(()) => {
             info!("✅ 配置保存并更新成功，域名: {}，检查间隔: {}秒", 
-                  payload.root_domain, payload.check_interval);
+                  root_domain, check_interval);
             Json(ApiResponse::<()> {
                 success: true,
                 data: None,
@@ -118,7 +749,7 @@ pub async fn save_config(
             })
         },
         Err(e) => {
-            error!("❌ 配置保存失败，域名: {} - {}", payload.root_domain, e);
+            error!("❌ 配置保存失败，域名: {} - {}", root_domain, e);
             Json(ApiResponse::<()> {
                 success: false,
                 data: None,
@@ -132,20 +763,24 @@ pub async fn get_config_status(
     State(service): State<ConfigService>,
 ) -> impl IntoResponse {
     let configured = service.has_configuration();
-    let current_config = if configured {
-        match service.load_configuration() {
-            Ok(config) => Some(serde_json::to_value(config).unwrap()),
-            Err(_) => None,
-        }
+    let config = if configured {
+        service.load_configuration().ok()
     } else {
         None
     };
+    let effective_check_interval = config.as_ref().map(|c| service.current_check_interval(c));
+    let dry_run = config.as_ref().is_some_and(|c| c.is_dry_run());
+    let current_config = config.as_ref().map(redacted_config);
     
     Json(ApiResponse {
         success: true,
         data: Some(ConfigStatus {
             configured,
             current_config,
+            effective_check_interval,
+            dry_run,
+            pause: service.pause_state(),
+            flapping: service.flap_state(),
         }),
         message: None,
     })
@@ -154,7 +789,7 @@ pub async fn get_config_status(
 pub async fn get_current_ip(
     State(service): State<ConfigService>,
 ) -> impl IntoResponse {
-    match service.get_current_ipv6() {
+    match service.get_current_ipv6().await {
         Ok(ip) => Json(ApiResponse {
             success: true,
             data: Some(ip),
@@ -168,28 +803,133 @@ pub async fn get_current_ip(
     }
 }
 
+/// 仪表盘所需的运行状态汇总
+#[derive(Debug, Serialize)]
+pub struct StatusOverview {
+    pub configured: bool,
+    pub monitor: MonitorStatus,
+    pub pause: Option<PauseState>,
+    pub status: ServiceStatus,
+    pub last_check: Option<CheckRun>,
+    pub next_check: Option<DateTime<Utc>>, // 预计的下一次定时检查时间，未运行、已暂停或尚未执行过定时检查时为null
+    pub current_ip: Option<String>,        // 当前检测到的IPv6地址，检测失败时为null
+    pub last_ip: Option<String>,           // 最后一次写入DNS的地址
+    pub check_interval: Option<u64>,       // 配置的检查间隔（秒）
+    pub effective_check_interval: Option<u64>,
+    pub flapping: Option<FlapState>,
+    pub reconcile: ReconcileState,
+}
+
+/// 一次返回监控状态、最近检查、下一次检查、当前IP和检查间隔
+pub async fn get_status(
+    State(service): State<ConfigService>,
+    State(monitor): State<MonitorHandle>,
+) -> Response {
+    let monitor = match monitor.status().await {
+        Ok(monitor) => monitor,
+        Err(e) => return admin_result::<StatusOverview>(Err(e), None, "获取运行状态失败"),
+    };
+    let config = service.load_configuration().ok();
+    let pause = service.pause_state();
+    let next_check = config
+        .as_ref()
+        .filter(|_| monitor.running && pause.is_none())
+        .and_then(|c| service.next_scheduled_check(c));
+    let current_ip = match &config {
+        Some(c) => service.detect_ipv6(c).await.ok(),
+        None => None,
+    };
+    
+    let overview = StatusOverview {
+        configured: config.is_some(),
+        pause,
+        status: service.current_status(),
+        last_check: service.last_check(),
+        next_check,
+        current_ip,
+        last_ip: config.as_ref().and_then(|c| c.last_ip.clone()),
+        check_interval: config.as_ref().map(|c| c.check_interval),
+        effective_check_interval: config.as_ref().map(|c| service.current_check_interval(c)),
+        flapping: service.flap_state(),
+        reconcile: service.reconcile_state(),
+        monitor,
+    };
+    admin_result(Ok(overview), None, "获取运行状态失败")
+}
+
+/// 列出本机网卡及其地址，用于选择检测地址的网卡
+pub async fn get_interfaces() -> Json<ApiResponse<Vec<NetworkInterface>>> {
+    match network::list_interfaces() {
+        Ok(interfaces) => Json(ApiResponse {
+            success: true,
+            data: Some(interfaces),
+            message: None,
+        }),
+        Err(e) => Json(ApiResponse {
+            success: false,
+            data: None,
+            message: Some(format!("获取网卡列表失败: {}", e)),
+        }),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RouterIpv4Query {
+    /// 路由器地址，默认使用IPv4默认网关
+    #[serde(default)]
+    pub gateway: Option<Ipv4Addr>,
+}
+
+/// 通过NAT-PMP或UPnP向本地路由器查询外部IPv4地址（为A记录准备）
+pub async fn get_router_ipv4(Query(query): Query<RouterIpv4Query>) -> Response {
+    let result = router_ipv4::get_router_ipv4(query.gateway).await;
+    let warning = match &result {
+        Ok(router) if !router.public => Some("路由器的外部地址不是公网地址，可能位于运营商级NAT或上级路由之后"),
+        _ => None,
+    };
+    admin_result(result, warning, "查询路由器外部IPv4失败")
+}
+
+/// 获取最近一次本地与外部IPv6的比对结果
+pub async fn get_ip_consistency(
+    State(service): State<ConfigService>,
+) -> Json<ApiResponse<IpConsistency>> {
+    match service.ip_consistency() {
+        Some(result) => Json(ApiResponse {
+            success: true,
+            data: Some(result),
+            message: None,
+        }),
+        None => Json(ApiResponse {
+            success: true,
+            data: None,
+            message: Some("尚未进行外部IP校验，请在设置中启用 verify_external_ip".to_string()),
+        }),
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct DnsUpdateRecordsResponse {
     pub records: Vec<DnsUpdateRecord>,
 }
 
 /// 获取DNS更新记录
-pub async fn get_dns_update_records() -> impl IntoResponse {
-    let db = match Database::new() {
-        Ok(db) => db,
-        Err(e) => {
-            error!("❌ 数据库连接失败: {}", e);
-            return Json(ApiResponse::<DnsUpdateRecordsResponse> {
-                success: false,
-                data: None,
-                message: Some(format!("数据库连接失败: {}", e)),
-            });
-        }
-    };
+pub async fn get_dns_update_records(
+    State(service): State<ConfigService>,
+) -> impl IntoResponse {
+    let db = service.database();
+    
+    let formatter = service
+        .load_configuration()
+        .map(|config| config.time_formatter())
+        .unwrap_or_default();
     
     match db.get_recent_dns_update_records(50) {
-        Ok(records) => {
+        Ok(mut records) => {
             info!("📊 获取到 {} 条DNS更新记录", records.len());
+            for record in &mut records {
+                record.timestamp_display = Some(formatter.format(&record.timestamp));
+            }
             Json(ApiResponse {
                 success: true,
                 data: Some(DnsUpdateRecordsResponse { records }),
@@ -205,4 +945,1290 @@ pub async fn get_dns_update_records() -> impl IntoResponse {
             })
         }
     }
-}
\ No newline at end of file
+}
+
+/// 按ID获取单条DNS更新记录，用于从指标示例（exemplar）跳转
+pub async fn get_dns_update_record(
+    State(service): State<ConfigService>,
+    Path(id): Path<i64>,
+) -> Response {
+    let formatter = service
+        .load_configuration()
+        .map(|config| config.time_formatter())
+        .unwrap_or_default();
+    match service.database().get_dns_update_record(id) {
+        Ok(Some(mut record)) => {
+            record.timestamp_display = Some(formatter.format(&record.timestamp));
+            Json(ApiResponse {
+                success: true,
+                data: Some(record),
+                message: None,
+            }).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(format!("没有找到更新记录: {}", id)),
+        })).into_response(),
+        Err(e) => {
+            error!("❌ 获取DNS更新记录失败: {}", e);
+            Json(ApiResponse::<()> {
+                success: false,
+                data: None,
+                message: Some(format!("获取DNS更新记录失败: {}", e)),
+            }).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainStatesResponse {
+    pub states: Vec<DomainState>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusEventsResponse {
+    pub current: ServiceStatus,
+    pub events: Vec<StatusEvent>,
+}
+
+/// 获取当前服务状态及最近的状态转换事件
+pub async fn get_status_events(
+    State(service): State<ConfigService>,
+) -> impl IntoResponse {
+    let formatter = service
+        .load_configuration()
+        .map(|config| config.time_formatter())
+        .unwrap_or_default();
+
+    match service.get_status_events(50) {
+        Ok(mut events) => {
+            for event in &mut events {
+                event.timestamp_display = Some(formatter.format(&event.timestamp));
+            }
+            Json(ApiResponse {
+                success: true,
+                data: Some(StatusEventsResponse {
+                    current: service.current_status(),
+                    events,
+                }),
+                message: None,
+            })
+        }
+        Err(e) => {
+            error!("❌ 获取状态事件失败: {}", e);
+            Json(ApiResponse::<StatusEventsResponse> {
+                success: false,
+                data: None,
+                message: Some(format!("获取状态事件失败: {}", e)),
+            })
+        }
+    }
+}
+
+/// 获取各域名的本地同步状态（包含Cloudflare侧的最后修改时间）
+pub async fn get_domain_states(
+    State(service): State<ConfigService>,
+) -> impl IntoResponse {
+    let formatter = service
+        .load_configuration()
+        .map(|config| config.time_formatter())
+        .unwrap_or_default();
+
+    match service.get_domain_states() {
+        Ok(mut states) => {
+            for state in &mut states {
+                state.cf_modified_on_display = state.cf_modified_on.map(|t| formatter.format(&t));
+                state.updated_at_display = Some(formatter.format(&state.updated_at));
+            }
+            Json(ApiResponse {
+                success: true,
+                data: Some(DomainStatesResponse { states }),
+                message: None,
+            })
+        }
+        Err(e) => {
+            error!("❌ 获取域名状态失败: {}", e);
+            Json(ApiResponse::<DomainStatesResponse> {
+                success: false,
+                data: None,
+                message: Some(format!("获取域名状态失败: {}", e)),
+            })
+        }
+    }
+}
+
+/// 获取各选中子域名的记录ID、当前内容、最后成功同步时间和最近的错误
+pub async fn get_domain_statuses(State(service): State<ConfigService>) -> Response {
+    let result = service.load_configuration().and_then(|config| {
+        let formatter = config.time_formatter();
+        let mut statuses = service.domain_statuses(&config)?;
+        for status in &mut statuses {
+            status.last_success_at_display = status.last_success_at.map(|t| formatter.format(&t));
+            status.last_error_at_display = status.last_error_at.map(|t| formatter.format(&t));
+        }
+        Ok(statuses)
+    });
+    admin_result::<Vec<DomainStatus>>(result, None, "获取域名状态失败")
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnershipResponse {
+    pub instance_id: String,
+    pub enabled: bool,
+    pub records: Vec<RecordOwnership>,
+}
+
+/// 获取本实例ID与本地登记的记录归属（包括与其他实例的冲突）
+pub async fn get_ownership(
+    State(service): State<ConfigService>,
+) -> impl IntoResponse {
+    let config = service.load_configuration().ok();
+    let formatter = config.as_ref().map(|c| c.time_formatter()).unwrap_or_default();
+    
+    match service.get_ownership() {
+        Ok((instance_id, mut records)) => {
+            for record in &mut records {
+                record.updated_at_display = Some(formatter.format(&record.updated_at));
+            }
+            Json(ApiResponse {
+                success: true,
+                data: Some(OwnershipResponse {
+                    instance_id,
+                    enabled: config.is_some_and(|c| c.ownership_enabled),
+                    records,
+                }),
+                message: None,
+            })
+        }
+        Err(e) => {
+            error!("❌ 获取记录归属失败: {}", e);
+            Json(ApiResponse::<OwnershipResponse> {
+                success: false,
+                data: None,
+                message: Some(format!("获取记录归属失败: {}", e)),
+            })
+        }
+    }
+}
+
+/// 路径中使用 "@" 表示根域名
+fn subdomain_from_path(name: &str) -> String {
+    if name == "@" {
+        String::new()
+    } else {
+        name.to_string()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletedSubdomainInfo {
+    pub subdomain: String,
+    pub full_domain: String,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_at_display: String,
+    pub restorable_until: DateTime<Utc>,
+    pub restorable: bool,
+}
+
+/// 获取已软删除的子域名列表
+pub async fn get_deleted_subdomains(
+    State(service): State<ConfigService>,
+) -> impl IntoResponse {
+    let config = match service.load_configuration() {
+        Ok(config) => config,
+        Err(e) => {
+            return Json(ApiResponse::<Vec<DeletedSubdomainInfo>> {
+                success: false,
+                data: None,
+                message: Some(format!("加载配置失败: {}", e)),
+            });
+        }
+    };
+    let formatter = config.time_formatter();
+
+    match service.list_deleted_subdomains() {
+        Ok(deleted) => {
+            let now = service.clock().utc_now();
+            let items = deleted
+                .into_iter()
+                .map(|d| {
+                    let restorable_until = d.deleted_at + chrono::Duration::days(SOFT_DELETE_RETENTION_DAYS);
+                    DeletedSubdomainInfo {
+                        full_domain: config.full_domain(&d.subdomain),
+                        deleted_at_display: formatter.format(&d.deleted_at),
+                        restorable: now <= restorable_until,
+                        subdomain: d.subdomain,
+                        deleted_at: d.deleted_at,
+                        restorable_until,
+                    }
+                })
+                .collect();
+            Json(ApiResponse {
+                success: true,
+                data: Some(items),
+                message: None,
+            })
+        }
+        Err(e) => Json(ApiResponse::<Vec<DeletedSubdomainInfo>> {
+            success: false,
+            data: None,
+            message: Some(format!("获取已删除子域名失败: {}", e)),
+        }),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddSubdomainRequest {
+    pub name: String,
+}
+
+/// 新增受管子域名，记录将在下一次更新时创建
+pub async fn add_subdomain(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Json(payload): Json<AddSubdomainRequest>,
+) -> impl IntoResponse {
+    match service.add_subdomain(&payload.name).await {
+        Ok(subdomain) => Json(ApiResponse {
+            success: true,
+            data: Some(subdomain),
+            message: Some("子域名已添加，将在下一次更新时创建记录".to_string()),
+        }),
+        Err(e) => {
+            error!("❌ 添加子域名失败: {}", e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("添加子域名失败: {}", e)),
+            })
+        }
+    }
+}
+
+/// 软删除子域名
+pub async fn delete_subdomain(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    simple_result(service.delete_subdomain(&subdomain_from_path(&name)).await, "子域名已删除，可在保留期内恢复", "删除子域名失败")
+}
+
+/// 恢复已软删除的子域名
+pub async fn restore_subdomain(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    simple_result(service.restore_subdomain(&subdomain_from_path(&name)).await, "子域名已恢复", "恢复子域名失败")
+}
+
+/// 彻底清除已软删除的子域名
+pub async fn purge_subdomain(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    simple_result(service.purge_subdomain(&subdomain_from_path(&name)).await, "子域名已彻底清除", "清除子域名失败")
+}
+
+/// 获取所有局域网主机
+pub async fn get_lan_hosts(State(service): State<ConfigService>) -> Response {
+    admin_result(service.list_lan_hosts(), None, "获取局域网主机失败")
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LanHostRequest {
+    pub suffix: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscoverQuery {
+    /// 不通过mDNS查询主机名，只读取邻居表
+    #[serde(default)]
+    pub skip_mdns: bool,
+}
+
+/// 扫描IPv6邻居表，列出可映射为局域网主机的邻居
+pub async fn discover_lan_hosts(
+    State(service): State<ConfigService>,
+    Query(query): Query<DiscoverQuery>,
+) -> Response {
+    admin_result(service.discover_lan_hosts(!query.skip_mdns).await, None, "发现局域网主机失败")
+}
+
+/// 新增或修改局域网主机，根域名使用 "@" 表示
+pub async fn put_lan_host(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Path(name): Path<String>,
+    Json(payload): Json<LanHostRequest>,
+) -> Response {
+    admin_result(
+        service.set_lan_host(&subdomain_from_path(&name), &payload.suffix, payload.description.as_deref()).await,
+        Some("局域网主机已保存，将在下一次更新时发布"),
+        "保存局域网主机失败",
+    )
+}
+
+/// 删除局域网主机，子域名仍在管理中
+pub async fn delete_lan_host(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Path(name): Path<String>,
+) -> Response {
+    match service.remove_lan_host(&subdomain_from_path(&name)) {
+        Ok(false) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(format!("局域网主机不存在: {}", name)),
+        })).into_response(),
+        result => admin_result(result.map(|_| ()), Some("局域网主机已删除"), "删除局域网主机失败"),
+    }
+}
+
+/// 导出各子域名的设置为CSV，便于在表格中批量编辑
+pub async fn export_domain_settings(State(service): State<ConfigService>) -> Response {
+    match service.export_domain_settings() {
+        Ok(text) => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"domains.csv\""),
+            ],
+            text,
+        ).into_response(),
+        Err(e) => admin_result::<()>(Err(e), None, "导出域名设置失败"),
+    }
+}
+
+/// 从CSV批量导入子域名设置，请求体为CSV文本
+pub async fn import_domain_settings(State(service): State<ConfigService>, _admin: AdminAuth, body: String) -> Response {
+    admin_result::<ImportReport>(service.import_domain_settings(&body).await, Some("域名设置已导入"), "导入域名设置失败")
+}
+
+/// 将无返回数据的操作结果转换为API响应
+fn simple_result(result: anyhow::Result<()>, ok_message: &str, err_prefix: &str) -> Json<ApiResponse<()>> {
+    match result {
+        Ok(()) => Json(ApiResponse {
+            success: true,
+            data: None,
+            message: Some(ok_message.to_string()),
+        }),
+        Err(e) => {
+            error!("❌ {}: {}", err_prefix, e);
+            Json(ApiResponse {
+                success: false,
+                data: None,
+                message: Some(format!("{}: {}", err_prefix, e)),
+            })
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulateFailureRequest {
+    pub kind: FaultKind,
+    pub cycles: u32,
+}
+
+/// 注入模拟故障，在接下来的N个检查周期内生效
+pub async fn inject_simulated_failure(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Json(payload): Json<SimulateFailureRequest>,
+) -> Response {
+    warn!("🧪 注入模拟故障: {:?}，持续 {} 个检查周期", payload.kind, payload.cycles);
+    service.fault_injector().inject(payload.kind, payload.cycles);
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(service.fault_injector().status()),
+        message: Some("模拟故障已注入".to_string()),
+    }).into_response()
+}
+
+/// 查询剩余的模拟故障周期
+pub async fn get_simulated_failure(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+) -> Response {
+    Json(ApiResponse::<FaultStatus> {
+        success: true,
+        data: Some(service.fault_injector().status()),
+        message: None,
+    }).into_response()
+}
+
+/// 清除所有模拟故障
+pub async fn clear_simulated_failure(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+) -> Response {
+    info!("🧪 清除所有模拟故障");
+    service.fault_injector().clear();
+
+    Json(ApiResponse {
+        success: true,
+        data: Some(service.fault_injector().status()),
+        message: Some("模拟故障已清除".to_string()),
+    }).into_response()
+}
+
+/// 立即执行一次数据库备份（管理接口）
+pub async fn run_backup(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+) -> Response {
+    let result = async {
+        let settings = BackupSettings::from_env()?
+            .ok_or_else(|| anyhow::anyhow!("未配置备份存储，请设置 BACKUP_TARGET 等环境变量"))?;
+        BackupService::new(settings, service.load_configuration().ok().as_ref())?.backup(service.database()).await
+    }.await;
+
+    match result {
+        Ok(name) => Json(ApiResponse {
+            success: true,
+            data: Some(name),
+            message: Some("备份完成".to_string()),
+        }).into_response(),
+        Err(e) => {
+            error!("❌ 数据库备份失败: {}", e);
+            Json(ApiResponse::<String> {
+                success: false,
+                data: None,
+                message: Some(format!("数据库备份失败: {}", e)),
+            }).into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DebugCaptureQuery {
+    #[serde(default = "default_capture_minutes")]
+    pub minutes: u64,
+}
+
+fn default_capture_minutes() -> u64 {
+    10
+}
+
+/// 开始调试抓取窗口（管理接口）：临时提高日志级别并记录脱敏后的Cloudflare请求，到期自动恢复
+pub async fn start_debug_capture(
+    _admin: AdminAuth,
+    Query(query): Query<DebugCaptureQuery>,
+) -> Response {
+    match debug_capture::start(query.minutes) {
+        Ok(status) => Json(ApiResponse {
+            success: true,
+            data: Some(status),
+            message: Some(format!("调试抓取已开始，持续 {} 分钟", query.minutes)),
+        }).into_response(),
+        Err(e) => Json(ApiResponse::<CaptureStatus> {
+            success: false,
+            data: Some(debug_capture::status()),
+            message: Some(format!("无法开始调试抓取: {}", e)),
+        }).into_response(),
+    }
+}
+
+/// 提前结束调试抓取（管理接口）
+pub async fn stop_debug_capture(_admin: AdminAuth) -> Response {
+    Json(ApiResponse {
+        success: true,
+        data: Some(debug_capture::stop()),
+        message: Some("调试抓取已结束".to_string()),
+    }).into_response()
+}
+
+/// 下载调试抓取文件（管理接口），每行一条JSON记录
+pub async fn download_debug_capture(_admin: AdminAuth) -> Response {
+    match std::fs::read(debug_capture::capture_path()) {
+        Ok(content) => (
+            [
+                (header::CONTENT_TYPE, "application/x-ndjson"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"debug-capture.jsonl\""),
+            ],
+            content,
+        ).into_response(),
+        Err(_) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some("还没有调试抓取文件".to_string()),
+        })).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChatOpsQuery {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// 聊天机器人命令接口：接收纯文本命令（或表单中的 text 字段），返回纯文本回复
+/// 令牌来自环境变量 CHATOPS_TOKEN，可通过 Authorization: Bearer 或 ?token= 传入
+pub async fn chatops_command(
+    State(service): State<ConfigService>,
+    Query(query): Query<ChatOpsQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    let plain = |status: StatusCode, text: String| {
+        (status, [(header::CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response()
+    };
+
+    let expected = match std::env::var("CHATOPS_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => return plain(StatusCode::FORBIDDEN, "命令接口未启用，请设置 CHATOPS_TOKEN 环境变量".to_string()),
+    };
+    let provided = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or(query.token);
+    if !secret_matches(provided.as_deref(), &expected) {
+        warn!("🔒 命令接口鉴权失败");
+        return plain(StatusCode::UNAUTHORIZED, "令牌无效".to_string());
+    }
+
+    let is_form = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/x-www-form-urlencoded"));
+    let text = if is_form { form_text_field(&body) } else { body };
+
+    info!("💬 收到命令: {}", text.trim());
+    match ChatCommand::parse(&text) {
+        Ok(command) => plain(StatusCode::OK, chatops::execute(&service, command).await),
+        Err(e) => plain(StatusCode::OK, format!("{}\n发送 help 查看可用命令", e)),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct NicUpdateQuery {
+    #[serde(default)]
+    pub hostname: String,
+    #[serde(default)]
+    pub myip: String,
+}
+
+/// DynDNS2兼容的更新接口，供路由器推送地址，返回纯文本返回码
+/// 用户名和密码来自环境变量 DYNDNS_USERNAME 和 DYNDNS_PASSWORD，通过HTTP Basic认证传入
+pub async fn nic_update(
+    State(service): State<ConfigService>,
+    Query(query): Query<NicUpdateQuery>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> Response {
+    let plain = |status: StatusCode, codes: &[ReturnCode]| {
+        let text = codes.iter().map(ReturnCode::to_string).collect::<Vec<_>>().join("\n");
+        (status, [(header::CONTENT_TYPE, "text/plain; charset=utf-8")], text).into_response()
+    };
+
+    let Some(expected) = dyndns::credentials() else {
+        warn!("🔒 DynDNS接口未启用，请设置 DYNDNS_USERNAME 和 DYNDNS_PASSWORD 环境变量");
+        return plain(StatusCode::FORBIDDEN, &[ReturnCode::BadAuth]);
+    };
+    let provided = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(dyndns::parse_basic_auth);
+    // 用户名和密码都要比较，不能因用户名不符而提前返回
+    let username_ok = secret_matches(provided.as_ref().map(|(username, _)| username.as_str()), &expected.0);
+    let password_ok = secret_matches(provided.as_ref().map(|(_, password)| password.as_str()), &expected.1);
+    if !(username_ok & password_ok) {
+        warn!("🔒 DynDNS接口鉴权失败");
+        let mut response = plain(StatusCode::UNAUTHORIZED, &[ReturnCode::BadAuth]);
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Basic realm=\"cloudflare-auto\""));
+        return response;
+    }
+
+    let peer = connect_info.map(|ConnectInfo(addr)| addr.ip());
+    plain(StatusCode::OK, &dyndns::update(&service, &query.hostname, &query.myip, peer).await)
+}
+
+/// 从表单请求体中取出 text 字段（Slack斜杠命令等使用表单提交）
+fn form_text_field(body: &str) -> String {
+    reqwest::Url::parse(&format!("http://localhost/?{}", body))
+        .ok()
+        .and_then(|url| url.query_pairs().find(|(key, _)| key == "text").map(|(_, value)| value.into_owned()))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxtRecordQuery {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTxtRecordRequest {
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub ttl: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTxtRecordRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DnsRecordQuery {
+    #[serde(default, rename = "type")]
+    pub record_type: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateDnsRecordRequest {
+    #[serde(rename = "type")]
+    pub record_type: String,
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub ttl: Option<u32>,
+    #[serde(default)]
+    pub proxied: bool,
+    #[serde(default)]
+    pub priority: Option<u16>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditDnsRecordRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub ttl: Option<u32>,
+    #[serde(default)]
+    pub proxied: Option<bool>,
+    #[serde(default)]
+    pub priority: Option<u16>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// 将管理接口的操作结果转换为API响应
+fn admin_result<T: Serialize>(result: anyhow::Result<T>, ok_message: Option<&str>, err_prefix: &str) -> Response {
+    match result {
+        Ok(data) => Json(ApiResponse {
+            success: true,
+            data: Some(data),
+            message: ok_message.map(str::to_string),
+        }).into_response(),
+        Err(e) => {
+            error!("❌ {}: {}", err_prefix, e);
+            Json(ApiResponse::<T> {
+                success: false,
+                data: None,
+                message: Some(format!("{}: {}", err_prefix, e)),
+            }).into_response()
+        }
+    }
+}
+
+/// 获取TXT记录，可按名称过滤（管理接口）
+pub async fn list_txt_records(
+    State(service): State<ConfigService>,
+    Query(query): Query<TxtRecordQuery>,
+    _admin: AdminAuth,
+) -> Response {
+    admin_result::<Vec<DnsRecord>>(service.list_txt_records(query.name.as_deref()).await, None, "获取TXT记录失败")
+}
+
+/// 创建TXT记录（管理接口）
+pub async fn create_txt_record(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Json(payload): Json<CreateTxtRecordRequest>,
+) -> Response {
+    admin_result(
+        service.create_txt_record(&payload.name, &payload.content, payload.ttl).await,
+        Some("TXT记录已创建"),
+        "创建TXT记录失败",
+    )
+}
+
+/// 修改TXT记录内容（管理接口）
+pub async fn update_txt_record(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+    _admin: AdminAuth,
+    Json(payload): Json<UpdateTxtRecordRequest>,
+) -> Response {
+    admin_result(
+        service.update_txt_record(&id, &payload.content).await,
+        Some("TXT记录已更新"),
+        "更新TXT记录失败",
+    )
+}
+
+/// 删除TXT记录（管理接口）
+pub async fn delete_txt_record(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+    _admin: AdminAuth,
+) -> Response {
+    admin_result(service.delete_txt_record(&id).await, Some("TXT记录已删除"), "删除TXT记录失败")
+}
+
+/// 获取最近的TXT记录变更历史（管理接口）
+pub async fn get_txt_record_history(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+) -> Response {
+    let formatter = service
+        .load_configuration()
+        .map(|config| config.time_formatter())
+        .unwrap_or_default();
+    let result = service.get_txt_record_changes(100).map(|mut changes: Vec<TxtRecordChange>| {
+        for change in &mut changes {
+            change.timestamp_display = Some(formatter.format(&change.timestamp));
+        }
+        changes
+    });
+    admin_result(result, None, "获取TXT变更历史失败")
+}
+
+/// 校验ACME钩子令牌，令牌来自环境变量 ACME_TOKEN
+/// 支持 Authorization: Bearer，或Basic认证的密码（兼容lego httpreq提供者）
+fn check_acme(headers: &HeaderMap) -> Result<(), (StatusCode, Json<ApiResponse<()>>)> {
+    let expected = match std::env::var("ACME_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => {
+            return Err((StatusCode::FORBIDDEN, Json(ApiResponse::<()> {
+                success: false,
+                data: None,
+                message: Some("ACME接口未启用，请设置 ACME_TOKEN 环境变量".to_string()),
+            })));
+        }
+    };
+
+    let authorization = headers.get("authorization").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let provided = match authorization.split_once(' ') {
+        Some(("Bearer", token)) => Some(token.to_string()),
+        Some(("Basic", encoded)) => base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|credentials| credentials.split_once(':').map(|(_, password)| password.to_string())),
+        _ => None,
+    };
+
+    if secret_matches(provided.as_deref(), &expected) {
+        Ok(())
+    } else {
+        warn!("🔒 ACME接口鉴权失败");
+        Err((StatusCode::UNAUTHORIZED, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some("ACME令牌无效".to_string()),
+        })))
+    }
+}
+
+/// 写入ACME DNS-01验证记录
+pub async fn acme_present(
+    State(service): State<ConfigService>,
+    headers: HeaderMap,
+    Json(payload): Json<AcmeRequest>,
+) -> Response {
+    if let Err(resp) = check_acme(&headers) {
+        return resp.into_response();
+    }
+    let result = async {
+        let (fqdn, value) = payload.challenge()?;
+        acme::present(&service, &fqdn, &value).await
+    }.await;
+    acme_response(result, "验证记录已写入", "写入ACME验证记录失败")
+}
+
+/// 清理ACME DNS-01验证记录
+pub async fn acme_cleanup(
+    State(service): State<ConfigService>,
+    headers: HeaderMap,
+    Json(payload): Json<AcmeRequest>,
+) -> Response {
+    if let Err(resp) = check_acme(&headers) {
+        return resp.into_response();
+    }
+    let result = async {
+        let (fqdn, value) = payload.challenge()?;
+        acme::cleanup(&service, &fqdn, &value).await
+    }.await;
+    acme_response(result, "验证记录已清理", "清理ACME验证记录失败")
+}
+
+/// 钩子脚本通常只检查HTTP状态码，失败时返回500
+fn acme_response<T: Serialize>(result: anyhow::Result<T>, ok_message: &str, err_prefix: &str) -> Response {
+    let status = if result.is_ok() { StatusCode::OK } else { StatusCode::INTERNAL_SERVER_ERROR };
+    (status, admin_result(result, Some(ok_message), err_prefix)).into_response()
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StartSyncOperationRequest {
+    /// 要同步的子域名，不填时同步全部受管子域名
+    #[serde(default)]
+    pub subdomains: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OperationCreated {
+    pub operation_id: String,
+}
+
+/// 在后台启动强制同步，立即返回操作ID
+pub async fn start_sync_operation(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    payload: Option<Json<StartSyncOperationRequest>>,
+) -> Response {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let result = operations::start_force_sync(&service, payload.subdomains)
+        .map(|operation_id| OperationCreated { operation_id });
+    match result {
+        Ok(created) => (StatusCode::ACCEPTED, Json(ApiResponse {
+            success: true,
+            data: Some(created),
+            message: Some("同步操作已开始".to_string()),
+        })).into_response(),
+        Err(e) => admin_result::<OperationCreated>(Err(e), None, "启动同步操作失败"),
+    }
+}
+
+/// 查询操作进度
+pub async fn get_operation(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+) -> Response {
+    match service.database().get_operation(&id) {
+        Ok(Some(operation)) => admin_result::<Operation>(Ok(operation), None, "获取操作失败"),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(format!("操作不存在: {}", id)),
+        })).into_response(),
+        Err(e) => admin_result::<Operation>(Err(e), None, "获取操作失败"),
+    }
+}
+
+/// 获取最近的操作
+pub async fn get_operations(State(service): State<ConfigService>) -> Response {
+    admin_result::<Vec<Operation>>(service.database().get_operations(50), None, "获取操作列表失败")
+}
+
+/// 获取区域内的DNS记录，可按类型和名称过滤（管理接口）
+pub async fn list_dns_records(
+    State(service): State<ConfigService>,
+    Query(query): Query<DnsRecordQuery>,
+    _admin: AdminAuth,
+) -> Response {
+    admin_result::<Vec<DnsRecord>>(
+        service.list_dns_records(query.record_type.as_deref(), query.name.as_deref()).await,
+        None,
+        "获取DNS记录失败",
+    )
+}
+
+/// 创建DNS记录（管理接口）
+pub async fn create_dns_record(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    Json(payload): Json<CreateDnsRecordRequest>,
+) -> Response {
+    let record = UpdateDnsRecordRequest {
+        record_type: payload.record_type,
+        name: payload.name,
+        content: payload.content,
+        ttl: payload.ttl.unwrap_or(1),
+        proxied: payload.proxied,
+        comment: payload.comment.filter(|c| !c.is_empty()),
+        tags: Vec::new(),
+        priority: payload.priority,
+        data: None,
+    };
+    admin_result(service.create_dns_record(record).await, Some("DNS记录已创建"), "创建DNS记录失败")
+}
+
+/// 修改DNS记录，只修改请求中设置的字段（管理接口）
+pub async fn edit_dns_record(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+    _admin: AdminAuth,
+    Json(payload): Json<EditDnsRecordRequest>,
+) -> Response {
+    let patch = PatchDnsRecordRequest {
+        name: payload.name,
+        content: payload.content,
+        ttl: payload.ttl,
+        priority: payload.priority,
+        proxied: payload.proxied,
+        comment: payload.comment,
+        tags: None,
+        data: None,
+    };
+    admin_result(service.edit_dns_record(&id, patch).await, Some("DNS记录已更新"), "更新DNS记录失败")
+}
+
+/// 删除DNS记录（管理接口）
+pub async fn delete_dns_record(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+    _admin: AdminAuth,
+) -> Response {
+    admin_result(service.delete_dns_record(&id).await, Some("DNS记录已删除"), "删除DNS记录失败")
+}
+
+/// 立即同步单个受管域名（管理接口），名称可填写子域名、完整域名，根域名用 @
+pub async fn sync_domain(
+    State(service): State<ConfigService>,
+    Path(name): Path<String>,
+    _admin: AdminAuth,
+) -> Response {
+    let result = async {
+        let config = service.load_configuration()?;
+        let subdomain = normalize_subdomain(&name, &config.root_domain)?;
+        service.sync_subdomain(&subdomain).await
+    }.await;
+    match result {
+        Ok(summary) => Json(ApiResponse {
+            success: summary.success_count == summary.total_count,
+            message: Some(summary.error_message.clone().unwrap_or_else(|| "域名已同步".to_string())),
+            data: Some(summary),
+        }).into_response(),
+        Err(e) => admin_result::<UpdateSummary>(Err(e), None, "同步域名失败"),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    /// 允许调用的接口，如 "POST /domains/home/sync"
+    pub scopes: Vec<String>,
+    /// 有效期（小时），默认24
+    #[serde(default = "default_token_ttl_hours")]
+    pub ttl_hours: u32,
+}
+
+fn default_token_ttl_hours() -> u32 {
+    24
+}
+
+/// 生成限定范围的API令牌（只接受 ADMIN_TOKEN），令牌明文只在响应中返回一次
+pub async fn create_api_token(
+    State(service): State<ConfigService>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> Response {
+    if let Err(resp) = check_admin(&headers) {
+        return resp.into_response();
+    }
+    admin_result(
+        service.create_api_token(&payload.name, &payload.scopes, payload.ttl_hours),
+        Some("API令牌已生成，请立即保存，之后无法再次查看"),
+        "生成API令牌失败",
+    )
+}
+
+/// 获取所有API令牌（只接受 ADMIN_TOKEN）
+pub async fn get_api_tokens(
+    State(service): State<ConfigService>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = check_admin(&headers) {
+        return resp.into_response();
+    }
+    admin_result::<Vec<ApiToken>>(service.get_api_tokens(), None, "获取API令牌失败")
+}
+
+/// 吊销API令牌（只接受 ADMIN_TOKEN）
+pub async fn revoke_api_token(
+    State(service): State<ConfigService>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(resp) = check_admin(&headers) {
+        return resp.into_response();
+    }
+    match service.revoke_api_token(&id) {
+        Ok(false) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(format!("API令牌不存在: {}", id)),
+        })).into_response(),
+        result => admin_result(result.map(|_| ()), Some("API令牌已吊销"), "吊销API令牌失败"),
+    }
+}
+
+/// Prometheus指标
+pub async fn get_metrics(State(service): State<ConfigService>, headers: HeaderMap) -> Response {
+    let format = metrics::Format::from_accept(headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()));
+    match render_metrics(&service, format) {
+        Ok(text) => ([(header::CONTENT_TYPE, format.content_type())], text).into_response(),
+        Err(e) => {
+            error!("❌ 获取指标失败: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("获取指标失败: {}", e)).into_response()
+        }
+    }
+}
+
+fn render_metrics(service: &ConfigService, format: metrics::Format) -> anyhow::Result<String> {
+    let db = service.database();
+    let config = service.has_configuration().then(|| service.load_configuration()).transpose()?;
+    // 只输出当前选中域名的状态，已删除的域名不再出现在指标中
+    let domains: Vec<DomainState> = match &config {
+        Some(config) => {
+            let selected: Vec<String> = config.selected_subdomains.iter().map(|s| config.full_domain(s)).collect();
+            db.get_domain_states()?.into_iter().filter(|s| selected.contains(&s.domain)).collect()
+        }
+        None => Vec::new(),
+    };
+    Ok(metrics::render(
+        &metrics::Snapshot {
+            instance: &db.instance_id()?,
+            zone: config.as_ref().map(|c| c.root_domain.as_str()).unwrap_or_default(),
+            totals: &db.get_update_totals()?,
+            writes: &db.write_stats(),
+            domains: &domains,
+        },
+        format,
+    ))
+}
+
+/// 预览下一次更新会对各域名做的修改，不修改Cloudflare记录
+pub async fn get_plan(State(service): State<ConfigService>) -> Response {
+    admin_result::<UpdatePlan>(service.plan_update().await, None, "计算更新计划失败")
+}
+
+/// 比对所有选中域名在Cloudflare上的实际记录与本机IP
+pub async fn get_drift(State(service): State<ConfigService>) -> Response {
+    admin_result::<DriftReport>(service.drift_report().await, None, "检查记录漂移失败")
+}
+
+#[derive(Debug, Serialize)]
+pub struct LanguagesResponse {
+    pub default_language: String,
+    pub languages: Vec<i18n::LanguageInfo>,
+}
+
+/// 支持的界面语言，以及配置中的默认界面语言
+pub async fn get_languages(State(service): State<ConfigService>) -> Response {
+    let default_language = match service.has_configuration() {
+        true => service.load_configuration().map(|c| c.ui_language),
+        false => Ok(DEFAULT_LANGUAGE.to_string()),
+    };
+    admin_result(
+        default_language.map(|default_language| LanguagesResponse {
+            default_language,
+            languages: i18n::languages(),
+        }),
+        None,
+        "获取界面语言失败",
+    )
+}
+
+/// 某种语言的界面文字包，路径为 /i18n/{lang}.json，前端与后端共用同一份文字目录
+pub async fn get_translations(Path(file): Path<String>) -> Response {
+    let lang = file.strip_suffix(".json").unwrap_or(&file);
+    match i18n::normalize_language(lang) {
+        Ok(lang) => Json(i18n::bundle(lang)).into_response(),
+        Err(e) => (StatusCode::NOT_FOUND, Json(ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(e.to_string()),
+        })).into_response(),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DomainDiffQuery {
+    /// 按强制同步计算，内容一致的记录也会重新写入
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 预览此刻同步单个域名会做的修改，域名可填写子域名、完整域名或 "@"
+pub async fn get_domain_diff(
+    State(service): State<ConfigService>,
+    Path(name): Path<String>,
+    Query(query): Query<DomainDiffQuery>,
+) -> Response {
+    admin_result::<DomainDiff>(service.domain_diff(&name, query.force).await, None, "计算域名差异失败")
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateNowRequest {
+    /// 为true时内容一致的记录也重新写入
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 立即执行一次检查更新，返回每个域名的处理结果
+pub async fn update_now(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    payload: Option<Json<UpdateNowRequest>>,
+) -> Response {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    match service.update_now(payload.force).await {
+        Ok(report) => {
+            let summary = &report.summary;
+            let message = format!("更新完成: 成功 {}/{} 个域名", summary.success_count, summary.total_count);
+            Json(ApiResponse {
+                success: summary.success_count == summary.total_count,
+                message: Some(message),
+                data: Some(report),
+            }).into_response()
+        }
+        Err(e) => admin_result::<UpdateReport>(Err(e), None, "立即更新失败"),
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookQuery {
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub ip: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WebhookTriggerRequest {
+    /// 要发布的地址，不填时按IP来源检测
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// 供外部系统（路由器脚本、CI、监控）触发立即检查更新，地址可通过请求体或 ?ip= 携带
+/// 令牌来自环境变量 WEBHOOK_TOKEN，可通过 Authorization: Bearer 或 ?token= 传入；调用方通常只检查状态码，失败时返回4xx/5xx
+pub async fn webhook_trigger(
+    State(service): State<ConfigService>,
+    Query(query): Query<WebhookQuery>,
+    headers: HeaderMap,
+    payload: Option<Json<WebhookTriggerRequest>>,
+) -> Response {
+    let reject = |status: StatusCode, message: String| {
+        (status, Json(ApiResponse::<()> { success: false, data: None, message: Some(message) })).into_response()
+    };
+
+    let expected = match std::env::var("WEBHOOK_TOKEN") {
+        Ok(token) if !token.is_empty() => token,
+        _ => return reject(StatusCode::FORBIDDEN, "Webhook接口未启用，请设置 WEBHOOK_TOKEN 环境变量".to_string()),
+    };
+    let provided = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or(query.token);
+    if !secret_matches(provided.as_deref(), &expected) {
+        warn!("🔒 Webhook接口鉴权失败");
+        return reject(StatusCode::UNAUTHORIZED, "Webhook令牌无效".to_string());
+    }
+
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    if let Some(ip) = payload.ip.or(query.ip).filter(|ip| !ip.trim().is_empty()) {
+        match service.accept_pushed_ip(&ip) {
+            Ok(ip) => info!("🪝 Webhook携带了地址: {}", ip),
+            Err(e) => {
+                warn!("⚠️ Webhook携带的地址被拒绝: {}", e);
+                return reject(StatusCode::BAD_REQUEST, format!("地址被拒绝: {}", e));
+            }
+        }
+    } else {
+        info!("🪝 Webhook触发立即更新");
+    }
+
+    match service.update_now(payload.force).await {
+        Ok(report) => {
+            let summary = &report.summary;
+            let success = summary.error_message.is_none();
+            let status = if success { StatusCode::OK } else { StatusCode::BAD_GATEWAY };
+            let message = format!("更新完成: 成功 {}/{} 个域名", summary.success_count, summary.total_count);
+            (status, Json(ApiResponse { success, message: Some(message), data: Some(report) })).into_response()
+        }
+        Err(e) => {
+            error!("❌ Webhook触发的更新失败: {}", e);
+            reject(StatusCode::INTERNAL_SERVER_ERROR, format!("立即更新失败: {}", e))
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PauseMonitorRequest {
+    /// 暂停时长，如 "30m"、"2h"、"1d"，不填时一直暂停到手动恢复
+    #[serde(default)]
+    pub duration: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// 获取定时检查的暂停状态，未暂停时data为null
+pub async fn get_monitor_pause(State(service): State<ConfigService>) -> Json<ApiResponse<PauseState>> {
+    Json(ApiResponse {
+        success: true,
+        data: service.pause_state(),
+        message: None,
+    })
+}
+
+/// 暂停定时检查（例如网络维护期间），暂停状态保存在数据库中，重启后仍然有效
+pub async fn pause_monitor(
+    State(service): State<ConfigService>,
+    _admin: AdminAuth,
+    payload: Option<Json<PauseMonitorRequest>>,
+) -> Response {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let result = payload
+        .duration
+        .as_deref()
+        .filter(|d| !d.trim().is_empty())
+        .map(chatops::parse_duration)
+        .transpose()
+        .map_err(|e| anyhow::anyhow!(e))
+        .and_then(|duration| {
+            let until = duration.map(|d| service.clock().utc_now() + d);
+            service.pause(until, payload.reason.filter(|r| !r.is_empty()))
+        });
+    admin_result(result, Some("定时检查已暂停"), "暂停失败")
+}
+
+/// 恢复定时检查
+pub async fn resume_monitor(State(service): State<ConfigService>, _admin: AdminAuth) -> Json<ApiResponse<()>> {
+    simple_result(service.resume().map(|_| ()), "定时检查已恢复", "恢复失败")
+}
+
+/// 获取监控服务的运行状态
+pub async fn get_monitor_status(State(monitor): State<MonitorHandle>) -> Response {
+    admin_result::<MonitorStatus>(monitor.status().await, None, "获取监控状态失败")
+}
+
+/// 启动定时检查，已在运行时按当前配置确认定时任务
+pub async fn start_monitor(State(monitor): State<MonitorHandle>, _admin: AdminAuth) -> Json<ApiResponse<()>> {
+    simple_result(monitor.start().await, "监控服务已启动", "启动监控服务失败")
+}
+
+/// 停止定时检查，重启程序后恢复；需要跨重启保持时使用暂停接口
+pub async fn stop_monitor(State(monitor): State<MonitorHandle>, _admin: AdminAuth) -> Json<ApiResponse<()>> {
+    simple_result(monitor.stop().await, "监控服务已停止", "停止监控服务失败")
+}
+
+/// 立即执行一次与定时任务相同的检查，IP未变化时不修改记录
+pub async fn trigger_monitor(State(monitor): State<MonitorHandle>, _admin: AdminAuth) -> Response {
+    let result = monitor.trigger().await;
+    let message = if matches!(result, Ok(true)) { "检查完成，已更新记录" } else { "检查完成，无需更新" };
+    admin_result::<bool>(result, Some(message), "执行检查失败")
+}
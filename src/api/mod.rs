@@ -1,4 +1,10 @@
+mod auth;
 mod handlers;
 mod routes;
+mod state;
+mod version;
+mod ws;
 
-pub use routes::configure_routes;
\ No newline at end of file
+pub use routes::configure_routes;
+pub use state::AppState;
+pub use version::API_VERSION;
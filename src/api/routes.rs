@@ -1,22 +1,28 @@
 use axum::{
-    routing::{get, post},
+    middleware,
+    routing::{delete, get, post, put},
     Router, response::Html,
 };
 use tower_http::services::ServeDir;
-use crate::services::config_service::ConfigService;
+use super::state::AppState;
 use super::handlers::*;
+use super::ws::domain_events_ws;
+use super::version::{deprecated_alias, negotiate_version};
 
-pub fn configure_routes() -> Router<ConfigService> {
+pub fn configure_routes() -> Router<AppState> {
     Router::new()
         // 根路径返回主页面
         .route("/", get(index_handler))
-        // API路由
-        .route("/api/test-config", post(test_config))
-        .route("/api/domain-list", post(get_domain_list))
-        .route("/api/save-config", post(save_config))
-        .route("/api/config-status", get(get_config_status))
-        .route("/api/current-ip", get(get_current_ip))
-        .route("/api/dns-update-records", get(get_dns_update_records))
+        // Prometheus指标
+        .route("/metrics", get(get_metrics))
+        // DynDNS2兼容的更新接口，路由器只能使用固定路径，不放在 /api 下
+        .route("/nic/update", get(nic_update))
+        // 单个域名活动的WebSocket推送
+        .route("/ws/domains/:name", get(domain_events_ws))
+        // API路由，当前版本位于 /api/v1 下
+        .nest("/api/v1", api_routes())
+        // 旧的无版本路径作为已弃用的别名保留一个版本周期
+        .nest("/api", api_routes().layer(middleware::from_fn(deprecated_alias)))
         // 静态文件服务
         .nest_service("/static", ServeDir::new("static"))
         // 为了兼容性，也提供直接的静态文件访问
@@ -24,6 +30,89 @@ pub fn configure_routes() -> Router<ConfigService> {
         .nest_service("/js", ServeDir::new("static/js"))
 }
 
+/// 版本化的API路由，路径不含 /api 前缀
+fn api_routes() -> Router<AppState> {
+    Router::new()
+        .route("/test-config", post(test_config))
+        .route("/domain-list", post(get_domain_list))
+        .route("/save-config", post(save_config))
+        .route("/config-status", get(get_config_status))
+        .route("/status", get(get_status))
+        .route("/current-ip", get(get_current_ip))
+        .route("/router-ipv4", get(get_router_ipv4))
+        .route("/ip-consistency", get(get_ip_consistency))
+        .route("/interfaces", get(get_interfaces))
+        .route("/dns-update-records", get(get_dns_update_records))
+        .route("/dns-update-records/:id", get(get_dns_update_record))
+        .route("/domain-states", get(get_domain_states))
+        .route("/plan", get(get_plan))
+        .route("/drift", get(get_drift))
+        // Web界面的多语言文字
+        .route("/i18n", get(get_languages))
+        .route("/i18n/:lang", get(get_translations))
+        .route("/domains/status", get(get_domain_statuses))
+        .route("/domains/:name/diff", get(get_domain_diff))
+        // 立即同步单个域名（管理接口）
+        .route("/domains/:name/sync", post(sync_domain))
+        // 子域名设置的CSV导出/导入（导入需要 ADMIN_TOKEN）
+        .route("/domains/export.csv", get(export_domain_settings))
+        .route("/domains/import", post(import_domain_settings))
+        .route("/update-now", post(update_now))
+        // 外部系统触发立即更新（需要 WEBHOOK_TOKEN）
+        .route("/webhook/trigger", post(webhook_trigger))
+        // 监控服务的运行状态与启动/停止/立即检查（停止只影响本次运行；启动、停止和立即检查需要 ADMIN_TOKEN）
+        .route("/monitor", get(get_monitor_status))
+        .route("/monitor/start", post(start_monitor))
+        .route("/monitor/stop", post(stop_monitor))
+        .route("/monitor/trigger", post(trigger_monitor))
+        // 暂停/恢复定时检查（需要 ADMIN_TOKEN）
+        .route("/monitor/pause", get(get_monitor_pause).post(pause_monitor))
+        .route("/monitor/resume", post(resume_monitor))
+        .route("/status-events", get(get_status_events))
+        .route("/ownership", get(get_ownership))
+        // 子域名新增/软删除/恢复/清除，根域名使用 "@" 表示（需要 ADMIN_TOKEN）
+        .route("/subdomains", post(add_subdomain))
+        .route("/subdomains/deleted", get(get_deleted_subdomains))
+        .route("/subdomains/:name", delete(delete_subdomain))
+        .route("/subdomains/:name/restore", post(restore_subdomain))
+        .route("/subdomains/:name/purge", post(purge_subdomain))
+        // 局域网主机：子域名发布 委派前缀 + 主机后缀
+        .route("/lan-hosts", get(get_lan_hosts))
+        .route("/lan-hosts/discover", get(discover_lan_hosts))
+        .route("/lan-hosts/:name", put(put_lan_host).delete(delete_lan_host))
+        // 后台强制同步（需要 ADMIN_TOKEN），通过操作ID查询进度
+        .route("/operations", get(get_operations))
+        .route("/operations/sync", post(start_sync_operation))
+        .route("/operations/:id", get(get_operation))
+        // 管理接口（需要 ADMIN_TOKEN，或范围包含该接口的API令牌）
+        .route("/admin/tokens", get(get_api_tokens).post(create_api_token))
+        .route("/admin/tokens/:id", delete(revoke_api_token))
+        .route(
+            "/admin/simulate-failure",
+            get(get_simulated_failure)
+                .post(inject_simulated_failure)
+                .delete(clear_simulated_failure),
+        )
+        .route("/admin/backup", post(run_backup))
+        .route("/dns-records", get(list_dns_records).post(create_dns_record))
+        .route("/dns-records/:id", put(edit_dns_record).delete(delete_dns_record))
+        .route("/txt-records", get(list_txt_records).post(create_txt_record))
+        .route("/txt-records/history", get(get_txt_record_history))
+        .route("/txt-records/:id", put(update_txt_record).delete(delete_txt_record))
+        // ACME DNS-01 钩子（需要 ACME_TOKEN）
+        .route("/acme/present", post(acme_present))
+        .route("/acme/cleanup", post(acme_cleanup))
+        // 聊天机器人命令接口（需要 CHATOPS_TOKEN）
+        .route("/chatops", post(chatops_command))
+        .route(
+            "/debug/capture",
+            get(download_debug_capture)
+                .post(start_debug_capture)
+                .delete(stop_debug_capture),
+        )
+        .layer(middleware::from_fn(negotiate_version))
+}
+
 // 根路径处理器，返回主页面
 async fn index_handler() -> Html<&'static str> {
     Html(include_str!("../../static/index.html"))
@@ -0,0 +1,28 @@
+use axum::extract::FromRef;
+use crate::services::config_service::ConfigService;
+use crate::services::monitor_service::MonitorHandle;
+
+/// 路由共享的应用状态，处理器按需提取其中的服务
+#[derive(Clone)]
+pub struct AppState {
+    pub config_service: ConfigService,
+    pub monitor: MonitorHandle,
+}
+
+impl AppState {
+    pub fn new(config_service: ConfigService, monitor: MonitorHandle) -> Self {
+        Self { config_service, monitor }
+    }
+}
+
+impl FromRef<AppState> for ConfigService {
+    fn from_ref(state: &AppState) -> Self {
+        state.config_service.clone()
+    }
+}
+
+impl FromRef<AppState> for MonitorHandle {
+    fn from_ref(state: &AppState) -> Self {
+        state.monitor.clone()
+    }
+}
@@ -0,0 +1,48 @@
+use axum::{
+    extract::Request,
+    http::{header::HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use super::handlers::ApiResponse;
+
+/// 当前API版本
+pub const API_VERSION: &str = "1";
+/// 版本协商请求/响应头
+pub const API_VERSION_HEADER: HeaderName = HeaderName::from_static("x-api-version");
+
+/// 版本协商：客户端可通过 X-API-Version 请求头声明期望的版本，不支持时返回400；
+/// 所有响应都带上当前版本号
+pub async fn negotiate_version(req: Request, next: Next) -> Response {
+    if let Some(requested) = req.headers().get(&API_VERSION_HEADER) {
+        let requested = requested.to_str().unwrap_or_default().trim();
+        if requested != API_VERSION {
+            let mut response = (StatusCode::BAD_REQUEST, Json(ApiResponse::<()> {
+                success: false,
+                data: None,
+                message: Some(format!("不支持的API版本: {}，当前支持的版本: {}", requested, API_VERSION)),
+            })).into_response();
+            response.headers_mut().insert(API_VERSION_HEADER, HeaderValue::from_static(API_VERSION));
+            return response;
+        }
+    }
+
+    let mut response = next.run(req).await;
+    response.headers_mut().insert(API_VERSION_HEADER, HeaderValue::from_static(API_VERSION));
+    response
+}
+
+/// 旧版无版本前缀的 /api/* 路径：仍然可用，但在响应中标记为已弃用并指向 /api/v1 的新路径
+pub async fn deprecated_alias(req: Request, next: Next) -> Response {
+    // 嵌套路由中的路径已去掉 /api 前缀
+    let successor = format!("</api/v1{}>; rel=\"successor-version\"", req.uri().path());
+    let mut response = next.run(req).await;
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert("link", link);
+    }
+    response
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, warn};
+use crate::services::config_service::{normalize_subdomain, ConfigService};
+use crate::services::events::DomainEvent;
+use super::handlers::ApiResponse;
+
+/// 通过WebSocket实时推送单个域名的活动事件（同步、确认、警告、错误），
+/// 域名可填写子域名、完整域名或 "@"
+pub async fn domain_events_ws(
+    ws: WebSocketUpgrade,
+    State(service): State<ConfigService>,
+    Path(name): Path<String>,
+) -> Response {
+    let domain = match service.load_configuration() {
+        Ok(config) => match normalize_subdomain(&name, &config.root_domain) {
+            Ok(subdomain) => config.full_domain(&subdomain),
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, format!("无效的域名: {}", e)),
+        },
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("加载配置失败: {}", e)),
+    };
+
+    // 在升级前订阅，避免漏掉连接建立期间的事件
+    let events = service.domain_events().subscribe();
+    ws.on_upgrade(move |socket| stream_domain_events(socket, events, domain))
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(ApiResponse::<()> {
+        success: false,
+        data: None,
+        message: Some(message),
+    })).into_response()
+}
+
+async fn stream_domain_events(mut socket: WebSocket, mut events: Receiver<DomainEvent>, domain: String) {
+    debug!("🔌 开始推送域名事件: {}", domain);
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.domain == domain => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ 域名事件推送过慢，已丢弃 {} 条事件: {}", skipped, domain);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // 其他客户端消息忽略，ping由axum自动回复
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    debug!("🔌 停止推送域名事件: {}", domain);
+}
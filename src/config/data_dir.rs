@@ -0,0 +1,79 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// 数据库文件名
+pub const DB_FILE_NAME: &str = "config.db";
+/// 默认数据目录
+pub const DEFAULT_DATA_DIR: &str = "data";
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 设置数据目录，只在程序启动时调用一次
+pub fn init_data_dir(dir: impl Into<PathBuf>) -> Result<()> {
+    let dir = dir.into();
+    std::fs::create_dir_all(&dir)?;
+    let _ = DATA_DIR.set(dir);
+    Ok(())
+}
+
+/// 数据目录：优先使用 --data-dir 参数设置的目录，其次为 DATA_DIR 环境变量，默认 ./data
+pub fn data_dir() -> PathBuf {
+    DATA_DIR
+        .get()
+        .cloned()
+        .or_else(|| std::env::var("DATA_DIR").ok().filter(|d| !d.is_empty()).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DATA_DIR))
+}
+
+/// 数据库文件路径
+pub fn db_path() -> PathBuf {
+    data_dir().join(DB_FILE_NAME)
+}
+
+/// 将旧版本放在工作目录下的 config.db 迁移到数据目录
+/// 旧文件复制到新位置后重命名保留，作为迁移前的备份
+pub fn migrate_legacy_database() -> Result<()> {
+    let legacy = Path::new(DB_FILE_NAME);
+    let target = db_path();
+
+    if !legacy.exists() {
+        return Ok(());
+    }
+    // 数据目录就是工作目录时无需迁移
+    if same_file(legacy, &target) {
+        return Ok(());
+    }
+    if target.exists() {
+        warn!(
+            "⚠️ 工作目录下存在旧的 {}，但数据目录中已有数据库 {}，将使用数据目录中的数据库",
+            DB_FILE_NAME,
+            target.display()
+        );
+        return Ok(());
+    }
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(legacy, &target)?;
+    let backup = format!("{}.legacy-{}", DB_FILE_NAME, Utc::now().format("%Y%m%dT%H%M%SZ"));
+    std::fs::rename(legacy, &backup)?;
+
+    info!(
+        "📦 已将旧数据库 ./{} 迁移到 {}，原文件保留为 ./{}",
+        DB_FILE_NAME,
+        target.display(),
+        backup
+    );
+    Ok(())
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
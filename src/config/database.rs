@@ -1,8 +1,245 @@
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, params};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use tracing::{info, warn};
+use crate::config::write_queue::{self, WriteQueue, WriteStats, WriteStatsSnapshot};
+use crate::utils::address_selection::AddressSelection;
+use crate::utils::network::PrefixRule;
+use crate::utils::prefix_delegation;
+use crate::utils::time_format::{default_timezone, TimeFormatter, DEFAULT_LOCALE};
+use crate::utils::i18n::DEFAULT_LANGUAGE;
+
+/// Cloudflare认证方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthType {
+    /// API令牌（Authorization: Bearer）
+    #[default]
+    ApiToken,
+    /// 旧版Global API Key（X-Auth-Email + X-Auth-Key）
+    GlobalKey,
+}
+
+impl AuthType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthType::ApiToken => "api_token",
+            AuthType::GlobalKey => "global_key",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "global_key" => AuthType::GlobalKey,
+            _ => AuthType::ApiToken,
+        }
+    }
+}
+
+/// 备用主机名：除选中的子域名外，同时把当前IP发布到该域名，可位于其他区域或账户
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BackupHostname {
+    pub name: String,            // 完整域名，例如 home.backup-example.net
+    pub zone_id: String,         // 该域名所在的区域ID
+    #[serde(default)]
+    pub api_key: Option<String>, // 该区域的API令牌，留空时使用主区域的凭据
+    #[serde(default = "default_backup_ttl")]
+    pub ttl: u32,                // 记录的TTL（秒），1表示自动
+}
+
+pub fn default_backup_ttl() -> u32 {
+    60
+}
+
+/// MQTT代理的连接设置，IP变化和更新结果发布到 topic 下的子主题
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MqttSettings {
+    pub broker: String, // host:port，未指定端口时使用1883
+    #[serde(default = "default_mqtt_topic")]
+    pub topic: String,  // 主题前缀，例如 cloudflare-auto
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub retain: bool,   // 发布保留消息，订阅方连接后立即收到最近一次的内容
+    #[serde(default)]
+    pub home_assistant: bool, // 按Home Assistant的MQTT发现约定发布传感器
+    #[serde(default = "default_discovery_prefix")]
+    pub discovery_prefix: String, // Home Assistant的发现主题前缀
+}
+
+pub fn default_mqtt_topic() -> String {
+    "cloudflare-auto".to_string()
+}
+
+pub fn default_discovery_prefix() -> String {
+    "homeassistant".to_string()
+}
+
+/// 事件通知Webhook：按事件类型向任意地址发送HTTP请求
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NotifyWebhook {
+    pub url: String,
+    #[serde(default = "default_webhook_method")]
+    pub method: String, // GET、POST、PUT、PATCH或DELETE，GET请求不带请求体
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body_template: Option<String>, // JSON请求体模板，可用 {new_ip} 等占位符，未设置时发送完整的事件JSON
+    #[serde(default)]
+    pub events: Vec<String>, // 触发的事件类型，为空时为除每日统计外的所有事件
+}
+
+pub fn default_webhook_method() -> String {
+    "POST".to_string()
+}
+
+/// ntfy推送，可使用 ntfy.sh 或自建服务器
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct NtfySettings {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    #[serde(default)]
+    pub token: Option<String>, // 访问令牌，主题设置了访问控制时需要
+    #[serde(default)]
+    pub priority: Option<u8>, // 1-5，未设置时失败为4，其他为3
+}
+
+pub fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// Gotify推送
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GotifySettings {
+    pub server: String,
+    pub token: String, // 应用令牌
+    #[serde(default)]
+    pub priority: Option<u8>, // 0-10，未设置时失败为8，其他为5
+}
+
+/// Bark推送（iOS），可使用官方服务器或自建的bark-server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct BarkSettings {
+    #[serde(default = "default_bark_server")]
+    pub server: String,
+    pub device_key: String, // App中推送地址里的设备Key
+    #[serde(default)]
+    pub group: Option<String>, // 通知分组，默认为 cloudflare-auto
+    #[serde(default)]
+    pub sound: Option<String>, // 提示音，如 alarm、minuet
+}
+
+pub fn default_bark_server() -> String {
+    "https://api.day.app".to_string()
+}
+
+/// 钉钉自定义机器人
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DingTalkSettings {
+    pub webhook_url: String, // https://oapi.dingtalk.com/robot/send?access_token=...
+    #[serde(default)]
+    pub secret: Option<String>, // 安全设置为"加签"时的密钥，以 SEC 开头
+}
+
+/// Slack Incoming Webhook通知
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SlackSettings {
+    pub webhook_url: String,
+    #[serde(default)]
+    pub channel: Option<String>, // 覆盖Webhook默认的频道，如 #ops，仅旧版Webhook支持
+    #[serde(default)]
+    pub template: Option<String>, // 消息模板，可用 {title}、{old_ip} 等占位符，未设置时使用默认格式
+}
+
+/// Telegram机器人通知：IP变化、更新失败和恢复时发送消息
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TelegramSettings {
+    pub bot_token: String, // 从 @BotFather 获得，形如 123456:ABC-DEF
+    pub chat_id: String,   // 用户、群组ID或 @频道用户名
+    #[serde(default)]
+    pub daily_summary: bool, // 每24小时发送一次更新统计
+    #[serde(default)]
+    pub api_url: Option<String>, // 自建Bot API服务器或反向代理地址，默认 https://api.telegram.org
+}
+
+/// 判断是否需要更新记录的方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeDetection {
+    /// 当前IP与数据库中最后记录的IP不同时更新
+    #[default]
+    LastIp,
+    /// 还通过系统解析器解析各域名，解析结果不含当前IP时更新
+    Resolver,
+    /// 还通过公共DoH解析器解析各域名，解析结果不含当前IP时更新
+    Doh,
+}
+
+impl ChangeDetection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeDetection::LastIp => "last_ip",
+            ChangeDetection::Resolver => "resolver",
+            ChangeDetection::Doh => "doh",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "resolver" => ChangeDetection::Resolver,
+            "doh" => ChangeDetection::Doh,
+            _ => ChangeDetection::LastIp,
+        }
+    }
+}
+
+/// 当前IPv6地址的来源
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IpSource {
+    /// 本机网卡上的地址（按网卡、线路策略和地址选择策略选择）
+    #[default]
+    Local,
+    /// 通过外部HTTP服务获取公网视角下的地址，适用于本机地址不是对外可见地址的场景
+    External,
+    /// 通过STUN服务器获取公网视角下的地址，不依赖HTTP服务
+    Stun,
+    /// 执行 IP_COMMAND 环境变量中的命令，以其输出作为地址
+    Command,
+    /// 使用路由器通过DynDNS2接口（/nic/update）推送的地址
+    #[serde(rename = "dyndns")]
+    DynDns,
+}
+
+impl IpSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IpSource::Local => "local",
+            IpSource::External => "external",
+            IpSource::Stun => "stun",
+            IpSource::Command => "command",
+            IpSource::DynDns => "dyndns",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "external" => IpSource::External,
+            "stun" => IpSource::Stun,
+            "command" => IpSource::Command,
+            "dyndns" => IpSource::DynDns,
+            _ => IpSource::Local,
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
@@ -12,6 +249,208 @@ pub struct AppConfig {
     pub selected_subdomains: Vec<String>,
     pub check_interval: u64, // 检查间隔（秒）
     pub last_ip: Option<String>,
+    #[serde(default)]
+    pub auth_type: AuthType,
+    #[serde(default)]
+    pub auth_email: Option<String>, // Global API Key模式下使用的账户邮箱
+    #[serde(default = "default_timezone")]
+    pub display_timezone: String, // 展示时间使用的IANA时区
+    #[serde(default = "default_locale")]
+    pub display_locale: String, // 展示时间使用的语言格式
+    #[serde(default = "default_true")]
+    pub startup_check: bool, // 程序启动时是否执行首次检查
+    #[serde(default)]
+    pub startup_delay: u64, // 首次检查前的等待时间（秒）
+    #[serde(default)]
+    pub verify_external_ip: bool, // 是否通过外部服务校验公网IPv6与本地选择是否一致
+    #[serde(default)]
+    pub prefix_rules: Vec<PrefixRule>, // 多线路时接受的前缀及优先策略，为空时使用系统首选地址
+    #[serde(default)]
+    pub status_webhook_url: Option<String>, // 状态转换时推送事件的Webhook地址
+    #[serde(default)]
+    pub domain_proxied: BTreeMap<String, bool>, // 各子域名是否通过Cloudflare代理，未配置的保留记录原状态
+    #[serde(default)]
+    pub uptime_kuma_push_url: Option<String>, // 每个周期结束后推送心跳的Uptime Kuma Push地址
+    #[serde(default)]
+    pub record_comment: Option<String>, // 写入受管记录的标记备注，例如 "managed by cloudflare-auto"
+    #[serde(default)]
+    pub cleanup_duplicates: bool, // 是否删除同名的多余AAAA记录，只保留指向当前IP的一条
+    #[serde(default)]
+    pub burst_interval: u64, // IP变化后突发检查的间隔（秒），0表示不启用
+    #[serde(default = "default_burst_duration")]
+    pub burst_duration: u64, // 突发检查持续的时间（分钟）
+    #[serde(default)]
+    pub round_robin: bool, // 轮询模式：同名的其他AAAA记录属于其他主机，只更新本机维护的记录
+    #[serde(default)]
+    pub ownership_enabled: bool, // 在记录备注中写入本实例的归属标记，不修改其他实例的记录
+    #[serde(default)]
+    pub proxied_default: bool, // 新建记录默认是否通过Cloudflare代理，domain_proxied中的配置优先
+    #[serde(default)]
+    pub dry_run: bool, // 演练模式：只记录将要做的修改，不调用Cloudflare的写入接口
+    #[serde(default)]
+    pub https_records: bool, // 同时维护同名HTTPS记录，使其ipv6hint与当前IP一致
+    #[serde(default = "default_flap_threshold")]
+    pub flap_threshold: u32, // 时间窗口内在同两个地址间来回切换的次数达到该值时告警IP抖动，0表示不检测
+    #[serde(default = "default_flap_window_hours")]
+    pub flap_window_hours: u64, // 统计IP抖动的时间窗口（小时）
+    #[serde(default)]
+    pub reconcile_interval: u64, // 对账间隔（分钟）：定期读取所有记录并修复在本程序之外被修改的记录，0表示不启用
+    #[serde(default = "default_ui_language")]
+    pub ui_language: String, // 默认界面语言，浏览器未选择语言时使用
+    #[serde(default)]
+    pub verify_propagation: bool, // 写入记录后通过公共DoH解析器确认新地址已生效，并在更新历史中记录是否确认及耗时
+    #[serde(default)]
+    pub change_detection: ChangeDetection, // 判断是否需要更新的方式：last_ip 只比对最后记录的IP，resolver/doh 还会解析各域名与当前IP比对
+    #[serde(default)]
+    pub backup_hostname: Option<BackupHostname>, // 备用主机名：同时发布当前IP的另一个域名，主区域API故障时客户端可改用该名称
+    #[serde(default = "default_update_parallelism")]
+    pub update_parallelism: u32, // 同时处理的域名数，域名较多时可加快更新
+    #[serde(default = "default_http_connect_timeout")]
+    pub http_connect_timeout: u64, // 连接Cloudflare API的超时（秒）
+    #[serde(default = "default_http_request_timeout")]
+    pub http_request_timeout: u64, // 单次Cloudflare API请求的总超时（秒），包括读取响应
+    #[serde(default)]
+    pub proxy_url: Option<String>, // 访问Cloudflare API使用的代理，支持 http://、https://、socks5:// 和 socks5h://
+    #[serde(default)]
+    pub ca_cert_path: Option<String>, // 额外信任的根证书（PEM文件路径），用于TLS拦截或私有API网关
+    #[serde(default)]
+    pub interface: Option<String>, // 检测地址使用的网卡，未设置时使用系统访问公网的首选地址
+    #[serde(default)]
+    pub address_selection: AddressSelection, // 多个IPv6地址时选择发布哪一个：策略、是否排除ULA、地址匹配条件
+    #[serde(default)]
+    pub ip_source: IpSource, // IP来源：local 使用本机网卡地址，external/stun 通过外部HTTP服务或STUN服务器获取公网视角下的地址，command 执行自定义命令
+    #[serde(default)]
+    pub external_ip_services: Vec<String>, // 外部IP检测服务，按顺序尝试，为空时使用内置列表
+    #[serde(default)]
+    pub stun_servers: Vec<String>, // STUN服务器（host:port），按顺序尝试，为空时使用内置列表
+    #[serde(default = "default_delegated_prefix_len")]
+    pub delegated_prefix_len: u8, // 委派前缀长度（48-64），主机地址由当前地址的该长度前缀与主机后缀拼接
+    #[serde(default)]
+    pub host_suffixes: BTreeMap<String, String>, // 各子域名对应的局域网主机后缀（读取自 lan_hosts 表），配置后发布 委派前缀 + 后缀 而不是本机地址
+    #[serde(default)]
+    pub multi_address: bool, // 发布所有符合条件的地址：每个地址一条AAAA记录，消失的地址对应的记录会被删除
+    #[serde(default)]
+    pub allowed_prefix: Option<String>, // 只发布该前缀内的地址（CIDR），未设置时不限制
+    #[serde(default)]
+    pub mqtt: Option<MqttSettings>, // MQTT代理：发布IP变化和更新结果
+    #[serde(default)]
+    pub telegram: Option<TelegramSettings>, // Telegram机器人通知
+    #[serde(default)]
+    pub discord_webhook_url: Option<String>, // IP变化、更新失败和恢复时推送消息的Discord Webhook地址
+    #[serde(default)]
+    pub slack: Option<SlackSettings>, // Slack通知
+    #[serde(default)]
+    pub notify_webhooks: Vec<NotifyWebhook>, // IP变化、更新成功和失败时调用的Webhook
+    #[serde(default)]
+    pub ntfy: Option<NtfySettings>, // ntfy推送
+    #[serde(default)]
+    pub gotify: Option<GotifySettings>, // Gotify推送
+    #[serde(default)]
+    pub wecom_webhook_url: Option<String>, // IP变化、更新失败和恢复时推送消息的企业微信群机器人Webhook地址
+    #[serde(default)]
+    pub dingtalk: Option<DingTalkSettings>, // IP变化、更新失败和恢复时推送消息的钉钉自定义机器人
+    #[serde(default)]
+    pub bark: Option<BarkSettings>, // IP变化、更新失败和恢复时推送到iPhone的Bark设置
+    #[serde(default)]
+    pub serverchan_key: Option<String>, // IP变化、更新失败和恢复时推送消息的Server酱SendKey
+    #[serde(default)]
+    pub pushplus_token: Option<String>, // IP变化、更新失败和恢复时推送消息的PushPlus令牌
+}
+
+fn default_locale() -> String {
+    DEFAULT_LOCALE.to_string()
+}
+
+fn default_ui_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub fn default_burst_duration() -> u64 {
+    10
+}
+
+pub fn default_flap_threshold() -> u32 {
+    4
+}
+
+pub fn default_flap_window_hours() -> u64 {
+    24
+}
+
+pub fn default_update_parallelism() -> u32 {
+    5
+}
+
+pub fn default_http_connect_timeout() -> u64 {
+    10
+}
+
+pub fn default_http_request_timeout() -> u64 {
+    30
+}
+
+pub fn default_delegated_prefix_len() -> u8 {
+    64
+}
+
+impl AppConfig {
+    /// 拼接子域名的完整域名，空子域名表示根域名
+    pub fn full_domain(&self, subdomain: &str) -> String {
+        if subdomain.is_empty() {
+            self.root_domain.clone()
+        } else {
+            format!("{}.{}", subdomain, self.root_domain)
+        }
+    }
+
+    /// 子域名配置的代理状态
+    pub fn proxied_for(&self, subdomain: &str) -> Option<bool> {
+        self.domain_proxied.get(subdomain).copied()
+    }
+
+    /// 子域名要发布的地址：配置了主机后缀时为当前地址的委派前缀与后缀拼接，否则为当前地址
+    pub fn host_address(&self, subdomain: &str, ip: IpAddr) -> IpAddr {
+        let (IpAddr::V6(v6), Some(suffix)) = (ip, self.host_suffixes.get(subdomain)) else {
+            return ip;
+        };
+        match prefix_delegation::parse_host_suffix(suffix, self.delegated_prefix_len) {
+            Ok(suffix) => IpAddr::V6(prefix_delegation::combine(v6, self.delegated_prefix_len, suffix)),
+            Err(e) => {
+                warn!("⚠️ 子域名 {} 的主机后缀无效，改用当前地址: {}", subdomain, e);
+                ip
+            }
+        }
+    }
+
+    /// 同 host_address，地址以字符串表示，无法解析时原样返回
+    pub fn host_ip(&self, subdomain: &str, current_ip: &str) -> String {
+        match current_ip.parse() {
+            Ok(ip) => self.host_address(subdomain, ip).to_string(),
+            Err(_) => current_ip.to_string(),
+        }
+    }
+
+    /// 新建子域名记录时使用的代理状态：优先使用子域名的配置，否则使用全局默认值
+    pub fn proxied_for_new(&self, subdomain: &str) -> bool {
+        self.proxied_for(subdomain).unwrap_or(self.proxied_default)
+    }
+
+    /// 是否处于演练模式，配置项或环境变量 DRY_RUN=1/true 任一开启即生效
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+            || std::env::var("DRY_RUN")
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+                .unwrap_or(false)
+    }
+
+    /// 按配置的时区和语言创建时间格式化器，配置无效时使用默认值
+    pub fn time_formatter(&self) -> TimeFormatter {
+        TimeFormatter::new(&self.display_timezone, &self.display_locale).unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -23,17 +462,269 @@ pub struct DnsUpdateRecord {
     pub domain_count: i32,
     pub success_count: i32,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub error_code: Option<u32>, // Cloudflare错误码（如 9109、81057）
+    #[serde(default)]
+    pub actions: ActionCounts, // 本次各类处理结果的域名数
+    #[serde(default)]
+    pub dry_run: bool, // 演练模式下的记录，只统计将要做的修改，并未实际写入
+    #[serde(default)]
+    pub verified: Option<bool>, // 写入的新地址是否都已在公共DNS中生效，未确认时为None
+    #[serde(default)]
+    pub verify_latency_ms: Option<u64>, // 最慢一个域名从写入到确认生效的毫秒数
+    #[serde(default)]
+    pub timestamp_display: Option<String>, // 按配置时区格式化的展示时间
+}
+
+/// 待写入的一条DNS更新记录
+#[derive(Debug, Clone, Default)]
+pub struct NewDnsUpdateRecord<'a> {
+    pub old_ip: Option<&'a str>,
+    pub new_ip: &'a str,
+    pub domain_count: i32,
+    pub success_count: i32,
+    pub error_message: Option<&'a str>,
+    pub error_code: Option<u32>,
+    pub actions: ActionCounts,
+    pub dry_run: bool,
+    pub verified: Option<bool>,
+    pub verify_latency_ms: Option<u64>,
+}
+
+/// 一次更新中各类处理结果的域名数，失败数为 domain_count - success_count
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ActionCounts {
+    pub created: i32,   // 新建了记录
+    pub updated: i32,   // 修改了已有记录
+    pub unchanged: i32, // 记录已是最新，无需修改
+}
+
+impl ActionCounts {
+    pub fn add(&mut self, other: &ActionCounts) {
+        self.created += other.created;
+        self.updated += other.updated;
+        self.unchanged += other.unchanged;
+    }
+}
+
+/// 历史记录的累计统计，用于Prometheus指标
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UpdateTotals {
+    pub runs: i64,
+    pub created: i64,
+    pub updated: i64,
+    pub unchanged: i64,
+    pub failed: i64,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_failure: Option<(i64, DateTime<Utc>)>, // 最近一条有失败域名的更新记录的ID与时间
+}
+
+/// 单个域名的本地同步状态
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DomainState {
+    pub domain: String,
+    pub record_id: Option<String>,
+    pub content: Option<String>,
+    pub cf_created_on: Option<DateTime<Utc>>, // Cloudflare记录创建时间
+    pub cf_modified_on: Option<DateTime<Utc>>, // Cloudflare记录最后修改时间
+    pub updated_at: DateTime<Utc>, // 本地最后一次观察或写入的时间
+    pub warning: Option<String>, // 无法同步的原因，例如同名的CNAME记录
+    #[serde(default)]
+    pub last_success_at: Option<DateTime<Utc>>, // 最后一次成功同步（写入或确认已是最新）的时间
+    #[serde(default)]
+    pub last_error: Option<String>, // 最近一次处理失败的原因，成功后保留以便排查
+    #[serde(default)]
+    pub last_error_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub cf_modified_on_display: Option<String>,
+    #[serde(default)]
+    pub updated_at_display: Option<String>,
+}
+
+/// 本地登记的记录归属
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordOwnership {
+    pub domain: String,
+    pub record_id: Option<String>,
+    pub owner: String,
+    pub status: String, // owned: 由owner实例维护; conflict: 属于其他实例，本实例已跳过
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub updated_at_display: Option<String>,
+}
+
+/// TXT记录变更历史
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TxtRecordChange {
+    pub id: i64,
+    pub timestamp: DateTime<Utc>,
+    pub action: String, // create / update / delete
+    pub name: String,
+    pub record_id: Option<String>,
+    pub old_content: Option<String>,
+    pub new_content: Option<String>,
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub timestamp_display: Option<String>,
+}
+
+/// 长时间操作中单个域名的错误
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OperationError {
+    pub domain: String,
+    pub message: String,
+}
+
+/// 长时间操作及其进度
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Operation {
+    pub id: String,
+    pub kind: String,
+    pub status: String, // pending / running / succeeded / failed
+    pub params: serde_json::Value,
+    pub total: i64,
+    pub done: i64,
+    pub errors: Vec<OperationError>,
+    pub message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 限定范围、会过期的API令牌，数据库中只保存令牌的SHA-256摘要
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>, // 允许调用的接口，如 "POST /domains/home/sync"
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// 局域网主机：子域名发布 委派前缀 + 该主机的后缀（接口标识）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LanHost {
+    pub subdomain: String,
+    pub suffix: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 定时检查的暂停状态
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PauseState {
+    pub paused_at: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>, // 为None时一直暂停，直到手动恢复
+    pub reason: Option<String>,
+}
+
+impl PauseState {
+    /// 在指定时间是否仍处于暂停中
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.until.is_none_or(|until| now < until)
+    }
+}
+
+/// 服务状态转换事件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusEvent {
+    pub id: i64,
+    pub from_status: String,
+    pub to_status: String,
+    pub reason: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub timestamp_display: Option<String>,
 }
 
+/// 已软删除的子域名
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletedSubdomain {
+    pub subdomain: String,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// 内存中最多暂存的更新记录数
+const MAX_QUEUED_UPDATE_RECORDS: usize = 100;
+
 #[derive(Clone)]
 pub struct Database {
     conn: Arc<Mutex<Connection>>,
+    pending: Arc<PendingWrites>,
+}
+
+/// 数据库繁忙、重试后仍无法写入而暂存在内存中的写入，下次写入时补写
+struct PendingWrites {
+    last_ip: Mutex<Option<String>>, // 只保留最新的IP
+    update_records: WriteQueue<UpdateRecordRow>,
+    stats: WriteStats,
+}
+
+/// 待写入的更新记录，记录时间在生成时确定，补写时保持不变
+struct UpdateRecordRow {
+    timestamp: String,
+    old_ip: Option<String>,
+    new_ip: String,
+    domain_count: i32,
+    success_count: i32,
+    error_message: Option<String>,
+    error_code: Option<u32>,
+    actions: ActionCounts,
+    dry_run: bool,
+    verified: Option<bool>,
+    verify_latency_ms: Option<u64>,
+}
+
+impl From<&NewDnsUpdateRecord<'_>> for UpdateRecordRow {
+    fn from(record: &NewDnsUpdateRecord) -> Self {
+        Self {
+            timestamp: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            old_ip: record.old_ip.map(str::to_string),
+            new_ip: record.new_ip.to_string(),
+            domain_count: record.domain_count,
+            success_count: record.success_count,
+            error_message: record.error_message.map(str::to_string),
+            error_code: record.error_code,
+            actions: record.actions,
+            dry_run: record.dry_run,
+            verified: record.verified,
+            verify_latency_ms: record.verify_latency_ms,
+        }
+    }
+}
+
+impl UpdateRecordRow {
+    fn insert(&self, conn: &Connection) -> rusqlite::Result<()> {
+        conn.execute(
+            "INSERT INTO dns_update_records (
+                timestamp, old_ip, new_ip, domain_count, success_count, error_message, error_code,
+                created_count, updated_count, unchanged_count, dry_run, verified, verify_latency_ms
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                self.timestamp,
+                self.old_ip,
+                self.new_ip,
+                self.domain_count,
+                self.success_count,
+                self.error_message,
+                self.error_code,
+                self.actions.created,
+                self.actions.updated,
+                self.actions.unchanged,
+                self.dry_run,
+                self.verified,
+                self.verify_latency_ms
+            ],
+        )?;
+        Ok(())
+    }
 }
 
 impl Database {
-    pub fn new() -> Result<Self> {
-        let db_path = "config.db";
-        let conn = Connection::open(db_path)?;
+    /// 打开指定路径的数据库并完成建表与升级，":memory:" 表示内存数据库（用于测试）
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path)?;
         
         // 创建配置表
         conn.execute(
@@ -62,41 +753,524 @@ impl Database {
             )",
             [],
         )?;
+
+        // 创建域名同步状态表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS domain_states (
+                domain TEXT PRIMARY KEY,
+                record_id TEXT,
+                content TEXT,
+                cf_created_on TEXT,
+                cf_modified_on TEXT,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // 创建受管子域名表（用于软删除与恢复）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS managed_subdomains (
+                subdomain TEXT PRIMARY KEY,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                deleted_at DATETIME
+            )",
+            [],
+        )?;
+
+        // 创建服务状态转换事件表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS status_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                from_status TEXT NOT NULL,
+                to_status TEXT NOT NULL,
+                reason TEXT,
+                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // 创建实例信息表（实例ID等）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS instance_meta (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // 创建记录归属表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS record_ownership (
+                domain TEXT PRIMARY KEY,
+                record_id TEXT,
+                owner TEXT NOT NULL,
+                status TEXT NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // 创建TXT记录变更历史表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS txt_record_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
+                action TEXT NOT NULL,
+                name TEXT NOT NULL,
+                record_id TEXT,
+                old_content TEXT,
+                new_content TEXT,
+                error_message TEXT
+            )",
+            [],
+        )?;
+
+        // 创建长时间操作表，作为持久化的任务队列，重启后可继续未完成的操作
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS operations (
+                id TEXT PRIMARY KEY,
+                kind TEXT NOT NULL,
+                status TEXT NOT NULL,
+                params TEXT NOT NULL DEFAULT '{}',
+                total INTEGER NOT NULL DEFAULT 0,
+                done INTEGER NOT NULL DEFAULT 0,
+                errors TEXT NOT NULL DEFAULT '[]',
+                message TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+
+        // 创建API令牌表
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS api_tokens (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                scopes TEXT NOT NULL DEFAULT '[]',
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                expires_at TEXT NOT NULL,
+                last_used_at TEXT
+            )",
+            [],
+        )?;
+
+        // 旧版本数据库升级：补充新增的配置列
+        Self::add_column_if_missing(&conn, "config", "auth_type", "TEXT DEFAULT 'api_token'")?;
+        Self::add_column_if_missing(&conn, "config", "auth_email", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "display_timezone", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "display_locale", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "startup_check", "INTEGER DEFAULT 1")?;
+        Self::add_column_if_missing(&conn, "config", "startup_delay", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "verify_external_ip", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "prefix_rules", "TEXT DEFAULT '[]'")?;
+        Self::add_column_if_missing(&conn, "config", "status_webhook_url", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "domain_proxied", "TEXT DEFAULT '{}'")?;
+        Self::add_column_if_missing(&conn, "config", "uptime_kuma_push_url", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "record_comment", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "cleanup_duplicates", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "burst_interval", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "burst_duration", "INTEGER DEFAULT 10")?;
+        Self::add_column_if_missing(&conn, "config", "round_robin", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "ownership_enabled", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "proxied_default", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "dry_run", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "https_records", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "flap_threshold", "INTEGER DEFAULT 4")?;
+        Self::add_column_if_missing(&conn, "config", "flap_window_hours", "INTEGER DEFAULT 24")?;
+        Self::add_column_if_missing(&conn, "config", "reconcile_interval", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "ui_language", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "verify_propagation", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "config", "change_detection", "TEXT DEFAULT 'last_ip'")?;
+        Self::add_column_if_missing(&conn, "config", "backup_hostname", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "update_parallelism", "INTEGER DEFAULT 5")?;
+        Self::add_column_if_missing(&conn, "config", "http_connect_timeout", "INTEGER DEFAULT 10")?;
+        Self::add_column_if_missing(&conn, "config", "http_request_timeout", "INTEGER DEFAULT 30")?;
+        Self::add_column_if_missing(&conn, "config", "proxy_url", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "ca_cert_path", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "interface", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "address_selection", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "ip_source", "TEXT DEFAULT 'local'")?;
+        Self::add_column_if_missing(&conn, "config", "external_ip_services", "TEXT DEFAULT '[]'")?;
+        Self::add_column_if_missing(&conn, "config", "stun_servers", "TEXT DEFAULT '[]'")?;
+        Self::add_column_if_missing(&conn, "config", "delegated_prefix_len", "INTEGER DEFAULT 64")?;
+        Self::add_column_if_missing(&conn, "config", "host_suffixes", "TEXT DEFAULT '{}'")?;
+        Self::add_column_if_missing(&conn, "config", "multi_address", "INTEGER DEFAULT 0")?;
+        // 最近一次发布的其他地址（发布所有地址时），不随配置保存
+        Self::add_column_if_missing(&conn, "config", "additional_ips", "TEXT DEFAULT '[]'")?;
+        Self::add_column_if_missing(&conn, "config", "pushed_ip", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "allowed_prefix", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "mqtt", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "telegram", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "discord_webhook_url", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "slack", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "notify_webhooks", "TEXT DEFAULT '[]'")?;
+        Self::add_column_if_missing(&conn, "config", "ntfy", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "gotify", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "wecom_webhook_url", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "dingtalk", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "bark", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "serverchan_key", "TEXT")?;
+        Self::add_column_if_missing(&conn, "config", "pushplus_token", "TEXT")?;
+
+        // 局域网主机后缀表；早期版本保存在配置的 host_suffixes 列中，迁移后清空该列
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS lan_hosts (
+                subdomain TEXT PRIMARY KEY,
+                suffix TEXT NOT NULL,
+                description TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        conn.execute(
+            "INSERT OR IGNORE INTO lan_hosts (subdomain, suffix)
+             SELECT j.key, j.value FROM config, json_each(config.host_suffixes) AS j
+             WHERE json_valid(config.host_suffixes)",
+            [],
+        )?;
+        conn.execute("UPDATE config SET host_suffixes = '{}' WHERE host_suffixes != '{}'", [])?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "error_code", "INTEGER")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "created_count", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "updated_count", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "unchanged_count", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "dry_run", "INTEGER DEFAULT 0")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "verified", "INTEGER")?;
+        Self::add_column_if_missing(&conn, "dns_update_records", "verify_latency_ms", "INTEGER")?;
+        Self::add_column_if_missing(&conn, "domain_states", "warning", "TEXT")?;
+        Self::add_column_if_missing(&conn, "domain_states", "last_success_at", "TEXT")?;
+        Self::add_column_if_missing(&conn, "domain_states", "last_error", "TEXT")?;
+        Self::add_column_if_missing(&conn, "domain_states", "last_error_at", "TEXT")?;
         
-        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+        conn.busy_timeout(write_queue::BUSY_TIMEOUT)?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            pending: Arc::new(PendingWrites {
+                last_ip: Mutex::new(None),
+                update_records: WriteQueue::new(MAX_QUEUED_UPDATE_RECORDS),
+                stats: WriteStats::default(),
+            }),
+        })
+    }
+
+    /// 如果表中不存在指定列则添加
+    fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|name| name.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        }
+
+        Ok(())
+    }
+
+    /// 执行写入：先补写之前暂存的写入，数据库繁忙时按退避间隔重试
+    fn write<T>(&self, mut op: impl FnMut(&Connection) -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+        write_queue::retry(&self.conn, &self.pending.stats, |conn| {
+            self.flush_pending(conn)?;
+            op(conn)
+        })
+    }
+
+    fn flush_pending(&self, conn: &Connection) -> rusqlite::Result<()> {
+        let written = self.pending.update_records.flush(|row| row.insert(conn))?;
+        let mut last_ip = self.pending.last_ip.lock().unwrap();
+        if let Some(ip) = last_ip.as_deref() {
+            conn.execute("UPDATE config SET last_ip = ?1", params![ip])?;
+            info!("💾 已补写暂存的最后IP: {}", ip);
+            *last_ip = None;
+        }
+        if written > 0 {
+            info!("💾 已补写 {} 条暂存的更新记录", written);
+        }
+        Ok(())
+    }
+
+    /// 暂存尚未写入的最后IP，读取配置时优先使用
+    fn pending_last_ip(&self) -> Option<String> {
+        self.pending.last_ip.lock().unwrap().clone()
+    }
+
+    /// 补写暂存的写入，数据库仍然繁忙时继续暂存
+    pub fn flush_pending_writes(&self) -> Result<()> {
+        if self.pending.update_records.is_empty() && self.pending.last_ip.lock().unwrap().is_none() {
+            return Ok(());
+        }
+        self.write(|_| Ok(()))?;
+        Ok(())
+    }
+
+    /// 写入重试、暂存与丢弃的统计
+    pub fn write_stats(&self) -> WriteStatsSnapshot {
+        let queued = self.pending.update_records.len() + usize::from(self.pending.last_ip.lock().unwrap().is_some());
+        self.pending.stats.snapshot(queued)
+    }
+
+    /// 生成数据库的一致性快照（VACUUM INTO），用于备份
+    /// 目标文件须不存在或为空文件，写入已有的空文件时保留其权限
+    pub fn snapshot_to(&self, path: &Path) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+        Ok(())
     }
 
     /// 保存配置
     pub fn save_config(&self, config: &AppConfig) -> Result<()> {
         let subdomains_json = serde_json::to_string(&config.selected_subdomains)
             .unwrap_or_else(|_| "[]".to_string());
+        let prefix_rules_json = serde_json::to_string(&config.prefix_rules)
+            .unwrap_or_else(|_| "[]".to_string());
+        let domain_proxied_json = serde_json::to_string(&config.domain_proxied)
+            .unwrap_or_else(|_| "{}".to_string());
+        let backup_hostname_json = config.backup_hostname.as_ref().and_then(|b| serde_json::to_string(b).ok());
+        let mqtt_json = config.mqtt.as_ref().and_then(|m| serde_json::to_string(m).ok());
+        let telegram_json = config.telegram.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let slack_json = config.slack.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let ntfy_json = config.ntfy.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let gotify_json = config.gotify.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let dingtalk_json = config.dingtalk.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let bark_json = config.bark.as_ref().and_then(|v| serde_json::to_string(v).ok());
+        let address_selection_json = serde_json::to_string(&config.address_selection)
+            .unwrap_or_else(|_| "{}".to_string());
+        let external_ip_services_json = serde_json::to_string(&config.external_ip_services)
+            .unwrap_or_else(|_| "[]".to_string());
+        let stun_servers_json = serde_json::to_string(&config.stun_servers)
+            .unwrap_or_else(|_| "[]".to_string());
+        let notify_webhooks_json = serde_json::to_string(&config.notify_webhooks)
+            .unwrap_or_else(|_| "[]".to_string());
         
-        let conn = self.conn.lock().unwrap();
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        
+        // 同步子域名的软删除状态
+        Self::sync_managed_subdomains(&tx, &config.selected_subdomains)?;
+        
+        // 最近发布的其他地址和路由器推送的地址是运行时状态，不在配置中，重写配置行时保留
+        let (additional_ips, pushed_ip): (Option<String>, Option<String>) = tx
+            .query_row("SELECT additional_ips, pushed_ip FROM config LIMIT 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?
+            .unwrap_or_default();
         
         // 先删除旧配置
-        conn.execute("DELETE FROM config", [])?;
+        tx.execute("DELETE FROM config", [])?;
         
         // 插入新配置
-        conn.execute(
+        tx.execute(
             "INSERT INTO config (
                 cloudflare_api_key, 
                 cloudflare_zone_id, 
                 root_domain, 
                 selected_subdomains, 
                 check_interval, 
-                last_ip
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                last_ip,
+                auth_type,
+                auth_email,
+                display_timezone,
+                display_locale,
+                startup_check,
+                startup_delay,
+                verify_external_ip,
+                prefix_rules,
+                status_webhook_url,
+                domain_proxied,
+                uptime_kuma_push_url,
+                record_comment,
+                cleanup_duplicates,
+                burst_interval,
+                burst_duration,
+                round_robin,
+                ownership_enabled,
+                proxied_default,
+                dry_run,
+                https_records,
+                flap_threshold,
+                flap_window_hours,
+                reconcile_interval,
+                ui_language,
+                verify_propagation,
+                change_detection,
+                backup_hostname,
+                update_parallelism,
+                http_connect_timeout,
+                http_request_timeout,
+                proxy_url,
+                ca_cert_path,
+                interface,
+                address_selection,
+                ip_source,
+                external_ip_services,
+                stun_servers,
+                delegated_prefix_len,
+                multi_address,
+                allowed_prefix,
+                mqtt,
+                telegram,
+                discord_webhook_url,
+                slack,
+                notify_webhooks,
+                ntfy,
+                gotify,
+                wecom_webhook_url,
+                dingtalk,
+                bark,
+                serverchan_key,
+                pushplus_token
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32, ?33, ?34, ?35, ?36, ?37, ?38, ?39, ?40, ?41, ?42, ?43, ?44, ?45, ?46, ?47, ?48, ?49, ?50, ?51, ?52, ?53, ?54, ?55, ?56, ?57, ?58)",
             params![
                 config.cloudflare_api_key,
                 config.cloudflare_zone_id,
                 config.root_domain,
                 subdomains_json,
                 config.check_interval,
-                config.last_ip
+                config.last_ip,
+                config.auth_type.as_str(),
+                config.auth_email,
+                config.display_timezone,
+                config.display_locale,
+                config.startup_check,
+                config.startup_delay,
+                config.verify_external_ip,
+                prefix_rules_json,
+                config.status_webhook_url,
+                domain_proxied_json,
+                config.uptime_kuma_push_url,
+                config.record_comment,
+                config.cleanup_duplicates,
+                config.burst_interval,
+                config.burst_duration,
+                config.round_robin,
+                config.ownership_enabled,
+                config.proxied_default,
+                config.dry_run,
+                config.https_records,
+                config.flap_threshold,
+                config.flap_window_hours,
+                config.reconcile_interval,
+                config.ui_language,
+                config.verify_propagation,
+                config.change_detection.as_str(),
+                backup_hostname_json,
+                config.update_parallelism,
+                config.http_connect_timeout,
+                config.http_request_timeout,
+                config.proxy_url,
+                config.ca_cert_path,
+                config.interface,
+                address_selection_json,
+                config.ip_source.as_str(),
+                external_ip_services_json,
+                stun_servers_json,
+                config.delegated_prefix_len,
+                config.multi_address,
+                config.allowed_prefix,
+                mqtt_json,
+                telegram_json,
+                config.discord_webhook_url,
+                slack_json,
+                notify_webhooks_json,
+                ntfy_json,
+                gotify_json,
+                config.wecom_webhook_url,
+                dingtalk_json,
+                bark_json,
+                config.serverchan_key,
+                config.pushplus_token
             ],
         )?;
+        tx.execute(
+            "UPDATE config SET additional_ips = COALESCE(?1, '[]'), pushed_ip = ?2",
+            params![additional_ips, pushed_ip],
+        )?;
+        
+        tx.commit()?;
+        // 保存的配置已包含最后IP，丢弃暂存的旧值
+        *self.pending.last_ip.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// 同步受管子域名：从选中列表移除的子域名标记为已删除，重新选中的则恢复
+    fn sync_managed_subdomains(conn: &Connection, selected: &[String]) -> Result<()> {
+        // 确保之前选中的子域名都有记录（兼容升级前的数据）
+        let previous: Option<String> = conn
+            .query_row("SELECT selected_subdomains FROM config LIMIT 1", [], |row| row.get(0))
+            .ok();
+        let previous: Vec<String> = previous
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        for subdomain in &previous {
+            conn.execute(
+                "INSERT OR IGNORE INTO managed_subdomains (subdomain) VALUES (?1)",
+                params![subdomain],
+            )?;
+        }
+        
+        for subdomain in selected {
+            conn.execute(
+                "INSERT INTO managed_subdomains (subdomain) VALUES (?1)
+                 ON CONFLICT(subdomain) DO UPDATE SET deleted_at = NULL",
+                params![subdomain],
+            )?;
+        }
+        
+        let active: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT subdomain FROM managed_subdomains WHERE deleted_at IS NULL")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        for subdomain in active.iter().filter(|s| !selected.contains(s)) {
+            conn.execute(
+                "UPDATE managed_subdomains SET deleted_at = CURRENT_TIMESTAMP WHERE subdomain = ?1",
+                params![subdomain],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    /// 获取已软删除的子域名
+    pub fn get_deleted_subdomains(&self) -> Result<Vec<DeletedSubdomain>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT subdomain, deleted_at FROM managed_subdomains
+             WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            Ok(DeletedSubdomain {
+                subdomain: row.get(0)?,
+                deleted_at: parse_db_timestamp(&row.get::<_, String>(1)?),
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        
+        Ok(result)
+    }
+
+    /// 彻底清除已软删除的子域名及其同步状态
+    pub fn purge_subdomain(&self, subdomain: &str, full_domain: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = tx.execute(
+            "DELETE FROM managed_subdomains WHERE subdomain = ?1 AND deleted_at IS NOT NULL",
+            params![subdomain],
+        )?;
+        if deleted > 0 {
+            tx.execute("DELETE FROM domain_states WHERE domain = ?1", params![full_domain])?;
+            tx.execute("DELETE FROM lan_hosts WHERE subdomain = ?1", params![subdomain])?;
+        }
+        tx.commit()?;
         
-        Ok(())
+        Ok(deleted > 0)
     }
 
     /// 加载配置
@@ -109,7 +1283,59 @@ impl Database {
                 root_domain, 
                 selected_subdomains, 
                 check_interval, 
-                last_ip 
+                last_ip,
+                auth_type,
+                auth_email,
+                display_timezone,
+                display_locale,
+                startup_check,
+                startup_delay,
+                verify_external_ip,
+                prefix_rules,
+                status_webhook_url,
+                domain_proxied,
+                uptime_kuma_push_url,
+                record_comment,
+                cleanup_duplicates,
+                burst_interval,
+                burst_duration,
+                round_robin,
+                ownership_enabled,
+                proxied_default,
+                dry_run,
+                https_records,
+                flap_threshold,
+                flap_window_hours,
+                reconcile_interval,
+                ui_language,
+                verify_propagation,
+                change_detection,
+                backup_hostname,
+                update_parallelism,
+                http_connect_timeout,
+                http_request_timeout,
+                proxy_url,
+                ca_cert_path,
+                interface,
+                address_selection,
+                ip_source,
+                external_ip_services,
+                stun_servers,
+                delegated_prefix_len,
+                multi_address,
+                allowed_prefix,
+                mqtt,
+                telegram,
+                discord_webhook_url,
+                slack,
+                notify_webhooks,
+                ntfy,
+                gotify,
+                wecom_webhook_url,
+                dingtalk,
+                bark,
+                serverchan_key,
+                pushplus_token
              FROM config LIMIT 1"
         )?;
         
@@ -117,6 +1343,14 @@ impl Database {
             let subdomains_json: String = row.get(3)?;
             let selected_subdomains: Vec<String> = serde_json::from_str(&subdomains_json)
                 .unwrap_or_else(|_| Vec::new());
+            let prefix_rules: Vec<PrefixRule> = row
+                .get::<_, Option<String>>(13)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            let domain_proxied: BTreeMap<String, bool> = row
+                .get::<_, Option<String>>(15)?
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
             
             Ok(AppConfig {
                 cloudflare_api_key: row.get(0)?,
@@ -125,10 +1359,72 @@ impl Database {
                 selected_subdomains,
                 check_interval: row.get(4)?,
                 last_ip: row.get(5)?,
+                auth_type: AuthType::parse(&row.get::<_, Option<String>>(6)?.unwrap_or_default()),
+                auth_email: row.get(7)?,
+                display_timezone: row.get::<_, Option<String>>(8)?.unwrap_or_else(default_timezone),
+                display_locale: row.get::<_, Option<String>>(9)?.unwrap_or_else(default_locale),
+                startup_check: row.get::<_, Option<bool>>(10)?.unwrap_or(true),
+                startup_delay: row.get::<_, Option<u64>>(11)?.unwrap_or(0),
+                verify_external_ip: row.get::<_, Option<bool>>(12)?.unwrap_or(false),
+                prefix_rules,
+                status_webhook_url: row.get(14)?,
+                domain_proxied,
+                uptime_kuma_push_url: row.get(16)?,
+                record_comment: row.get(17)?,
+                cleanup_duplicates: row.get::<_, Option<bool>>(18)?.unwrap_or(false),
+                burst_interval: row.get::<_, Option<u64>>(19)?.unwrap_or(0),
+                burst_duration: row.get::<_, Option<u64>>(20)?.unwrap_or_else(default_burst_duration),
+                round_robin: row.get::<_, Option<bool>>(21)?.unwrap_or(false),
+                ownership_enabled: row.get::<_, Option<bool>>(22)?.unwrap_or(false),
+                proxied_default: row.get::<_, Option<bool>>(23)?.unwrap_or(false),
+                dry_run: row.get::<_, Option<bool>>(24)?.unwrap_or(false),
+                https_records: row.get::<_, Option<bool>>(25)?.unwrap_or(false),
+                flap_threshold: row.get::<_, Option<u32>>(26)?.unwrap_or_else(default_flap_threshold),
+                flap_window_hours: row.get::<_, Option<u64>>(27)?.unwrap_or_else(default_flap_window_hours),
+                reconcile_interval: row.get::<_, Option<u64>>(28)?.unwrap_or(0),
+                ui_language: row.get::<_, Option<String>>(29)?.unwrap_or_else(default_ui_language),
+                verify_propagation: row.get::<_, Option<bool>>(30)?.unwrap_or(false),
+                change_detection: ChangeDetection::parse(&row.get::<_, Option<String>>(31)?.unwrap_or_default()),
+                backup_hostname: row.get::<_, Option<String>>(32)?.and_then(|json| serde_json::from_str(&json).ok()),
+                update_parallelism: row.get::<_, Option<u32>>(33)?.unwrap_or_else(default_update_parallelism),
+                http_connect_timeout: row.get::<_, Option<u64>>(34)?.unwrap_or_else(default_http_connect_timeout),
+                http_request_timeout: row.get::<_, Option<u64>>(35)?.unwrap_or_else(default_http_request_timeout),
+                proxy_url: row.get(36)?,
+                ca_cert_path: row.get(37)?,
+                interface: row.get(38)?,
+                address_selection: row.get::<_, Option<String>>(39)?.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                ip_source: IpSource::parse(&row.get::<_, Option<String>>(40)?.unwrap_or_default()),
+                external_ip_services: row.get::<_, Option<String>>(41)?.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                stun_servers: row.get::<_, Option<String>>(42)?.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                delegated_prefix_len: row.get::<_, Option<u8>>(43)?.unwrap_or_else(default_delegated_prefix_len),
+                host_suffixes: BTreeMap::new(),
+                multi_address: row.get::<_, Option<bool>>(44)?.unwrap_or(false),
+                allowed_prefix: row.get(45)?,
+                mqtt: row.get::<_, Option<String>>(46)?.and_then(|json| serde_json::from_str(&json).ok()),
+                telegram: row.get::<_, Option<String>>(47)?.and_then(|json| serde_json::from_str(&json).ok()),
+                discord_webhook_url: row.get(48)?,
+                slack: row.get::<_, Option<String>>(49)?.and_then(|json| serde_json::from_str(&json).ok()),
+                notify_webhooks: row.get::<_, Option<String>>(50)?.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default(),
+                ntfy: row.get::<_, Option<String>>(51)?.and_then(|json| serde_json::from_str(&json).ok()),
+                gotify: row.get::<_, Option<String>>(52)?.and_then(|json| serde_json::from_str(&json).ok()),
+                wecom_webhook_url: row.get(53)?,
+                dingtalk: row.get::<_, Option<String>>(54)?.and_then(|json| serde_json::from_str(&json).ok()),
+                bark: row.get::<_, Option<String>>(55)?.and_then(|json| serde_json::from_str(&json).ok()),
+                serverchan_key: row.get(56)?,
+                pushplus_token: row.get(57)?,
             })
         })?;
+        drop(stmt);
+        let host_suffixes = Self::query_lan_hosts(&conn, "", params![])?
+            .into_iter()
+            .map(|host| (host.subdomain, host.suffix))
+            .collect();
         
-        Ok(config)
+        Ok(AppConfig {
+            last_ip: self.pending_last_ip().or(config.last_ip),
+            host_suffixes,
+            ..config
+        })
     }
 
     /// 检查是否有配置
@@ -141,17 +1437,49 @@ impl Database {
         count > 0
     }
 
-    /// 更新最后记录的IP地址
+    /// 更新最后记录的IP地址，数据库繁忙时暂存，下次写入时补写
     pub fn update_last_ip(&self, ip: &str) -> Result<()> {
+        match self.write(|conn| conn.execute("UPDATE config SET last_ip = ?1", params![ip])) {
+            Err(e) if write_queue::is_busy(&e) => {
+                warn!("⚠️ 数据库繁忙，最后IP暂存在内存中: {}", ip);
+                *self.pending.last_ip.lock().unwrap() = Some(ip.to_string());
+                Ok(())
+            }
+            result => result.map(|_| ()).map_err(Into::into),
+        }
+    }
+
+    /// 更新最近一次发布的其他地址（发布所有地址时，除last_ip外的地址）
+    pub fn update_additional_ips(&self, ips: &[String]) -> Result<()> {
+        let json = serde_json::to_string(ips)?;
+        self.write(|conn| conn.execute("UPDATE config SET additional_ips = ?1", params![json]))?;
+        Ok(())
+    }
+
+    /// 获取最近一次发布的其他地址
+    pub fn get_additional_ips(&self) -> Result<Vec<String>> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "UPDATE config SET last_ip = ?1",
-            params![ip],
-        )?;
-        
+        let json: Option<String> = conn
+            .query_row("SELECT additional_ips FROM config LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        Ok(json.and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default())
+    }
+
+    /// 保存路由器通过DynDNS接口推送的地址
+    pub fn update_pushed_ip(&self, ip: &str) -> Result<()> {
+        self.write(|conn| conn.execute("UPDATE config SET pushed_ip = ?1", params![ip]))?;
         Ok(())
     }
 
+    /// 获取路由器最近一次推送的地址
+    pub fn get_pushed_ip(&self) -> Result<Option<String>> {
+        let conn = self.conn.lock().unwrap();
+        let ip: Option<Option<String>> = conn
+            .query_row("SELECT pushed_ip FROM config LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        Ok(ip.flatten())
+    }
+
     /// 获取最后记录的IP地址
     pub fn get_last_ip(&self) -> Result<Option<String>> {
         let conn = self.conn.lock().unwrap();
@@ -159,53 +1487,101 @@ impl Database {
         
         let last_ip: Option<String> = stmt.query_row([], |row| row.get(0))?;
         
-        Ok(last_ip)
+        Ok(self.pending_last_ip().or(last_ip))
     }
 
-    /// 添加DNS更新记录
-    pub fn add_dns_update_record(
-        &self,
-        old_ip: Option<String>,
-        new_ip: &str,
-        domain_count: i32,
-        success_count: i32,
-        error_message: Option<String>,
-    ) -> Result<()> {
+    /// 添加DNS更新记录，数据库繁忙时暂存，下次写入时补写
+    pub fn add_dns_update_record(&self, record: &NewDnsUpdateRecord) -> Result<()> {
+        let row = UpdateRecordRow::from(record);
+        match self.write(|conn| row.insert(conn)) {
+            Err(e) if write_queue::is_busy(&e) => {
+                warn!("⚠️ 数据库繁忙，更新记录暂存在内存中");
+                self.pending.update_records.push(row, &self.pending.stats);
+                Ok(())
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// 统计所有更新记录的累计结果，不含演练模式的记录
+    pub fn get_update_totals(&self) -> Result<UpdateTotals> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO dns_update_records (old_ip, new_ip, domain_count, success_count, error_message) 
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![old_ip, new_ip, domain_count, success_count, error_message],
+        let totals = conn.query_row(
+            "SELECT COUNT(*),
+                COALESCE(SUM(created_count), 0),
+                COALESCE(SUM(updated_count), 0),
+                COALESCE(SUM(unchanged_count), 0),
+                COALESCE(SUM(domain_count - success_count), 0),
+                MAX(timestamp)
+             FROM dns_update_records WHERE dry_run = 0",
+            [],
+            |row| {
+                Ok(UpdateTotals {
+                    runs: row.get(0)?,
+                    created: row.get(1)?,
+                    updated: row.get(2)?,
+                    unchanged: row.get(3)?,
+                    failed: row.get(4)?,
+                    last_run: row.get::<_, Option<String>>(5)?.map(|t| parse_db_timestamp(&t)),
+                    last_failure: None,
+                })
+            },
         )?;
-        
-        Ok(())
+        let last_failure = conn
+            .query_row(
+                "SELECT id, timestamp FROM dns_update_records
+                 WHERE dry_run = 0 AND domain_count > success_count
+                 ORDER BY timestamp DESC, id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, parse_db_timestamp(&row.get::<_, String>(1)?))),
+            )
+            .optional()?;
+        Ok(UpdateTotals { last_failure, ..totals })
     }
 
     /// 获取所有DNS更新记录，按时间倒序排列
     pub fn get_dns_update_records(&self, limit: Option<i32>) -> Result<Vec<DnsUpdateRecord>> {
-        let conn = self.conn.lock().unwrap();
-        let mut query = "
-            SELECT id, timestamp, old_ip, new_ip, domain_count, success_count, error_message 
-            FROM dns_update_records 
-            ORDER BY timestamp DESC
-        ".to_string();
-        
+        let mut clause = "ORDER BY timestamp DESC, id DESC".to_string();
         if let Some(limit) = limit {
-            query.push_str(&format!(" LIMIT {}", limit));
+            clause.push_str(&format!(" LIMIT {}", limit));
         }
+        self.query_dns_update_records(&clause, params![])
+    }
+
+    /// 按ID获取单条DNS更新记录
+    pub fn get_dns_update_record(&self, id: i64) -> Result<Option<DnsUpdateRecord>> {
+        Ok(self.query_dns_update_records("WHERE id = ?1", params![id])?.pop())
+    }
+
+    fn query_dns_update_records(&self, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<DnsUpdateRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let query = format!(
+            "SELECT id, timestamp, old_ip, new_ip, domain_count, success_count, error_message, error_code,
+                created_count, updated_count, unchanged_count, dry_run, verified, verify_latency_ms
+            FROM dns_update_records {}",
+            clause
+        );
         
         let mut stmt = conn.prepare(&query)?;
-        let records = stmt.query_map([], |row| {
+        let records = stmt.query_map(args, |row| {
             Ok(DnsUpdateRecord {
                 id: row.get(0)?,
-                timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
+                timestamp: parse_db_timestamp(&row.get::<_, String>(1)?),
                 old_ip: row.get(2)?,
                 new_ip: row.get(3)?,
                 domain_count: row.get(4)?,
                 success_count: row.get(5)?,
                 error_message: row.get(6)?,
+                error_code: row.get(7)?,
+                actions: ActionCounts {
+                    created: row.get::<_, Option<i32>>(8)?.unwrap_or(0),
+                    updated: row.get::<_, Option<i32>>(9)?.unwrap_or(0),
+                    unchanged: row.get::<_, Option<i32>>(10)?.unwrap_or(0),
+                },
+                dry_run: row.get::<_, Option<bool>>(11)?.unwrap_or(false),
+                verified: row.get(12)?,
+                verify_latency_ms: row.get(13)?,
+                timestamp_display: None,
             })
         })?;
         
@@ -217,8 +1593,765 @@ impl Database {
         Ok(result)
     }
 
+    /// 添加服务状态转换事件
+    pub fn add_status_event(&self, from_status: &str, to_status: &str, reason: Option<&str>) -> Result<()> {
+        self.write(|conn| conn.execute(
+            "INSERT INTO status_events (from_status, to_status, reason) VALUES (?1, ?2, ?3)",
+            params![from_status, to_status, reason],
+        ))?;
+        Ok(())
+    }
+
+    /// 获取最近的服务状态转换事件
+    pub fn get_status_events(&self, limit: i32) -> Result<Vec<StatusEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, from_status, to_status, reason, timestamp FROM status_events
+             ORDER BY id DESC LIMIT ?1"
+        )?;
+        
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(StatusEvent {
+                id: row.get(0)?,
+                from_status: row.get(1)?,
+                to_status: row.get(2)?,
+                reason: row.get(3)?,
+                timestamp: parse_db_timestamp(&row.get::<_, String>(4)?),
+                timestamp_display: None,
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        
+        Ok(result)
+    }
+
+    /// 本实例的ID，首次调用时随机生成并持久保存
+    pub fn instance_id(&self) -> Result<String> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT OR IGNORE INTO instance_meta (key, value) VALUES ('instance_id', lower(hex(randomblob(6))))",
+            [],
+        )?;
+        let id = conn.query_row(
+            "SELECT value FROM instance_meta WHERE key = 'instance_id'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// 读取保存的暂停状态
+    pub fn get_pause_state(&self) -> Result<Option<PauseState>> {
+        let conn = self.conn.lock().unwrap();
+        let value: Option<String> = conn
+            .query_row("SELECT value FROM instance_meta WHERE key = 'pause'", [], |row| row.get(0))
+            .optional()?;
+        Ok(value.and_then(|json| serde_json::from_str(&json).ok()))
+    }
+
+    /// 保存暂停状态，None表示已恢复
+    pub fn save_pause_state(&self, state: Option<&PauseState>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        match state {
+            Some(state) => conn.execute(
+                "INSERT INTO instance_meta (key, value) VALUES ('pause', ?1)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+                params![serde_json::to_string(state)?],
+            )?,
+            None => conn.execute("DELETE FROM instance_meta WHERE key = 'pause'", [])?,
+        };
+        Ok(())
+    }
+
+    /// 登记域名记录的归属
+    pub fn save_ownership(&self, domain: &str, record_id: Option<&str>, owner: &str, status: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO record_ownership (domain, record_id, owner, status, updated_at)
+             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)
+             ON CONFLICT(domain) DO UPDATE SET
+                record_id = COALESCE(excluded.record_id, record_ownership.record_id),
+                owner = excluded.owner,
+                status = excluded.status,
+                updated_at = CURRENT_TIMESTAMP",
+            params![domain, record_id, owner, status],
+        )?;
+        Ok(())
+    }
+
+    /// 获取本地登记的所有记录归属
+    pub fn get_ownership(&self) -> Result<Vec<RecordOwnership>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT domain, record_id, owner, status, updated_at FROM record_ownership ORDER BY domain"
+        )?;
+        
+        let rows = stmt.query_map([], |row| {
+            Ok(RecordOwnership {
+                domain: row.get(0)?,
+                record_id: row.get(1)?,
+                owner: row.get(2)?,
+                status: row.get(3)?,
+                updated_at: parse_db_timestamp(&row.get::<_, String>(4)?),
+                updated_at_display: None,
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        
+        Ok(result)
+    }
+
+    /// 添加TXT记录变更历史
+    pub fn add_txt_record_change(
+        &self,
+        action: &str,
+        name: &str,
+        record_id: Option<&str>,
+        old_content: Option<&str>,
+        new_content: Option<&str>,
+        error_message: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO txt_record_history (action, name, record_id, old_content, new_content, error_message)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![action, name, record_id, old_content, new_content, error_message],
+        )?;
+        Ok(())
+    }
+
+    /// 获取最近的TXT记录变更历史
+    pub fn get_txt_record_changes(&self, limit: i32) -> Result<Vec<TxtRecordChange>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, action, name, record_id, old_content, new_content, error_message
+             FROM txt_record_history ORDER BY id DESC LIMIT ?1"
+        )?;
+        
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(TxtRecordChange {
+                id: row.get(0)?,
+                timestamp: parse_db_timestamp(&row.get::<_, String>(1)?),
+                action: row.get(2)?,
+                name: row.get(3)?,
+                record_id: row.get(4)?,
+                old_content: row.get(5)?,
+                new_content: row.get(6)?,
+                error_message: row.get(7)?,
+                timestamp_display: None,
+            })
+        })?;
+        
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+        
+        Ok(result)
+    }
+
+    /// 创建长时间操作，返回随机生成的操作ID
+    pub fn create_operation(&self, kind: &str, params: &serde_json::Value, total: i64) -> Result<String> {
+        let conn = self.conn.lock().unwrap();
+        let id = conn.query_row(
+            "INSERT INTO operations (id, kind, status, params, total)
+             VALUES (lower(hex(randomblob(8))), ?1, 'pending', ?2, ?3) RETURNING id",
+            params![kind, params.to_string(), total],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// 更新操作状态与进度，message为None时保留原值
+    pub fn update_operation(
+        &self,
+        id: &str,
+        status: &str,
+        done: i64,
+        errors: &[OperationError],
+        message: Option<&str>,
+    ) -> Result<()> {
+        let errors_json = serde_json::to_string(errors).unwrap_or_else(|_| "[]".to_string());
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE operations SET status = ?2, done = ?3, errors = ?4,
+                message = COALESCE(?5, message), updated_at = CURRENT_TIMESTAMP
+             WHERE id = ?1",
+            params![id, status, done, errors_json, message],
+        )?;
+        Ok(())
+    }
+
+    /// 获取单个操作
+    pub fn get_operation(&self, id: &str) -> Result<Option<Operation>> {
+        Ok(self.query_operations("WHERE id = ?1", params![id])?.into_iter().next())
+    }
+
+    /// 获取最近的操作
+    pub fn get_operations(&self, limit: i64) -> Result<Vec<Operation>> {
+        self.query_operations("ORDER BY created_at DESC, rowid DESC LIMIT ?1", params![limit])
+    }
+
+    /// 获取尚未完成的操作（程序重启前被中断的操作）
+    pub fn get_unfinished_operations(&self) -> Result<Vec<Operation>> {
+        self.query_operations("WHERE status IN ('pending', 'running') ORDER BY rowid", params![])
+    }
+
+    fn query_operations(&self, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<Operation>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, kind, status, params, total, done, errors, message, created_at, updated_at
+             FROM operations {}",
+            clause
+        ))?;
+
+        let rows = stmt.query_map(args, |row| {
+            Ok(Operation {
+                id: row.get(0)?,
+                kind: row.get(1)?,
+                status: row.get(2)?,
+                params: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                total: row.get(4)?,
+                done: row.get(5)?,
+                errors: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                message: row.get(7)?,
+                created_at: parse_db_timestamp(&row.get::<_, String>(8)?),
+                updated_at: parse_db_timestamp(&row.get::<_, String>(9)?),
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+
+        Ok(result)
+    }
+
+    /// 保存新生成的API令牌，返回令牌信息
+    pub fn create_api_token(&self, name: &str, token_hash: &str, scopes: &[String], expires_at: DateTime<Utc>) -> Result<ApiToken> {
+        let id: String = {
+            let conn = self.conn.lock().unwrap();
+            conn.query_row(
+                "INSERT INTO api_tokens (id, name, token_hash, scopes, expires_at)
+                 VALUES (lower(hex(randomblob(8))), ?1, ?2, ?3, ?4) RETURNING id",
+                params![name, token_hash, serde_json::to_string(scopes)?, expires_at.to_rfc3339()],
+                |row| row.get(0),
+            )?
+        };
+        self.query_api_tokens("WHERE id = ?1", params![id])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("API令牌保存失败"))
+    }
+
+    /// 按摘要查找API令牌（包括已过期的）
+    pub fn find_api_token(&self, token_hash: &str) -> Result<Option<ApiToken>> {
+        Ok(self.query_api_tokens("WHERE token_hash = ?1", params![token_hash])?.into_iter().next())
+    }
+
+    /// 获取所有API令牌，最新的在前
+    pub fn get_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        self.query_api_tokens("ORDER BY created_at DESC, rowid DESC", params![])
+    }
+
+    /// 吊销API令牌，返回是否存在
+    pub fn delete_api_token(&self, id: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM api_tokens WHERE id = ?1", params![id])? > 0)
+    }
+
+    /// 记录API令牌的最近使用时间
+    pub fn touch_api_token(&self, id: &str, used_at: DateTime<Utc>) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("UPDATE api_tokens SET last_used_at = ?2 WHERE id = ?1", params![id, used_at.to_rfc3339()])?;
+        Ok(())
+    }
+
+    fn query_api_tokens(&self, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<ApiToken>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, name, scopes, created_at, expires_at, last_used_at FROM api_tokens {}",
+            clause
+        ))?;
+
+        let rows = stmt.query_map(args, |row| {
+            Ok(ApiToken {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                scopes: serde_json::from_str(&row.get::<_, String>(2)?).unwrap_or_default(),
+                created_at: parse_db_timestamp(&row.get::<_, String>(3)?),
+                expires_at: parse_db_timestamp(&row.get::<_, String>(4)?),
+                last_used_at: row.get::<_, Option<String>>(5)?.as_deref().map(parse_db_timestamp),
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+
+        Ok(result)
+    }
+
+    /// 获取所有局域网主机，按子域名排序
+    pub fn get_lan_hosts(&self) -> Result<Vec<LanHost>> {
+        let conn = self.conn.lock().unwrap();
+        Self::query_lan_hosts(&conn, "", params![])
+    }
+
+    /// 新增或修改局域网主机，返回保存后的记录
+    pub fn upsert_lan_host(&self, subdomain: &str, suffix: &str, description: Option<&str>) -> Result<LanHost> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO lan_hosts (subdomain, suffix, description) VALUES (?1, ?2, ?3)
+             ON CONFLICT(subdomain) DO UPDATE SET
+                suffix = excluded.suffix,
+                description = excluded.description,
+                updated_at = CURRENT_TIMESTAMP",
+            params![subdomain, suffix, description],
+        )?;
+        Self::query_lan_hosts(&conn, "WHERE subdomain = ?1", params![subdomain])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("局域网主机保存失败"))
+    }
+
+    /// 删除局域网主机，返回是否存在
+    pub fn delete_lan_host(&self, subdomain: &str) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        Ok(conn.execute("DELETE FROM lan_hosts WHERE subdomain = ?1", params![subdomain])? > 0)
+    }
+
+    fn query_lan_hosts(conn: &Connection, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<LanHost>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT subdomain, suffix, description, created_at, updated_at FROM lan_hosts {} ORDER BY subdomain",
+            clause
+        ))?;
+        let rows = stmt.query_map(args, |row| {
+            Ok(LanHost {
+                subdomain: row.get(0)?,
+                suffix: row.get(1)?,
+                description: row.get(2)?,
+                created_at: parse_db_timestamp(&row.get::<_, String>(3)?),
+                updated_at: parse_db_timestamp(&row.get::<_, String>(4)?),
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for row in rows {
+            result.push(row?);
+        }
+
+        Ok(result)
+    }
+
     /// 获取最近的DNS更新记录
     pub fn get_recent_dns_update_records(&self, count: i32) -> Result<Vec<DnsUpdateRecord>> {
         self.get_dns_update_records(Some(count))
     }
-}
\ No newline at end of file
+
+    /// 保存域名同步状态（记录Cloudflare侧的创建/修改时间）
+    pub fn save_domain_state(
+        &self,
+        domain: &str,
+        record_id: &str,
+        content: &str,
+        cf_created_on: Option<DateTime<Utc>>,
+        cf_modified_on: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.write(|conn| conn.execute(
+            "INSERT INTO domain_states (domain, record_id, content, cf_created_on, cf_modified_on, updated_at, last_success_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+             ON CONFLICT(domain) DO UPDATE SET
+                record_id = excluded.record_id,
+                content = excluded.content,
+                cf_created_on = excluded.cf_created_on,
+                cf_modified_on = excluded.cf_modified_on,
+                warning = NULL,
+                updated_at = CURRENT_TIMESTAMP,
+                last_success_at = CURRENT_TIMESTAMP",
+            params![
+                domain,
+                record_id,
+                content,
+                cf_created_on.map(|t| t.to_rfc3339()),
+                cf_modified_on.map(|t| t.to_rfc3339())
+            ],
+        ))?;
+
+        Ok(())
+    }
+
+    /// 记录域名无法同步的原因，保留已有的记录信息
+    pub fn save_domain_warning(&self, domain: &str, warning: &str) -> Result<()> {
+        self.write(|conn| conn.execute(
+            "INSERT INTO domain_states (domain, warning, updated_at)
+             VALUES (?1, ?2, CURRENT_TIMESTAMP)
+             ON CONFLICT(domain) DO UPDATE SET
+                warning = excluded.warning,
+                updated_at = CURRENT_TIMESTAMP",
+            params![domain, warning],
+        ))?;
+
+        Ok(())
+    }
+
+    /// 记录域名最近一次处理失败的原因，保留已有的记录信息
+    pub fn save_domain_error(&self, domain: &str, error: &str) -> Result<()> {
+        self.write(|conn| conn.execute(
+            "INSERT INTO domain_states (domain, last_error, last_error_at)
+             VALUES (?1, ?2, CURRENT_TIMESTAMP)
+             ON CONFLICT(domain) DO UPDATE SET
+                last_error = excluded.last_error,
+                last_error_at = excluded.last_error_at",
+            params![domain, error],
+        ))?;
+
+        Ok(())
+    }
+
+    /// 获取单个域名的同步状态
+    pub fn get_domain_state(&self, domain: &str) -> Result<Option<DomainState>> {
+        Ok(self
+            .query_domain_states("WHERE domain = ?1", params![domain])?
+            .into_iter()
+            .next())
+    }
+
+    /// 获取所有域名的同步状态
+    pub fn get_domain_states(&self) -> Result<Vec<DomainState>> {
+        self.query_domain_states("ORDER BY domain", params![])
+    }
+
+    fn query_domain_states(&self, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<DomainState>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT domain, record_id, content, cf_created_on, cf_modified_on, updated_at, warning,
+                    last_success_at, last_error, last_error_at
+             FROM domain_states {}",
+            clause
+        ))?;
+
+        let states = stmt.query_map(args, |row| {
+            Ok(DomainState {
+                domain: row.get(0)?,
+                record_id: row.get(1)?,
+                content: row.get(2)?,
+                cf_created_on: row.get::<_, Option<String>>(3)?.map(|t| parse_db_timestamp(&t)),
+                cf_modified_on: row.get::<_, Option<String>>(4)?.map(|t| parse_db_timestamp(&t)),
+                updated_at: parse_db_timestamp(&row.get::<_, String>(5)?),
+                warning: row.get(6)?,
+                last_success_at: row.get::<_, Option<String>>(7)?.map(|t| parse_db_timestamp(&t)),
+                last_error: row.get(8)?,
+                last_error_at: row.get::<_, Option<String>>(9)?.map(|t| parse_db_timestamp(&t)),
+                cf_modified_on_display: None,
+                updated_at_display: None,
+            })
+        })?;
+
+        let mut result = Vec::new();
+        for state in states {
+            result.push(state?);
+        }
+
+        Ok(result)
+    }
+}
+
+/// 解析数据库中的时间戳，SQLite的CURRENT_TIMESTAMP为UTC的 "YYYY-MM-DD HH:MM:SS" 格式
+fn parse_db_timestamp(value: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .map(|naive| naive.and_utc())
+        })
+        .unwrap_or_else(|_| Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn memory_db() -> Database {
+        Database::new(":memory:").unwrap()
+    }
+
+    fn sample_config() -> AppConfig {
+        serde_json::from_value(json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["", "home", "nas"],
+            "check_interval": 120,
+            "last_ip": "2001:db8::1",
+            "auth_type": "global_key",
+            "auth_email": "admin@example.com",
+            "display_timezone": "Asia/Shanghai",
+            "startup_check": false,
+            "startup_delay": 30,
+            "domain_proxied": { "home": true },
+            "record_comment": "managed by cloudflare-auto",
+            "cleanup_duplicates": true,
+            "burst_interval": 15,
+            "burst_duration": 5,
+            "round_robin": true,
+            "ownership_enabled": true,
+            "proxied_default": true,
+            "dry_run": true,
+            "https_records": true,
+            "flap_threshold": 4,
+            "flap_window_hours": 24,
+            "reconcile_interval": 0,
+            "ui_language": "zh-CN",
+            "verify_propagation": false,
+            "change_detection": "last_ip",
+            "backup_hostname": null,
+            "update_parallelism": 5,
+            "http_connect_timeout": 10,
+            "http_request_timeout": 30,
+            "proxy_url": null,
+            "ca_cert_path": null,
+            "interface": null,
+            "address_selection": {"strategy": "system", "exclude_ula": false, "match": null},
+            "ip_source": "local",
+            "external_ip_services": [],
+            "stun_servers": [],
+            "delegated_prefix_len": 56,
+            "multi_address": true,
+            "allowed_prefix": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_config_roundtrip() {
+        let db = memory_db();
+        assert!(!db.has_config());
+
+        let config = sample_config();
+        db.save_config(&config).unwrap();
+        let loaded = db.load_config().unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&config).unwrap()
+        );
+
+        // 局域网主机保存在单独的表中，加载配置时合并
+        db.upsert_lan_host("nas", "::1:0:0:0:10", Some("NAS")).unwrap();
+        let loaded = db.load_config().unwrap();
+        assert_eq!(loaded.host_ip("nas", "2001:db8:aa00:1::1"), "2001:db8:aa00:1::10");
+        assert_eq!(loaded.host_ip("home", "2001:db8:aa00:1::1"), "2001:db8:aa00:1::1");
+        assert_eq!(db.upsert_lan_host("nas", "::1:0:0:0:11", None).unwrap().suffix, "::1:0:0:0:11");
+
+        db.update_last_ip("2001:db8::2").unwrap();
+        assert_eq!(db.get_last_ip().unwrap().as_deref(), Some("2001:db8::2"));
+        db.update_pushed_ip("2001:db8::9").unwrap();
+        db.update_additional_ips(&["2001:db8:1::2".to_string()]).unwrap();
+
+        // 再次保存覆盖旧配置，移除的子域名进入软删除列表
+        let mut config = loaded;
+        config.selected_subdomains = vec!["home".to_string()];
+        db.save_config(&config).unwrap();
+        assert_eq!(db.load_config().unwrap().selected_subdomains, ["home"]);
+        // 运行时状态不随配置覆盖
+        assert_eq!(db.get_pushed_ip().unwrap().as_deref(), Some("2001:db8::9"));
+        assert_eq!(db.get_additional_ips().unwrap(), ["2001:db8:1::2"]);
+        let mut deleted: Vec<_> = db.get_deleted_subdomains().unwrap().into_iter().map(|d| d.subdomain).collect();
+        deleted.sort();
+        assert_eq!(deleted, ["", "nas"]);
+        assert!(db.purge_subdomain("nas", "nas.example.com").unwrap());
+        assert!(!db.purge_subdomain("home", "home.example.com").unwrap());
+    }
+
+    #[test]
+    fn test_history_queries() {
+        let db = memory_db();
+        for i in 0..5 {
+            let new_ip = format!("2001:db8::{}", i);
+            db.add_dns_update_record(&NewDnsUpdateRecord {
+                new_ip: &new_ip,
+                domain_count: 3,
+                success_count: 2,
+                error_message: Some("失败"),
+                error_code: Some(81057),
+                actions: ActionCounts { created: 1, updated: 0, unchanged: 1 },
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        let records = db.get_recent_dns_update_records(3).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].new_ip, "2001:db8::4");
+        assert_eq!(records[0].error_code, Some(81057));
+        assert_eq!(records[0].actions, ActionCounts { created: 1, updated: 0, unchanged: 1 });
+        assert_eq!(db.get_dns_update_records(None).unwrap().len(), 5);
+
+        let totals = db.get_update_totals().unwrap();
+        assert_eq!((totals.runs, totals.created, totals.unchanged, totals.failed), (5, 5, 5, 5));
+        assert!(totals.last_run.is_some());
+
+        db.add_status_event("unknown", "in_sync", None).unwrap();
+        db.add_status_event("in_sync", "failing", Some("获取当前IP失败")).unwrap();
+        let events = db.get_status_events(10).unwrap();
+        assert_eq!(events[0].to_status, "failing");
+        assert_eq!(events[1].from_status, "unknown");
+
+        db.add_txt_record_change("create", "_acme-challenge.example.com", Some("r1"), None, Some("abc"), None)
+            .unwrap();
+        db.add_txt_record_change("delete", "_acme-challenge.example.com", Some("r1"), Some("abc"), None, None)
+            .unwrap();
+        let changes = db.get_txt_record_changes(1).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].action, "delete");
+    }
+
+    #[test]
+    fn test_domain_state_and_ownership() {
+        let db = memory_db();
+        db.save_domain_warning("home.example.com", "CNAME冲突").unwrap();
+        let state = db.get_domain_state("home.example.com").unwrap().unwrap();
+        assert_eq!(state.record_id, None);
+        assert_eq!(state.warning.as_deref(), Some("CNAME冲突"));
+
+        // 成功写入记录后清除警告
+        db.save_domain_state("home.example.com", "r1", "2001:db8::1", None, None).unwrap();
+        let state = db.get_domain_state("home.example.com").unwrap().unwrap();
+        assert_eq!(state.record_id.as_deref(), Some("r1"));
+        assert_eq!(state.warning, None);
+        assert!(state.last_success_at.is_some());
+
+        // 失败原因单独记录，不影响已有的记录信息
+        db.save_domain_error("home.example.com", "更新域名失败").unwrap();
+        let state = db.get_domain_state("home.example.com").unwrap().unwrap();
+        assert_eq!(state.record_id.as_deref(), Some("r1"));
+        assert_eq!(state.last_error.as_deref(), Some("更新域名失败"));
+        assert!(state.last_error_at.is_some());
+
+        let id = db.instance_id().unwrap();
+        assert_eq!(db.instance_id().unwrap(), id);
+        db.save_ownership("home.example.com", Some("r1"), &id, "owned").unwrap();
+        db.save_ownership("home.example.com", None, "other", "conflict").unwrap();
+        let owned = db.get_ownership().unwrap();
+        assert_eq!(owned[0].record_id.as_deref(), Some("r1"));
+        assert_eq!(owned[0].status, "conflict");
+    }
+
+    #[test]
+    fn test_operation_lifecycle() {
+        let db = memory_db();
+        let id = db.create_operation("force_sync", &json!({ "subdomains": null }), 2).unwrap();
+        assert_eq!(db.get_unfinished_operations().unwrap().len(), 1);
+
+        let errors = [OperationError { domain: "nas.example.com".to_string(), message: "失败".to_string() }];
+        db.update_operation(&id, "running", 1, &errors, None).unwrap();
+        db.update_operation(&id, "failed", 2, &errors, Some("成功 1/2 个域名")).unwrap();
+
+        let operation = db.get_operation(&id).unwrap().unwrap();
+        assert_eq!((operation.status.as_str(), operation.done, operation.total), ("failed", 2, 2));
+        assert_eq!(operation.errors[0].domain, "nas.example.com");
+        assert_eq!(operation.message.as_deref(), Some("成功 1/2 个域名"));
+        assert!(db.get_unfinished_operations().unwrap().is_empty());
+        assert!(db.get_operation("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_migrates_legacy_schema() {
+        let path = std::env::temp_dir().join(format!("cloudflare-auto-migrate-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            // 最早版本的配置表只有基本字段
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE config (
+                    id INTEGER PRIMARY KEY,
+                    cloudflare_api_key TEXT NOT NULL,
+                    cloudflare_zone_id TEXT NOT NULL,
+                    root_domain TEXT NOT NULL,
+                    selected_subdomains TEXT NOT NULL,
+                    check_interval INTEGER DEFAULT 300,
+                    last_ip TEXT
+                );
+                INSERT INTO config (cloudflare_api_key, cloudflare_zone_id, root_domain, selected_subdomains)
+                VALUES ('token', 'zone', 'example.com', '[\"home\"]');",
+            )
+            .unwrap();
+        }
+
+        let db = Database::new(&path).unwrap();
+        let config = db.load_config().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.selected_subdomains, ["home"]);
+        assert_eq!(config.check_interval, 300);
+        assert_eq!(config.auth_type, AuthType::ApiToken);
+        assert!(config.startup_check);
+        assert_eq!(config.burst_duration, default_burst_duration());
+        assert!(!config.ownership_enabled);
+        assert!(!config.proxied_default);
+    }
+
+    #[test]
+    fn test_concurrent_access() {
+        let db = memory_db();
+        let handles: Vec<_> = (0..8)
+            .map(|thread| {
+                let db = db.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let new_ip = format!("2001:db8::{}:{}", thread, i);
+                        db.add_dns_update_record(&NewDnsUpdateRecord { new_ip: &new_ip, ..Default::default() })
+                            .unwrap();
+                        db.save_domain_state(&format!("host{}.example.com", thread), "r", "::1", None, None)
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(db.get_dns_update_records(None).unwrap().len(), 200);
+        assert_eq!(db.get_domain_states().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_busy_writes_are_queued_and_flushed() {
+        let path = std::env::temp_dir().join(format!("cloudflare-auto-busy-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = Database::new(&path).unwrap();
+        db.save_config(&sample_config()).unwrap();
+
+        // 另一个连接持有写锁，模拟备份或外部工具占用数据库
+        let other = Connection::open(&path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE").unwrap();
+        db.add_dns_update_record(&NewDnsUpdateRecord { new_ip: "2001:db8::2", ..Default::default() })
+            .unwrap();
+        db.update_last_ip("2001:db8::2").unwrap();
+        let stats = db.write_stats();
+        assert_eq!(stats.queued, 2);
+        assert!(stats.retried > 0);
+        // 读取配置时使用暂存的IP，避免下个周期重复更新
+        assert_eq!(db.load_config().unwrap().last_ip.as_deref(), Some("2001:db8::2"));
+
+        other.execute_batch("COMMIT").unwrap();
+        db.flush_pending_writes().unwrap();
+        assert_eq!(db.write_stats().queued, 0);
+        assert_eq!(db.get_dns_update_records(None).unwrap()[0].new_ip, "2001:db8::2");
+        let stored: Option<String> = other.query_row("SELECT last_ip FROM config", [], |row| row.get(0)).unwrap();
+        assert_eq!(stored.as_deref(), Some("2001:db8::2"));
+        drop(other);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
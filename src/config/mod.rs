@@ -1 +1,3 @@
-pub mod database;
\ No newline at end of file
+pub mod data_dir;
+pub mod database;
+pub mod write_queue;
@@ -0,0 +1,146 @@
+use rusqlite::{Connection, ErrorCode};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// SQLite等待其他连接释放锁的时间，超时后按退避间隔重试
+pub const BUSY_TIMEOUT: Duration = Duration::from_millis(250);
+/// 每次重试前的等待时间，全部用完后放弃
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+];
+
+/// 数据库是否因其他连接持有锁而暂时无法写入
+pub fn is_busy(error: &rusqlite::Error) -> bool {
+    matches!(
+        error.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// 执行写入，数据库繁忙时释放连接并按退避间隔重试
+pub fn retry<T>(
+    conn: &Mutex<Connection>,
+    stats: &WriteStats,
+    mut op: impl FnMut(&Connection) -> rusqlite::Result<T>,
+) -> rusqlite::Result<T> {
+    for delay in RETRY_DELAYS {
+        let result = op(&conn.lock().unwrap());
+        match result {
+            Err(e) if is_busy(&e) => {
+                stats.retried.fetch_add(1, Ordering::Relaxed);
+                warn!("⚠️ 数据库繁忙，{}毫秒后重试写入", delay.as_millis());
+                std::thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+    op(&conn.lock().unwrap())
+}
+
+/// 写入重试与暂存的累计次数
+#[derive(Debug, Default)]
+pub struct WriteStats {
+    retried: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// 写入统计的快照，用于Prometheus指标
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct WriteStatsSnapshot {
+    pub retried: u64, // 因数据库繁忙重试的次数
+    pub queued: u64,  // 当前暂存在内存中等待补写的写入数
+    pub dropped: u64, // 暂存队列已满而丢弃的写入数
+}
+
+impl WriteStats {
+    pub fn snapshot(&self, queued: usize) -> WriteStatsSnapshot {
+        WriteStatsSnapshot {
+            retried: self.retried.load(Ordering::Relaxed),
+            queued: queued as u64,
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 重试后仍无法写入的行，暂存在内存中等待下次写入时补写，超出容量时丢弃最早的
+pub struct WriteQueue<T> {
+    rows: Mutex<VecDeque<T>>,
+    capacity: usize,
+}
+
+impl<T> WriteQueue<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            rows: Mutex::new(VecDeque::new()),
+            capacity,
+        }
+    }
+
+    /// 暂存一行，队列已满时丢弃最早的一行并计数
+    pub fn push(&self, row: T, stats: &WriteStats) {
+        let mut rows = self.rows.lock().unwrap();
+        if rows.len() >= self.capacity {
+            rows.pop_front();
+            stats.dropped.fetch_add(1, Ordering::Relaxed);
+            warn!("⚠️ 待补写的数据库写入已达上限 {}，丢弃最早的一条", self.capacity);
+        }
+        rows.push_back(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 按暂存顺序补写，写入失败时保留该行及之后的行
+    pub fn flush(&self, mut write: impl FnMut(&T) -> rusqlite::Result<()>) -> rusqlite::Result<usize> {
+        let mut rows = self.rows.lock().unwrap();
+        let mut written = 0;
+        while let Some(row) = rows.front() {
+            write(row)?;
+            rows.pop_front();
+            written += 1;
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_drops_oldest_and_keeps_failed_rows() {
+        let stats = WriteStats::default();
+        let queue = WriteQueue::new(2);
+        for row in 1..=3 {
+            queue.push(row, &stats);
+        }
+        assert_eq!(stats.snapshot(queue.len()).dropped, 1);
+
+        let busy = rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY), None);
+        assert!(is_busy(&busy));
+        let mut written = Vec::new();
+        let result = queue.flush(|row| {
+            if *row == 3 {
+                return Err(rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY), None));
+            }
+            written.push(*row);
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(written, vec![2]);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.flush(|_| Ok(())).unwrap(), 1);
+        assert!(queue.is_empty());
+    }
+}
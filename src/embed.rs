@@ -0,0 +1,404 @@
+use crate::api::{self, AppState};
+use crate::config::data_dir::{self, init_data_dir, DB_FILE_NAME};
+use crate::config::database::{AppConfig, BarkSettings, Database, DingTalkSettings, GotifySettings, IpSource, NotifyWebhook, NtfySettings, SlackSettings, TelegramSettings};
+use crate::services::cloudflare::CloudflareConfig;
+use crate::services::config_service::ConfigService;
+use crate::services::events::DomainEvent;
+use crate::services::monitor_service::{MonitorHandle, MonitorService, MonitorStatus};
+use crate::services::operations;
+use crate::utils::clock::system_clock;
+use crate::utils::network::PrefixRule;
+use anyhow::Result;
+use axum::Router;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, watch};
+use tracing::info;
+
+/// 新建配置时的默认检查间隔（秒），与Web界面一致
+const DEFAULT_CHECK_INTERVAL: u64 = 300;
+
+/// 通知渠道
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationChannel {
+    /// 服务状态转换、IP抖动等事件推送到该Webhook
+    StatusWebhook(String),
+    /// 每个检查周期结束后推送心跳的Uptime Kuma Push地址
+    UptimeKuma(String),
+    /// IP变化、更新失败和恢复时发送Telegram消息
+    Telegram(TelegramSettings),
+    /// IP变化、更新失败和恢复时推送到该Discord Webhook
+    Discord(String),
+    /// IP变化、更新失败和恢复时推送到Slack Incoming Webhook
+    Slack(SlackSettings),
+    /// IP变化、更新失败和恢复时推送到ntfy主题
+    Ntfy(NtfySettings),
+    /// IP变化、更新失败和恢复时推送到Gotify
+    Gotify(GotifySettings),
+    /// IP变化、更新失败和恢复时推送到企业微信群机器人
+    WeCom(String),
+    /// IP变化、更新失败和恢复时推送到钉钉自定义机器人
+    DingTalk(DingTalkSettings),
+    /// IP变化、更新失败和恢复时推送到iPhone上的Bark
+    Bark(BarkSettings),
+    /// IP变化、更新失败和恢复时通过Server酱推送到微信，值为SendKey
+    ServerChan(String),
+    /// IP变化、更新失败和恢复时通过PushPlus推送到微信，值为令牌
+    PushPlus(String),
+    /// IP变化、更新成功和失败时调用的事件Webhook，可添加多个，一起替换已保存的列表
+    Webhook(NotifyWebhook),
+}
+
+/// 构建嵌入式引擎，未设置的项保留数据目录中已保存的配置
+#[derive(Debug, Default, Clone)]
+pub struct CloudflareAutoBuilder {
+    data_dir: Option<PathBuf>,
+    web_addr: Option<SocketAddr>,
+    overrides: ConfigOverrides,
+}
+
+/// 构建时写入保存配置的设置
+#[derive(Debug, Default, Clone)]
+struct ConfigOverrides {
+    cloudflare: Option<CloudflareConfig>,
+    subdomains: Option<Vec<String>>,
+    prefix_rules: Option<Vec<PrefixRule>>,
+    verify_external_ip: Option<bool>,
+    external_ip_services: Option<Vec<String>>,
+    stun_servers: Option<Vec<String>>,
+    notifications: Vec<NotificationChannel>,
+    check_interval: Option<u64>,
+    burst: Option<(u64, u64)>, // (突发间隔秒数, 持续分钟数)
+    reconcile_interval: Option<u64>, // 分钟
+    startup_check: Option<bool>,
+    startup_delay: Option<u64>,
+}
+
+impl CloudflareAutoBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 数据目录（数据库、调试抓取等），默认与命令行程序相同：DATA_DIR 环境变量或 ./data
+    pub fn data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(dir.into());
+        self
+    }
+
+    /// DNS服务商的账户与区域，数据目录中没有配置时必须设置
+    pub fn cloudflare(mut self, config: CloudflareConfig) -> Self {
+        self.overrides.cloudflare = Some(config);
+        self
+    }
+
+    /// 要维护的子域名，根域名用空字符串
+    pub fn subdomains<I, S>(mut self, subdomains: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overrides.subdomains = Some(subdomains.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// IP来源：多线路时接受的前缀及优先级，不设置时使用系统首选地址
+    pub fn prefix_rules(mut self, rules: Vec<PrefixRule>) -> Self {
+        self.overrides.prefix_rules = Some(rules);
+        self
+    }
+
+    /// IP来源：通过外部HTTP服务获取公网视角下的IPv6，按顺序尝试，传空列表使用内置服务
+    pub fn external_ip_services<I, S>(mut self, services: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overrides.external_ip_services = Some(services.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// IP来源：通过STUN服务器（host:port）获取公网视角下的IPv6，按顺序尝试，传空列表使用内置服务器
+    pub fn stun_servers<I, S>(mut self, servers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.overrides.stun_servers = Some(servers.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// 是否通过外部服务校验检测到的公网IPv6
+    pub fn verify_external_ip(mut self, enabled: bool) -> Self {
+        self.overrides.verify_external_ip = Some(enabled);
+        self
+    }
+
+    /// 添加通知渠道，同类渠道以最后一次设置为准，事件Webhook可以添加多个
+    pub fn notification(mut self, channel: NotificationChannel) -> Self {
+        self.overrides.notifications.push(channel);
+        self
+    }
+
+    /// 定时检查的间隔
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.overrides.check_interval = Some(interval.as_secs().max(1));
+        self
+    }
+
+    /// IP变化后在 duration 内每隔 interval 检查一次，interval 为0时关闭突发检查
+    pub fn burst(mut self, interval: Duration, duration: Duration) -> Self {
+        self.overrides.burst = Some((interval.as_secs(), duration.as_secs() / 60));
+        self
+    }
+
+    /// 每隔 interval 读取所有记录并修复被外部修改的记录，为0时不对账
+    pub fn reconcile_interval(mut self, interval: Duration) -> Self {
+        self.overrides.reconcile_interval = Some(interval.as_secs() / 60);
+        self
+    }
+
+    /// 启动时是否执行首次检查，以及执行前的等待时间
+    pub fn startup_check(mut self, enabled: bool, delay: Duration) -> Self {
+        self.overrides.startup_check = Some(enabled);
+        self.overrides.startup_delay = Some(delay.as_secs());
+        self
+    }
+
+    /// 运行时同时在该地址提供Web管理界面和API，静态文件从工作目录下的 static/ 读取
+    pub fn web(mut self, addr: SocketAddr) -> Self {
+        self.web_addr = Some(addr);
+        self
+    }
+
+    /// 打开数据库并写入设置，不访问网络；调用返回句柄的 run 后才开始检查
+    pub async fn build(self) -> Result<CloudflareAuto> {
+        let dir = self.data_dir.unwrap_or_else(data_dir::data_dir);
+        init_data_dir(&dir)?;
+        let config_service = ConfigService::with_clock(Database::new(dir.join(DB_FILE_NAME))?, system_clock());
+
+        let stored = config_service.has_configuration().then(|| config_service.load_configuration()).transpose()?;
+        if let Some(config) = self.overrides.apply(stored)? {
+            // 直接写入数据库，保留上次更新的IP，首次检查时再按需更新
+            config_service.database().save_config(&config)?;
+        }
+
+        let monitor = MonitorService::new(config_service.clone()).await?.spawn();
+        info!("📦 嵌入式引擎已就绪，数据目录: {}", dir.display());
+        Ok(CloudflareAuto {
+            config_service,
+            monitor,
+            web_addr: self.web_addr,
+            shutdown: Arc::new(watch::channel(false).0),
+        })
+    }
+}
+
+impl ConfigOverrides {
+    /// 将设置合并到已保存的配置，没有配置且未设置服务商时返回None
+    fn apply(&self, stored: Option<AppConfig>) -> Result<Option<AppConfig>> {
+        let mut config = match (stored, &self.cloudflare) {
+            (Some(config), _) => config,
+            (None, Some(cloudflare)) => serde_json::from_value(json!({
+                "cloudflare_api_key": cloudflare.api_key,
+                "cloudflare_zone_id": cloudflare.zone_id,
+                "root_domain": cloudflare.root_domain,
+                "selected_subdomains": [],
+                "check_interval": DEFAULT_CHECK_INTERVAL,
+            }))?,
+            (None, None) => return Ok(None),
+        };
+
+        if let Some(cloudflare) = &self.cloudflare {
+            config.cloudflare_api_key = cloudflare.api_key.clone();
+            config.cloudflare_zone_id = cloudflare.zone_id.clone();
+            config.root_domain = cloudflare.root_domain.clone();
+            config.auth_type = cloudflare.auth_type;
+            config.auth_email = cloudflare.auth_email.clone();
+            config.http_connect_timeout = cloudflare.timeouts.connect_secs;
+            config.http_request_timeout = cloudflare.timeouts.request_secs;
+            config.proxy_url = cloudflare.proxy_url.clone();
+            config.ca_cert_path = cloudflare.ca_cert_path.clone();
+        }
+        if let Some(subdomains) = &self.subdomains {
+            config.selected_subdomains = subdomains.clone();
+        }
+        if let Some(rules) = &self.prefix_rules {
+            config.prefix_rules = rules.clone();
+        }
+        if let Some(enabled) = self.verify_external_ip {
+            config.verify_external_ip = enabled;
+        }
+        if let Some(services) = &self.external_ip_services {
+            config.ip_source = IpSource::External;
+            config.external_ip_services = services.clone();
+        }
+        if let Some(servers) = &self.stun_servers {
+            config.ip_source = IpSource::Stun;
+            config.stun_servers = servers.clone();
+        }
+        for channel in &self.notifications {
+            match channel {
+                NotificationChannel::StatusWebhook(url) => config.status_webhook_url = Some(url.clone()),
+                NotificationChannel::UptimeKuma(url) => config.uptime_kuma_push_url = Some(url.clone()),
+                NotificationChannel::Telegram(settings) => config.telegram = Some(settings.clone()),
+                NotificationChannel::Discord(url) => config.discord_webhook_url = Some(url.clone()),
+                NotificationChannel::Slack(settings) => config.slack = Some(settings.clone()),
+                NotificationChannel::Ntfy(settings) => config.ntfy = Some(settings.clone()),
+                NotificationChannel::Gotify(settings) => config.gotify = Some(settings.clone()),
+                NotificationChannel::WeCom(url) => config.wecom_webhook_url = Some(url.clone()),
+                NotificationChannel::DingTalk(settings) => config.dingtalk = Some(settings.clone()),
+                NotificationChannel::Bark(settings) => config.bark = Some(settings.clone()),
+                NotificationChannel::ServerChan(key) => config.serverchan_key = Some(key.clone()),
+                NotificationChannel::PushPlus(token) => config.pushplus_token = Some(token.clone()),
+                NotificationChannel::Webhook(_) => {}
+            }
+        }
+        let webhooks: Vec<NotifyWebhook> = self
+            .notifications
+            .iter()
+            .filter_map(|channel| match channel {
+                NotificationChannel::Webhook(webhook) => Some(webhook.clone()),
+                _ => None,
+            })
+            .collect();
+        if !webhooks.is_empty() {
+            config.notify_webhooks = webhooks;
+        }
+        if let Some(interval) = self.check_interval {
+            config.check_interval = interval;
+        }
+        if let Some((interval, duration)) = self.burst {
+            config.burst_interval = interval;
+            config.burst_duration = duration;
+        }
+        if let Some(interval) = self.reconcile_interval {
+            config.reconcile_interval = interval;
+        }
+        if let Some(enabled) = self.startup_check {
+            config.startup_check = enabled;
+        }
+        if let Some(delay) = self.startup_delay {
+            config.startup_delay = delay;
+        }
+        Ok(Some(config))
+    }
+}
+
+/// 嵌入式引擎的句柄，可克隆后在多个任务中使用
+#[derive(Clone)]
+pub struct CloudflareAuto {
+    config_service: ConfigService,
+    monitor: MonitorHandle,
+    web_addr: Option<SocketAddr>,
+    shutdown: Arc<watch::Sender<bool>>,
+}
+
+impl CloudflareAuto {
+    /// 启动定时检查并执行首次检查，一直运行到调用 shutdown；设置了Web地址时同时提供Web服务
+    pub async fn run(&self) -> Result<()> {
+        self.monitor.start().await?;
+        MonitorService::run_startup_check(&self.config_service).await;
+        operations::resume_unfinished(&self.config_service);
+
+        let mut shutdown = self.shutdown.subscribe();
+        let stopped = async move {
+            let _ = shutdown.wait_for(|stop| *stop).await;
+        };
+        match self.web_addr {
+            Some(addr) => {
+                let listener = TcpListener::bind(addr).await?;
+                info!("🌐 Web服务启动在: http://{}", listener.local_addr()?);
+                axum::serve(listener, self.router().into_make_service_with_connect_info::<SocketAddr>())
+                    .with_graceful_shutdown(stopped)
+                    .await?;
+            }
+            None => stopped.await,
+        }
+
+        self.monitor.stop().await?;
+        info!("👋 嵌入式引擎已停止");
+        Ok(())
+    }
+
+    /// 让 run 停止定时检查并返回
+    pub fn shutdown(&self) {
+        self.shutdown.send_replace(true);
+    }
+
+    /// 立即执行一次检查，返回是否更新了记录
+    pub async fn trigger(&self) -> Result<bool> {
+        self.monitor.trigger().await
+    }
+
+    /// 订阅各域名的活动事件（同步、确认、警告、错误）
+    pub fn events(&self) -> broadcast::Receiver<DomainEvent> {
+        self.config_service.domain_events().subscribe()
+    }
+
+    pub async fn status(&self) -> Result<MonitorStatus> {
+        self.monitor.status().await
+    }
+
+    /// Web管理界面和API的路由，可合并到宿主程序自己的axum服务中；
+    /// 以 into_make_service_with_connect_info::<SocketAddr>() 提供服务时，DynDNS推送可以省略 myip
+    pub fn router(&self) -> Router {
+        Router::new()
+            .merge(api::configure_routes())
+            .with_state(AppState::new(self.config_service.clone(), self.monitor.clone()))
+    }
+
+    /// 配置服务，用于读取配置、更新历史等更底层的操作
+    pub fn config_service(&self) -> &ConfigService {
+        &self.config_service
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overrides_create_or_merge_config() {
+        let builder = CloudflareAutoBuilder::new()
+            .cloudflare(CloudflareConfig {
+                api_key: "token".to_string(),
+                zone_id: "zone".to_string(),
+                root_domain: "example.com".to_string(),
+                auth_type: Default::default(),
+                auth_email: None,
+                timeouts: Default::default(),
+                proxy_url: None,
+                ca_cert_path: None,
+            })
+            .subdomains(["home", ""])
+            .notification(NotificationChannel::StatusWebhook("http://hook".to_string()))
+            .burst(Duration::from_secs(30), Duration::from_secs(600));
+
+        let created = builder.overrides.apply(None).unwrap().unwrap();
+        assert_eq!(created.root_domain, "example.com");
+        assert_eq!(created.selected_subdomains, vec!["home", ""]);
+        assert_eq!(created.check_interval, DEFAULT_CHECK_INTERVAL);
+        assert_eq!((created.burst_interval, created.burst_duration), (30, 10));
+        assert_eq!(created.status_webhook_url.as_deref(), Some("http://hook"));
+
+        // 已有配置时只修改设置过的项，保留上次更新的IP
+        let mut stored = created.clone();
+        stored.last_ip = Some("2001:db8::1".to_string());
+        stored.check_interval = 60;
+        let merged = CloudflareAutoBuilder::new()
+            .subdomains(["nas"])
+            .overrides
+            .apply(Some(stored))
+            .unwrap()
+            .unwrap();
+        assert_eq!(merged.selected_subdomains, vec!["nas"]);
+        assert_eq!(merged.check_interval, 60);
+        assert_eq!(merged.last_ip.as_deref(), Some("2001:db8::1"));
+
+        assert!(CloudflareAutoBuilder::new().overrides.apply(None).unwrap().is_none());
+    }
+}
@@ -1,4 +1,7 @@
 pub mod config;
 pub mod services;
 pub mod utils;
-pub mod api;
\ No newline at end of file
+pub mod api;
+pub mod embed;
+
+pub use embed::{CloudflareAuto, CloudflareAutoBuilder, NotificationChannel};
@@ -1,24 +1,36 @@
-mod config;
-mod services;
-mod utils;
-mod api;
-
 use axum::Router;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::env;
+use std::path::PathBuf;
 use tokio::{net::TcpListener, signal};
 use tracing::{info, error, warn};
-use crate::services::{config_service::ConfigService, monitor_service::MonitorService};
-use crate::utils::logger::{init_logger, start_log_cleanup_task};
+use cloudflare_auto::api::{self, AppState};
+use cloudflare_auto::config::data_dir::{self, init_data_dir};
+use cloudflare_auto::services::{backup, config_service::ConfigService, monitor_service::MonitorService, network_watcher, operations};
+use cloudflare_auto::utils::logger::{init_logger, start_log_cleanup_task};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // 初始化日志系统 - 支持控制台和文件同步输出
-    let _guard = init_logger()?;
+    // 解析命令行参数：--data-dir <目录> 指定数据目录
+    let cli = parse_args(env::args().skip(1));
+
+    // 初始化日志系统 - 默认控制台和文件同步输出，LOG_TARGET 可改为 stdout/journald/syslog
+    // 指定 --print-addr 时控制台日志写到标准错误，标准输出只有监听地址一行
+    let _guard = init_logger(cli.print_addr)?;
+    
+    let args = cli.rest;
+    init_data_dir(cli.data_dir.unwrap_or_else(data_dir::data_dir))?;
+    info!("📁 数据目录: {}", data_dir::data_dir().display());
+    data_dir::migrate_legacy_database()?;
+    
+    // 恢复命令：cloudflare-auto restore-backup [备份文件名]，需在服务停止时执行
+    if args.first().map(String::as_str) == Some("restore-backup") {
+        return backup::restore_from_env(args.get(1).map(String::as_str)).await;
+    }
     
     info!("🚀 启动Cloudflare自动IPv6更新服务...");
-    info!("📝 日志系统已初始化，支持控制台和文件同步输出");
+    info!("📝 日志系统已初始化");
     
     // 启动日志清理定时任务
     if let Err(e) = start_log_cleanup_task().await {
@@ -30,6 +42,11 @@ async fn main() -> anyhow::Result<()> {
     let config_service = ConfigService::new()?;
     info!("✅ 配置服务初始化完成");
     
+    // 启动定时备份任务（未配置 BACKUP_TARGET 时不启动）
+    if let Err(e) = backup::start_backup_task(config_service.database().clone()).await {
+        warn!("⚠️ 启动备份任务失败: {}", e);
+    }
+    
     // 初始化监控服务
     info!("🔍 初始化监控服务...");
     let mut monitor_service = MonitorService::new(config_service.clone()).await?;
@@ -43,40 +60,91 @@ async fn main() -> anyhow::Result<()> {
     }
     info!("✅ 监控服务启动成功");
 
-    // 程序启动时立即执行一次检查更新
-    info!("🔍 程序启动，立即执行首次IP检查...");
-    if let Err(e) = monitor_service.check_and_update_now().await {
-        warn!("⚠️ 首次IP检查失败: {}", e);
-    } else {
-        info!("✅ 首次IP检查完成");
-    }
+    // 程序启动时按配置执行首次检查更新（可关闭或延迟）
+    MonitorService::run_startup_check(&config_service).await;
+    let monitor = monitor_service.spawn();
+    
+    // 网络变化时立即检查，不必等到下一个检查间隔（目前支持macOS）
+    network_watcher::spawn(monitor.clone());
+    
+    // 继续执行上次退出时未完成的后台操作
+    operations::resume_unfinished(&config_service);
     
     // 创建Web服务器
     info!("🌐 创建Web服务器...");
     let app = Router::new()
         .merge(api::configure_routes())
-        .with_state(config_service);
+        .with_state(AppState::new(config_service, monitor));
     
     // 读取监听地址，优先使用环境变量 BIND_ADDR（示例：0.0.0.0:3000），默认 127.0.0.1:3000
+    // 端口为0时由系统分配空闲端口，实际地址见日志、--print-addr 输出或端口文件
     let bind_addr_str = env::var("BIND_ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
     let addr = SocketAddr::from_str(&bind_addr_str)
         .map_err(|e| anyhow::anyhow!("无效的 BIND_ADDR 格式：{} ({})", bind_addr_str, e))?;
-    info!("🌐 Web服务启动在: http://{}", addr);
-    info!("📱 可通过浏览器访问Web管理界面");
     
     // 启动服务器
     info!("🚀 启动HTTP服务器...");
     let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    info!("🌐 Web服务启动在: http://{}", local_addr);
+    info!("📱 可通过浏览器访问Web管理界面");
+    
+    // 供包装脚本读取实际监听地址
+    if cli.print_addr {
+        println!("{}", local_addr);
+    }
+    let port_file = cli.port_file.or_else(|| env::var_os("PORT_FILE").map(PathBuf::from));
+    if let Some(path) = &port_file {
+        std::fs::write(path, format!("{}\n", local_addr.port()))
+            .map_err(|e| anyhow::anyhow!("写入端口文件失败 {}: {}", path.display(), e))?;
+        info!("📝 已将监听端口写入: {}", path.display());
+    }
     info!("✅ HTTP服务器启动成功，等待连接...");
     
-    axum::serve(listener, app)
+    // 记录请求的来源地址，DynDNS推送没有携带 myip 时使用
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
         .await?;
     
+    if let Some(path) = &port_file {
+        let _ = std::fs::remove_file(path);
+    }
     info!("👋 服务已正常关闭");
     Ok(())
 }
 
+/// 命令行参数
+#[derive(Default)]
+struct CliArgs {
+    data_dir: Option<PathBuf>,  // --data-dir 指定的数据目录
+    print_addr: bool,           // --print-addr：启动后在标准输出打印实际监听地址，控制台日志改到标准错误
+    port_file: Option<PathBuf>, // --port-file：启动后将实际监听端口写入该文件
+    rest: Vec<String>,          // 其余的位置参数
+}
+
+/// 解析命令行参数
+fn parse_args(mut args: impl Iterator<Item = String>) -> CliArgs {
+    let mut cli = CliArgs::default();
+    
+    while let Some(arg) = args.next() {
+        if arg == "--data-dir" {
+            cli.data_dir = args.next().map(PathBuf::from);
+        } else if let Some(dir) = arg.strip_prefix("--data-dir=") {
+            cli.data_dir = Some(PathBuf::from(dir));
+        } else if arg == "--print-addr" {
+            cli.print_addr = true;
+        } else if arg == "--port-file" {
+            cli.port_file = args.next().map(PathBuf::from);
+        } else if let Some(path) = arg.strip_prefix("--port-file=") {
+            cli.port_file = Some(PathBuf::from(path));
+        } else {
+            cli.rest.push(arg);
+        }
+    }
+    
+    cli
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
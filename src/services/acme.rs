@@ -0,0 +1,109 @@
+use crate::services::config_service::ConfigService;
+use anyhow::{Result, anyhow};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+/// ACME DNS-01 验证记录的名称前缀
+const CHALLENGE_PREFIX: &str = "_acme-challenge.";
+
+/// ACME钩子请求，兼容lego httpreq提供者的两种模式：
+/// 默认模式提供 fqdn 和 value，RAW模式提供 domain、token 和 keyAuth
+#[derive(Debug, Deserialize)]
+pub struct AcmeRequest {
+    #[serde(default)]
+    pub fqdn: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub domain: Option<String>,
+    #[serde(default, rename = "keyAuth")]
+    pub key_auth: Option<String>,
+}
+
+impl AcmeRequest {
+    /// 解析出验证记录的完整名称和TXT内容
+    pub fn challenge(&self) -> Result<(String, String)> {
+        match (&self.fqdn, &self.value, &self.domain, &self.key_auth) {
+            (Some(fqdn), Some(value), _, _) => Ok((fqdn.clone(), value.clone())),
+            (_, _, Some(domain), Some(key_auth)) => Ok((
+                format!("{}{}", CHALLENGE_PREFIX, domain.trim_start_matches("*.")),
+                key_auth_digest(key_auth),
+            )),
+            _ => Err(anyhow!("请求需要包含 fqdn 和 value，或 domain 和 keyAuth")),
+        }
+    }
+}
+
+/// DNS-01的TXT记录内容：keyAuth的SHA-256摘要，按无填充的base64url编码
+pub fn key_auth_digest(key_auth: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(key_auth.as_bytes()))
+}
+
+/// 校验验证记录名称：必须以 _acme-challenge. 开头且位于配置的根域名下
+fn challenge_name(fqdn: &str, root_domain: &str) -> Result<String> {
+    let name = fqdn.trim().trim_end_matches('.').to_ascii_lowercase();
+    let root = root_domain.to_ascii_lowercase();
+    let in_zone = name == format!("{}{}", CHALLENGE_PREFIX, root) || name.ends_with(&format!(".{}", root));
+    if !name.starts_with(CHALLENGE_PREFIX) || !in_zone {
+        return Err(anyhow!("{} 不是 {} 下的ACME验证记录", fqdn, root));
+    }
+    Ok(name)
+}
+
+/// 写入验证记录，内容相同的记录已存在时直接返回（便于钩子重试）
+pub async fn present(service: &ConfigService, fqdn: &str, value: &str) -> Result<()> {
+    let config = service.load_configuration()?;
+    let name = challenge_name(fqdn, &config.root_domain)?;
+
+    let existing = service.list_txt_records(Some(&name)).await?;
+    if existing.iter().any(|record| txt_matches(&record.content, value)) {
+        info!("🔐 ACME验证记录已存在: {}", name);
+        return Ok(());
+    }
+
+    service.create_txt_record(&name, value, Some(120)).await?;
+    info!("🔐 已写入ACME验证记录: {}", name);
+    Ok(())
+}
+
+/// 删除内容匹配的验证记录，同名的其他验证记录（如通配符证书的另一条）保留
+pub async fn cleanup(service: &ConfigService, fqdn: &str, value: &str) -> Result<usize> {
+    let config = service.load_configuration()?;
+    let name = challenge_name(fqdn, &config.root_domain)?;
+
+    let mut removed = 0;
+    for record in service.list_txt_records(Some(&name)).await? {
+        if txt_matches(&record.content, value) {
+            service.delete_txt_record(&record.id).await?;
+            removed += 1;
+        }
+    }
+    info!("🔐 已清理 {} 条ACME验证记录: {}", removed, name);
+    Ok(removed)
+}
+
+/// Cloudflare返回的TXT内容可能带有引号
+fn txt_matches(content: &str, value: &str) -> bool {
+    content.trim_matches('"') == value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_resolution() {
+        assert_eq!(key_auth_digest(""), "47DEQpj8HBSa-_TImW-5JCeuQeRkm5NMpJWZG3hSuFU");
+
+        let request = AcmeRequest { fqdn: None, value: None, domain: Some("*.home.example.com".into()), key_auth: Some("k".into()) };
+        assert_eq!(request.challenge().unwrap().0, "_acme-challenge.home.example.com");
+
+        assert_eq!(challenge_name("_acme-challenge.Home.example.com.", "example.com").unwrap(), "_acme-challenge.home.example.com");
+        assert!(challenge_name("_acme-challenge.example.com", "example.com").is_ok());
+        assert!(challenge_name("home.example.com", "example.com").is_err());
+        assert!(challenge_name("_acme-challenge.example.org", "example.com").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+use crate::config::database::ApiToken;
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// API令牌的前缀，便于与 ADMIN_TOKEN 区分，也便于在日志或代码仓库中识别泄露的令牌
+pub const TOKEN_PREFIX: &str = "cfat_";
+/// 令牌有效期上限（小时）
+pub const MAX_TTL_HOURS: u32 = 24 * 365;
+/// 令牌管理接口的路径，API令牌不能用来生成或吊销令牌
+const TOKEN_ADMIN_PATH: &str = "/admin/tokens";
+const METHODS: [&str; 5] = ["*", "GET", "POST", "PUT", "DELETE"];
+
+/// 新生成的令牌，明文只在生成时返回一次
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ApiToken,
+}
+
+/// API令牌被拒绝的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenDenial {
+    Unknown,    // 令牌不存在或已吊销
+    Expired,    // 令牌已过期
+    OutOfScope, // 令牌的范围不包含本次请求
+}
+
+/// 生成新令牌
+pub fn generate() -> String {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(secret))
+}
+
+/// 令牌的SHA-256摘要，数据库中只保存摘要
+pub fn hash(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// 校验并规范化令牌范围，每项为 "方法 路径"，如 "POST /domains/home/sync"
+/// 方法可用 * 表示任意方法，路径中的 * 匹配一段；路径可带 /api/v1 前缀
+pub fn normalize_scopes(scopes: &[String]) -> Result<Vec<String>> {
+    if scopes.is_empty() {
+        return Err(anyhow!("至少需要一个允许调用的接口"));
+    }
+    scopes.iter().map(|scope| normalize_scope(scope)).collect()
+}
+
+fn normalize_scope(scope: &str) -> Result<String> {
+    let (method, path) = scope
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| anyhow!("令牌范围格式应为 \"方法 路径\": {}", scope))?;
+    let method = method.to_uppercase();
+    if !METHODS.contains(&method.as_str()) {
+        return Err(anyhow!("不支持的请求方法: {}", method));
+    }
+
+    let path = path.trim();
+    let path = path.strip_prefix("/api/v1").or_else(|| path.strip_prefix("/api")).unwrap_or(path);
+    let path = path.trim_end_matches('/');
+    if !path.starts_with('/') || path.contains(char::is_whitespace) {
+        return Err(anyhow!("接口路径应以 / 开头: {}", scope));
+    }
+    if path.starts_with(TOKEN_ADMIN_PATH) {
+        return Err(anyhow!("API令牌不能用于管理令牌: {}", scope));
+    }
+    Ok(format!("{} {}", method, path))
+}
+
+/// 令牌范围是否允许本次请求，path为不含 /api/v1 前缀的路径
+pub fn allows(scopes: &[String], method: &str, path: &str) -> bool {
+    if path.starts_with(TOKEN_ADMIN_PATH) {
+        return false;
+    }
+    scopes.iter().any(|scope| match scope.split_once(' ') {
+        Some((allowed, pattern)) => (allowed == "*" || allowed == method) && covers(pattern, path),
+        None => false,
+    })
+}
+
+/// 路径模式是否匹配路径，模式中的 * 匹配一段
+fn covers(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.trim_end_matches('/').split('/');
+    let mut path = path.trim_end_matches('/').split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some("*"), Some(segment)) if !segment.is_empty() => {}
+            (Some(expected), Some(segment)) if expected == segment => {}
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_matching() {
+        let scopes = normalize_scopes(&["post /api/v1/domains/home/sync".to_string(), "GET /operations/*".to_string()]).unwrap();
+        assert_eq!(scopes, vec!["POST /domains/home/sync", "GET /operations/*"]);
+
+        assert!(allows(&scopes, "POST", "/domains/home/sync"));
+        assert!(!allows(&scopes, "GET", "/domains/home/sync"));
+        assert!(!allows(&scopes, "POST", "/domains/nas/sync"));
+        assert!(allows(&scopes, "GET", "/operations/3f2a"));
+        assert!(!allows(&scopes, "GET", "/operations"));
+        assert!(!allows(&scopes, "GET", "/operations/3f2a/extra"));
+
+        assert!(normalize_scopes(&["* /admin/tokens".to_string()]).is_err());
+        assert!(normalize_scopes(&["PATCH /dns-records".to_string()]).is_err());
+        assert!(normalize_scopes(&[]).is_err());
+        // 通配的范围也不会覆盖令牌管理接口
+        let admin = normalize_scopes(&["* /admin/*".to_string()]).unwrap();
+        assert!(allows(&admin, "POST", "/admin/backup"));
+        assert!(!allows(&admin, "GET", "/admin/tokens"));
+
+        let token = generate();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_eq!(hash(&token).len(), 64);
+        assert_ne!(hash(&token), hash(&generate()));
+    }
+}
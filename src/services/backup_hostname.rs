@@ -0,0 +1,135 @@
+use crate::config::database::{AppConfig, AuthType, BackupHostname};
+use crate::services::cloudflare::{CloudflareClient, CloudflareConfig, DnsRecord, HttpTimeouts, PatchDnsRecordRequest, UpdateDnsRecordRequest};
+use crate::services::config_service::DomainOutcome;
+use anyhow::{Result, anyhow};
+use std::net::IpAddr;
+
+/// Cloudflare允许的TTL范围（秒），另外1表示自动
+const MIN_TTL: u32 = 30;
+const MAX_TTL: u32 = 86400;
+
+/// 校验备用主机名的设置
+pub fn validate(backup: &BackupHostname) -> Result<()> {
+    let name = backup.name.trim();
+    if !name.contains('.') || name.contains(char::is_whitespace) {
+        return Err(anyhow!("备用主机名必须是完整域名: {}", name));
+    }
+    if backup.zone_id.trim().is_empty() {
+        return Err(anyhow!("备用主机名需要填写所在的区域ID"));
+    }
+    if backup.ttl != 1 && !(MIN_TTL..=MAX_TTL).contains(&backup.ttl) {
+        return Err(anyhow!("备用主机名的TTL须为1（自动）或 {}-{} 秒", MIN_TTL, MAX_TTL));
+    }
+    Ok(())
+}
+
+/// 访问备用主机名所在区域的客户端，没有单独的令牌时使用主区域的凭据
+pub fn client(config: &AppConfig, backup: &BackupHostname) -> CloudflareClient {
+    let api_key = backup.api_key.as_deref().filter(|key| !key.trim().is_empty());
+    CloudflareClient::new(CloudflareConfig {
+        api_key: api_key.unwrap_or(&config.cloudflare_api_key).to_string(),
+        zone_id: backup.zone_id.clone(),
+        root_domain: backup.name.clone(),
+        auth_type: if api_key.is_some() { AuthType::ApiToken } else { config.auth_type },
+        auth_email: if api_key.is_some() { None } else { config.auth_email.clone() },
+        timeouts: HttpTimeouts::from_app_config(config),
+        proxy_url: config.proxy_url.clone(),
+        ca_cert_path: config.ca_cert_path.clone(),
+    })
+}
+
+/// 对备用主机名的处理
+#[derive(Debug)]
+enum Action<'a> {
+    Unchanged(&'a DnsRecord),
+    Update(&'a DnsRecord),
+    Create,
+}
+
+/// 优先使用已指向当前IP的记录，地址和TTL都一致时无需修改，否则修改第一条记录
+fn plan<'a>(records: &'a [DnsRecord], backup: &BackupHostname, ip: &str) -> Action<'a> {
+    let current = records.iter().find(|r| r.content == ip).or(records.first());
+    match current {
+        Some(record) if record.content == ip && record.ttl == backup.ttl => Action::Unchanged(record),
+        Some(record) => Action::Update(record),
+        None => Action::Create,
+    }
+}
+
+/// 将当前IP发布到备用主机名，返回处理结果和发布后的记录
+pub async fn publish(
+    client: &CloudflareClient,
+    backup: &BackupHostname,
+    ip: IpAddr,
+    comment: Option<String>,
+) -> Result<(DomainOutcome, DnsRecord)> {
+    let records = client.get_aaaa_records(&backup.name).await?;
+    let content = ip.to_string();
+    match plan(&records, backup, &content) {
+        Action::Unchanged(record) => Ok((DomainOutcome::Unchanged, record.clone())),
+        Action::Update(record) => {
+            let patch = PatchDnsRecordRequest {
+                content: Some(content),
+                ttl: Some(backup.ttl),
+                ..Default::default()
+            };
+            Ok((DomainOutcome::Updated, client.update_dns_record(&record.id, patch).await?))
+        }
+        Action::Create => {
+            let created = client
+                .create_dns_record(UpdateDnsRecordRequest {
+                    record_type: "AAAA".to_string(),
+                    name: backup.name.clone(),
+                    content,
+                    ttl: backup.ttl,
+                    proxied: false,
+                    comment,
+                    tags: Vec::new(),
+                    priority: None,
+                    data: None,
+                })
+                .await?;
+            Ok((DomainOutcome::Created, created))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, content: &str, ttl: u32) -> DnsRecord {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": "home.backup.net",
+            "type": "AAAA",
+            "content": content,
+            "proxied": false,
+            "ttl": ttl,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plan_backup_record() {
+        let backup = BackupHostname {
+            name: "home.backup.net".to_string(),
+            zone_id: "zone".to_string(),
+            api_key: None,
+            ttl: 60,
+        };
+        let ip = "2001:db8::1";
+        let old = record("old", "2001:db8::2", 60);
+        let current = record("current", ip, 60);
+        let long_ttl = record("ttl", ip, 3600);
+
+        assert!(matches!(plan(&[], &backup, ip), Action::Create));
+        assert!(matches!(plan(std::slice::from_ref(&old), &backup, ip), Action::Update(r) if r.id == "old"));
+        assert!(matches!(plan(&[old, current], &backup, ip), Action::Unchanged(r) if r.id == "current"));
+        assert!(matches!(plan(&[long_ttl], &backup, ip), Action::Update(r) if r.id == "ttl"));
+
+        assert!(validate(&backup).is_ok());
+        assert!(validate(&BackupHostname { ttl: 10, ..backup.clone() }).is_err());
+        assert!(validate(&BackupHostname { name: "home".to_string(), ..backup }).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::config::database::BarkSettings;
+use crate::services::notify::{NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// 接口的响应，code 为200表示推送成功
+#[derive(Debug, Deserialize)]
+struct BarkResponse {
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+/// 校验Bark设置
+pub fn validate(settings: &BarkSettings) -> Result<()> {
+    let parsed = reqwest::Url::parse(settings.server.trim()).map_err(|_| anyhow!("Bark服务器地址无效: {}", settings.server))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("Bark服务器地址只支持 http 和 https: {}", settings.server));
+    }
+    let key = settings.device_key.trim();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(anyhow!("Bark设备Key无效: {}，只需填写推送地址 https://api.day.app/<Key>/ 中的Key", key));
+    }
+    if settings.group.as_deref().is_some_and(|g| g.trim().is_empty()) {
+        return Err(anyhow!("Bark通知分组不能为空"));
+    }
+    Ok(())
+}
+
+/// 推送接口地址，服务器可以部署在子路径下
+fn push_url(settings: &BarkSettings) -> String {
+    format!("{}/push", settings.server.trim().trim_end_matches('/'))
+}
+
+/// 更新失败和地址不一致使用时效性通知，专注模式下也会立即提醒
+fn payload(settings: &BarkSettings, event: &NotifyEvent) -> Value {
+    let level = if matches!(event.kind, NotifyKind::UpdateFailed | NotifyKind::IpMismatch) { "timeSensitive" } else { "active" };
+    let lines = event.lines();
+    let mut payload = json!({
+        "device_key": settings.device_key.trim(),
+        "title": event.title(),
+        "body": if lines.is_empty() { event.title() } else { lines.join("\n") },
+        "group": settings.group.as_deref().map(str::trim).unwrap_or("cloudflare-auto"),
+        "level": level,
+    });
+    if let Some(sound) = &settings.sound {
+        payload["sound"] = Value::String(sound.trim().to_string());
+    }
+    payload
+}
+
+/// 推送到设备
+pub async fn send(client: &Client, settings: &BarkSettings, event: &NotifyEvent) -> Result<()> {
+    let response: BarkResponse = client
+        .post(push_url(settings))
+        .json(&payload(settings, event))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if response.code != 200 {
+        return Err(anyhow!("Bark返回错误 {}: {}", response.code, response.message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_bark_payload() {
+        let mut settings: BarkSettings = serde_json::from_str(r#"{"device_key": "AbC123", "sound": "alarm"}"#).unwrap();
+        assert!(validate(&settings).is_ok());
+        assert_eq!(push_url(&settings), "https://api.day.app/push");
+
+        let mut event = NotifyEvent::new(NotifyKind::UpdateFailed, "example.com", Utc::now());
+        event.message = Some("状态: failing".to_string());
+        assert_eq!(
+            payload(&settings, &event),
+            json!({
+                "device_key": "AbC123",
+                "title": "❌ example.com DNS更新失败",
+                "body": "状态: failing",
+                "group": "cloudflare-auto",
+                "level": "timeSensitive",
+                "sound": "alarm",
+            })
+        );
+
+        settings.device_key = "https://api.day.app/AbC123/".to_string();
+        assert!(validate(&settings).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use crate::services::config_service::{normalize_subdomain, ConfigService};
+use chrono::{Duration, Utc};
+use std::fmt::Write;
+
+/// 暂停允许的最长时间（天）
+const MAX_PAUSE_DAYS: i64 = 30;
+
+/// 聊天机器人可用的文本命令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatCommand {
+    /// 查看服务状态
+    Status,
+    /// 立即同步，指定子域名时只同步该子域名
+    Sync(Option<String>),
+    /// 立即同步并重写所有域名记录，即使内容已经一致
+    ForceSync,
+    /// 暂停定时检查一段时间
+    Pause(Duration),
+    /// 恢复定时检查
+    Resume,
+    /// 显示帮助
+    Help,
+}
+
+const HELP_TEXT: &str = "可用命令:
+status            查看服务状态
+sync              立即检查并更新所有域名
+sync <子域名>     立即同步指定子域名（根域名使用 @）
+sync --force      立即重写所有域名记录，即使内容已经一致
+pause <时长>      暂停定时检查，如 30m、1h、2d
+resume            恢复定时检查
+help              显示本帮助";
+
+impl ChatCommand {
+    /// 解析文本命令，忽略开头的 "/" 和多余空白，命令名不区分大小写
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut words = text.trim().trim_start_matches('/').split_whitespace();
+        let name = words.next().unwrap_or_default().to_lowercase();
+        let arg = words.next();
+        if words.next().is_some() {
+            return Err(format!("参数过多: {}", text.trim()));
+        }
+
+        match (name.as_str(), arg) {
+            ("status", None) => Ok(ChatCommand::Status),
+            ("sync", Some("--force" | "-f")) => Ok(ChatCommand::ForceSync),
+            ("sync", arg) => Ok(ChatCommand::Sync(arg.map(str::to_string))),
+            ("pause", Some(arg)) => parse_duration(arg).map(ChatCommand::Pause),
+            ("pause", None) => Err("请指定暂停时长，如 pause 1h".to_string()),
+            ("resume", None) => Ok(ChatCommand::Resume),
+            ("help" | "", None) => Ok(ChatCommand::Help),
+            _ => Err(format!("无法识别的命令: {}", text.trim())),
+        }
+    }
+}
+
+/// 解析时长，支持 s/m/h/d 后缀，不带后缀时按分钟计算
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.to_lowercase();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: i64 = number.parse().map_err(|_| format!("无效的时长: {}", value))?;
+    let duration = match unit {
+        "s" => Duration::seconds(number),
+        "" | "m" | "min" => Duration::minutes(number),
+        "h" => Duration::hours(number),
+        "d" => Duration::days(number),
+        _ => return Err(format!("无效的时长单位: {}", unit)),
+    };
+
+    if number <= 0 || duration > Duration::days(MAX_PAUSE_DAYS) {
+        return Err(format!("暂停时长需大于0且不超过{}天", MAX_PAUSE_DAYS));
+    }
+    Ok(duration)
+}
+
+/// 执行命令并返回纯文本回复
+pub async fn execute(service: &ConfigService, command: ChatCommand) -> String {
+    match command {
+        ChatCommand::Status => status_text(service).await,
+        ChatCommand::Sync(None) | ChatCommand::ForceSync => {
+            match service.check_and_update_now(command == ChatCommand::ForceSync).await {
+                Ok(true) => "✅ 同步完成".to_string(),
+                Ok(false) => format!("⚠️ 同步未成功，当前状态: {}", service.current_status().as_str()),
+                Err(e) => format!("❌ 同步失败: {}", e),
+            }
+        }
+        ChatCommand::Sync(Some(name)) => {
+            let result = async {
+                let config = service.load_configuration()?;
+                let subdomain = normalize_subdomain(&name, &config.root_domain)?;
+                let summary = service.sync_subdomain(&subdomain).await?;
+                Ok::<_, anyhow::Error>((config.full_domain(&subdomain), summary))
+            }.await;
+            match result {
+                Ok((domain, summary)) if summary.success_count > 0 => format!("✅ 已同步 {}", domain),
+                Ok((domain, summary)) => format!(
+                    "❌ 同步 {} 失败: {}",
+                    domain,
+                    summary.error_message.unwrap_or_default()
+                ),
+                Err(e) => format!("❌ 同步失败: {}", e),
+            }
+        }
+        ChatCommand::Pause(duration) => {
+            let until = service.clock().utc_now() + duration;
+            match service.pause(Some(until), Some("聊天机器人命令".to_string())) {
+                Ok(_) => format!("⏸️ 定时检查已暂停，至 {}", format_time(service, until)),
+                Err(e) => format!("❌ 暂停失败: {}", e),
+            }
+        }
+        ChatCommand::Resume => match service.resume() {
+            Ok(_) => "▶️ 定时检查已恢复".to_string(),
+            Err(e) => format!("❌ 恢复失败: {}", e),
+        },
+        ChatCommand::Help => HELP_TEXT.to_string(),
+    }
+}
+
+async fn status_text(service: &ConfigService) -> String {
+    let config = match service.load_configuration() {
+        Ok(config) => config,
+        Err(_) => return "⚠️ 尚未配置".to_string(),
+    };
+
+    let mut text = String::new();
+    let _ = writeln!(text, "状态: {}", service.current_status().as_str());
+    let _ = writeln!(
+        text,
+        "当前IP: {}",
+        service.detect_ipv6(&config).await.unwrap_or_else(|e| format!("检测失败 ({})", e))
+    );
+    let _ = writeln!(text, "上次IP: {}", config.last_ip.as_deref().unwrap_or("无"));
+    let _ = writeln!(
+        text,
+        "管理域名: {}",
+        config
+            .selected_subdomains
+            .iter()
+            .map(|s| config.full_domain(s))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    match service.pause_state().map(|state| state.until) {
+        Some(Some(until)) => { let _ = write!(text, "定时检查: 已暂停至 {}", format_time(service, until)); }
+        Some(None) => { let _ = write!(text, "定时检查: 已暂停，直到手动恢复"); }
+        None => { let _ = write!(text, "定时检查: 运行中"); }
+    }
+    text
+}
+
+fn format_time(service: &ConfigService, time: chrono::DateTime<Utc>) -> String {
+    service
+        .load_configuration()
+        .map(|c| c.time_formatter())
+        .unwrap_or_default()
+        .format(&time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(ChatCommand::parse("status"), Ok(ChatCommand::Status));
+        assert_eq!(ChatCommand::parse("/Sync home"), Ok(ChatCommand::Sync(Some("home".to_string()))));
+        assert_eq!(ChatCommand::parse("sync --force"), Ok(ChatCommand::ForceSync));
+        assert_eq!(ChatCommand::parse("pause 1h"), Ok(ChatCommand::Pause(Duration::hours(1))));
+        assert_eq!(ChatCommand::parse("pause 30"), Ok(ChatCommand::Pause(Duration::minutes(30))));
+        assert_eq!(ChatCommand::parse(""), Ok(ChatCommand::Help));
+        assert!(ChatCommand::parse("pause").is_err());
+        assert!(ChatCommand::parse("pause 0h").is_err());
+        assert!(ChatCommand::parse("pause 1y").is_err());
+        assert!(ChatCommand::parse("reboot").is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Cloudflare响应中 errors 数组的单个元素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudflareApiError {
+    pub code: u32,
+    pub message: String,
+}
+
+/// Cloudflare标准响应信封中的状态与错误部分
+#[derive(Debug, Deserialize)]
+struct ErrorEnvelope {
+    #[serde(default)]
+    success: Option<bool>,
+    #[serde(default)]
+    errors: Vec<CloudflareApiError>,
+}
+
+/// 结构化的Cloudflare API错误
+#[derive(Debug, Clone, Error)]
+pub enum CloudflareError {
+    /// 令牌无效或认证失败 (9109, 10000, 9103)
+    #[error("认证失败 [{code}]: {message}")]
+    Authentication { code: u32, message: String },
+    /// 完全相同的记录已存在 (81057, 81058)
+    #[error("记录已存在 [{code}]: {message}")]
+    DuplicateRecord { code: u32, message: String },
+    /// 同名的CNAME或其他记录冲突 (81053, 81054)
+    #[error("记录冲突 [{code}]: {message}")]
+    RecordConflict { code: u32, message: String },
+    /// 请求频率超限 (971, 10100)
+    #[error("请求过于频繁 [{code}]: {message}")]
+    RateLimited { code: u32, message: String },
+    /// HTTP 429，retry_after为响应头 Retry-After 指定的等待秒数
+    #[error("请求过于频繁 (HTTP 429): {message}")]
+    TooManyRequests { retry_after: Option<u64>, message: String },
+    /// 其他Cloudflare API错误
+    #[error("Cloudflare API错误 [{code}]: {message}")]
+    Api { code: u32, message: String },
+    /// 无法解析错误信息的HTTP失败
+    #[error("HTTP请求失败: {status} {body}")]
+    Http { status: u16, body: String },
+}
+
+impl CloudflareError {
+    /// 根据错误码归类
+    pub fn from_api_error(error: &CloudflareApiError) -> Self {
+        let code = error.code;
+        let message = error.message.clone();
+        match code {
+            9103 | 9109 | 10000 => CloudflareError::Authentication { code, message },
+            81057 | 81058 => CloudflareError::DuplicateRecord { code, message },
+            81053 | 81054 => CloudflareError::RecordConflict { code, message },
+            971 | 10100 => CloudflareError::RateLimited { code, message },
+            _ => CloudflareError::Api { code, message },
+        }
+    }
+
+    /// 从失败的HTTP响应构造错误，读取 Retry-After 响应头和响应体
+    pub async fn from_http_response(response: Response) -> Self {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok());
+        let body = response.text().await.unwrap_or_default();
+
+        match Self::from_response(status, &body) {
+            CloudflareError::TooManyRequests { message, .. } => {
+                CloudflareError::TooManyRequests { retry_after, message }
+            }
+            other => other,
+        }
+    }
+
+    /// 从HTTP状态码和响应体构造错误，优先使用响应体中的第一个错误
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        let envelope: Option<ErrorEnvelope> = serde_json::from_str(body).ok();
+        let first_error = envelope.as_ref().and_then(|e| e.errors.first());
+
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return CloudflareError::TooManyRequests {
+                retry_after: None,
+                message: first_error
+                    .map(|e| format!("[{}] {}", e.code, e.message))
+                    .unwrap_or_else(|| body.chars().take(200).collect()),
+            };
+        }
+
+        match first_error {
+            Some(error) => Self::from_api_error(error),
+            None => CloudflareError::Http {
+                status: status.as_u16(),
+                body: body.chars().take(200).collect(),
+            },
+        }
+    }
+
+    /// 校验Cloudflare标准响应信封
+    /// HTTP状态码成功时Cloudflare仍可能返回 success:false，此时同样按 errors 构造结构化错误
+    pub fn check_envelope(status: StatusCode, body: &str) -> Result<(), CloudflareError> {
+        if !status.is_success() {
+            return Err(Self::from_response(status, body));
+        }
+
+        let http_error = || CloudflareError::Http {
+            status: status.as_u16(),
+            body: body.chars().take(200).collect(),
+        };
+        let envelope: ErrorEnvelope = serde_json::from_str(body).map_err(|_| http_error())?;
+        if envelope.success != Some(false) {
+            return Ok(());
+        }
+        Err(envelope.errors.first().map(Self::from_api_error).unwrap_or_else(http_error))
+    }
+
+    /// Cloudflare错误码，HTTP错误时为None
+    pub fn code(&self) -> Option<u32> {
+        match self {
+            CloudflareError::Authentication { code, .. }
+            | CloudflareError::DuplicateRecord { code, .. }
+            | CloudflareError::RecordConflict { code, .. }
+            | CloudflareError::RateLimited { code, .. }
+            | CloudflareError::Api { code, .. } => Some(*code),
+            CloudflareError::TooManyRequests { .. } | CloudflareError::Http { .. } => None,
+        }
+    }
+
+    /// 限流错误建议的等待时间
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            CloudflareError::TooManyRequests { retry_after, .. } => retry_after.map(Duration::from_secs),
+            _ => None,
+        }
+    }
+
+    /// 是否值得重试，认证失败和记录冲突重试也不会成功
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            CloudflareError::Authentication { .. }
+            | CloudflareError::DuplicateRecord { .. }
+            | CloudflareError::RecordConflict { .. } => false,
+            CloudflareError::RateLimited { .. } | CloudflareError::TooManyRequests { .. } => true,
+            CloudflareError::Api { .. } => true,
+            CloudflareError::Http { status, .. } => *status >= 500,
+        }
+    }
+}
+
+/// 错误是否由无法连接服务器引起（连接失败或超时），而不是服务器返回了错误
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors_array() {
+        let body = r#"{"success":false,"errors":[{"code":81057,"message":"Record already exists."}],"messages":[],"result":null}"#;
+        let error = CloudflareError::from_response(StatusCode::BAD_REQUEST, body);
+        assert!(matches!(error, CloudflareError::DuplicateRecord { code: 81057, .. }));
+        assert!(!error.is_retryable());
+
+        let body = r#"{"success":false,"errors":[{"code":9109,"message":"Invalid access token"}]}"#;
+        let error = CloudflareError::from_response(StatusCode::FORBIDDEN, body);
+        assert!(matches!(error, CloudflareError::Authentication { code: 9109, .. }));
+        assert_eq!(error.code(), Some(9109));
+    }
+
+    #[test]
+    fn test_429_is_too_many_requests() {
+        let body = r#"{"success":false,"errors":[{"code":971,"message":"Please wait and consider throttling your request speed"}]}"#;
+        let error = CloudflareError::from_response(StatusCode::TOO_MANY_REQUESTS, body);
+        assert!(matches!(error, CloudflareError::TooManyRequests { .. }));
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_unparseable_body_falls_back_to_http() {
+        let error = CloudflareError::from_response(StatusCode::BAD_GATEWAY, "<html>bad gateway</html>");
+        assert!(matches!(error, CloudflareError::Http { status: 502, .. }));
+        assert!(error.is_retryable());
+        assert_eq!(error.code(), None);
+    }
+
+    #[test]
+    fn test_success_false_with_http_200() {
+        let body = r#"{"success":false,"errors":[{"code":81058,"message":"An identical record already exists."}],"messages":[],"result":null}"#;
+        let error = CloudflareError::check_envelope(StatusCode::OK, body).unwrap_err();
+        assert!(matches!(error, CloudflareError::DuplicateRecord { code: 81058, .. }));
+
+        let body = r#"{"success":false,"errors":[],"messages":[],"result":null}"#;
+        let error = CloudflareError::check_envelope(StatusCode::OK, body).unwrap_err();
+        assert!(matches!(error, CloudflareError::Http { status: 200, .. }));
+
+        let body = r#"{"success":true,"errors":[],"messages":[],"result":{"id":"abc"}}"#;
+        assert!(CloudflareError::check_envelope(StatusCode::OK, body).is_ok());
+        assert!(CloudflareError::check_envelope(StatusCode::OK, "not json").is_err());
+    }
+}
@@ -1,93 +1,832 @@
-use crate::config::database::{Database, AppConfig};
-use crate::services::cloudflare::{CloudflareClient, CloudflareConfig};
-use crate::utils::network::get_preferred_ipv6;
-use anyhow::Result;
-use tracing::{info, error};
+use crate::config::data_dir::db_path;
+use crate::config::database::{ActionCounts, ApiToken, ChangeDetection, Database, IpSource, LanHost, AppConfig, NewDnsUpdateRecord, DeletedSubdomain, DomainState, PauseState, RecordOwnership, StatusEvent, TxtRecordChange};
+use crate::services::api_tokens::{self, CreatedApiToken, TokenDenial};
+use crate::services::backup_hostname;
+use crate::services::bark;
+use crate::services::cloudflare::{http_client, http_client_builder, CloudflareClient, CloudflareConfig, ConnectionReport, DnsRecord, PatchDnsRecordRequest, UpdateDnsRecordRequest};
+use crate::services::cloudflare_error::{self, CloudflareError};
+use crate::services::dingtalk;
+use crate::services::discord;
+use crate::services::domain_settings::{self, ImportReport};
+use crate::services::drift::{self, DriftReport};
+use crate::services::events::{DomainEventKind, DomainEvents};
+use crate::services::fault_injection::{CycleFaults, FaultInjector};
+use crate::services::flapping::{self, FlapState};
+use crate::services::gotify;
+use crate::services::https_record::{self, SvcbData};
+use crate::services::live_dns::{self, LiveAnswer};
+use crate::services::mqtt::{self, HomeAssistantState, MqttMessage};
+use crate::services::notify::{self, NotifyEvent, NotifyKind};
+use crate::services::notify_webhook;
+use crate::services::ntfy;
+use crate::services::ownership;
+use crate::services::propagation::{self, Propagation};
+use crate::services::pushplus;
+use crate::services::schedule::{AdaptiveSchedule, BurstSettings, OFFLINE_AFTER_FAILURES};
+use crate::services::serverchan;
+use crate::services::slack;
+use crate::services::status::{send_status_webhook, ServiceStatus, StatusTracker};
+use crate::services::telegram;
+use crate::services::uptime_kuma;
+use crate::services::wecom;
+use crate::utils::address_selection::AddressSelection;
+use crate::utils::clock::{system_clock, SharedClock};
+use crate::utils::network::{
+    check_allowed_prefix, check_publishable, get_external_ipv6, get_ipv6_address_info, get_ipv6_by_prefix_rules, get_local_ipv6,
+    get_preferred_ipv6, interface_source_address,
+};
+use crate::utils::ip_command::run_ip_command;
+use crate::utils::lan_discovery::{self, Neighbor};
+use crate::utils::prefix_delegation;
+use crate::utils::stun::get_stun_ipv6;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::future::Future;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Instant;
+use std::sync::{Arc, RwLock};
+use tokio::sync::{watch, Mutex, MutexGuard, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{info, error, warn, debug};
+
+/// 软删除子域名的可恢复保留期（天）
+pub const SOFT_DELETE_RETENTION_DAYS: i64 = 30;
+
+/// 检查IP抖动时读取的更新历史条数上限
+const FLAP_HISTORY_LIMIT: i32 = 200;
 
 #[derive(Clone)]
 pub struct ConfigService {
     db: Database,
+    faults: FaultInjector,
+    cycle_lock: Arc<Mutex<()>>,
+    ip_consistency: Arc<RwLock<Option<IpConsistency>>>,
+    status: StatusTracker,
+    schedule: Arc<RwLock<AdaptiveSchedule>>,
+    pause: Arc<RwLock<Option<PauseState>>>,
+    flapping: Arc<RwLock<Option<FlapState>>>,
+    last_check: Arc<RwLock<Option<CheckRun>>>,
+    reconcile: Arc<RwLock<ReconcileState>>,
+    summary_due: Arc<RwLock<Option<DateTime<Utc>>>>,
+    events: DomainEvents,
+    config_changes: Arc<watch::Sender<u64>>,
+    dry_run_ip: Arc<RwLock<Option<String>>>,
+    clock: SharedClock,
+}
+
+/// 本地选择的IPv6与外部服务看到的IPv6的比对结果
+#[derive(Debug, Clone, Serialize)]
+pub struct IpConsistency {
+    pub local_ip: String,
+    pub external_ip: Option<String>,
+    pub consistent: Option<bool>, // 外部检测失败、无法判断时为null
+    pub error: Option<String>,
+    pub mismatch_since: Option<DateTime<Utc>>, // 持续不一致的起始时间，无法判断的周期沿用之前的状态
+    pub checked_at: DateTime<Utc>,
+}
+
+/// 发现的局域网邻居，附带按委派前缀拆出的后缀和已映射的子域名
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredHost {
+    #[serde(flatten)]
+    pub neighbor: Neighbor,
+    pub suffix: String,
+    /// 地址位于当前检测到的委派前缀内，映射后发布的地址与该地址一致
+    pub in_prefix: bool,
+    pub subdomain: Option<String>,
 }
 
 impl ConfigService {
     pub fn new() -> Result<Self> {
-        let db = Database::new()?;
-        Ok(Self { db })
+        Ok(Self::with_clock(Database::new(db_path())?, system_clock()))
     }
 
-    /// 测试Cloudflare配置
-    pub async fn test_config(
-        &self, 
-        api_key: &str, 
-        zone_id: &str, 
-        root_domain: &str
-    ) -> Result<bool> {
-        let config = CloudflareConfig {
-            api_key: api_key.to_string(),
-            zone_id: zone_id.to_string(),
-            root_domain: root_domain.to_string(),
+    /// 使用指定的数据库和时钟创建服务，测试中可传入内存数据库和手动时钟
+    pub fn with_clock(db: Database, clock: SharedClock) -> Self {
+        // 恢复重启前保存的暂停状态
+        let pause = db.get_pause_state().unwrap_or_else(|e| {
+            warn!("⚠️ 读取暂停状态失败: {}", e);
+            None
+        });
+        Self {
+            db,
+            faults: FaultInjector::new(),
+            cycle_lock: Arc::new(Mutex::new(())),
+            ip_consistency: Arc::new(RwLock::new(None)),
+            status: StatusTracker::new(),
+            schedule: Arc::new(RwLock::new(AdaptiveSchedule::new())),
+            pause: Arc::new(RwLock::new(pause)),
+            flapping: Arc::new(RwLock::new(None)),
+            last_check: Arc::new(RwLock::new(None)),
+            reconcile: Arc::new(RwLock::new(ReconcileState::default())),
+            summary_due: Arc::new(RwLock::new(None)),
+            events: DomainEvents::new(),
+            config_changes: Arc::new(watch::channel(0).0),
+            dry_run_ip: Arc::new(RwLock::new(None)),
+            clock,
+        }
+    }
+
+    /// 服务使用的时钟
+    pub fn clock(&self) -> &SharedClock {
+        &self.clock
+    }
+
+    /// 底层数据库，用于备份
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// 获取更新周期锁
+    /// 检查周期在持有锁期间使用同一份配置快照，配置写入也需持有该锁，
+    /// 从而保证新配置只会在两个周期之间生效
+    pub async fn lock_cycle(&self) -> MutexGuard<'_, ()> {
+        self.cycle_lock.lock().await
+    }
+
+    /// 获取模拟故障注入器
+    pub fn fault_injector(&self) -> &FaultInjector {
+        &self.faults
+    }
+
+    /// 演练模式下该IP是否已经演练过，避免IP不变时每个周期重复记录相同的演练结果
+    pub fn dry_run_previewed(&self, ip: &str) -> bool {
+        self.dry_run_ip.read().unwrap().as_deref() == Some(ip)
+    }
+
+    /// 记录已演练过的IP
+    pub fn mark_dry_run(&self, ip: &str) {
+        *self.dry_run_ip.write().unwrap() = Some(ip.to_string());
+    }
+
+    /// 定时任务触发时判断是否需要检查，需要时记录本次检查
+    pub fn take_scheduled_check(&self, settings: &BurstSettings) -> bool {
+        let now = self.clock.now();
+        let mut schedule = self.schedule.write().unwrap();
+        if !schedule.is_due(now, settings) {
+            return false;
+        }
+        schedule.mark_checked(now, settings);
+        true
+    }
+
+    /// 按当前生效的间隔预计的下一次定时检查时间，本次运行尚未执行过定时检查时为None
+    pub fn next_scheduled_check(&self, config: &AppConfig) -> Option<DateTime<Utc>> {
+        let settings = BurstSettings::from_config(config);
+        let due = self.schedule.read().unwrap().next_due(&settings)?;
+        let now = self.clock.now();
+        Some(self.clock.utc_now() + chrono::Duration::from_std(due.saturating_duration_since(now)).ok()?)
+    }
+
+    /// 单个域名的活动事件（同步、确认、警告、错误）
+    pub fn domain_events(&self) -> &DomainEvents {
+        &self.events
+    }
+
+    /// 记录一次检查的结果
+    pub fn note_check_result(&self, result: &Result<bool>) {
+        *self.last_check.write().unwrap() = Some(CheckRun {
+            finished_at: self.clock.utc_now(),
+            updated: matches!(result, Ok(true)),
+            status: self.current_status(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+
+    /// 最近一次检查的结果，本次运行尚未检查过时为None
+    pub fn last_check(&self) -> Option<CheckRun> {
+        self.last_check.read().unwrap().clone()
+    }
+
+    /// 判断是否到了对账时间，到期时安排下一次对账
+    /// 启用后第一次对账在一个对账间隔之后执行，关闭时清除安排
+    pub fn take_reconcile_due(&self, config: &AppConfig) -> bool {
+        let mut state = self.reconcile.write().unwrap();
+        if config.reconcile_interval == 0 {
+            state.next_due = None;
+            return false;
+        }
+        let now = self.clock.utc_now();
+        let next = now + chrono::Duration::minutes(config.reconcile_interval as i64);
+        match state.next_due {
+            Some(due) if now >= due => {
+                state.next_due = Some(next);
+                true
+            }
+            Some(_) => false,
+            None => {
+                state.next_due = Some(next);
+                false
+            }
+        }
+    }
+
+    /// 最近一次对账的结果和下一次对账的预计时间
+    pub fn reconcile_state(&self) -> ReconcileState {
+        self.reconcile.read().unwrap().clone()
+    }
+
+    /// 对账：读取所有选中域名的记录，修复与本机IP或配置不一致的记录，不依赖last_ip是否变化
+    /// 只有修复了记录或处理失败时才写入更新历史
+    pub async fn reconcile(&self) -> Result<ReconcileRun> {
+        let _cycle = self.lock_cycle().await;
+        let config = self.load_configuration()?;
+        let faults = self.faults.take_cycle();
+        let current_ip = self.detect_ipv6_for_cycle(&config, faults).await?;
+        let client = self.cloudflare_client_for_cycle(CloudflareConfig::from_app_config(&config), faults);
+        
+        info!("🔎 开始对账 {} 个域名记录", config.selected_subdomains.len());
+        let mut summary = self.update_selected_domains(&config, &client, &current_ip, false).await?;
+        summary.backup_hostname = self.publish_backup_hostname(&config, &current_ip, summary.dry_run).await;
+        let run = ReconcileRun {
+            finished_at: self.clock.utc_now(),
+            checked: summary.total_count,
+            repaired: summary.actions.created + summary.actions.updated,
+            failed: summary.total_count - summary.success_count,
+            dry_run: summary.dry_run,
         };
+        if run.repaired > 0 || run.failed > 0 {
+            warn!("🛠️ 对账完成: 修复 {} 个记录，失败 {} 个", run.repaired, run.failed);
+            self.record_update(&config, config.last_ip.clone(), &current_ip, &summary);
+            self.report_summary_status(&config, &summary);
+        } else {
+            info!("✅ 对账完成，{} 个记录均与本机IP一致", run.checked);
+        }
+        if summary.success_count > 0 && !summary.dry_run && config.last_ip.as_deref() != Some(current_ip.as_str()) {
+            self.update_last_ip(&current_ip)?;
+        }
         
-        let client = CloudflareClient::new(config);
-        client.test_connection().await
+        self.reconcile.write().unwrap().last = Some(run.clone());
+        Ok(run)
     }
 
-    /// 保存配置
-    pub fn save_configuration(
-        &self,
-        api_key: String,
-        zone_id: String,
-        root_domain: String,
-        selected_subdomains: Vec<String>,
-        check_interval: u64,
-    ) -> Result<()> {
-        // 先获取当前IP，用于初始化配置
-        let current_ip = match get_preferred_ipv6() {
-            Ok(ip) => Some(ip.to_string()),
-            Err(_) => None,
+    /// 检测到IP变化后进入突发检查模式；只有委派前缀变化时记录新旧前缀及需要重写的主机记录数
+    pub fn note_ip_change(&self, config: &AppConfig, old_ip: Option<&str>, new_ip: &str) {
+        self.publish_mqtt(config, "ip_change", json!({
+            "old_ip": old_ip,
+            "new_ip": new_ip,
+            "root_domain": config.root_domain,
+            "timestamp": self.clock.utc_now(),
+        }));
+        if let (Some(Ok(old)), Ok(new)) = (old_ip.map(str::parse::<Ipv6Addr>), new_ip.parse::<Ipv6Addr>()) {
+            if let Some((old_prefix, new_prefix)) = prefix_delegation::prefix_only_change(old, new, config.delegated_prefix_len) {
+                info!(
+                    "🔀 委派前缀变化: {} -> {}，将重写 {} 个主机记录",
+                    old_prefix,
+                    new_prefix,
+                    config.selected_subdomains.iter().filter(|s| config.host_suffixes.contains_key(*s)).count()
+                );
+            }
+        }
+        let settings = BurstSettings::from_config(config);
+        if !settings.enabled() {
+            return;
+        }
+        self.schedule.write().unwrap().note_change(self.clock.now(), &settings);
+        info!(
+            "⚡ 进入突发检查模式: 接下来 {} 分钟内每 {} 秒检查一次",
+            config.burst_duration, config.burst_interval
+        );
+    }
+
+    /// 记录一次无法连接网络的检查，返回应报告的状态：持续不可达时为离线，否则为失败
+    pub fn note_unreachable(&self, config: &AppConfig) -> ServiceStatus {
+        let settings = BurstSettings::from_config(config);
+        let mut schedule = self.schedule.write().unwrap();
+        if schedule.note_unreachable(&settings) {
+            warn!(
+                "📴 连续 {} 次检查无法连接网络，检查间隔延长到 {} 秒",
+                OFFLINE_AFTER_FAILURES,
+                schedule.current_interval(&settings).as_secs()
+            );
+        } else if schedule.is_offline() {
+            debug!("📴 网络仍不可达，检查间隔延长到 {} 秒", schedule.current_interval(&settings).as_secs());
+        }
+        match schedule.is_offline() {
+            true => ServiceStatus::Offline,
+            false => ServiceStatus::Failing,
+        }
+    }
+
+    /// 记录一次网络正常的检查，离线期间恢复时回到正常的检查间隔
+    pub fn note_reachable(&self) {
+        if self.schedule.write().unwrap().note_reachable() {
+            info!("📶 网络已恢复，回到正常的检查间隔");
+        }
+    }
+
+    /// 当前生效的检查间隔（秒）
+    pub fn current_check_interval(&self, config: &AppConfig) -> u64 {
+        let settings = BurstSettings::from_config(config);
+        self.schedule.read().unwrap().current_interval(&settings).as_secs()
+    }
+
+    /// 暂停定时检查，until为None时一直暂停到手动恢复；暂停状态保存在数据库中，重启后仍然有效
+    /// 手动触发的更新不受影响
+    pub fn pause(&self, until: Option<DateTime<Utc>>, reason: Option<String>) -> Result<PauseState> {
+        let state = PauseState {
+            paused_at: self.clock.utc_now(),
+            until,
+            reason,
+        };
+        self.db.save_pause_state(Some(&state))?;
+        *self.pause.write().unwrap() = Some(state.clone());
+        match until {
+            Some(until) => info!("⏸️ 定时检查已暂停，至 {}", until.to_rfc3339()),
+            None => info!("⏸️ 定时检查已暂停，直到手动恢复"),
+        }
+        Ok(state)
+    }
+
+    /// 恢复定时检查，返回恢复前是否处于暂停中
+    pub fn resume(&self) -> Result<bool> {
+        let was_paused = self.pause_state().is_some();
+        self.db.save_pause_state(None)?;
+        if self.pause.write().unwrap().take().is_some() && was_paused {
+            info!("▶️ 定时检查已恢复");
+        }
+        Ok(was_paused)
+    }
+
+    /// 当前的暂停状态，未暂停或已到期时为None
+    pub fn pause_state(&self) -> Option<PauseState> {
+        let state = self.pause.read().unwrap().clone();
+        state.filter(|state| state.is_active(self.clock.utc_now()))
+    }
+
+    /// 当前的IP抖动状态，未抖动时为None
+    pub fn flap_state(&self) -> Option<FlapState> {
+        self.flapping.read().unwrap().clone()
+    }
+
+    /// 根据更新历史检查IP是否在两个地址间反复切换；抖动开始和结束时各告警一次，期间不推送状态变化通知
+    pub fn check_flapping(&self, config: &AppConfig) {
+        let detected = match self.detect_flapping(config) {
+            Ok(detected) => detected,
+            Err(e) => {
+                error!("❌ 检查IP抖动失败: {}", e);
+                return;
+            }
         };
         
-        let config = AppConfig {
-            cloudflare_api_key: api_key,
-            cloudflare_zone_id: zone_id,
-            root_domain,
-            selected_subdomains,
-            check_interval,
-            last_ip: current_ip,
+        let mut flapping = self.flapping.write().unwrap();
+        let (event, state) = match (flapping.as_ref(), detected) {
+            (None, Some(addresses)) => {
+                warn!(
+                    "🔀 检测到IP抖动: 地址在 {} 与 {} 之间反复切换，请检查IP来源配置；抖动停止前不再推送更新通知",
+                    addresses[0], addresses[1]
+                );
+                let state = FlapState { addresses, since: self.clock.utc_now() };
+                *flapping = Some(state.clone());
+                ("ip_flapping", state)
+            }
+            (Some(state), None) => {
+                info!("✅ IP抖动已停止，恢复推送更新通知");
+                let state = state.clone();
+                *flapping = None;
+                ("ip_flapping_resolved", state)
+            }
+            _ => return,
         };
         
-        self.db.save_config(&config)
+        if let Some(url) = config.status_webhook_url.clone() {
+            let root_domain = config.root_domain.clone();
+            let client = http_client(config);
+            tokio::spawn(async move {
+                if let Err(e) = flapping::send_flap_webhook(&client, &url, &root_domain, event, &state).await {
+                    warn!("⚠️ IP抖动告警推送失败: {}", e);
+                }
+            });
+        }
     }
 
-    /// 保存配置并立即更新
-    pub async fn save_configuration_and_update(
-        &self,
-        api_key: String,
-        zone_id: String,
-        root_domain: String,
-        selected_subdomains: Vec<String>,
-        check_interval: u64,
-    ) -> Result<()> {
-        // 先获取当前IP，用于初始化配置
-        let current_ip = match get_preferred_ipv6() {
-            Ok(ip) => Some(ip.to_string()),
-            Err(_) => None,
+    /// 统计时间窗口内最近的地址变化，判断是否在抖动
+    fn detect_flapping(&self, config: &AppConfig) -> Result<Option<[String; 2]>> {
+        if config.flap_threshold == 0 {
+            return Ok(None);
+        }
+        let since = self.clock.utc_now() - chrono::Duration::hours(config.flap_window_hours as i64);
+        let records = self.db.get_dns_update_records(Some(FLAP_HISTORY_LIMIT))?;
+        let changes: Vec<(&str, &str)> = records
+            .iter()
+            .filter(|r| !r.dry_run && r.timestamp >= since)
+            .filter_map(|r| r.old_ip.as_deref().map(|old_ip| (old_ip, r.new_ip.as_str())))
+            .filter(|(old_ip, new_ip)| old_ip != new_ip)
+            .collect();
+        Ok(flapping::detect(&changes, config.flap_threshold))
+    }
+
+    /// 检查周期内检测当前IPv6地址，若注入了检测故障则直接失败
+    pub async fn detect_ipv6_for_cycle(&self, config: &AppConfig, faults: CycleFaults) -> Result<String> {
+        if faults.detection {
+            warn!("🧪 模拟故障生效: IP检测失败");
+            return Err(anyhow!("模拟故障: IP检测失败"));
+        }
+        self.detect_ipv6(config).await
+    }
+
+    /// 按配置检测IPv6地址：IP来源为external/stun时通过外部HTTP服务或STUN服务器获取（指定了网卡时从该网卡发出），
+    /// 为command时执行自定义命令，为dyndns时使用路由器推送的地址，否则使用本机地址；配置了固定后缀时发布 前缀+固定后缀。
+    /// 最终地址为链路本地、回环、文档示例等明显错误的地址，或不在 allowed_prefix 内时返回错误，不会发布
+    pub async fn detect_ipv6(&self, config: &AppConfig) -> Result<String> {
+        let ip = match config.ip_source {
+            IpSource::Local => self.detect_local_ipv6(config)?,
+            IpSource::External => {
+                get_external_ipv6(http_client_builder(config), &config.external_ip_services, Self::source_address(config)?).await?
+            }
+            IpSource::Stun => get_stun_ipv6(&config.stun_servers, Self::source_address(config)?).await?,
+            IpSource::Command => run_ip_command().await?,
+            IpSource::DynDns => self.pushed_ipv6()?,
+        };
+        let ip = Self::pin_suffix(&config.address_selection, ip)?;
+        check_publishable(ip, true)?;
+        check_allowed_prefix(ip, config.allowed_prefix.as_deref())?;
+        Ok(ip.to_string())
+    }
+
+    /// 向外部服务发出请求时绑定的源地址，多出口的主机上让请求经由指定网卡对应的线路发出
+    fn source_address(config: &AppConfig) -> Result<Option<Ipv6Addr>> {
+        config.interface.as_deref().map(interface_source_address).transpose()
+    }
+
+    /// 检测本机IPv6地址：配置了线路前缀规则时按线路策略选择，否则按地址选择策略选择（默认为系统首选地址，
+    /// 并跳过临时地址）；指定了网卡时只使用该网卡上的地址
+    fn detect_local_ipv6(&self, config: &AppConfig) -> Result<Ipv6Addr> {
+        let interface = config.interface.as_deref();
+        let selection = &config.address_selection;
+        if config.prefix_rules.is_empty() {
+            let candidates = get_ipv6_address_info(interface)?;
+            let preferred = match get_local_ipv6() {
+                Ok(IpAddr::V6(ip)) => Some(ip),
+                _ => None,
+            };
+            return selection.select(&candidates, preferred);
+        }
+        
+        let (ip, label) = get_ipv6_by_prefix_rules(&config.prefix_rules, interface, selection.skip_temporary)?;
+        debug!("🛣️ 按线路策略选择 {} 的地址: {}", label, ip);
+        match ip {
+            IpAddr::V6(ip) => Ok(ip),
+            IpAddr::V4(ip) => Err(anyhow!("线路策略选择的地址 {} 不是IPv6地址", ip)),
+        }
+    }
+
+    fn pin_suffix(selection: &AddressSelection, ip: Ipv6Addr) -> Result<Ipv6Addr> {
+        let pinned = selection.apply_suffix(ip)?;
+        if pinned != ip {
+            debug!("📌 使用固定后缀: {} -> {}", ip, pinned);
+        }
+        Ok(pinned)
+    }
+
+    /// 发布所有地址时，除当前IP外其他符合地址选择条件的本机地址（已排序）；未开启或IP来源不是本机时为空
+    pub fn additional_addresses(&self, config: &AppConfig, current_ip: &str) -> Result<Vec<String>> {
+        if !config.multi_address || config.ip_source != IpSource::Local {
+            return Ok(Vec::new());
+        }
+        let candidates = get_ipv6_address_info(config.interface.as_deref())?;
+        let mut addresses: Vec<String> = config
+            .address_selection
+            .qualifying(&candidates)?
+            .into_iter()
+            .map(|ip| ip.to_string())
+            .filter(|ip| ip != current_ip)
+            .collect();
+        addresses.sort();
+        Ok(addresses)
+    }
+
+    /// 最近一次发布的其他地址
+    pub fn published_additional_ips(&self) -> Vec<String> {
+        self.db.get_additional_ips().unwrap_or_default()
+    }
+
+    pub fn update_additional_ips(&self, ips: &[String]) -> Result<()> {
+        self.db.update_additional_ips(ips)
+    }
+
+    /// 路由器通过DynDNS接口推送的地址，IP来源为dyndns时作为检测结果
+    pub fn get_pushed_ip(&self) -> Result<Option<String>> {
+        self.db.get_pushed_ip()
+    }
+
+    pub fn update_pushed_ip(&self, ip: &str) -> Result<()> {
+        self.db.update_pushed_ip(ip)
+    }
+
+    /// 校验外部系统携带的地址并保存为推送的地址，IP来源需要为dyndns，否则下一次定时检查会把记录改回检测到的地址
+    pub fn accept_pushed_ip(&self, ip: &str) -> Result<Ipv6Addr> {
+        let config = self.load_configuration()?;
+        if config.ip_source != IpSource::DynDns {
+            return Err(anyhow!("携带地址需要IP来源为 dyndns，当前为 {}", config.ip_source.as_str()));
+        }
+        let ip: Ipv6Addr = ip.trim().parse().map_err(|_| anyhow!("不是有效的IPv6地址: {}", ip))?;
+        check_publishable(ip, true)?;
+        check_allowed_prefix(ip, config.allowed_prefix.as_deref())?;
+        self.update_pushed_ip(&ip.to_string())?;
+        Ok(ip)
+    }
+
+    fn pushed_ipv6(&self) -> Result<Ipv6Addr> {
+        let ip = self.get_pushed_ip()?.ok_or_else(|| anyhow!("IP来源为dyndns，但路由器还没有通过 /nic/update 推送地址"))?;
+        ip.parse().map_err(|_| anyhow!("推送的地址无效: {}", ip))
+    }
+
+    /// 若启用了外部校验，比对本地选择的IPv6与外部服务看到的IPv6，变为不一致时告警并通知；IP来源本身为公网视角时不校验
+    pub async fn verify_external_ip(&self, config: &AppConfig, local_ip: &str) {
+        if !config.verify_external_ip || config.ip_source != IpSource::Local {
+            return;
+        }
+        
+        let source = Self::source_address(config).unwrap_or_default();
+        let (external_ip, consistent, error) = match get_external_ipv6(http_client_builder(config), &config.external_ip_services, source).await {
+            Ok(external) => {
+                let consistent = external.to_string() == local_ip;
+                if consistent {
+                    debug!("✅ 外部检测的IPv6与本地一致: {}", local_ip);
+                } else {
+                    warn!(
+                        "⚠️ 本地选择的IPv6 {} 与外部服务看到的IPv6 {} 不一致，DNS可能指向了错误的地址",
+                        local_ip, external
+                    );
+                }
+                (Some(external.to_string()), Some(consistent), None)
+            }
+            Err(e) => {
+                warn!("⚠️ 外部IPv6检测失败，无法校验本地地址: {}", e);
+                (None, None, Some(e.to_string()))
+            }
+        };
+
+        let now = self.clock.utc_now();
+        let previous_since = self.ip_consistency().and_then(|c| c.mismatch_since);
+        let mismatch_since = Self::mismatch_since(previous_since, consistent, now);
+        if let (None, Some(false), Some(external)) = (previous_since, consistent, &external_ip) {
+            let mut event = NotifyEvent::new(NotifyKind::IpMismatch, &config.root_domain, now);
+            event.new_ip = Some(local_ip.to_string());
+            event.message = Some(format!("外部服务看到的地址: {}", external));
+            self.notify(config, event);
+        }
+
+        let result = IpConsistency {
+            local_ip: local_ip.to_string(),
+            external_ip,
+            consistent,
+            error,
+            mismatch_since,
+            checked_at: now,
+        };
+        if let Ok(mut guard) = self.ip_consistency.write() {
+            *guard = Some(result);
+        }
+    }
+
+    /// 不一致的起始时间：恢复一致时清除，无法判断时沿用之前的状态，避免检测偶尔失败导致重复通知
+    fn mismatch_since(previous: Option<DateTime<Utc>>, consistent: Option<bool>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match consistent {
+            Some(true) => None,
+            Some(false) => Some(previous.unwrap_or(now)),
+            None => previous,
+        }
+    }
+
+    /// 最近一次本地/外部IPv6比对结果
+    pub fn ip_consistency(&self) -> Option<IpConsistency> {
+        self.ip_consistency.read().ok().and_then(|guard| guard.clone())
+    }
+
+    /// 为检查周期创建Cloudflare客户端，若注入了Cloudflare故障则所有请求返回500
+    pub fn cloudflare_client_for_cycle(&self, config: CloudflareConfig, faults: CycleFaults) -> CloudflareClient {
+        if faults.cloudflare {
+            warn!("🧪 模拟故障生效: Cloudflare返回500");
+        }
+        CloudflareClient::new(config)
+            .with_simulated_server_error(faults.cloudflare)
+            .with_clock(self.clock.clone())
+    }
+
+    /// 当前服务状态
+    pub fn current_status(&self) -> ServiceStatus {
+        self.status.current()
+    }
+
+    /// 上报检查周期得出的服务状态：每个周期向Uptime Kuma推送心跳，
+    /// 仅在状态变化时记录事件并推送Webhook
+    pub fn report_status(&self, config: &AppConfig, status: ServiceStatus, reason: Option<String>) {
+        if let Some(push_url) = config.uptime_kuma_push_url.clone() {
+            let message = reason.clone().unwrap_or_else(|| status.as_str().to_string());
+            let client = http_client(config);
+            tokio::spawn(async move {
+                if let Err(e) = uptime_kuma::push(&client, &push_url, status, &message).await {
+                    warn!("⚠️ Uptime Kuma推送失败: {}", e);
+                }
+            });
+        }
+        
+        let Some(transition) = self.status.observe(status, reason) else {
+            return;
         };
         
-        let config = AppConfig {
-            cloudflare_api_key: api_key,
-            cloudflare_zone_id: zone_id,
-            root_domain: root_domain.clone(),
-            selected_subdomains: selected_subdomains.clone(),
-            check_interval,
-            last_ip: current_ip,
+        info!(
+            "🚦 服务状态变化: {} -> {}{}",
+            transition.from.as_str(),
+            transition.to.as_str(),
+            transition.reason.as_deref().map(|r| format!(" ({})", r)).unwrap_or_default()
+        );
+        
+        if let Err(e) = self.db.add_status_event(
+            transition.from.as_str(),
+            transition.to.as_str(),
+            transition.reason.as_deref(),
+        ) {
+            error!("❌ 记录状态事件失败: {}", e);
+        }
+        
+        if self.flap_state().is_some() {
+            debug!("🔇 IP抖动期间不推送状态变化通知");
+            return;
+        }
+        if let Some(event) = notify::from_transition(&config.root_domain, &transition, config.last_ip.clone()) {
+            self.notify(config, event);
+        }
+        if let Some(url) = config.status_webhook_url.clone() {
+            let root_domain = config.root_domain.clone();
+            let client = http_client(config);
+            tokio::spawn(async move {
+                if let Err(e) = send_status_webhook(&client, &url, &root_domain, &transition).await {
+                    warn!("⚠️ 状态Webhook推送失败: {}", e);
+                }
+            });
+        }
+    }
+
+    /// 在后台把事件发送到所有已配置的通知渠道，发送失败只记录日志：
+    /// 聊天和推送渠道接收地址变化、失败和恢复，每日统计只发送到开启了统计的渠道，事件Webhook按各自订阅的事件接收
+    fn notify(&self, config: &AppConfig, event: NotifyEvent) {
+        let daily = event.kind == NotifyKind::DailySummary;
+        let chat = matches!(
+            event.kind,
+            NotifyKind::IpChanged | NotifyKind::UpdateFailed | NotifyKind::Recovered | NotifyKind::IpMismatch
+        );
+        // 各渠道共用一个客户端，与Cloudflare API请求使用相同的代理和根证书
+        let client = http_client(config);
+        if let Some(settings) = config.telegram.clone().filter(|t| chat || (daily && t.daily_summary)) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Telegram".to_string(), async move { telegram::send(&client, &settings, &event).await });
+        }
+        if let Some(url) = config.discord_webhook_url.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Discord".to_string(), async move { discord::send(&client, &url, &event).await });
+        }
+        if let Some(settings) = config.slack.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Slack".to_string(), async move { slack::send(&client, &settings, &event).await });
+        }
+        if let Some(settings) = config.ntfy.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("ntfy".to_string(), async move { ntfy::send(&client, &settings, &event).await });
+        }
+        if let Some(settings) = config.gotify.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Gotify".to_string(), async move { gotify::send(&client, &settings, &event).await });
+        }
+        if let Some(url) = config.wecom_webhook_url.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("企业微信".to_string(), async move { wecom::send(&client, &url, &event).await });
+        }
+        if let Some(settings) = config.dingtalk.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("钉钉".to_string(), async move { dingtalk::send(&client, &settings, &event).await });
+        }
+        if let Some(settings) = config.bark.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Bark".to_string(), async move { bark::send(&client, &settings, &event).await });
+        }
+        if let Some(key) = config.serverchan_key.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("Server酱".to_string(), async move { serverchan::send(&client, &key, &event).await });
+        }
+        if let Some(token) = config.pushplus_token.clone().filter(|_| chat) {
+            let (client, event) = (client.clone(), event.clone());
+            Self::spawn_notification("PushPlus".to_string(), async move { pushplus::send(&client, &token, &event).await });
+        }
+        for webhook in config.notify_webhooks.iter().filter(|w| notify_webhook::wants(w, event.kind)).cloned() {
+            let (client, event) = (client.clone(), event.clone());
+            let channel = format!("事件Webhook {}", webhook.url);
+            Self::spawn_notification(channel, async move { notify_webhook::send(&client, &webhook, &event).await });
+        }
+    }
+
+    fn spawn_notification(channel: String, send: impl Future<Output = Result<()>> + Send + 'static) {
+        tokio::spawn(async move {
+            match send.await {
+                Ok(()) => debug!("📨 已发送{}通知", channel),
+                Err(e) => warn!("⚠️ {}通知发送失败: {}", channel, e),
+            }
+        });
+    }
+
+    /// 有渠道开启每日统计时，第一份统计在开启24小时后发送，之后每24小时一次
+    pub fn send_daily_summary_if_due(&self) {
+        let Ok(config) = self.load_configuration() else { return };
+        let enabled = config.telegram.as_ref().is_some_and(|t| t.daily_summary);
+        let now = self.clock.utc_now();
+        {
+            let mut due = self.summary_due.write().unwrap();
+            if !enabled {
+                *due = None;
+                return;
+            }
+            let next = now + chrono::Duration::hours(24);
+            match *due {
+                Some(at) if now >= at => *due = Some(next),
+                Some(_) => return,
+                None => {
+                    *due = Some(next);
+                    return;
+                }
+            }
+        }
+        match self.db.get_dns_update_records(None) {
+            Ok(records) => {
+                let event = notify::daily_summary(&config.root_domain, &records, now, config.last_ip.clone(), self.current_status());
+                self.notify(&config, event);
+            }
+            Err(e) => warn!("⚠️ 读取更新历史失败，跳过每日统计: {}", e),
+        }
+    }
+
+    /// 配置了MQTT代理时在后台把消息发布到 主题前缀/subtopic，发布失败只记录日志；
+    /// 启用Home Assistant时同时发布传感器的发现配置和最新状态
+    fn publish_mqtt(&self, config: &AppConfig, subtopic: &str, payload: serde_json::Value) {
+        let Some(settings) = config.mqtt.clone() else {
+            return;
+        };
+        let mut messages = vec![MqttMessage::under(&settings, subtopic, payload.to_string())];
+        if settings.home_assistant {
+            match self.db.get_dns_update_records(None) {
+                Ok(records) => {
+                    let mut state = HomeAssistantState::from_history(&records);
+                    state.current_ip = state.current_ip.or_else(|| config.last_ip.clone());
+                    messages.extend(mqtt::home_assistant_messages(&settings, &config.root_domain, &state));
+                }
+                Err(e) => warn!("⚠️ 读取更新历史失败，跳过Home Assistant状态: {}", e),
+            }
+        }
+        tokio::spawn(async move {
+            match mqtt::publish(&settings, &messages).await {
+                Ok(()) => debug!("📡 已发布MQTT消息"),
+                Err(e) => warn!("⚠️ MQTT发布失败: {}", e),
+            }
+        });
+    }
+
+    /// 获取最近的状态转换事件
+    pub fn get_status_events(&self, limit: i32) -> Result<Vec<StatusEvent>> {
+        self.db.get_status_events(limit)
+    }
+
+    /// 根据更新结果上报状态
+    pub fn report_summary_status(&self, config: &AppConfig, summary: &UpdateSummary) {
+        let status = ServiceStatus::from_counts(summary.success_count, summary.total_count);
+        let reason = match status {
+            ServiceStatus::InSync => None,
+            _ => summary.error_message.clone(),
         };
+        self.report_status(config, status, reason);
+    }
+
+    /// 测试Cloudflare配置
+    pub async fn test_config(&self, cf_config: CloudflareConfig) -> Result<ConnectionReport> {
+        let client = CloudflareClient::new(cf_config);
+        client.test_connection().await
+    }
+
+    /// 保存配置，若有正在进行的检查周期则等待其完成
+    pub async fn save_configuration(&self, mut config: AppConfig) -> Result<()> {
+        // 先获取当前IP，用于初始化配置
+        config.last_ip = self.detect_ipv6(&config).await.ok();
         
+        let _cycle = self.lock_cycle().await;
         self.db.save_config(&config)?;
+        self.config_changes.send_modify(|version| *version += 1);
+        Ok(())
+    }
+
+    /// 订阅配置保存通知，每次保存配置后收到新的版本号
+    pub fn subscribe_config_changes(&self) -> watch::Receiver<u64> {
+        self.config_changes.subscribe()
+    }
+
+    /// 保存配置并立即更新
+    pub async fn save_configuration_and_update(&self, config: AppConfig) -> Result<()> {
+        self.save_configuration(config).await?;
         
         // 保存配置后立即执行更新
         info!("💾 配置保存完成，开始立即更新...");
-        if let Err(e) = self.check_and_update_now().await {
+        if let Err(e) = self.check_and_update_now(false).await {
             error!("❌ 立即更新失败: {}", e);
             // 不返回错误，因为配置保存成功了
         }
@@ -106,19 +845,9 @@ impl ConfigService {
     }
 
     /// 获取域名列表
-    pub async fn get_domain_list(
-        &self,
-        api_key: &str,
-        zone_id: &str,
-        root_domain: &str
-    ) -> Result<Vec<String>> {
-        let config = CloudflareConfig {
-            api_key: api_key.to_string(),
-            zone_id: zone_id.to_string(),
-            root_domain: root_domain.to_string(),
-        };
-        
-        let client = CloudflareClient::new(config);
+    pub async fn get_domain_list(&self, cf_config: CloudflareConfig) -> Result<Vec<String>> {
+        let root_domain = cf_config.root_domain.clone();
+        let client = CloudflareClient::new(cf_config);
         let records = client.get_dns_records().await?;
         
         // 提取所有子域名
@@ -150,115 +879,2201 @@ impl ConfigService {
         self.db.get_last_ip()
     }
 
-    /// 获取当前IPv6地址
-    pub fn get_current_ipv6(&self) -> Result<String> {
-        let ip = get_preferred_ipv6()?;
-        Ok(ip.to_string())
+    /// 获取当前IPv6地址，已有配置时遵循线路策略
+    pub async fn get_current_ipv6(&self) -> Result<String> {
+        match self.load_configuration() {
+            Ok(config) => self.detect_ipv6(&config).await,
+            Err(_) => Ok(get_preferred_ipv6()?.to_string()),
+        }
     }
 
-    /// 立即执行IP检查和更新
-    pub async fn check_and_update_now(&self) -> Result<bool> {
+    /// 立即执行IP检查和更新，force为true时重写所有选中的记录，即使内容已经一致
+    /// 返回是否有域名更新成功
+    pub async fn check_and_update_now(&self, force: bool) -> Result<bool> {
         if !self.has_configuration() {
             info!("⚠️ 没有配置，跳过立即更新");
             return Ok(false);
         }
+        let report = self.update_now(force).await?;
+        Ok(report.summary.success_count > 0)
+    }
+
+    /// 立即执行IP检查和更新，返回包含每个域名处理结果的报告
+    pub async fn update_now(&self, force: bool) -> Result<UpdateReport> {
+        if !self.has_configuration() {
+            return Err(anyhow!("尚未保存配置"));
+        }
 
+        // 整个周期持有锁，并使用同一份配置快照
+        let _cycle = self.lock_cycle().await;
         let config = self.load_configuration()?;
+        let faults = self.faults.take_cycle();
         
         // 获取当前IP
-        let current_ip = match get_preferred_ipv6() {
-            Ok(ip) => ip.to_string(),
+        let current_ip = match self.detect_ipv6_for_cycle(&config, faults).await {
+            Ok(ip) => ip,
             Err(e) => {
                 error!("❌ 获取当前IP失败: {}", e);
-                return Ok(false);
+                self.report_status(&config, ServiceStatus::Failing, Some(format!("获取当前IP失败: {}", e)));
+                return Err(anyhow!("获取当前IP失败: {}", e));
             }
         };
 
         info!("🌐 立即更新 - 当前检测到的IPv6地址: {}", current_ip);
+        if force {
+            info!("🔁 强制更新：内容一致的记录也会重新写入");
+        }
+        self.verify_external_ip(&config, &current_ip).await;
+        if config.last_ip.as_deref() != Some(current_ip.as_str()) {
+            self.note_ip_change(&config, config.last_ip.as_deref(), &current_ip);
+        }
         
         // 创建Cloudflare客户端
-        let cf_config = CloudflareConfig {
-            api_key: config.cloudflare_api_key,
-            zone_id: config.cloudflare_zone_id,
-            root_domain: config.root_domain.clone(),
-        };
-        
-        let client = CloudflareClient::new(cf_config);
+        let cf_config = CloudflareConfig::from_app_config(&config);
+        let client = self.cloudflare_client_for_cycle(cf_config, faults);
         
         // 更新选中的子域名
-        let mut success_count = 0;
-        let mut total_count = 0;
-        let mut error_message = None;
+        let mut summary = self.update_selected_domains(&config, &client, &current_ip, force).await?;
+        summary.backup_hostname = self.publish_backup_hostname(&config, &current_ip, summary.dry_run).await;
         
-        info!("📝 立即更新 - 开始更新 {} 个域名记录", config.selected_subdomains.len());
+        // 记录DNS更新记录
+        self.record_update(&config, config.last_ip.clone(), &current_ip, &summary);
+        self.report_summary_status(&config, &summary);
         
-        for subdomain in &config.selected_subdomains {
-            total_count += 1;
-            
-            let full_domain = if subdomain.is_empty() {
-                config.root_domain.clone()
-            } else {
-                format!("{}.{}", subdomain, config.root_domain)
-            };
-            
-            info!("🔍 立即更新 - 处理域名: {}", full_domain);
-            
-            match client.get_aaaa_records(&full_domain).await {
-                Ok(records) => {
-                    if let Some(record) = records.first() {
-                        // 检查IP是否真的发生了变化
-                        if record.content == current_ip {
-                            info!("✅ 立即更新 - IP地址未变化，跳过更新: {} -> {}", full_domain, current_ip);
-                            success_count += 1; // 这种情况也算成功
-                            continue;
-                        }
-                        
-                        // 更新现有记录
-                        if let Ok(true) = client.update_dns_record(&record.id, current_ip.parse()?).await {
-                            success_count += 1;
-                            info!("✅ 立即更新 - 成功更新域名: {} -> {}", full_domain, current_ip);
-                        } else {
-                            error!("❌ 立即更新 - 更新域名失败: {}", full_domain);
-                            error_message = Some(format!("更新域名失败: {}", full_domain));
-                        }
-                    } else {
-                        // 创建新记录
-                        if let Ok(true) = client.create_aaaa_record(subdomain, current_ip.parse()?).await {
-                            success_count += 1;
-                            info!("✅ 立即更新 - 成功创建域名: {} -> {}", full_domain, current_ip);
-                        } else {
-                            error!("❌ 立即更新 - 创建域名失败: {}", full_domain);
-                            error_message = Some(format!("创建域名失败: {}", full_domain));
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("❌ 立即更新 - 获取域名记录失败 {}: {}", full_domain, e);
-                    error_message = Some(format!("获取域名记录失败 {}: {}", full_domain, e));
+        // 更新最后记录的IP，演练模式下保持不变
+        if summary.success_count > 0 {
+            if !summary.dry_run {
+                self.update_last_ip(&current_ip)?;
+                if summary.error_message.is_none() {
+                    self.update_additional_ips(&self.additional_addresses(&config, &current_ip)?)?;
                 }
             }
+            info!("🎉 立即更新完成: 成功 {}/{} 个域名", summary.success_count, summary.total_count);
+        } else {
+            error!("❌ 立即更新 - 所有域名更新都失败了");
         }
         
-        // 记录DNS更新记录
-        let last_ip = self.get_last_ip()?;
-        if let Err(e) = self.db.add_dns_update_record(
-            last_ip.clone(),
-            &current_ip,
-            total_count as i32,
-            success_count as i32,
-            error_message.clone(),
-        ) {
-            error!("❌ 记录DNS更新记录失败: {}", e);
+        Ok(UpdateReport {
+            ip_changed: config.last_ip.as_deref() != Some(current_ip.as_str()),
+            previous_ip: config.last_ip,
+            current_ip,
+            force,
+            summary,
+        })
+    }
+
+    /// 立即同步单个子域名，不受IP是否变化影响，也不更新最后记录的IP
+    pub async fn sync_subdomain(&self, subdomain: &str) -> Result<UpdateSummary> {
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        if !config.selected_subdomains.iter().any(|s| s == subdomain) {
+            return Err(anyhow!("子域名未被管理: {}", config.full_domain(subdomain)));
         }
         
-        // 更新最后记录的IP
-        if success_count > 0 {
-            self.update_last_ip(&current_ip)?;
-            info!("🎉 立即更新完成: 成功 {}/{} 个域名", success_count, total_count);
-            Ok(true)
-        } else {
-            error!("❌ 立即更新 - 所有域名更新都失败了");
-            Ok(false)
-        }
+        let faults = self.faults.take_cycle();
+        let current_ip = self.detect_ipv6_for_cycle(&config, faults).await?;
+        let old_ip = config.last_ip.clone();
+        config.selected_subdomains = vec![subdomain.to_string()];
+        
+        let client = self.cloudflare_client_for_cycle(CloudflareConfig::from_app_config(&config), faults);
+        let summary = self.update_selected_domains(&config, &client, &current_ip, false).await?;
+        self.record_update(&config, old_ip, &current_ip, &summary);
+        Ok(summary)
     }
-}
\ No newline at end of file
+
+    /// 按并发数分批同步子域名并通过回调报告进度，不受IP是否变化影响
+    /// subdomains为None时同步全部受管子域名，内容已一致的记录也会重写；全部受管子域名都同步成功时才更新最后记录的IP
+    pub async fn force_sync<F>(&self, subdomains: Option<Vec<String>>, mut on_domain: F) -> Result<UpdateSummary>
+    where
+        F: FnMut(&str, Option<&str>),
+    {
+        let _cycle = self.lock_cycle().await;
+        let config = self.load_configuration()?;
+        let subdomains = match subdomains {
+            Some(list) => {
+                if let Some(unknown) = list.iter().find(|s| !config.selected_subdomains.contains(s)) {
+                    return Err(anyhow!("子域名未被管理: {}", config.full_domain(unknown)));
+                }
+                list
+            }
+            None => config.selected_subdomains.clone(),
+        };
+        
+        let faults = self.faults.take_cycle();
+        let current_ip = self.detect_ipv6_for_cycle(&config, faults).await?;
+        let client = self.cloudflare_client_for_cycle(CloudflareConfig::from_app_config(&config), faults);
+        let mut summary = UpdateSummary {
+            dry_run: config.is_dry_run(),
+            ..Default::default()
+        };
+        
+        // 按配置的并发数分批处理，每批完成后报告其中各域名的结果
+        let parallelism = config.update_parallelism.max(1) as usize;
+        for batch in subdomains.chunks(parallelism) {
+            let mut partial = config.clone();
+            partial.selected_subdomains = batch.to_vec();
+            
+            match self.update_selected_domains(&partial, &client, &current_ip, true).await {
+                Ok(result) => summary.merge(result),
+                Err(e) => {
+                    summary.total_count += batch.len() as i32;
+                    summary.error_message = Some(e.to_string());
+                    for subdomain in batch {
+                        summary.domains.push(DomainResult {
+                            subdomain: subdomain.clone(),
+                            domain: config.full_domain(subdomain),
+                            outcome: DomainOutcome::Failed,
+                            error: Some(e.to_string()),
+                            propagation: None,
+                        });
+                    }
+                }
+            }
+            for subdomain in batch {
+                let full_domain = config.full_domain(subdomain);
+                let error = match summary.domains.iter().rev().find(|d| &d.subdomain == subdomain) {
+                    Some(result) if result.outcome != DomainOutcome::Failed => None,
+                    result => Some(result.and_then(|d| d.error.clone()).unwrap_or_else(|| format!("同步失败: {}", full_domain))),
+                };
+                on_domain(&full_domain, error.as_deref());
+            }
+        }
+        
+        self.record_update(&config, config.last_ip.clone(), &current_ip, &summary);
+        self.report_summary_status(&config, &summary);
+        // 只有覆盖全部选中子域名且没有失败时才推进最后记录的IP，否则未同步的域名在下个周期仍会被更新
+        let covered_all = config.selected_subdomains.iter().all(|s| summary.domains.iter().any(|d| &d.subdomain == s));
+        if covered_all && summary.error_message.is_none() && summary.success_count > 0 && !summary.dry_run {
+            self.update_last_ip(&current_ip)?;
+        }
+        Ok(summary)
+    }
+
+    /// 获取TXT记录，可按名称（子域名或完整域名）过滤
+    pub async fn list_txt_records(&self, name: Option<&str>) -> Result<Vec<DnsRecord>> {
+        let config = self.load_configuration()?;
+        let name = name.map(|name| Self::record_name(&config, name)).transpose()?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        client.get_txt_records(name.as_deref()).await
+    }
+
+    /// 创建TXT记录并写入变更历史
+    pub async fn create_txt_record(&self, name: &str, content: &str, ttl: Option<u32>) -> Result<DnsRecord> {
+        let config = self.load_configuration()?;
+        let name = Self::record_name(&config, name)?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        
+        let result = client.create_txt_record(&name, content, ttl.unwrap_or(1), None).await;
+        let record_id = result.as_ref().ok().map(|r| r.id.as_str());
+        self.record_txt_change("create", &name, record_id, None, Some(content), result.as_ref().err());
+        result
+    }
+
+    /// 修改TXT记录内容并写入变更历史
+    pub async fn update_txt_record(&self, record_id: &str, content: &str) -> Result<DnsRecord> {
+        let (client, existing) = self.find_txt_record(record_id).await?;
+        let patch = PatchDnsRecordRequest {
+            content: Some(content.to_string()),
+            ..Default::default()
+        };
+        
+        let result = client.update_dns_record(record_id, patch).await;
+        self.record_txt_change(
+            "update",
+            &existing.name,
+            Some(record_id),
+            Some(&existing.content),
+            Some(content),
+            result.as_ref().err(),
+        );
+        result
+    }
+
+    /// 删除TXT记录并写入变更历史，返回被删除的记录
+    pub async fn delete_txt_record(&self, record_id: &str) -> Result<DnsRecord> {
+        let (client, existing) = self.find_txt_record(record_id).await?;
+        
+        let result = client.delete_dns_record(record_id).await;
+        self.record_txt_change(
+            "delete",
+            &existing.name,
+            Some(record_id),
+            Some(&existing.content),
+            None,
+            result.as_ref().err(),
+        );
+        result.map(|_| existing)
+    }
+
+    /// 获取区域内的DNS记录，可按记录类型和名称（子域名或完整域名）过滤
+    pub async fn list_dns_records(&self, record_type: Option<&str>, name: Option<&str>) -> Result<Vec<DnsRecord>> {
+        let config = self.load_configuration()?;
+        let record_type = record_type.map(supported_record_type).transpose()?;
+        let name = name.map(|name| Self::record_name(&config, name)).transpose()?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        client.list_dns_records(record_type, name.as_deref()).await
+    }
+
+    /// 创建任意支持类型的DNS记录，名称可以是子域名或完整域名
+    pub async fn create_dns_record(&self, mut record: UpdateDnsRecordRequest) -> Result<DnsRecord> {
+        let config = self.load_configuration()?;
+        record.record_type = supported_record_type(&record.record_type)?.to_string();
+        record.name = Self::record_name(&config, &record.name)?;
+        validate_record(&record.record_type, &record.content, record.proxied, record.priority)?;
+        
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        let created = client.create_dns_record(record).await?;
+        info!("➕ 已创建{}记录: {} -> {}", created.record_type, created.name, created.content);
+        Ok(created)
+    }
+
+    /// 修改DNS记录，只修改请求中设置的字段
+    pub async fn edit_dns_record(&self, record_id: &str, mut patch: PatchDnsRecordRequest) -> Result<DnsRecord> {
+        let (config, client, existing) = self.find_managed_record(record_id).await?;
+        if let Some(name) = patch.name.take() {
+            patch.name = Some(Self::record_name(&config, &name)?);
+        }
+        validate_record(
+            &existing.record_type,
+            patch.content.as_deref().unwrap_or(&existing.content),
+            patch.proxied.unwrap_or(existing.proxied),
+            patch.priority.or(existing.priority),
+        )?;
+        
+        let updated = client.update_dns_record(record_id, patch).await?;
+        info!("📝 已修改{}记录: {} -> {}", updated.record_type, updated.name, updated.content);
+        Ok(updated)
+    }
+
+    /// 删除DNS记录，返回被删除的记录
+    pub async fn delete_dns_record(&self, record_id: &str) -> Result<DnsRecord> {
+        let (_, client, existing) = self.find_managed_record(record_id).await?;
+        client.delete_dns_record(record_id).await?;
+        info!("🗑️ 已删除{}记录: {} -> {}", existing.record_type, existing.name, existing.content);
+        Ok(existing)
+    }
+
+    /// 查找记录，不支持的记录类型（如NS、SRV）不允许通过通用接口修改
+    async fn find_managed_record(&self, record_id: &str) -> Result<(AppConfig, CloudflareClient, DnsRecord)> {
+        let config = self.load_configuration()?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        let record = client.get_dns_record(record_id).await?;
+        supported_record_type(&record.record_type)?;
+        Ok((config, client, record))
+    }
+
+    /// 生成限定范围的API令牌，ttl_hours为有效期（小时）
+    pub fn create_api_token(&self, name: &str, scopes: &[String], ttl_hours: u32) -> Result<CreatedApiToken> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("请填写令牌名称，便于日后识别用途"));
+        }
+        if ttl_hours == 0 || ttl_hours > api_tokens::MAX_TTL_HOURS {
+            return Err(anyhow!("令牌有效期须为 1-{} 小时", api_tokens::MAX_TTL_HOURS));
+        }
+        let scopes = api_tokens::normalize_scopes(scopes)?;
+        
+        let token = api_tokens::generate();
+        let expires_at = self.clock.utc_now() + chrono::Duration::hours(ttl_hours as i64);
+        let info = self.db.create_api_token(name, &api_tokens::hash(&token), &scopes, expires_at)?;
+        info!("🔑 已生成API令牌 {}（{}），有效期至 {}: {}", info.name, info.id, expires_at.to_rfc3339(), scopes.join(", "));
+        Ok(CreatedApiToken { token, info })
+    }
+
+    /// 所有API令牌（不含令牌明文）
+    pub fn get_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        self.db.get_api_tokens()
+    }
+
+    /// 吊销API令牌，返回令牌是否存在
+    pub fn revoke_api_token(&self, id: &str) -> Result<bool> {
+        let revoked = self.db.delete_api_token(id)?;
+        if revoked {
+            info!("🔑 已吊销API令牌 {}", id);
+        }
+        Ok(revoked)
+    }
+
+    /// 校验API令牌能否调用指定接口，path为不含 /api/v1 前缀的路径
+    pub fn authorize_api_token(&self, token: &str, method: &str, path: &str) -> std::result::Result<ApiToken, TokenDenial> {
+        let info = match self.db.find_api_token(&api_tokens::hash(token)) {
+            Ok(Some(info)) => info,
+            Ok(None) => return Err(TokenDenial::Unknown),
+            Err(e) => {
+                error!("❌ 查询API令牌失败: {}", e);
+                return Err(TokenDenial::Unknown);
+            }
+        };
+        let now = self.clock.utc_now();
+        if info.expires_at <= now {
+            return Err(TokenDenial::Expired);
+        }
+        if !api_tokens::allows(&info.scopes, method, path) {
+            return Err(TokenDenial::OutOfScope);
+        }
+        if let Err(e) = self.db.touch_api_token(&info.id, now) {
+            warn!("⚠️ 记录API令牌使用时间失败: {}", e);
+        }
+        Ok(info)
+    }
+
+    /// 获取最近的TXT记录变更历史
+    pub fn get_txt_record_changes(&self, limit: i32) -> Result<Vec<TxtRecordChange>> {
+        self.db.get_txt_record_changes(limit)
+    }
+
+    /// 查找TXT记录，记录不存在或不是TXT类型时返回错误，避免误改其他类型的记录
+    async fn find_txt_record(&self, record_id: &str) -> Result<(CloudflareClient, DnsRecord)> {
+        let config = self.load_configuration()?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        let record = client.get_dns_record(record_id).await?;
+        if record.record_type != "TXT" {
+            return Err(anyhow!("记录 {} 不是TXT记录（类型为 {}）", record_id, record.record_type));
+        }
+        Ok((client, record))
+    }
+
+    /// 记录名称允许填写子域名或完整域名，"@" 表示根域名
+    fn record_name(config: &AppConfig, name: &str) -> Result<String> {
+        Ok(config.full_domain(&normalize_subdomain(name, &config.root_domain)?))
+    }
+
+    fn record_txt_change(
+        &self,
+        action: &str,
+        name: &str,
+        record_id: Option<&str>,
+        old_content: Option<&str>,
+        new_content: Option<&str>,
+        error: Option<&anyhow::Error>,
+    ) {
+        let error_message = error.map(|e| e.to_string());
+        match &error_message {
+            None => info!("📝 TXT记录{}成功: {}", action, name),
+            Some(e) => error!("❌ TXT记录{}失败: {} - {}", action, name, e),
+        }
+        if let Err(e) = self.db.add_txt_record_change(
+            action,
+            name,
+            record_id,
+            old_content,
+            new_content,
+            error_message.as_deref(),
+        ) {
+            error!("❌ 记录TXT变更历史失败: {}", e);
+        }
+    }
+
+    /// 写入一条DNS更新记录，失败时仅记录日志
+    pub fn record_update(&self, config: &AppConfig, old_ip: Option<String>, new_ip: &str, summary: &UpdateSummary) {
+        let (verified, verify_latency_ms) =
+            propagation::summarize(summary.domains.iter().filter_map(|d| d.propagation.as_ref())).unzip();
+        if let Err(e) = self.db.add_dns_update_record(&NewDnsUpdateRecord {
+            old_ip: old_ip.as_deref(),
+            new_ip,
+            domain_count: summary.total_count,
+            success_count: summary.success_count,
+            error_message: summary.error_message.as_deref(),
+            error_code: summary.error_code,
+            actions: summary.actions,
+            dry_run: summary.dry_run,
+            verified,
+            verify_latency_ms,
+        }) {
+            error!("❌ 记录DNS更新记录失败: {}", e);
+        }
+        let kind = match old_ip.as_deref() == Some(new_ip) {
+            false => Some(NotifyKind::IpChanged),
+            true if summary.error_message.is_none() => Some(NotifyKind::UpdateSucceeded),
+            true => None,
+        };
+        if let Some(kind) = kind.filter(|_| !summary.dry_run && summary.success_count > 0) {
+            let mut event = NotifyEvent::new(kind, &config.root_domain, self.clock.utc_now());
+            event.old_ip = old_ip.clone();
+            event.new_ip = Some(new_ip.to_string());
+            event.domains = summary.domains.clone();
+            self.notify(config, event);
+        }
+        self.publish_mqtt(config, "update", json!({
+            "old_ip": old_ip,
+            "new_ip": new_ip,
+            "root_domain": config.root_domain,
+            "total_count": summary.total_count,
+            "success_count": summary.success_count,
+            "error_message": summary.error_message,
+            "actions": summary.actions,
+            "dry_run": summary.dry_run,
+            "timestamp": self.clock.utc_now(),
+        }));
+    }
+
+    /// 获取所有域名的同步状态
+    pub fn get_domain_states(&self) -> Result<Vec<DomainState>> {
+        self.db.get_domain_states()
+    }
+
+    /// 新增受管子域名，Cloudflare中尚不存在的记录会在下一次更新时创建
+    pub async fn add_subdomain(&self, name: &str) -> Result<String> {
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        let subdomain = normalize_subdomain(name, &config.root_domain)?;
+        if config.selected_subdomains.contains(&subdomain) {
+            return Err(anyhow!("子域名已在管理中: {}", config.full_domain(&subdomain)));
+        }
+        
+        config.selected_subdomains.push(subdomain.clone());
+        self.db.save_config(&config)?;
+        info!("➕ 已新增子域名: {}，将在下一次更新时创建记录", config.full_domain(&subdomain));
+        Ok(subdomain)
+    }
+
+    /// 所有局域网主机
+    pub fn list_lan_hosts(&self) -> Result<Vec<LanHost>> {
+        self.db.get_lan_hosts()
+    }
+
+    /// 新增或修改局域网主机，子域名尚未管理时一并加入管理
+    pub async fn set_lan_host(&self, name: &str, suffix: &str, description: Option<&str>) -> Result<LanHost> {
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        let subdomain = normalize_subdomain(name, &config.root_domain)?;
+        let suffix = suffix.trim();
+        prefix_delegation::parse_host_suffix(suffix, config.delegated_prefix_len)?;
+        let description = description.map(str::trim).filter(|d| !d.is_empty());
+        
+        let host = self.db.upsert_lan_host(&subdomain, suffix, description)?;
+        if !config.selected_subdomains.contains(&subdomain) {
+            config.selected_subdomains.push(subdomain.clone());
+            self.db.save_config(&config)?;
+            info!("➕ 已新增子域名: {}，将在下一次更新时创建记录", config.full_domain(&subdomain));
+        }
+        info!("🏠 局域网主机 {} 的后缀: {}", config.full_domain(&subdomain), suffix);
+        Ok(host)
+    }
+
+    /// 扫描IPv6邻居表（可选通过mDNS查询主机名），供映射为局域网主机
+    pub async fn discover_lan_hosts(&self, mdns: bool) -> Result<Vec<DiscoveredHost>> {
+        let config = self.load_configuration()?;
+        let prefix_len = config.delegated_prefix_len;
+        let mut neighbors = lan_discovery::read_neighbors().await?;
+        if mdns {
+            lan_discovery::resolve_hostnames(&mut neighbors).await;
+        }
+        let prefix = match self.detect_ipv6(&config).await {
+            Ok(ip) => ip.parse::<Ipv6Addr>().ok().map(|ip| prefix_delegation::prefix_of(ip, prefix_len)),
+            Err(e) => {
+                warn!("⚠️ 无法获取当前IPv6，不判断邻居是否位于委派前缀内: {}", e);
+                None
+            }
+        };
+        let hosts = self.db.get_lan_hosts()?;
+
+        let mut discovered: Vec<DiscoveredHost> = neighbors
+            .into_iter()
+            .map(|neighbor| {
+                let suffix = prefix_delegation::suffix_of(neighbor.address, prefix_len);
+                let subdomain = hosts
+                    .iter()
+                    .find(|host| prefix_delegation::parse_host_suffix(&host.suffix, prefix_len).ok() == Some(u128::from(suffix)))
+                    .map(|host| host.subdomain.clone());
+                DiscoveredHost {
+                    in_prefix: prefix.is_some_and(|prefix| prefix.contains(neighbor.address)),
+                    suffix: suffix.to_string(),
+                    subdomain,
+                    neighbor,
+                }
+            })
+            .collect();
+        // 位于委派前缀内、接口标识稳定的邻居排在前面
+        discovered.sort_by_key(|host| (!host.in_prefix, !host.neighbor.eui64, host.neighbor.address));
+        info!("🔍 发现 {} 个IPv6邻居", discovered.len());
+        Ok(discovered)
+    }
+
+    /// 删除局域网主机，子域名仍在管理中，之后发布本机地址；返回是否存在
+    pub fn remove_lan_host(&self, name: &str) -> Result<bool> {
+        let config = self.load_configuration()?;
+        let subdomain = normalize_subdomain(name, &config.root_domain)?;
+        let removed = self.db.delete_lan_host(&subdomain)?;
+        if removed {
+            info!("🏠 已删除局域网主机 {}", config.full_domain(&subdomain));
+        }
+        Ok(removed)
+    }
+
+    /// 备用主机名是否尚未指向当前IP（上次发布失败或还没有发布过），演练模式下不重试
+    pub fn backup_hostname_pending(&self, config: &AppConfig, current_ip: &str) -> bool {
+        let Some(backup) = &config.backup_hostname else {
+            return false;
+        };
+        if config.is_dry_run() {
+            return false;
+        }
+        match self.db.get_domain_state(&backup.name) {
+            Ok(Some(state)) => state.content.as_deref() != Some(current_ip),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// 将当前IP同时发布到备用主机名，失败不影响主区域的更新结果
+    pub async fn publish_backup_hostname(&self, config: &AppConfig, current_ip: &str, dry_run: bool) -> Option<DomainResult> {
+        let backup = config.backup_hostname.as_ref()?;
+        let ip: IpAddr = current_ip.parse().ok()?;
+        if dry_run {
+            info!("🧪 [演练] 将发布备用主机名: {} -> {}", backup.name, current_ip);
+            return None;
+        }
+        
+        let client = backup_hostname::client(config, backup);
+        let result = match backup_hostname::publish(&client, backup, ip, config.record_comment.clone()).await {
+            Ok((outcome, record)) => {
+                match outcome {
+                    DomainOutcome::Unchanged => debug!("✅ 备用主机名已是最新: {}", backup.name),
+                    _ => info!("🛟 已发布备用主机名: {} -> {} (TTL {})", backup.name, current_ip, record.ttl),
+                }
+                self.save_domain_state(&backup.name, &record);
+                DomainResult {
+                    subdomain: backup.name.clone(),
+                    domain: backup.name.clone(),
+                    outcome,
+                    error: None,
+                    propagation: None,
+                }
+            }
+            Err(e) => {
+                warn!("⚠️ 发布备用主机名失败 {}: {}", backup.name, e);
+                if let Err(e) = self.db.save_domain_error(&backup.name, &e.to_string()) {
+                    error!("❌ 保存域名状态失败 {}: {}", backup.name, e);
+                }
+                DomainResult {
+                    subdomain: backup.name.clone(),
+                    domain: backup.name.clone(),
+                    outcome: DomainOutcome::Failed,
+                    error: Some(e.to_string()),
+                    propagation: None,
+                }
+            }
+        };
+        self.publish_domain_result(&result, current_ip, false);
+        Some(result)
+    }
+
+    /// 尚未指向当前IP的选中域名：新增或恢复后还没有本地状态、首次同步失败，
+    /// 或最后写入的地址与当前IP不同（如上次更新时只有该域名失败）
+    pub fn domains_behind(&self, config: &AppConfig, current_ip: &str) -> Vec<String> {
+        config
+            .selected_subdomains
+            .iter()
+            .filter(|subdomain| match self.db.get_domain_state(&config.full_domain(subdomain)) {
+                Ok(state) => Self::is_behind(state.as_ref(), &config.host_ip(subdomain, current_ip)),
+                Err(_) => false,
+            })
+            .map(|subdomain| config.full_domain(subdomain))
+            .collect()
+    }
+
+    fn is_behind(state: Option<&DomainState>, current_ip: &str) -> bool {
+        match state {
+            None => true,
+            // 有警告（如CNAME冲突）的域名需要用户处理，不在每个周期重试
+            Some(state) if state.record_id.is_none() => state.warning.is_none(),
+            Some(state) => !state.content.as_deref().is_some_and(|content| drift::same_address(content, current_ip)),
+        }
+    }
+
+    /// 各选中子域名的记录与最近一次同步结果，按选中顺序返回
+    pub fn domain_statuses(&self, config: &AppConfig) -> Result<Vec<DomainStatus>> {
+        config
+            .selected_subdomains
+            .iter()
+            .map(|subdomain| {
+                let domain = config.full_domain(subdomain);
+                let state = self.db.get_domain_state(&domain)?;
+                Ok(DomainStatus::new(subdomain, domain, state))
+            })
+            .collect()
+    }
+
+    /// 软删除子域名：从选中列表移除，保留其状态以便在保留期内恢复
+    pub async fn delete_subdomain(&self, subdomain: &str) -> Result<()> {
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        if !config.selected_subdomains.iter().any(|s| s == subdomain) {
+            return Err(anyhow!("子域名未被管理: {}", config.full_domain(subdomain)));
+        }
+        
+        config.selected_subdomains.retain(|s| s != subdomain);
+        self.db.save_config(&config)?;
+        info!("🗑️ 子域名已软删除: {}", config.full_domain(subdomain));
+        Ok(())
+    }
+
+    /// 获取已软删除的子域名
+    pub fn list_deleted_subdomains(&self) -> Result<Vec<DeletedSubdomain>> {
+        self.db.get_deleted_subdomains()
+    }
+
+    /// 恢复保留期内的已删除子域名
+    pub async fn restore_subdomain(&self, subdomain: &str) -> Result<()> {
+        let deleted = self.find_deleted_subdomain(subdomain)?;
+        let deadline = deleted.deleted_at + chrono::Duration::days(SOFT_DELETE_RETENTION_DAYS);
+        if self.clock.utc_now() > deadline {
+            return Err(anyhow!(
+                "子域名已超过 {} 天保留期，无法恢复，只能清除",
+                SOFT_DELETE_RETENTION_DAYS
+            ));
+        }
+        
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        config.selected_subdomains.push(subdomain.to_string());
+        self.db.save_config(&config)?;
+        info!("♻️ 子域名已恢复: {}", config.full_domain(subdomain));
+        Ok(())
+    }
+
+    /// 彻底清除已删除的子域名及其状态
+    pub async fn purge_subdomain(&self, subdomain: &str) -> Result<()> {
+        self.find_deleted_subdomain(subdomain)?;
+        let _cycle = self.lock_cycle().await;
+        let config = self.load_configuration()?;
+        let full_domain = config.full_domain(subdomain);
+        self.db.purge_subdomain(subdomain, &full_domain)?;
+        info!("🧹 子域名已彻底清除: {}", full_domain);
+        Ok(())
+    }
+
+    /// 导出各子域名的设置（是否启用、代理状态）为CSV
+    pub fn export_domain_settings(&self) -> Result<String> {
+        let config = self.load_configuration()?;
+        let deleted = self.db.get_deleted_subdomains()?;
+        Ok(domain_settings::to_csv(&domain_settings::collect(&config, &deleted)))
+    }
+
+    /// 从CSV批量导入子域名设置，CSV中没有出现的子域名保持不变
+    pub async fn import_domain_settings(&self, text: &str) -> Result<ImportReport> {
+        let _cycle = self.lock_cycle().await;
+        let mut config = self.load_configuration()?;
+        let import = domain_settings::parse_csv(text, &config.root_domain)?;
+        let report = domain_settings::apply(&mut config, import);
+        self.db.save_config(&config)?;
+        info!(
+            "📥 已导入 {} 个子域名的设置: 新增 {}，停用 {}，修改代理状态 {}",
+            report.total,
+            report.enabled.len(),
+            report.disabled.len(),
+            report.proxied_changed.len()
+        );
+        Ok(report)
+    }
+
+    fn find_deleted_subdomain(&self, subdomain: &str) -> Result<DeletedSubdomain> {
+        self.db
+            .get_deleted_subdomains()?
+            .into_iter()
+            .find(|d| d.subdomain == subdomain)
+            .ok_or_else(|| anyhow!("没有找到已删除的子域名: {}", subdomain))
+    }
+
+    /// 将当前IP同步到所有选中的域名，force为true时内容一致的记录也重新写入
+    /// 按配置的并发数同时处理多个域名，结果按选中顺序汇总
+    pub async fn update_selected_domains(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        current_ip: &str,
+        force: bool,
+    ) -> Result<UpdateSummary> {
+        let ip: IpAddr = current_ip.parse()?;
+        let mut summary = UpdateSummary {
+            dry_run: config.is_dry_run(),
+            ..Default::default()
+        };
+        let context = self.publish_context(config, current_ip)?;
+        let parallelism = config.update_parallelism.max(1) as usize;
+        
+        if summary.dry_run {
+            info!("🧪 演练模式：检查 {} 个域名记录，只记录将要做的修改", config.selected_subdomains.len());
+        } else {
+            info!("📝 开始更新 {} 个域名记录（同时处理 {} 个）", config.selected_subdomains.len(), parallelism);
+        }
+        
+        let permits = Arc::new(Semaphore::new(parallelism));
+        let shared = Arc::new((config.clone(), client.clone(), context));
+        let mut tasks = JoinSet::new();
+        for (index, subdomain) in config.selected_subdomains.iter().enumerate() {
+            let service = self.clone();
+            let permits = permits.clone();
+            let shared = shared.clone();
+            let subdomain = subdomain.clone();
+            tasks.spawn(async move {
+                let (config, client, context) = &*shared;
+                // 配置了主机后缀的子域名发布 委派前缀 + 后缀
+                let ip = config.host_address(&subdomain, ip);
+                let permit = permits.acquire_owned().await;
+                let result = service.update_domain(config, client, &subdomain, ip, context, force).await;
+                drop(permit);
+                (index, service.verify_domain_result(config, result, ip).await)
+            });
+        }
+        
+        let mut results: Vec<Option<UpdateSummary>> = vec![None; config.selected_subdomains.len()];
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(e) => error!("❌ 域名处理任务异常退出: {}", e),
+            }
+        }
+        for (subdomain, result) in config.selected_subdomains.iter().zip(results) {
+            match result {
+                Some(result) => summary.merge(result),
+                None => {
+                    summary.total_count += 1;
+                    summary.error_message = Some(format!("处理域名失败: {}", config.full_domain(subdomain)));
+                    summary.note_domain_result(subdomain, &config.full_domain(subdomain), (summary.success_count, summary.actions));
+                }
+            }
+        }
+        
+        Ok(summary)
+    }
+
+    /// 处理单个域名，返回只含该域名的汇总
+    async fn update_domain(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        subdomain: &str,
+        ip: IpAddr,
+        context: &PublishContext,
+        force: bool,
+    ) -> (UpdateSummary, bool) {
+        let current_ip = ip.to_string();
+        let mut summary = UpdateSummary {
+            total_count: 1,
+            dry_run: config.is_dry_run(),
+            ..Default::default()
+        };
+        let full_domain = config.full_domain(subdomain);
+        let before = (summary.success_count, summary.actions);
+        
+        debug!("🔍 处理域名: {}", full_domain);
+        
+        let mut proxied = false;
+        let owner = context.owner.as_deref();
+        match self.plan_domain(config, client, subdomain, &current_ip, context, force).await {
+            Ok(plan) if summary.dry_run => Self::log_dry_run(&plan, &mut summary),
+            Ok(plan) => {
+                proxied = plan.proxied.or(plan.record.as_ref().map(|r| r.proxied)).unwrap_or(false);
+                let missing = plan.additional.clone();
+                self.apply_plan(config, client, plan, ip, owner, &mut summary).await;
+                // 主记录处理成功后再为其他地址新建记录
+                if !missing.is_empty() && summary.success_count > before.0 {
+                    self.create_additional_records(config, client, subdomain, &missing, owner, &mut summary).await;
+                }
+            }
+            Err(e) => {
+                error!("❌ 获取域名记录失败 {}: {}", full_domain, e);
+                summary.record_error(format!("获取域名记录失败 {}: {}", full_domain, e), &e);
+            }
+        }
+        summary.note_domain_result(subdomain, &full_domain, before);
+        if let Some(result) = summary.domains.last() {
+            self.publish_domain_result(result, &current_ip, summary.dry_run);
+        }
+        if let Some(error) = summary.domains.last().and_then(|d| d.error.as_deref()) {
+            if !summary.dry_run {
+                if let Err(e) = self.db.save_domain_error(&full_domain, error) {
+                    error!("❌ 保存域名错误失败 {}: {}", full_domain, e);
+                }
+            }
+        }
+        
+        let synced = summary.domains.last().is_some_and(|d| d.outcome != DomainOutcome::Failed);
+        if config.https_records && !config.round_robin && synced {
+            if summary.dry_run {
+                info!("🧪 [演练] 将同步HTTPS记录的ipv6hint: {} -> {}", full_domain, current_ip);
+            } else if let Err(e) = Self::sync_https_record(config, client, &full_domain, &current_ip).await {
+                // HTTPS记录只是辅助，失败不影响AAAA记录的结果
+                warn!("⚠️ 同步HTTPS记录失败 {}: {}", full_domain, e);
+                self.events.publish(&full_domain, DomainEventKind::Warning, format!("同步HTTPS记录失败: {}", e));
+            }
+        }
+        (summary, proxied)
+    }
+
+    /// 开启生效确认时，等待新写入的地址在公共DNS中生效；不占用并发名额
+    async fn verify_domain_result(&self, config: &AppConfig, (mut summary, proxied): (UpdateSummary, bool), ip: IpAddr) -> UpdateSummary {
+        let IpAddr::V6(v6) = ip else { return summary };
+        let Some(result) = summary.domains.last_mut() else { return summary };
+        // 代理的记录在公共DNS中解析为Cloudflare的地址，无法确认
+        let written = matches!(result.outcome, DomainOutcome::Created | DomainOutcome::Updated);
+        if config.verify_propagation && written && !summary.dry_run && !proxied {
+            let propagation = propagation::verify(&result.domain, v6, Instant::now()).await;
+            self.publish_propagation(&result.domain, &propagation);
+            result.propagation = Some(propagation);
+        }
+        summary
+    }
+
+    /// 发布新地址在公共DNS中的生效确认结果
+    fn publish_propagation(&self, domain: &str, propagation: &Propagation) {
+        match &propagation.resolver {
+            Some(resolver) if propagation.verified => {
+                info!("🌍 公共DNS已返回新地址: {}（{}，耗时 {} 毫秒）", domain, resolver, propagation.latency_ms);
+                self.events.publish(
+                    domain,
+                    DomainEventKind::Verification,
+                    format!("公共DNS已返回新地址（{}，耗时 {} 毫秒）", resolver, propagation.latency_ms),
+                );
+            }
+            _ => {
+                warn!("⚠️ 写入后 {} 毫秒内公共DNS仍未返回新地址: {}", propagation.latency_ms, domain);
+                self.events.publish(
+                    domain,
+                    DomainEventKind::Warning,
+                    format!("写入后 {} 毫秒内公共DNS仍未返回新地址", propagation.latency_ms),
+                );
+            }
+        }
+    }
+
+    /// 发布单个域名的处理结果事件
+    fn publish_domain_result(&self, result: &DomainResult, current_ip: &str, dry_run: bool) {
+        let prefix = if dry_run { "[演练] " } else { "" };
+        let (kind, message) = match result.outcome {
+            DomainOutcome::Created => (DomainEventKind::Sync, format!("{}已新建记录 -> {}", prefix, current_ip)),
+            DomainOutcome::Updated => (DomainEventKind::Sync, format!("{}已更新记录 -> {}", prefix, current_ip)),
+            DomainOutcome::Unchanged => (DomainEventKind::Verification, format!("记录已是最新: {}", current_ip)),
+            DomainOutcome::Failed => (DomainEventKind::Error, result.error.clone().unwrap_or_else(|| "处理失败".to_string())),
+        };
+        self.events.publish(&result.domain, kind, message);
+    }
+
+    /// 同步同名HTTPS记录的ipv6hint：没有HTTPS记录时新建，已有记录只修改ipv6hint，保留其他参数
+    async fn sync_https_record(
+        config: &AppConfig,
+        client: &CloudflareClient,
+        full_domain: &str,
+        current_ip: &str,
+    ) -> Result<()> {
+        let records = client.get_https_records(full_domain).await?;
+        if records.is_empty() {
+            client
+                .create_https_record(full_domain, &SvcbData::for_ip(current_ip), config.record_comment.clone())
+                .await?;
+            info!("✅ 已创建HTTPS记录: {} ipv6hint={}", full_domain, current_ip);
+            return Ok(());
+        }
+        
+        for record in records {
+            let Some(data) = SvcbData::from_record(&record) else {
+                warn!("⚠️ 无法解析HTTPS记录 {} 的数据，已跳过", record.id);
+                continue;
+            };
+            if data.is_alias() || https_record::ipv6hint(&data.value) == Some(current_ip) {
+                continue;
+            }
+            let data = SvcbData {
+                value: https_record::set_ipv6hint(&data.value, current_ip),
+                ..data
+            };
+            let patch = PatchDnsRecordRequest {
+                data: Some(serde_json::to_value(&data)?),
+                ..Default::default()
+            };
+            client.update_dns_record(&record.id, patch).await?;
+            info!("✅ 已更新HTTPS记录的ipv6hint: {} -> {}", full_domain, current_ip);
+        }
+        Ok(())
+    }
+
+    /// 计算所有选中域名在下一次更新中的处理计划，只读取Cloudflare记录，不做任何修改
+    pub async fn plan_update(&self) -> Result<UpdatePlan> {
+        let config = self.load_configuration()?;
+        let current_ip = self.detect_ipv6(&config).await?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        let context = self.publish_context(&config, &current_ip)?;
+        
+        let mut domains = Vec::new();
+        for subdomain in &config.selected_subdomains {
+            let host_ip = config.host_ip(subdomain, &current_ip);
+            let plan = match self.plan_domain(&config, &client, subdomain, &host_ip, &context, false).await {
+                Ok(plan) => plan,
+                Err(e) => {
+                    let mut plan = DomainPlan::new(subdomain, config.full_domain(subdomain), PlanAction::Error);
+                    plan.reason = Some(format!("获取域名记录失败: {}", e));
+                    plan
+                }
+            };
+            domains.push(plan);
+        }
+        
+        let ip_changed = config.last_ip.as_deref() != Some(current_ip.as_str());
+        let additional_changed = context.additional != self.published_additional_ips();
+        Ok(UpdatePlan {
+            will_run: ip_changed || additional_changed || !self.domains_behind(&config, &current_ip).is_empty(),
+            ip_changed,
+            last_ip: config.last_ip.clone(),
+            current_ip,
+            domains,
+        })
+    }
+
+    /// 读取所有选中域名在Cloudflare上的AAAA记录并与本机IP比对，
+    /// 不依赖last_ip，可发现记录被外部修改或上次更新未生效的情况
+    pub async fn drift_report(&self) -> Result<DriftReport> {
+        let config = self.load_configuration()?;
+        let current_ip = self.detect_ipv6(&config).await?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        
+        let mut domains = Vec::new();
+        for subdomain in &config.selected_subdomains {
+            let full_domain = config.full_domain(subdomain);
+            let host_ip = config.host_ip(subdomain, &current_ip);
+            let result = match client.get_aaaa_records(&full_domain).await {
+                Ok(records) => drift::compare(subdomain, full_domain, &records, &host_ip),
+                Err(e) => drift::failed(subdomain, full_domain, format!("获取域名记录失败: {}", e)),
+            };
+            if result.status == drift::DriftStatus::Drifted {
+                warn!("⚠️ 域名 {} 的记录与应发布的IP不一致: {:?} != {}", result.domain, result.live_contents, host_ip);
+            }
+            domains.push(result);
+        }
+        Ok(DriftReport::new(current_ip, config.last_ip.clone(), domains))
+    }
+
+    /// 按配置的变化检测方式解析各选中域名，返回解析结果不含当前IP的域名；
+    /// last_ip 模式、解析失败和Cloudflare代理的域名不计入，避免因解析故障反复读取Cloudflare记录
+    pub async fn stale_live_domains(&self, config: &AppConfig, current_ip: &str) -> Vec<String> {
+        let Ok(ip) = current_ip.parse() else { return Vec::new() };
+        if config.change_detection == ChangeDetection::LastIp {
+            return Vec::new();
+        }
+        
+        let mut stale = Vec::new();
+        for subdomain in &config.selected_subdomains {
+            let domain = config.full_domain(subdomain);
+            let ip: Ipv6Addr = config.host_ip(subdomain, current_ip).parse().unwrap_or(ip);
+            match live_dns::resolve(&domain, config.change_detection).await {
+                Ok(addresses) => match live_dns::classify(addresses, ip) {
+                    LiveAnswer::Current => debug!("✅ {} 已解析到当前IP", domain),
+                    LiveAnswer::Proxied => debug!("☁️ {} 解析到Cloudflare代理地址，跳过比对", domain),
+                    LiveAnswer::Stale(addresses) => {
+                        info!("🔎 {} 解析到 {:?}，与应发布的IP {} 不一致", domain, addresses, ip);
+                        stale.push(domain);
+                    }
+                },
+                Err(e) => warn!("⚠️ 解析域名失败 {}: {}", domain, e),
+            }
+        }
+        stale
+    }
+
+    /// 计算此刻同步单个域名会做的修改，供手动同步前确认，不修改Cloudflare记录
+    pub async fn domain_diff(&self, name: &str, force: bool) -> Result<DomainDiff> {
+        let config = self.load_configuration()?;
+        let subdomain = normalize_subdomain(name, &config.root_domain)?;
+        if !config.selected_subdomains.contains(&subdomain) {
+            return Err(anyhow!("子域名未被管理: {}", config.full_domain(&subdomain)));
+        }
+        
+        let current_ip = self.detect_ipv6(&config).await?;
+        let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config));
+        let context = self.publish_context(&config, &current_ip)?;
+        let host_ip = config.host_ip(&subdomain, &current_ip);
+        let plan = self.plan_domain(&config, &client, &subdomain, &host_ip, &context, force).await?;
+        Ok(DomainDiff::from_plan(plan, host_ip))
+    }
+
+    /// 演练模式：按处理计划记录将要做的修改，不调用写入接口
+    fn log_dry_run(plan: &DomainPlan, summary: &mut UpdateSummary) {
+        let content = plan.new_content.as_deref().unwrap_or("-");
+        match plan.action {
+            PlanAction::Create => {
+                info!("🧪 [演练] 将新建记录: {} -> {}", plan.domain, content);
+                summary.success_count += 1;
+                summary.actions.created += 1;
+            }
+            PlanAction::Update => {
+                info!(
+                    "🧪 [演练] 将更新记录: {} {} -> {}",
+                    plan.domain,
+                    plan.current_content.as_deref().unwrap_or("-"),
+                    content
+                );
+                summary.success_count += 1;
+                summary.actions.updated += 1;
+            }
+            PlanAction::Unchanged => {
+                debug!("🧪 [演练] 记录无需修改: {}", plan.domain);
+                summary.success_count += 1;
+                summary.actions.unchanged += 1;
+            }
+            PlanAction::Skip | PlanAction::Error => {
+                let reason = plan.reason.clone().unwrap_or_default();
+                warn!("🧪 [演练] 将跳过: {}", reason);
+                let error = anyhow!("{}", reason);
+                summary.record_error(reason, &error);
+            }
+        }
+        if !plan.additional.is_empty() {
+            info!("🧪 [演练] 将为 {} 新建其他地址的记录: {}", plan.domain, plan.additional.join(", "));
+        }
+        if plan.delete_duplicates {
+            info!(
+                "🧪 [演练] 将删除 {} 的多余AAAA记录: {}",
+                plan.domain,
+                plan.duplicates.iter().map(|r| r.content.as_str()).collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    /// 一次更新中各域名共用的归属标记和其他地址
+    fn publish_context(&self, config: &AppConfig, current_ip: &str) -> Result<PublishContext> {
+        Ok(PublishContext {
+            owner: self.owner_for(config)?,
+            additional: self.additional_addresses(config, current_ip)?,
+        })
+    }
+
+    /// 启用归属登记时返回本实例ID，本实例只修改带有自己标记或没有标记的记录
+    fn owner_for(&self, config: &AppConfig) -> Result<Option<String>> {
+        if config.ownership_enabled {
+            Ok(Some(self.db.instance_id()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// 计算单个域名的处理计划，只读取Cloudflare记录，不做任何修改
+    async fn plan_domain(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        subdomain: &str,
+        current_ip: &str,
+        context: &PublishContext,
+        force: bool,
+    ) -> Result<DomainPlan> {
+        let full_domain = config.full_domain(subdomain);
+        let records = client.get_aaaa_records(&full_domain).await?;
+        let owner = context.owner.as_deref();
+        // 发布所有地址时其他地址各保留一条记录，不参与主记录的选择；局域网主机只发布一个地址
+        let multi = config.multi_address && !config.host_suffixes.contains_key(subdomain);
+        let additional = if multi { context.additional.as_slice() } else { &[] };
+        let (records, missing) = Self::split_additional(records, additional);
+        // 其他实例的记录不参与主记录的选择，也不会作为多余记录删除
+        let (records, foreign) = ownership::partition_foreign(records, owner);
+        let foreign_owners: Vec<String> = foreign.iter().filter_map(ownership::owner_of).map(str::to_string).collect();
+        
+        let owned_id = self.db.get_domain_state(&full_domain).ok().flatten().and_then(|s| s.record_id);
+        if let Some(record) = Self::select_primary(config, &records, current_ip, owned_id.as_deref()) {
+            // 检查IP和受管属性是否真的发生了变化
+            // 强制更新时即使没有变化也重新写入内容和代理状态
+            let patch = Self::build_patch(config, subdomain, record, current_ip, owner).or_else(|| {
+                force.then(|| PatchDnsRecordRequest {
+                    content: Some(current_ip.to_string()),
+                    proxied: config.proxied_for(subdomain),
+                    ..Default::default()
+                })
+            });
+            let action = if patch.is_some() { PlanAction::Update } else { PlanAction::Unchanged };
+            let mut plan = DomainPlan::new(subdomain, full_domain, action);
+            plan.record_id = Some(record.id.clone());
+            plan.current_content = Some(record.content.clone());
+            if let Some(patch) = &patch {
+                plan.new_content = patch.content.clone();
+                plan.proxied = patch.proxied;
+                plan.comment = patch.comment.clone();
+            }
+            // 轮询模式下其余记录属于其他主机，不做处理；发布所有地址时已消失的地址总是删除
+            if !config.round_robin {
+                plan.duplicates = records.iter().filter(|r| r.id != record.id).cloned().collect();
+                plan.delete_duplicates = (config.cleanup_duplicates || multi) && !plan.duplicates.is_empty();
+            }
+            plan.additional = missing;
+            plan.foreign_owners = foreign_owners;
+            plan.record = Some(record.clone());
+            plan.patch = patch;
+            return Ok(plan);
+        }
+        
+        // 非轮询模式下该名称只有其他实例的记录时由对方维护，不新建第二条记录
+        if let Some(foreign) = foreign_owners.first().filter(|_| !config.round_robin) {
+            let mut plan = DomainPlan::new(subdomain, full_domain.clone(), PlanAction::Skip);
+            plan.reason = Some(format!("域名 {} 的记录属于其他实例 {}", full_domain, foreign));
+            plan.conflict = Some(PlanConflict::Owner(foreign.clone()));
+            plan.foreign_owners = foreign_owners;
+            return Ok(plan);
+        }
+        
+        // 同名CNAME记录存在时无法创建AAAA记录，给出明确的提示
+        if let Some(target) = Self::find_cname(client, &full_domain).await {
+            let mut plan = DomainPlan::new(subdomain, full_domain.clone(), PlanAction::Skip);
+            plan.reason = Some(format!(
+                "域名 {} 已存在指向 {} 的CNAME记录，无法创建AAAA记录，请删除CNAME记录或取消选择该域名",
+                full_domain, target
+            ));
+            plan.conflict = Some(PlanConflict::Cname);
+            return Ok(plan);
+        }
+        
+        let mut plan = DomainPlan::new(subdomain, full_domain, PlanAction::Create);
+        plan.new_content = Some(current_ip.to_string());
+        plan.proxied = Some(config.proxied_for_new(subdomain));
+        plan.comment = Self::new_record_comment(config, owner);
+        plan.additional = missing;
+        plan.foreign_owners = foreign_owners;
+        Ok(plan)
+    }
+
+    /// 把同名记录分为主记录的候选和其他地址已有的记录，返回候选记录和尚没有记录的其他地址
+    fn split_additional(records: Vec<DnsRecord>, additional: &[String]) -> (Vec<DnsRecord>, Vec<String>) {
+        let missing = additional
+            .iter()
+            .filter(|ip| !records.iter().any(|record| drift::same_address(&record.content, ip)))
+            .cloned()
+            .collect();
+        let candidates = records
+            .into_iter()
+            .filter(|record| !additional.iter().any(|ip| drift::same_address(&record.content, ip)))
+            .collect();
+        (candidates, missing)
+    }
+
+    /// 新建记录的备注：启用归属登记时带有本实例的归属标记
+    fn new_record_comment(config: &AppConfig, owner: Option<&str>) -> Option<String> {
+        match owner {
+            Some(id) => ownership::claim_comment(None, config.record_comment.as_deref(), id),
+            None => config.record_comment.clone(),
+        }
+    }
+
+    /// 发布所有地址时，为尚没有记录的其他地址新建AAAA记录，失败时记录错误以便下个周期重试
+    async fn create_additional_records(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        subdomain: &str,
+        addresses: &[String],
+        owner: Option<&str>,
+        summary: &mut UpdateSummary,
+    ) {
+        let full_domain = config.full_domain(subdomain);
+        for address in addresses {
+            let Ok(ip) = address.parse::<IpAddr>() else { continue };
+            let comment = Self::new_record_comment(config, owner);
+            match client.create_aaaa_record(subdomain, ip, config.proxied_for_new(subdomain), comment).await {
+                Ok(_) => {
+                    summary.actions.created += 1;
+                    info!("✅ 已新建其他地址的记录: {} -> {}", full_domain, address);
+                    self.events.publish(&full_domain, DomainEventKind::Sync, format!("已新建其他地址的记录 -> {}", address));
+                }
+                Err(e) => {
+                    error!("❌ 新建其他地址的记录失败: {} -> {}: {}", full_domain, address, e);
+                    summary.record_error(format!("新建其他地址的记录失败: {} -> {} - {}", full_domain, address, e), &e);
+                }
+            }
+        }
+    }
+
+    /// 按处理计划修改Cloudflare记录并更新本地状态
+    async fn apply_plan(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        plan: DomainPlan,
+        ip: IpAddr,
+        owner: Option<&str>,
+        summary: &mut UpdateSummary,
+    ) {
+        let full_domain = plan.domain.as_str();
+        let current_ip = ip.to_string();
+        match plan.action {
+            PlanAction::Skip => {
+                let reason = plan.reason.clone().unwrap_or_default();
+                let error = anyhow!("{}", reason);
+                warn!("⚠️ {}，已跳过", reason);
+                match &plan.conflict {
+                    Some(PlanConflict::Owner(foreign)) => {
+                        if let Err(e) = self.db.save_ownership(full_domain, None, foreign, ownership::STATUS_CONFLICT) {
+                            error!("❌ 保存记录归属失败 {}: {}", full_domain, e);
+                        }
+                        summary.record_error(format!("归属冲突: {}", error), &error);
+                    }
+                    Some(PlanConflict::Cname) => {
+                        self.save_domain_warning(full_domain, &reason);
+                        summary.record_error(format!("CNAME冲突: {}", error), &error);
+                    }
+                    None => summary.record_error(reason, &error),
+                }
+            }
+            PlanAction::Unchanged | PlanAction::Update => {
+                let Some(record) = &plan.record else { return };
+                // 轮询模式下其他实例的记录属于其他主机，是正常情况
+                if !plan.foreign_owners.is_empty() && !config.round_robin {
+                    let owners = plan.foreign_owners.join(", ");
+                    warn!("⚠️ 域名 {} 存在属于其他实例的同名记录（{}），只更新本实例的记录", full_domain, owners);
+                    self.events.publish(full_domain, DomainEventKind::Warning, format!("存在属于其他实例 {} 的同名记录，未做修改", owners));
+                }
+                self.check_out_of_band_edit(full_domain, record);
+                
+                let Some(patch) = plan.patch else {
+                    debug!("✅ IP地址未变化，跳过更新: {} -> {}", full_domain, current_ip);
+                    self.save_domain_state(full_domain, record);
+                    summary.success_count += 1; // 这种情况也算成功
+                    summary.actions.unchanged += 1;
+                    self.handle_duplicate_records(client, full_domain, record, &current_ip, &plan.duplicates, plan.delete_duplicates).await;
+                    return;
+                };
+                
+                // 更新现有记录，主记录写入成功后才处理多余记录，失败时保留原有记录
+                debug!("📝 更新现有DNS记录: {} -> {}", full_domain, current_ip);
+                match client.update_dns_record(&record.id, patch).await {
+                    Ok(updated) => {
+                        self.save_domain_state(full_domain, &updated);
+                        summary.success_count += 1;
+                        summary.actions.updated += 1;
+                        info!("✅ 成功更新域名: {} -> {}", full_domain, current_ip);
+                        self.handle_duplicate_records(client, full_domain, &updated, &current_ip, &plan.duplicates, plan.delete_duplicates).await;
+                    }
+                    Err(e) => {
+                        error!("❌ 更新域名失败: {} - {}", full_domain, e);
+                        summary.record_error(format!("更新域名失败: {} - {}", full_domain, e), &e);
+                    }
+                }
+            }
+            PlanAction::Create => {
+                // 创建新记录
+                debug!("➕ 创建新DNS记录: {} -> {}", full_domain, current_ip);
+                match client.create_aaaa_record(
+                    &plan.subdomain,
+                    ip,
+                    plan.proxied.unwrap_or(false),
+                    plan.comment.clone(),
+                ).await {
+                    Ok(created) => {
+                        self.save_domain_state(full_domain, &created);
+                        summary.success_count += 1;
+                        summary.actions.created += 1;
+                        info!("✅ 成功创建域名: {} -> {}", full_domain, current_ip);
+                    }
+                    // 完全相同的记录已存在时改为更新现有记录
+                    Err(e) if matches!(
+                        e.downcast_ref::<CloudflareError>(),
+                        Some(CloudflareError::DuplicateRecord { .. })
+                    ) => {
+                        warn!("⚠️ 创建域名时记录已存在，改为更新现有记录: {}", full_domain);
+                        match self.update_existing_record(config, client, &plan.subdomain, full_domain, &current_ip, owner).await {
+                            Ok(record) => {
+                                self.save_domain_state(full_domain, &record);
+                                summary.success_count += 1;
+                                summary.actions.updated += 1;
+                                info!("✅ 成功更新已存在的域名记录: {} -> {}", full_domain, current_ip);
+                            }
+                            Err(e) => {
+                                error!("❌ 更新已存在的域名记录失败: {} - {}", full_domain, e);
+                                summary.record_error(format!("更新已存在的域名记录失败: {} - {}", full_domain, e), &e);
+                            }
+                        }
+                    }
+                    // 查询CNAME之后才出现的同名冲突记录
+                    Err(e) if matches!(
+                        e.downcast_ref::<CloudflareError>(),
+                        Some(CloudflareError::RecordConflict { .. })
+                    ) => {
+                        warn!("⚠️ 域名 {} 存在同名的冲突记录: {}", full_domain, e);
+                        self.save_domain_warning(full_domain, &format!("存在同名的冲突记录: {}", e));
+                        summary.record_error(format!("记录冲突: {} - {}", full_domain, e), &e);
+                    }
+                    Err(e) => {
+                        error!("❌ 创建域名失败: {} - {}", full_domain, e);
+                        summary.record_error(format!("创建域名失败: {} - {}", full_domain, e), &e);
+                    }
+                }
+            }
+            PlanAction::Error => {}
+        }
+    }
+
+    /// 选择同名AAAA记录中由本机维护的一条，依次匹配：
+    /// 已指向当前IP的记录、上次写入的记录ID、上次记录的IP；
+    /// 非轮询模式下都不匹配时使用第一条，轮询模式下返回None以新建本机的记录
+    fn select_primary<'a>(
+        config: &AppConfig,
+        records: &'a [DnsRecord],
+        current_ip: &str,
+        owned_record_id: Option<&str>,
+    ) -> Option<&'a DnsRecord> {
+        records
+            .iter()
+            .find(|record| record.content == current_ip)
+            .or_else(|| owned_record_id.and_then(|id| records.iter().find(|record| record.id == id)))
+            .or_else(|| {
+                let last_ip = config.last_ip.as_deref()?;
+                records.iter().find(|record| record.content == last_ip)
+            })
+            .or_else(|| if config.round_robin { None } else { records.first() })
+    }
+
+    /// 处理同名的多余AAAA记录（例如旧前缀遗留的记录）：开启清理或发布所有地址时删除，否则只告警
+    /// 只在主记录已写入并指向当前IP后调用，删除除该记录外的其他记录，避免该名称没有可用的记录
+    async fn handle_duplicate_records(
+        &self,
+        client: &CloudflareClient,
+        full_domain: &str,
+        primary: &DnsRecord,
+        current_ip: &str,
+        duplicates: &[DnsRecord],
+        delete: bool,
+    ) {
+        let stale: Vec<&DnsRecord> = duplicates.iter().filter(|r| r.id != primary.id).collect();
+        if stale.is_empty() {
+            return;
+        }
+        if delete && !drift::same_address(&primary.content, current_ip) {
+            warn!("⚠️ 域名 {} 的主记录未指向当前IP {}，暂不删除多余记录", full_domain, current_ip);
+            return;
+        }
+        if !delete {
+            warn!(
+                "⚠️ 域名 {} 存在 {} 条多余的AAAA记录: {}（可开启 cleanup_duplicates 自动清理）",
+                full_domain,
+                stale.len(),
+                stale.iter().map(|r| r.content.as_str()).collect::<Vec<_>>().join(", ")
+            );
+            return;
+        }
+        
+        for record in stale {
+            match client.delete_dns_record(&record.id).await {
+                Ok(()) => info!("🧹 已删除多余的AAAA记录: {} -> {}", full_domain, record.content),
+                Err(e) => warn!("⚠️ 删除多余的AAAA记录失败: {} -> {}: {}", full_domain, record.content, e),
+            }
+        }
+    }
+
+    /// 计算将记录同步到当前IP和配置所需的修改，无需修改时返回None
+    fn build_patch(
+        config: &AppConfig,
+        subdomain: &str,
+        record: &DnsRecord,
+        current_ip: &str,
+        owner: Option<&str>,
+    ) -> Option<PatchDnsRecordRequest> {
+        // 只有配置了代理状态且与记录不一致时才修改
+        let proxied = config
+            .proxied_for(subdomain)
+            .filter(|proxied| *proxied != record.proxied);
+        // 标记备注只写入没有备注的记录，保留用户已有的备注；启用归属登记时追加本实例的归属标记
+        let comment = match owner {
+            Some(id) => ownership::claim_comment(record.comment.as_deref(), config.record_comment.as_deref(), id),
+            None => config
+                .record_comment
+                .clone()
+                .filter(|_| record.comment.as_deref().unwrap_or_default().is_empty()),
+        };
+        
+        if record.content == current_ip && proxied.is_none() && comment.is_none() {
+            return None;
+        }
+        
+        Some(PatchDnsRecordRequest {
+            content: Some(current_ip.to_string()),
+            proxied,
+            comment,
+            ..Default::default()
+        })
+    }
+
+    /// 创建记录遇到81057（记录已存在）时，重新查找该记录并更新
+    async fn update_existing_record(
+        &self,
+        config: &AppConfig,
+        client: &CloudflareClient,
+        subdomain: &str,
+        full_domain: &str,
+        current_ip: &str,
+        owner: Option<&str>,
+    ) -> Result<DnsRecord> {
+        let records = client.get_aaaa_records(full_domain).await?;
+        let (records, foreign) = ownership::partition_foreign(records, owner);
+        let record = match Self::select_primary(config, &records, current_ip, None) {
+            Some(record) => record,
+            None => match foreign.first().and_then(ownership::owner_of) {
+                Some(foreign) => return Err(anyhow!("已存在的记录属于其他实例 {}", foreign)),
+                None => return Err(anyhow!("Cloudflare报告记录已存在，但未能查询到该记录")),
+            },
+        };
+        
+        match Self::build_patch(config, subdomain, record, current_ip, owner) {
+            Some(patch) => client.update_dns_record(&record.id, patch).await,
+            None => Ok(record.clone()),
+        }
+    }
+
+    /// 比较Cloudflare侧的修改时间与本地记录，发现外部修改时告警
+    fn check_out_of_band_edit(&self, domain: &str, record: &DnsRecord) {
+        let stored = match self.db.get_domain_state(domain) {
+            Ok(Some(state)) => state,
+            _ => return,
+        };
+        
+        if let (Some(stored_modified), Some(live_modified)) = (stored.cf_modified_on, record.modified_on) {
+            if live_modified > stored_modified {
+                warn!(
+                    "⚠️ 检测到域名 {} 的记录在本程序之外被修改: {} -> {} (Cloudflare修改时间 {})",
+                    domain,
+                    stored.content.as_deref().unwrap_or("无"),
+                    record.content,
+                    live_modified.to_rfc3339()
+                );
+                self.events.publish(
+                    domain,
+                    DomainEventKind::Warning,
+                    format!("记录在本程序之外被修改: {} -> {}", stored.content.as_deref().unwrap_or("无"), record.content),
+                );
+            }
+        }
+    }
+
+    /// 保存域名的本地同步状态，失败时仅记录日志
+    fn save_domain_state(&self, domain: &str, record: &DnsRecord) {
+        if let Err(e) = self.db.save_domain_state(
+            domain,
+            &record.id,
+            &record.content,
+            record.created_on,
+            record.modified_on,
+        ) {
+            error!("❌ 保存域名状态失败 {}: {}", domain, e);
+        }
+        
+        // 记录带有归属标记时同步登记到本地归属表
+        if let Some(owner) = ownership::owner_of(record) {
+            if let Err(e) = self.db.save_ownership(domain, Some(&record.id), owner, ownership::STATUS_OWNED) {
+                error!("❌ 保存记录归属失败 {}: {}", domain, e);
+            }
+        }
+    }
+
+    /// 查询同名CNAME记录，返回其目标；查询失败时不阻止创建，由创建结果决定
+    async fn find_cname(client: &CloudflareClient, full_domain: &str) -> Option<String> {
+        match client.get_cname_records(full_domain).await {
+            Ok(records) => records.into_iter().next().map(|record| record.content),
+            Err(e) => {
+                warn!("⚠️ 查询CNAME记录失败 {}: {}", full_domain, e);
+                None
+            }
+        }
+    }
+
+    fn save_domain_warning(&self, domain: &str, warning: &str) {
+        if let Err(e) = self.db.save_domain_warning(domain, warning) {
+            error!("❌ 保存域名状态失败 {}: {}", domain, e);
+        }
+    }
+
+    /// 本实例的归属信息与本地登记的记录归属
+    pub fn get_ownership(&self) -> Result<(String, Vec<RecordOwnership>)> {
+        Ok((self.db.instance_id()?, self.db.get_ownership()?))
+    }
+}
+
+/// 域名在一次更新中的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    /// 新建AAAA记录
+    Create,
+    /// 修改已有记录
+    Update,
+    /// 记录已是最新
+    Unchanged,
+    /// 存在冲突，跳过该域名
+    Skip,
+    /// 无法读取记录，未能计算
+    Error,
+}
+
+/// 跳过域名的原因
+#[derive(Debug, Clone)]
+enum PlanConflict {
+    /// 记录属于其他实例
+    Owner(String),
+    /// 同名存在CNAME记录
+    Cname,
+}
+
+/// 单个域名的处理计划
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainPlan {
+    pub subdomain: String,
+    pub domain: String,
+    pub action: PlanAction,
+    pub record_id: Option<String>,
+    pub current_content: Option<String>,
+    pub new_content: Option<String>, // 将写入的地址，不修改地址时为None
+    pub proxied: Option<bool>,       // 将设置的代理状态，不修改时为None
+    pub comment: Option<String>,     // 将写入的备注，不修改时为None
+    pub duplicates: Vec<DnsRecord>,  // 同名的多余AAAA记录
+    pub delete_duplicates: bool,     // 是否会删除多余记录
+    pub additional: Vec<String>,     // 发布所有地址时将新建记录的其他地址
+    pub foreign_owners: Vec<String>, // 同名记录中属于其他实例的归属者，这些记录不做修改
+    pub reason: Option<String>,
+    #[serde(skip)]
+    record: Option<DnsRecord>,
+    #[serde(skip)]
+    patch: Option<PatchDnsRecordRequest>,
+    #[serde(skip)]
+    conflict: Option<PlanConflict>,
+}
+
+impl DomainPlan {
+    fn new(subdomain: &str, domain: String, action: PlanAction) -> Self {
+        Self {
+            subdomain: subdomain.to_string(),
+            domain,
+            action,
+            record_id: None,
+            current_content: None,
+            new_content: None,
+            proxied: None,
+            comment: None,
+            duplicates: Vec::new(),
+            delete_duplicates: false,
+            additional: Vec::new(),
+            foreign_owners: Vec::new(),
+            reason: None,
+            record: None,
+            patch: None,
+            conflict: None,
+        }
+    }
+}
+
+/// DNS记录中由本程序管理的字段
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecordValues {
+    pub content: String,
+    pub ttl: u32, // 1表示自动
+    pub proxied: bool,
+    pub comment: Option<String>,
+}
+
+impl From<&DnsRecord> for RecordValues {
+    fn from(record: &DnsRecord) -> Self {
+        Self {
+            content: record.content.clone(),
+            ttl: record.ttl,
+            proxied: record.proxied,
+            comment: record.comment.clone(),
+        }
+    }
+}
+
+/// 单个字段的变化，新建记录时 from 为None
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub from: Option<serde_json::Value>,
+    pub to: serde_json::Value,
+}
+
+/// 一次更新中各域名共用的上下文
+struct PublishContext {
+    owner: Option<String>,      // 启用归属登记时为本实例ID
+    additional: Vec<String>,    // 发布所有地址时除当前IP外的其他地址
+}
+
+/// 单个域名此刻同步会做的修改
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainDiff {
+    pub subdomain: String,
+    pub domain: String,
+    pub action: PlanAction,
+    pub detected_ip: String,
+    pub record_id: Option<String>,
+    pub current: Option<RecordValues>,  // Cloudflare上的当前值，记录不存在时为None
+    pub proposed: Option<RecordValues>, // 同步后的值，不做修改时为None
+    pub changes: Vec<FieldChange>,
+    pub duplicates: Vec<DnsRecord>,
+    pub delete_duplicates: bool,
+    pub additional: Vec<String>,
+    pub reason: Option<String>,
+}
+
+impl DomainDiff {
+    fn from_plan(plan: DomainPlan, detected_ip: String) -> Self {
+        let current = plan.record.as_ref().map(RecordValues::from);
+        let proposed = match (plan.action, &current) {
+            // 新建的记录TTL为自动
+            (PlanAction::Create, _) => Some(RecordValues {
+                content: plan.new_content.clone().unwrap_or_else(|| detected_ip.clone()),
+                ttl: 1,
+                proxied: plan.proxied.unwrap_or(false),
+                comment: plan.comment.clone(),
+            }),
+            (PlanAction::Update, Some(current)) => Some(RecordValues {
+                content: plan.new_content.clone().unwrap_or_else(|| current.content.clone()),
+                ttl: current.ttl,
+                proxied: plan.proxied.unwrap_or(current.proxied),
+                comment: plan.comment.clone().or_else(|| current.comment.clone()),
+            }),
+            _ => None,
+        };
+        let changes = proposed
+            .as_ref()
+            .map(|proposed| Self::changes(current.as_ref(), proposed))
+            .unwrap_or_default();
+        
+        Self {
+            subdomain: plan.subdomain,
+            domain: plan.domain,
+            action: plan.action,
+            detected_ip,
+            record_id: plan.record_id,
+            current,
+            proposed,
+            changes,
+            duplicates: plan.duplicates,
+            delete_duplicates: plan.delete_duplicates,
+            additional: plan.additional,
+            reason: plan.reason,
+        }
+    }
+
+    /// 逐个字段比较，列出有变化的字段
+    fn changes(current: Option<&RecordValues>, proposed: &RecordValues) -> Vec<FieldChange> {
+        let fields = [
+            ("content", json!(proposed.content), current.map(|c| json!(c.content))),
+            ("ttl", json!(proposed.ttl), current.map(|c| json!(c.ttl))),
+            ("proxied", json!(proposed.proxied), current.map(|c| json!(c.proxied))),
+            ("comment", json!(proposed.comment), current.map(|c| json!(c.comment))),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, to, from)| match from {
+                Some(from) => from != to,
+                None => !to.is_null(),
+            })
+            .map(|(field, to, from)| FieldChange { field, from, to })
+            .collect()
+    }
+}
+
+/// 下一次更新的预览
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdatePlan {
+    pub current_ip: String,
+    pub last_ip: Option<String>,
+    pub ip_changed: bool,
+    pub will_run: bool, // 定时检查是否会执行更新（IP变化或有尚未同步的子域名）
+    pub domains: Vec<DomainPlan>,
+}
+
+/// 单个选中子域名的同步状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainStatus {
+    pub subdomain: String,
+    pub domain: String,
+    pub record_id: Option<String>,
+    pub content: Option<String>, // 最后一次同步时记录的内容
+    pub cf_modified_on: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub warning: Option<String>,
+    pub failing: bool, // 最近一次处理失败（错误晚于最后一次成功）
+    pub last_success_at_display: Option<String>,
+    pub last_error_at_display: Option<String>,
+}
+
+impl DomainStatus {
+    fn new(subdomain: &str, domain: String, state: Option<DomainState>) -> Self {
+        let mut status = Self {
+            subdomain: subdomain.to_string(),
+            domain,
+            record_id: None,
+            content: None,
+            cf_modified_on: None,
+            last_success_at: None,
+            last_error: None,
+            last_error_at: None,
+            warning: None,
+            failing: false,
+            last_success_at_display: None,
+            last_error_at_display: None,
+        };
+        let Some(state) = state else { return status };
+        
+        status.failing = match (state.last_error_at, state.last_success_at) {
+            (Some(error_at), Some(success_at)) => error_at >= success_at,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        status.record_id = state.record_id;
+        status.content = state.content;
+        status.cf_modified_on = state.cf_modified_on;
+        status.last_success_at = state.last_success_at;
+        status.last_error = state.last_error;
+        status.last_error_at = state.last_error_at;
+        status.warning = state.warning;
+        status
+    }
+}
+
+/// 一次检查的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckRun {
+    pub finished_at: DateTime<Utc>,
+    pub updated: bool,         // 是否更新了DNS记录
+    pub status: ServiceStatus, // 检查后的服务状态
+    pub error: Option<String>,
+}
+
+/// 定期对账的状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileState {
+    pub next_due: Option<DateTime<Utc>>, // 未启用对账时为null
+    pub last: Option<ReconcileRun>,
+}
+
+/// 一次对账的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconcileRun {
+    pub finished_at: DateTime<Utc>,
+    pub checked: i32,  // 检查的域名数
+    pub repaired: i32, // 新建或修改的记录数
+    pub failed: i32,
+    pub dry_run: bool, // 演练模式下只记录将要修复的记录
+}
+
+/// 一次更新周期的结果汇总
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct UpdateSummary {
+    pub total_count: i32,
+    pub success_count: i32,
+    pub error_message: Option<String>,
+    pub error_code: Option<u32>,
+    pub actions: ActionCounts,
+    pub dry_run: bool, // 演练模式，没有实际写入
+    pub domains: Vec<DomainResult>, // 每个域名的处理结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup_hostname: Option<DomainResult>, // 备用主机名的发布结果
+    #[serde(skip)]
+    pub unreachable: bool, // 最后一个错误是否因无法连接Cloudflare引起
+}
+
+/// 单个域名在本次更新中的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainOutcome {
+    Created,
+    Updated,
+    Unchanged,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainResult {
+    pub subdomain: String,
+    pub domain: String,
+    pub outcome: DomainOutcome,
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub propagation: Option<Propagation>, // 开启生效确认时，新地址在公共DNS中的确认结果
+}
+
+/// 立即更新的结果报告
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateReport {
+    pub current_ip: String,
+    pub previous_ip: Option<String>,
+    pub ip_changed: bool,
+    pub force: bool,
+    #[serde(flatten)]
+    pub summary: UpdateSummary,
+}
+
+impl UpdateSummary {
+    /// 合并另一部分域名的汇总，错误信息取最后一个
+    fn merge(&mut self, other: UpdateSummary) {
+        self.total_count += other.total_count;
+        self.success_count += other.success_count;
+        self.actions.add(&other.actions);
+        self.domains.extend(other.domains);
+        if other.error_message.is_some() {
+            self.error_message = other.error_message;
+            self.error_code = other.error_code;
+            self.unreachable = other.unreachable;
+        }
+    }
+
+    /// 对比处理该域名前的汇总，记录该域名的处理结果
+    fn note_domain_result(&mut self, subdomain: &str, domain: &str, (success_count, actions): (i32, ActionCounts)) {
+        let outcome = if self.success_count == success_count {
+            DomainOutcome::Failed
+        } else if self.actions.created > actions.created {
+            DomainOutcome::Created
+        } else if self.actions.updated > actions.updated {
+            DomainOutcome::Updated
+        } else {
+            DomainOutcome::Unchanged
+        };
+        let error = (outcome == DomainOutcome::Failed).then(|| self.error_message.clone()).flatten();
+        self.domains.push(DomainResult {
+            subdomain: subdomain.to_string(),
+            domain: domain.to_string(),
+            outcome,
+            error,
+            propagation: None,
+        });
+    }
+
+    /// 记录错误信息，若为Cloudflare结构化错误则同时记录错误码
+    fn record_error(&mut self, message: String, error: &anyhow::Error) {
+        self.error_message = Some(message);
+        self.unreachable = cloudflare_error::is_unreachable(error);
+        if let Some(code) = error.downcast_ref::<CloudflareError>().and_then(|e| e.code()) {
+            self.error_code = Some(code);
+        }
+    }
+}
+
+/// 通用记录管理接口支持的记录类型
+pub const SUPPORTED_RECORD_TYPES: [&str; 5] = ["A", "AAAA", "TXT", "CNAME", "MX"];
+
+/// 检查记录类型是否受支持（不区分大小写），返回规范的类型名
+fn supported_record_type(record_type: &str) -> Result<&'static str> {
+    SUPPORTED_RECORD_TYPES
+        .iter()
+        .find(|t| t.eq_ignore_ascii_case(record_type.trim()))
+        .copied()
+        .ok_or_else(|| anyhow!("不支持的记录类型: {}（支持 {}）", record_type, SUPPORTED_RECORD_TYPES.join("/")))
+}
+
+/// 校验记录内容与类型是否匹配，避免把明显错误的请求发送到Cloudflare
+fn validate_record(record_type: &str, content: &str, proxied: bool, priority: Option<u16>) -> Result<()> {
+    let content = content.trim();
+    match record_type {
+        "A" if content.parse::<std::net::Ipv4Addr>().is_err() => {
+            return Err(anyhow!("A记录的内容必须是IPv4地址: {}", content));
+        }
+        "AAAA" if content.parse::<std::net::Ipv6Addr>().is_err() => {
+            return Err(anyhow!("AAAA记录的内容必须是IPv6地址: {}", content));
+        }
+        "CNAME" | "MX" if content.is_empty() || content.contains(char::is_whitespace) => {
+            return Err(anyhow!("{}记录的内容必须是域名: {}", record_type, content));
+        }
+        "TXT" if content.is_empty() => return Err(anyhow!("TXT记录的内容不能为空")),
+        "MX" if priority.is_none() => return Err(anyhow!("MX记录需要指定优先级 priority")),
+        _ => {}
+    }
+    if proxied && !matches!(record_type, "A" | "AAAA" | "CNAME") {
+        return Err(anyhow!("{}记录不能开启代理", record_type));
+    }
+    Ok(())
+}
+
+/// 校验并规范化用户输入的子域名，可以输入完整域名，"@" 或空字符串表示根域名
+pub fn normalize_subdomain(name: &str, root_domain: &str) -> Result<String> {
+    let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+    let root = root_domain.to_ascii_lowercase();
+    let subdomain = if name == "@" || name == root {
+        ""
+    } else {
+        name.strip_suffix(&format!(".{}", root)).unwrap_or(&name)
+    };
+    if subdomain.is_empty() {
+        return Ok(String::new());
+    }
+    
+    if subdomain.len() + root.len() + 1 > 253 {
+        return Err(anyhow!("域名过长: {}.{}", subdomain, root));
+    }
+    for (index, label) in subdomain.split('.').enumerate() {
+        // 通配符只能作为第一段
+        if label == "*" && index == 0 {
+            continue;
+        }
+        let valid = !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(anyhow!("无效的子域名: {}", subdomain));
+        }
+    }
+    
+    Ok(subdomain.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::test_support::{mock_cloudflare, mock_records, record_json, success_body};
+
+    fn record(id: &str, content: &str) -> DnsRecord {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "name": "home.example.com", "type": "AAAA",
+            "content": content, "proxied": false, "ttl": 1
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_select_primary_keeps_other_hosts_in_round_robin() {
+        let mut config: AppConfig = serde_json::from_value(serde_json::json!({
+            "cloudflare_api_key": "", "cloudflare_zone_id": "", "root_domain": "example.com",
+            "selected_subdomains": ["home"], "check_interval": 300, "last_ip": "2001:db8::2"
+        }))
+        .unwrap();
+        let records = [record("a", "2001:db8::1"), record("b", "2001:db8::2")];
+        let select = |config: &AppConfig, owned| {
+            ConfigService::select_primary(config, &records, "2001:db8::3", owned).map(|r| r.id.as_str())
+        };
+
+        assert_eq!(select(&config, Some("a")), Some("a"));
+        assert_eq!(select(&config, None), Some("b"));
+
+        config.last_ip = None;
+        assert_eq!(select(&config, None), Some("a"));
+        config.round_robin = true;
+        assert_eq!(select(&config, None), None);
+    }
+
+    #[test]
+    fn test_mismatch_since_survives_unknown_checks() {
+        let start = Utc::now();
+        let later = start + chrono::Duration::minutes(5);
+        assert_eq!(ConfigService::mismatch_since(None, Some(false), start), Some(start));
+        assert_eq!(ConfigService::mismatch_since(Some(start), Some(false), later), Some(start));
+        assert_eq!(ConfigService::mismatch_since(Some(start), None, later), Some(start));
+        assert_eq!(ConfigService::mismatch_since(None, None, later), None);
+        assert_eq!(ConfigService::mismatch_since(Some(start), Some(true), later), None);
+    }
+
+    #[test]
+    fn test_split_additional_addresses() {
+        let records = vec![record("a", "2001:db8::1"), record("b", "2001:db8:1::1"), record("c", "2001:db8:9::1")];
+        let additional = ["2001:db8:1:0::1".to_string(), "2001:db8:2::1".to_string()];
+        let (candidates, missing) = ConfigService::split_additional(records.clone(), &additional);
+        // 已有记录的其他地址不参与主记录选择，剩下的旧地址记录会作为多余记录删除
+        assert_eq!(candidates.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec!["a", "c"]);
+        assert_eq!(missing, vec!["2001:db8:2::1".to_string()]);
+
+        let (candidates, missing) = ConfigService::split_additional(records, &[]);
+        assert_eq!((candidates.len(), missing.len()), (3, 0));
+    }
+
+    #[test]
+    fn test_domain_behind_uses_its_own_content() {
+        let now = Utc::now();
+        let state = |record_id: Option<&str>, content: Option<&str>, warning: Option<&str>| DomainState {
+            domain: "home.example.com".to_string(),
+            record_id: record_id.map(str::to_string),
+            content: content.map(str::to_string),
+            cf_created_on: None,
+            cf_modified_on: None,
+            updated_at: now,
+            warning: warning.map(str::to_string),
+            last_success_at: None,
+            last_error: None,
+            last_error_at: None,
+            cf_modified_on_display: None,
+            updated_at_display: None,
+        };
+        let current = "2001:db8::1";
+
+        assert!(ConfigService::is_behind(None, current));
+        assert!(!ConfigService::is_behind(Some(&state(Some("a"), Some("2001:db8:0::1"), None)), current));
+        // 上次更新中失败的域名保留旧地址，即使全局的last_ip已是当前IP也需要重试
+        assert!(ConfigService::is_behind(Some(&state(Some("a"), Some("2001:db8::2"), None)), current));
+        assert!(ConfigService::is_behind(Some(&state(None, None, None)), current));
+        assert!(!ConfigService::is_behind(Some(&state(None, None, Some("CNAME冲突"))), current));
+    }
+
+    #[test]
+    fn test_normalize_subdomain() {
+        assert_eq!(normalize_subdomain(" NAS ", "example.com").unwrap(), "nas");
+        assert_eq!(normalize_subdomain("nas.home.example.com.", "example.com").unwrap(), "nas.home");
+        assert_eq!(normalize_subdomain("@", "example.com").unwrap(), "");
+        assert_eq!(normalize_subdomain("*.lab", "example.com").unwrap(), "*.lab");
+        assert!(normalize_subdomain("-bad", "example.com").is_err());
+        assert!(normalize_subdomain("a..b", "example.com").is_err());
+        assert!(normalize_subdomain("lab.*", "example.com").is_err());
+    }
+
+    #[test]
+    fn test_plan_serialization_hides_internal_state() {
+        let mut plan = DomainPlan::new("home", "home.example.com".to_string(), PlanAction::Create);
+        plan.new_content = Some("2001:db8::1".to_string());
+        plan.patch = Some(PatchDnsRecordRequest::default());
+        plan.conflict = Some(PlanConflict::Cname);
+        
+        let value = serde_json::to_value(&plan).unwrap();
+        assert_eq!(value["action"], "create");
+        assert_eq!(value["new_content"], "2001:db8::1");
+        assert!(value.get("patch").is_none());
+        assert!(value.get("conflict").is_none());
+    }
+
+    #[test]
+    fn test_domain_diff_lists_changed_fields() {
+        let mut plan = DomainPlan::new("home", "home.example.com".to_string(), PlanAction::Update);
+        plan.record = Some(record("r1", "2001:db8::1"));
+        plan.new_content = Some("2001:db8::2".to_string());
+        plan.proxied = Some(true);
+        
+        let diff = DomainDiff::from_plan(plan, "2001:db8::2".to_string());
+        let fields: Vec<_> = diff.changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec!["content", "proxied"]);
+        assert_eq!(diff.changes[0].from, Some(json!("2001:db8::1")));
+        assert_eq!(diff.proposed.unwrap().ttl, 1);
+        
+        // 无需修改时没有差异
+        let mut plan = DomainPlan::new("home", "home.example.com".to_string(), PlanAction::Unchanged);
+        plan.record = Some(record("r1", "2001:db8::2"));
+        let diff = DomainDiff::from_plan(plan, "2001:db8::2".to_string());
+        assert!(diff.proposed.is_none() && diff.changes.is_empty());
+    }
+
+    #[test]
+    fn test_pause_state_survives_restart() {
+        let db = Database::new(":memory:").unwrap();
+        let service = ConfigService::with_clock(db.clone(), crate::utils::clock::system_clock());
+        service.pause(None, Some("网络维护".to_string())).unwrap();
+        
+        // 使用同一数据库重新创建服务，模拟程序重启
+        let restarted = ConfigService::with_clock(db, crate::utils::clock::system_clock());
+        let state = restarted.pause_state().unwrap();
+        assert_eq!(state.until, None);
+        assert_eq!(state.reason.as_deref(), Some("网络维护"));
+        
+        assert!(restarted.resume().unwrap());
+        assert!(ConfigService::with_clock(restarted.database().clone(), crate::utils::clock::system_clock())
+            .pause_state()
+            .is_none());
+    }
+
+    #[test]
+    fn test_reconcile_runs_once_per_interval() {
+        use crate::utils::clock::ManualClock;
+        let clock = Arc::new(ManualClock::new(Utc::now()));
+        let service = ConfigService::with_clock(Database::new(":memory:").unwrap(), clock.clone());
+        let mut config: AppConfig = serde_json::from_value(serde_json::json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["home"],
+            "check_interval": 300,
+            "reconcile_interval": 60
+        }))
+        .unwrap();
+        
+        // 启用后第一次检查只安排对账，一个间隔后才执行
+        assert!(!service.take_reconcile_due(&config));
+        let next = service.reconcile_state().next_due.unwrap();
+        clock.advance(std::time::Duration::from_secs(59 * 60));
+        assert!(!service.take_reconcile_due(&config));
+        clock.advance(std::time::Duration::from_secs(60));
+        assert!(service.take_reconcile_due(&config));
+        assert!(service.reconcile_state().next_due.unwrap() > next);
+        assert!(!service.take_reconcile_due(&config));
+        
+        config.reconcile_interval = 0;
+        assert!(!service.take_reconcile_due(&config));
+        assert!(service.reconcile_state().next_due.is_none());
+    }
+
+    #[test]
+    fn test_validate_record() {
+        assert_eq!(supported_record_type("mx").unwrap(), "MX");
+        assert!(supported_record_type("NS").is_err());
+        assert!(validate_record("A", "192.0.2.1", true, None).is_ok());
+        assert!(validate_record("A", "2001:db8::1", false, None).is_err());
+        assert!(validate_record("AAAA", "2001:db8::1", false, None).is_ok());
+        assert!(validate_record("CNAME", "target.example.net", true, None).is_ok());
+        assert!(validate_record("TXT", "v=spf1 -all", true, None).is_err());
+        assert!(validate_record("MX", "mail.example.com", false, None).is_err());
+        assert!(validate_record("MX", "mail.example.com", false, Some(10)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_makes_no_writes() {
+        let mut server = mockito::Server::new_async().await;
+        let (service, client, config) = mock_cloudflare(&server, &["home", "new"], true);
+        mock_records(&mut server, "home.example.com", json!([record_json("r1", "home.example.com", "2001:db8::1")])).await;
+        mock_records(&mut server, "new.example.com", json!([])).await;
+        let mut writes = Vec::new();
+        for method in ["PATCH", "POST", "PUT", "DELETE"] {
+            writes.push(server.mock(method, mockito::Matcher::Any).expect(0).create_async().await);
+        }
+
+        let summary = service.update_selected_domains(&config, &client, "2001:db8::2", false).await.unwrap();
+        assert!(summary.dry_run);
+        assert_eq!((summary.actions.updated, summary.actions.created), (1, 1));
+        for mock in writes {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_force_patches_unchanged_record() {
+        let mut server = mockito::Server::new_async().await;
+        let (service, client, config) = mock_cloudflare(&server, &["home"], false);
+        let current = record_json("r1", "home.example.com", "2001:db8::2");
+        mock_records(&mut server, "home.example.com", json!([current.clone()])).await;
+        let patch = server
+            .mock("PATCH", "/zones/z/dns_records/r1")
+            .match_body(mockito::Matcher::PartialJson(json!({ "content": "2001:db8::2" })))
+            .with_body(success_body(current))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let summary = service.update_selected_domains(&config, &client, "2001:db8::2", false).await.unwrap();
+        assert_eq!(summary.actions.unchanged, 1);
+        let summary = service.update_selected_domains(&config, &client, "2001:db8::2", true).await.unwrap();
+        assert_eq!((summary.success_count, summary.actions.updated), (1, 1));
+        patch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_summary_counts_mixed_failures() {
+        let mut server = mockito::Server::new_async().await;
+        let (service, client, config) = mock_cloudflare(&server, &["a", "b", "c"], false);
+        mock_records(&mut server, "a.example.com", json!([record_json("ra", "a.example.com", "2001:db8::1")])).await;
+        mock_records(&mut server, "b.example.com", json!([record_json("rb", "b.example.com", "2001:db8::1")])).await;
+        server
+            .mock("GET", "/zones/z/dns_records")
+            .match_query(mockito::Matcher::UrlEncoded("name".into(), "c.example.com".into()))
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("PATCH", "/zones/z/dns_records/ra")
+            .with_body(success_body(record_json("ra", "a.example.com", "2001:db8::2")))
+            .create_async()
+            .await;
+        server
+            .mock("PATCH", "/zones/z/dns_records/rb")
+            .with_status(403)
+            .with_body(r#"{"success":false,"errors":[{"code":10000,"message":"Authentication error"}],"messages":[],"result":null}"#)
+            .create_async()
+            .await;
+
+        let summary = service.update_selected_domains(&config, &client, "2001:db8::2", false).await.unwrap();
+        assert_eq!((summary.total_count, summary.success_count), (3, 1));
+        assert!(summary.error_message.is_some());
+        let outcomes: Vec<_> = summary.domains.iter().map(|d| (d.subdomain.as_str(), d.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            vec![("a", DomainOutcome::Updated), ("b", DomainOutcome::Failed), ("c", DomainOutcome::Failed)]
+        );
+    }
+}
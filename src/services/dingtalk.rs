@@ -0,0 +1,111 @@
+use crate::config::database::DingTalkSettings;
+use crate::services::notify::NotifyEvent;
+use anyhow::{Result, anyhow};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+/// 接口的响应，HTTP状态码总是200，errcode 非0表示失败
+#[derive(Debug, Deserialize)]
+struct DingTalkResponse {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+/// 加签密钥，空字符串视为未设置
+fn secret(settings: &DingTalkSettings) -> Option<&str> {
+    settings.secret.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// 校验钉钉机器人设置
+pub fn validate(settings: &DingTalkSettings) -> Result<()> {
+    let url = settings.webhook_url.trim();
+    let parsed = reqwest::Url::parse(url).map_err(|_| anyhow!("钉钉机器人地址无效: {}", url))?;
+    let has_token = parsed.query_pairs().any(|(name, value)| name == "access_token" && !value.is_empty());
+    if !matches!(parsed.scheme(), "http" | "https") || !has_token {
+        return Err(anyhow!("钉钉机器人地址应形如 https://oapi.dingtalk.com/robot/send?access_token=<token>"));
+    }
+    if secret(settings).is_some_and(|s| !s.starts_with("SEC")) {
+        return Err(anyhow!("钉钉加签密钥应以 SEC 开头，请在机器人安全设置中复制"));
+    }
+    Ok(())
+}
+
+/// 加签：以 "时间戳\n密钥" 为内容、密钥为key计算HMAC-SHA256，base64编码后作为 sign 参数
+fn sign(secret: &str, timestamp: i64) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).expect("HMAC接受任意长度的密钥");
+    mac.update(format!("{}\n{}", timestamp, secret).as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// 请求地址，设置了密钥时附加毫秒时间戳和签名（URL编码由查询参数处理）
+fn signed_url(settings: &DingTalkSettings, timestamp: i64) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(settings.webhook_url.trim())?;
+    if let Some(secret) = secret(settings) {
+        url.query_pairs_mut()
+            .append_pair("timestamp", &timestamp.to_string())
+            .append_pair("sign", &sign(secret, timestamp));
+    }
+    Ok(url)
+}
+
+/// Markdown消息，钉钉需要空行才会换行
+fn payload(event: &NotifyEvent) -> Value {
+    let mut text = format!("#### {}", event.title());
+    for line in event.lines() {
+        text.push_str("\n\n");
+        text.push_str(&line);
+    }
+    json!({
+        "msgtype": "markdown",
+        "markdown": { "title": event.title(), "text": text },
+    })
+}
+
+/// 发送到自定义机器人
+pub async fn send(client: &Client, settings: &DingTalkSettings, event: &NotifyEvent) -> Result<()> {
+    let response: DingTalkResponse = client
+        .post(signed_url(settings, Utc::now().timestamp_millis())?)
+        .json(&payload(event))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if response.errcode != 0 {
+        return Err(anyhow!("钉钉返回错误 {}: {}", response.errcode, response.errmsg));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notify::NotifyKind;
+
+    #[test]
+    fn test_dingtalk_signed_payload() {
+        let mut settings = DingTalkSettings {
+            webhook_url: "https://oapi.dingtalk.com/robot/send?access_token=abc".to_string(),
+            secret: Some("SECtest".to_string()),
+        };
+        assert!(validate(&settings).is_ok());
+        assert_eq!(
+            signed_url(&settings, 1700000000000).unwrap().as_str(),
+            "https://oapi.dingtalk.com/robot/send?access_token=abc&timestamp=1700000000000&sign=aZLLrriXgn05YbwaGR7knYsLeJADjr9NwLaNNKpxh4g%3D"
+        );
+
+        let mut event = NotifyEvent::new(NotifyKind::UpdateFailed, "example.com", Utc::now());
+        event.message = Some("状态: failing".to_string());
+        assert_eq!(payload(&event)["markdown"]["text"], "#### ❌ example.com DNS更新失败\n\n状态: failing");
+
+        settings.secret = Some("abc".to_string());
+        assert!(validate(&settings).is_err());
+    }
+}
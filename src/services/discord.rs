@@ -0,0 +1,119 @@
+use crate::services::notify::{domain_outcome, NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// Discord对嵌入内容的限制：最多25个字段，字段值最多1024个字符，描述最多4096个字符
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_VALUE: usize = 1024;
+const MAX_DESCRIPTION: usize = 4096;
+
+/// 校验Webhook地址，形如 https://discord.com/api/webhooks/<id>/<token>
+pub fn validate(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| anyhow!("Discord Webhook地址无效: {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") || !parsed.path().contains("/webhooks/") {
+        return Err(anyhow!("Discord Webhook地址应形如 https://discord.com/api/webhooks/<id>/<token>"));
+    }
+    Ok(())
+}
+
+/// 超出长度时截断并以省略号结尾
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max - 1).collect();
+    format!("{}…", kept)
+}
+
+/// 嵌入内容：新旧地址为并排字段，每个域名一个字段，失败原因放在描述中
+fn embed(event: &NotifyEvent) -> Value {
+    let color = match event.kind {
+        NotifyKind::IpChanged => 0x3498db,
+        NotifyKind::UpdateSucceeded => 0x2ecc71,
+        NotifyKind::UpdateFailed => 0xe74c3c,
+        NotifyKind::Recovered => 0x2ecc71,
+        NotifyKind::DailySummary => 0x95a5a6,
+        NotifyKind::IpMismatch => 0xf39c12,
+    };
+    let mut fields = Vec::new();
+    if let Some(old_ip) = &event.old_ip {
+        fields.push(json!({"name": "旧地址", "value": old_ip, "inline": true}));
+    }
+    if let Some(new_ip) = &event.new_ip {
+        let name = if event.kind == NotifyKind::IpChanged { "新地址" } else { "当前地址" };
+        fields.push(json!({"name": name, "value": new_ip, "inline": true}));
+    }
+    for domain in &event.domains {
+        let (emoji, outcome) = domain_outcome(domain);
+        fields.push(json!({
+            "name": format!("{} {}", emoji, domain.domain),
+            "value": truncate(outcome, MAX_FIELD_VALUE),
+            "inline": false,
+        }));
+    }
+    if fields.len() > MAX_FIELDS {
+        let hidden = fields.len() - (MAX_FIELDS - 1);
+        fields.truncate(MAX_FIELDS - 1);
+        fields.push(json!({"name": "…", "value": format!("另有 {} 项未显示", hidden), "inline": false}));
+    }
+
+    let mut embed = json!({
+        "title": event.title(),
+        "color": color,
+        "fields": fields,
+        "timestamp": event.timestamp.to_rfc3339(),
+    });
+    if let Some(message) = &event.message {
+        embed["description"] = Value::String(truncate(message, MAX_DESCRIPTION));
+    }
+    embed
+}
+
+/// 发送带嵌入内容的消息，Discord成功时返回204
+pub async fn send(client: &Client, url: &str, event: &NotifyEvent) -> Result<()> {
+    client
+        .post(url)
+        .json(&json!({
+            "username": "cloudflare-auto",
+            "embeds": [embed(event)],
+        }))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::config_service::{DomainOutcome, DomainResult};
+    use chrono::Utc;
+
+    #[test]
+    fn test_discord_embed() {
+        assert!(validate("https://discord.com/api/webhooks/1/abc").is_ok());
+        assert!(validate("https://discord.com/channels/1").is_err());
+
+        let mut event = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        event.old_ip = Some("2001:db8::1".to_string());
+        event.new_ip = Some("2001:db8::2".to_string());
+        event.domains = (0..30)
+            .map(|i| DomainResult {
+                subdomain: format!("h{}", i),
+                domain: format!("h{}.example.com", i),
+                outcome: DomainOutcome::Updated,
+                error: None,
+                propagation: None,
+            })
+            .collect();
+        let embed = embed(&event);
+        let fields = embed["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), MAX_FIELDS);
+        assert_eq!(fields[1], json!({"name": "新地址", "value": "2001:db8::2", "inline": true}));
+        assert_eq!(fields[2]["name"], "✅ h0.example.com");
+        assert_eq!(fields[24]["value"], "另有 8 项未显示");
+
+        assert_eq!(truncate("域名记录", 2), "域…");
+    }
+}
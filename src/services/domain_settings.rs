@@ -0,0 +1,180 @@
+use crate::config::database::{AppConfig, DeletedSubdomain};
+use crate::services::config_service::normalize_subdomain;
+use crate::utils::csv;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// CSV的列，导出时按此顺序输出
+pub const COLUMNS: [&str; 3] = ["name", "enabled", "proxied"];
+
+/// 单个子域名的设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DomainSettings {
+    pub subdomain: String,
+    pub enabled: bool,         // 是否在监控列表中，false表示已软删除
+    pub proxied: Option<bool>, // 未设置时保留记录原有的代理状态
+}
+
+/// 当前所有子域名的设置：选中的子域名在前，之后是已软删除的子域名
+pub fn collect(config: &AppConfig, deleted: &[DeletedSubdomain]) -> Vec<DomainSettings> {
+    let enabled = config.selected_subdomains.iter().map(|s| (s, true));
+    let disabled = deleted.iter().map(|d| (&d.subdomain, false));
+    enabled
+        .chain(disabled)
+        .map(|(subdomain, enabled)| DomainSettings {
+            subdomain: subdomain.clone(),
+            enabled,
+            proxied: config.proxied_for(subdomain),
+        })
+        .collect()
+}
+
+/// 导出为CSV，根域名写作 "@"，未设置代理状态时proxied列留空
+pub fn to_csv(settings: &[DomainSettings]) -> String {
+    let mut text = csv::write_row(&COLUMNS);
+    for setting in settings {
+        let name = if setting.subdomain.is_empty() { "@" } else { setting.subdomain.as_str() };
+        let proxied = setting.proxied.map(|p| p.to_string()).unwrap_or_default();
+        text.push_str(&csv::write_row(&[name, &setting.enabled.to_string(), &proxied]));
+    }
+    text
+}
+
+/// 解析后的导入内容
+#[derive(Debug)]
+pub struct ParsedImport {
+    pub rows: Vec<DomainSettings>,
+    pub has_enabled: bool,            // 没有enabled列时所有行视为启用
+    pub has_proxied: bool,            // 没有proxied列时不修改代理设置
+    pub ignored_columns: Vec<String>, // 不支持的列
+}
+
+/// 解析导入的CSV，第一行为表头，必须包含name列
+pub fn parse_csv(text: &str, root_domain: &str) -> Result<ParsedImport> {
+    let mut rows = csv::parse(text)?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or_else(|| anyhow!("CSV为空"))?
+        .iter()
+        .map(|column| column.trim().to_ascii_lowercase())
+        .collect();
+    let position = |name: &str| header.iter().position(|column| column == name);
+    let name_index = position("name").ok_or_else(|| anyhow!("CSV缺少name列"))?;
+    let enabled_index = position("enabled");
+    let proxied_index = position("proxied");
+    let ignored_columns = header
+        .iter()
+        .filter(|column| !column.is_empty() && !COLUMNS.contains(&column.as_str()))
+        .cloned()
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut settings = Vec::new();
+    // 表头是第1行
+    for (line, row) in rows.enumerate().map(|(i, row)| (i + 2, row)) {
+        let field = |index: Option<usize>| index.and_then(|i| row.get(i)).map(|f| f.trim()).unwrap_or("");
+        let subdomain = normalize_subdomain(field(Some(name_index)), root_domain)
+            .map_err(|e| anyhow!("第{}行: {}", line, e))?;
+        if !seen.insert(subdomain.clone()) {
+            return Err(anyhow!("第{}行: 子域名重复: {}", line, field(Some(name_index))));
+        }
+        let enabled = match field(enabled_index) {
+            "" => true,
+            value => parse_bool(value).ok_or_else(|| anyhow!("第{}行: enabled 无效: {}", line, value))?,
+        };
+        let proxied = match field(proxied_index) {
+            "" => None,
+            value => Some(parse_bool(value).ok_or_else(|| anyhow!("第{}行: proxied 无效: {}", line, value))?),
+        };
+        settings.push(DomainSettings { subdomain, enabled, proxied });
+    }
+
+    Ok(ParsedImport {
+        rows: settings,
+        has_enabled: enabled_index.is_some(),
+        has_proxied: proxied_index.is_some(),
+        ignored_columns,
+    })
+}
+
+/// 表格中常见的布尔写法
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "y" | "on" | "是" => Some(true),
+        "false" | "0" | "no" | "n" | "off" | "否" => Some(false),
+        _ => None,
+    }
+}
+
+/// 导入结果
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct ImportReport {
+    pub total: usize,
+    pub enabled: Vec<String>,   // 新加入监控列表的子域名
+    pub disabled: Vec<String>,  // 移出监控列表（软删除）的子域名
+    pub proxied_changed: Vec<String>,
+    pub ignored_columns: Vec<String>,
+}
+
+/// 将导入的设置合并到配置中，CSV中没有出现的子域名保持不变
+pub fn apply(config: &mut AppConfig, import: ParsedImport) -> ImportReport {
+    let mut report = ImportReport {
+        total: import.rows.len(),
+        ignored_columns: import.ignored_columns,
+        ..Default::default()
+    };
+    for row in import.rows {
+        let selected = config.selected_subdomains.contains(&row.subdomain);
+        if row.enabled && !selected {
+            config.selected_subdomains.push(row.subdomain.clone());
+            report.enabled.push(row.subdomain.clone());
+        } else if !row.enabled && selected && import.has_enabled {
+            config.selected_subdomains.retain(|s| s != &row.subdomain);
+            report.disabled.push(row.subdomain.clone());
+        }
+
+        if import.has_proxied && config.proxied_for(&row.subdomain) != row.proxied {
+            match row.proxied {
+                Some(proxied) => config.domain_proxied.insert(row.subdomain.clone(), proxied),
+                None => config.domain_proxied.remove(&row.subdomain),
+            };
+            report.proxied_changed.push(row.subdomain);
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_import_merges_listed_subdomains() {
+        let mut config: AppConfig = serde_json::from_value(json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["home", "nas", "old"],
+            "check_interval": 300,
+            "domain_proxied": {"home": true}
+        }))
+        .unwrap();
+        let exported = to_csv(&collect(&config, &[]));
+        assert_eq!(exported, "name,enabled,proxied\r\nhome,true,true\r\nnas,true,\r\nold,true,\r\n");
+
+        let text = "Name,Enabled,Proxied,TTL\nhome.example.com,yes,,300\n@,true,false,\nold,false,,\n";
+        let import = parse_csv(text, "example.com").unwrap();
+        let report = apply(&mut config, import);
+        assert_eq!(config.selected_subdomains, vec!["home", "nas", ""]);
+        assert_eq!(config.proxied_for("home"), None);
+        assert_eq!(config.proxied_for(""), Some(false));
+        assert_eq!(report.enabled, vec![""]);
+        assert_eq!(report.disabled, vec!["old"]);
+        assert_eq!(report.ignored_columns, vec!["ttl"]);
+
+        assert!(parse_csv("name\nhome\nHOME\n", "example.com").is_err());
+        assert!(parse_csv("name,enabled\nhome,maybe\n", "example.com").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::services::cloudflare::DnsRecord;
+use serde::Serialize;
+use std::net::Ipv6Addr;
+
+/// 单个域名的Cloudflare记录与本机IP的比对结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftStatus {
+    /// 记录指向本机IP
+    InSync,
+    /// 记录存在但指向其他地址
+    Drifted,
+    /// 没有AAAA记录
+    Missing,
+    /// 无法读取记录
+    Error,
+}
+
+/// 单个域名的比对结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainDrift {
+    pub subdomain: String,
+    pub domain: String,
+    pub status: DriftStatus,
+    pub live_contents: Vec<String>, // Cloudflare上该名称的全部AAAA记录内容
+    pub reason: Option<String>,
+}
+
+/// 所有选中域名的比对结果
+#[derive(Debug, Clone, Serialize)]
+pub struct DriftReport {
+    pub current_ip: String,
+    pub last_ip: Option<String>,
+    pub last_ip_matches: bool, // 本地记录的last_ip与本机IP一致，即定时检查会认为无需更新
+    pub drifted: usize,        // 状态不是in_sync的域名数
+    pub domains: Vec<DomainDrift>,
+}
+
+impl DriftReport {
+    pub fn new(current_ip: String, last_ip: Option<String>, domains: Vec<DomainDrift>) -> Self {
+        Self {
+            last_ip_matches: last_ip.as_deref().is_some_and(|ip| same_address(ip, &current_ip)),
+            drifted: domains.iter().filter(|d| d.status != DriftStatus::InSync).count(),
+            current_ip,
+            last_ip,
+            domains,
+        }
+    }
+}
+
+/// 比较某个名称的AAAA记录与本机IP：任意一条记录指向本机即视为已同步（轮询模式下其余记录属于其他主机）
+pub fn compare(subdomain: &str, domain: String, records: &[DnsRecord], current_ip: &str) -> DomainDrift {
+    let live_contents: Vec<String> = records.iter().map(|r| r.content.clone()).collect();
+    let (status, reason) = if records.is_empty() {
+        (DriftStatus::Missing, Some("没有AAAA记录".to_string()))
+    } else if live_contents.iter().any(|content| same_address(content, current_ip)) {
+        (DriftStatus::InSync, None)
+    } else {
+        (DriftStatus::Drifted, Some(format!("记录未指向本机IP {}", current_ip)))
+    };
+    DomainDrift {
+        subdomain: subdomain.to_string(),
+        domain,
+        status,
+        live_contents,
+        reason,
+    }
+}
+
+/// 读取记录失败时的比对结果
+pub fn failed(subdomain: &str, domain: String, error: String) -> DomainDrift {
+    DomainDrift {
+        subdomain: subdomain.to_string(),
+        domain,
+        status: DriftStatus::Error,
+        live_contents: Vec::new(),
+        reason: Some(error),
+    }
+}
+
+/// 按地址比较，忽略IPv6的不同写法（如前导零、零压缩和大小写）
+pub fn same_address(a: &str, b: &str) -> bool {
+    match (a.parse::<Ipv6Addr>(), b.parse::<Ipv6Addr>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(content: &str) -> DnsRecord {
+        serde_json::from_value(json!({
+            "id": "id",
+            "type": "AAAA",
+            "name": "home.example.com",
+            "content": content,
+            "ttl": 1,
+            "proxied": false
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compare_live_records_with_local_ip() {
+        let ip = "2001:db8::1";
+        let domain = || "home.example.com".to_string();
+        let synced = compare("home", domain(), &[record("2001:DB8:0:0::0001")], ip);
+        assert_eq!(synced.status, DriftStatus::InSync);
+
+        let drifted = compare("home", domain(), &[record("2001:db8::2")], ip);
+        assert_eq!(drifted.status, DriftStatus::Drifted);
+        assert_eq!(drifted.live_contents, vec!["2001:db8::2"]);
+        assert_eq!(compare("home", domain(), &[], ip).status, DriftStatus::Missing);
+
+        // last_ip与本机IP一致时记录仍可能已漂移
+        let report = DriftReport::new(ip.to_string(), Some(ip.to_string()), vec![synced, drifted]);
+        assert!(report.last_ip_matches);
+        assert_eq!(report.drifted, 1);
+    }
+}
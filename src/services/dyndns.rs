@@ -0,0 +1,192 @@
+use crate::config::database::{AppConfig, IpSource};
+use crate::services::config_service::ConfigService;
+use crate::utils::network::{check_allowed_prefix, check_publishable};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr};
+use tracing::{info, warn};
+
+/// DynDNS2协议的返回码，每个主机名一行，路由器按第一个单词判断结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReturnCode {
+    Good(Ipv6Addr),
+    NoChange(Ipv6Addr),
+    BadAuth,
+    NotFqdn,
+    NoHost,
+    DnsError,
+    ServerError,
+}
+
+impl fmt::Display for ReturnCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReturnCode::Good(ip) => write!(f, "good {}", ip),
+            ReturnCode::NoChange(ip) => write!(f, "nochg {}", ip),
+            ReturnCode::BadAuth => f.write_str("badauth"),
+            ReturnCode::NotFqdn => f.write_str("notfqdn"),
+            ReturnCode::NoHost => f.write_str("nohost"),
+            ReturnCode::DnsError => f.write_str("dnserr"),
+            ReturnCode::ServerError => f.write_str("911"),
+        }
+    }
+}
+
+/// 接口的用户名和密码，来自环境变量 DYNDNS_USERNAME 和 DYNDNS_PASSWORD，任一未设置时接口不启用
+pub fn credentials() -> Option<(String, String)> {
+    let read = |name| std::env::var(name).ok().filter(|value: &String| !value.is_empty());
+    Some((read("DYNDNS_USERNAME")?, read("DYNDNS_PASSWORD")?))
+}
+
+/// 解析 Authorization: Basic 头中的用户名和密码
+pub fn parse_basic_auth(authorization: &str) -> Option<(String, String)> {
+    let (scheme, encoded) = authorization.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    Some((username.to_string(), password.to_string()))
+}
+
+/// 主机名对应的受管子域名，根域名本身为 "@"
+fn subdomain_of(hostname: &str, root_domain: &str, managed: &[String]) -> Result<String, ReturnCode> {
+    let hostname = hostname.trim().trim_end_matches('.').to_ascii_lowercase();
+    if !hostname.contains('.') {
+        return Err(ReturnCode::NotFqdn);
+    }
+    let root = root_domain.to_ascii_lowercase();
+    let subdomain = if hostname == root {
+        "@".to_string()
+    } else {
+        hostname.strip_suffix(&format!(".{}", root)).ok_or(ReturnCode::NoHost)?.to_string()
+    };
+    if managed.iter().any(|name| name.eq_ignore_ascii_case(&subdomain)) {
+        Ok(subdomain)
+    } else {
+        Err(ReturnCode::NoHost)
+    }
+}
+
+/// myip 可以是逗号分隔的IPv4和IPv6地址（如FritzBox的 <ipaddr>,<ip6addr>），取第一个IPv6地址
+pub fn pick_ipv6(myip: &str) -> Option<Ipv6Addr> {
+    myip.split(',').find_map(|ip| ip.trim().trim_start_matches('[').trim_end_matches(']').parse().ok())
+}
+
+/// 推送的地址：没有传 myip 时按DynDNS2的约定使用请求的来源地址，来源为IPv4（包括映射到IPv6的IPv4）时没有可用地址
+pub fn pushed_ipv6(myip: &str, peer: Option<IpAddr>) -> Option<Ipv6Addr> {
+    if !myip.trim().is_empty() {
+        return pick_ipv6(myip);
+    }
+    match peer? {
+        IpAddr::V6(ip) if ip.to_ipv4_mapped().is_none() => Some(ip),
+        _ => None,
+    }
+}
+
+/// 处理一次推送：校验主机名和地址，地址有变化时记录为推送地址并立即更新所有受管域名，返回每个主机名的返回码；
+/// peer 为请求的来源地址，没有传 myip 时使用
+pub async fn update(service: &ConfigService, hostnames: &str, myip: &str, peer: Option<IpAddr>) -> Vec<ReturnCode> {
+    if !service.has_configuration() {
+        warn!("⚠️ DynDNS推送被忽略: 尚未保存配置");
+        return vec![ReturnCode::ServerError];
+    }
+    let config = match service.load_configuration() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("⚠️ DynDNS推送被忽略: {}", e);
+            return vec![ReturnCode::ServerError];
+        }
+    };
+    if config.ip_source != IpSource::DynDns {
+        warn!("⚠️ DynDNS推送被忽略: IP来源不是 dyndns");
+        return vec![ReturnCode::ServerError];
+    }
+
+    let hosts: Vec<Result<String, ReturnCode>> = hostnames
+        .split(',')
+        .filter(|hostname| !hostname.trim().is_empty())
+        .map(|hostname| subdomain_of(hostname, &config.root_domain, &config.selected_subdomains))
+        .collect();
+    if hosts.is_empty() {
+        return vec![ReturnCode::NotFqdn];
+    }
+    if hosts.iter().all(Result::is_err) {
+        warn!("⚠️ DynDNS推送的主机名不是受管域名: {}", hostnames);
+        return hosts.into_iter().filter_map(Result::err).collect();
+    }
+
+    let result = match pushed_ipv6(myip, peer) {
+        Some(ip) => publish(service, &config, ip).await,
+        None if myip.trim().is_empty() => {
+            warn!("⚠️ DynDNS推送没有携带 myip，且请求来源不是IPv6地址: {:?}", peer);
+            ReturnCode::DnsError
+        }
+        None => {
+            warn!("⚠️ DynDNS推送中没有IPv6地址（只支持AAAA记录）: {}", myip);
+            ReturnCode::DnsError
+        }
+    };
+    hosts.into_iter().map(|host| host.err().unwrap_or_else(|| result.clone())).collect()
+}
+
+async fn publish(service: &ConfigService, config: &AppConfig, ip: Ipv6Addr) -> ReturnCode {
+    if let Err(e) = check_publishable(ip, true).and_then(|_| check_allowed_prefix(ip, config.allowed_prefix.as_deref())) {
+        warn!("⚠️ 拒绝DynDNS推送的地址: {}", e);
+        return ReturnCode::DnsError;
+    }
+    let ip_text = ip.to_string();
+    // 与上次推送相同，且按该地址应发布的地址（可能应用了固定后缀）已经发布过
+    if service.get_pushed_ip().ok().flatten().as_deref() == Some(ip_text.as_str()) {
+        let published = service.detect_ipv6(config).await.ok();
+        if published.is_some() && published == config.last_ip {
+            return ReturnCode::NoChange(ip);
+        }
+    }
+
+    info!("📥 路由器通过DynDNS推送了新地址: {}", ip);
+    if let Err(e) = service.update_pushed_ip(&ip_text) {
+        warn!("⚠️ 保存推送的地址失败: {}", e);
+        return ReturnCode::ServerError;
+    }
+    match service.update_now(false).await {
+        Ok(report) if report.summary.error_message.is_none() => ReturnCode::Good(ip),
+        Ok(report) => {
+            warn!("⚠️ DynDNS推送后更新失败: {}", report.summary.error_message.unwrap_or_default());
+            ReturnCode::DnsError
+        }
+        Err(e) => {
+            warn!("⚠️ DynDNS推送后更新失败: {}", e);
+            ReturnCode::DnsError
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dyndns_request_parsing() {
+        let header = format!("Basic {}", STANDARD.encode("router:s3cret:x"));
+        assert_eq!(parse_basic_auth(&header), Some(("router".to_string(), "s3cret:x".to_string())));
+        assert_eq!(parse_basic_auth("Bearer abc"), None);
+
+        let managed = vec!["@".to_string(), "nas".to_string()];
+        assert_eq!(subdomain_of("NAS.example.com.", "example.com", &managed), Ok("nas".to_string()));
+        assert_eq!(subdomain_of("example.com", "example.com", &managed), Ok("@".to_string()));
+        assert_eq!(subdomain_of("www.example.com", "example.com", &managed), Err(ReturnCode::NoHost));
+        assert_eq!(subdomain_of("nas.example.org", "example.com", &managed), Err(ReturnCode::NoHost));
+        assert_eq!(subdomain_of("nas", "example.com", &managed), Err(ReturnCode::NotFqdn));
+
+        let ip: Ipv6Addr = "2400:cb00::1".parse().unwrap();
+        assert_eq!(pick_ipv6("203.0.113.7,2400:cb00::1"), Some(ip));
+        assert_eq!(pick_ipv6("[2400:cb00::1]"), Some(ip));
+        assert_eq!(pick_ipv6("203.0.113.7"), None);
+        assert_eq!(pushed_ipv6("", Some(IpAddr::V6(ip))), Some(ip));
+        assert_eq!(pushed_ipv6("", Some("::ffff:203.0.113.7".parse().unwrap())), None);
+        assert_eq!(pushed_ipv6("203.0.113.7", Some(IpAddr::V6(ip))), None);
+        assert_eq!(ReturnCode::Good(ip).to_string(), "good 2400:cb00::1");
+    }
+}
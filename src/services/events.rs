@@ -0,0 +1,80 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// 事件通道的容量，订阅方处理不及时时丢弃最旧的事件
+const EVENT_CAPACITY: usize = 256;
+
+/// 域名事件的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainEventKind {
+    /// 新建或修改了记录
+    Sync,
+    /// 确认记录已是最新
+    Verification,
+    /// 需要注意但不影响同步的情况，如记录在程序之外被修改
+    Warning,
+    /// 处理失败
+    Error,
+}
+
+/// 与单个域名相关的活动事件
+#[derive(Debug, Clone, Serialize)]
+pub struct DomainEvent {
+    pub domain: String,
+    pub kind: DomainEventKind,
+    pub message: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 域名事件的广播通道，没有订阅方时事件直接丢弃
+#[derive(Clone)]
+pub struct DomainEvents {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl Default for DomainEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DomainEvents {
+    pub fn new() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_CAPACITY).0,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, domain: &str, kind: DomainEventKind, message: impl Into<String>) {
+        let _ = self.sender.send(DomainEvent {
+            domain: domain.to_string(),
+            kind,
+            message: message.into(),
+            timestamp: Utc::now(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let events = DomainEvents::new();
+        // 没有订阅方时发布不报错
+        events.publish("home.example.com", DomainEventKind::Sync, "ignored");
+
+        let mut receiver = events.subscribe();
+        events.publish("home.example.com", DomainEventKind::Error, "更新域名失败");
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.domain, "home.example.com");
+        assert_eq!(event.kind, DomainEventKind::Error);
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// 可注入的模拟故障类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FaultKind {
+    /// 模拟IP检测失败
+    Detection,
+    /// 模拟Cloudflare返回500
+    Cloudflare,
+}
+
+/// 单个检查周期内生效的模拟故障
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CycleFaults {
+    pub detection: bool,
+    pub cloudflare: bool,
+}
+
+/// 剩余的模拟故障周期数
+#[derive(Debug, Clone, Serialize)]
+pub struct FaultStatus {
+    pub detection_cycles: u32,
+    pub cloudflare_cycles: u32,
+}
+
+/// 模拟故障注入器，用于在真实故障发生前验证告警配置
+#[derive(Clone, Default)]
+pub struct FaultInjector {
+    detection_cycles: Arc<AtomicU32>,
+    cloudflare_cycles: Arc<AtomicU32>,
+}
+
+impl FaultInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为接下来的N个检查周期注入指定故障，N为0时清除
+    pub fn inject(&self, kind: FaultKind, cycles: u32) {
+        self.counter(kind).store(cycles, Ordering::SeqCst);
+    }
+
+    /// 清除所有模拟故障
+    pub fn clear(&self) {
+        self.detection_cycles.store(0, Ordering::SeqCst);
+        self.cloudflare_cycles.store(0, Ordering::SeqCst);
+    }
+
+    pub fn status(&self) -> FaultStatus {
+        FaultStatus {
+            detection_cycles: self.detection_cycles.load(Ordering::SeqCst),
+            cloudflare_cycles: self.cloudflare_cycles.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 开始一个检查周期，消耗一次剩余的故障次数
+    pub fn take_cycle(&self) -> CycleFaults {
+        CycleFaults {
+            detection: Self::consume(&self.detection_cycles),
+            cloudflare: Self::consume(&self.cloudflare_cycles),
+        }
+    }
+
+    fn counter(&self, kind: FaultKind) -> &AtomicU32 {
+        match kind {
+            FaultKind::Detection => &self.detection_cycles,
+            FaultKind::Cloudflare => &self.cloudflare_cycles,
+        }
+    }
+
+    fn consume(counter: &AtomicU32) -> bool {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
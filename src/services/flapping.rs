@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use anyhow::Result;
+use reqwest::Client;
+
+/// IP抖动状态：地址在同两个值之间反复切换，通常说明IP来源配置有误，而不是真实的地址变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FlapState {
+    pub addresses: [String; 2],
+    pub since: DateTime<Utc>,
+}
+
+/// 根据最近的地址变化（按时间倒序，每项为 旧地址 -> 新地址）判断是否在抖动，
+/// 最近 threshold 次变化都在同两个地址间来回切换时返回这两个地址
+pub fn detect(changes: &[(&str, &str)], threshold: u32) -> Option<[String; 2]> {
+    let threshold = threshold as usize;
+    // 更新失败后同一变化会重复记录，先合并连续相同的变化
+    let mut distinct: Vec<(&str, &str)> = Vec::new();
+    for change in changes {
+        if distinct.last() != Some(change) {
+            distinct.push(*change);
+        }
+        if distinct.len() > threshold {
+            break;
+        }
+    }
+    if threshold == 0 || distinct.len() < threshold {
+        return None;
+    }
+
+    let (from, to) = distinct[0];
+    let alternating = distinct[..threshold].iter().enumerate().all(|(i, change)| {
+        let expected = if i % 2 == 0 { (from, to) } else { (to, from) };
+        *change == expected
+    });
+    alternating.then(|| [from.to_string(), to.to_string()])
+}
+
+/// Webhook推送的抖动告警
+#[derive(Debug, Serialize)]
+struct FlapWebhookPayload<'a> {
+    event: &'static str,
+    root_domain: &'a str,
+    #[serde(flatten)]
+    state: &'a FlapState,
+    timestamp: DateTime<Utc>,
+}
+
+/// 向状态Webhook推送抖动开始（ip_flapping）或结束（ip_flapping_resolved）事件
+pub async fn send_flap_webhook(client: &Client, url: &str, root_domain: &str, event: &'static str, state: &FlapState) -> Result<()> {
+    client
+        .post(url)
+        .json(&FlapWebhookPayload {
+            event,
+            root_domain,
+            state,
+            timestamp: Utc::now(),
+        })
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_alternating_addresses() {
+        let (a, b, c) = ("2001:db8::a", "2001:db8::b", "2001:db8::c");
+        let flapping = [(b, a), (a, b), (a, b), (b, a), (a, b)];
+        assert_eq!(detect(&flapping, 4), Some([b.to_string(), a.to_string()]));
+        assert_eq!(detect(&flapping, 5), None);
+        assert_eq!(detect(&flapping, 0), None);
+
+        // 出现第三个地址说明是真实的变化
+        assert_eq!(detect(&[(c, a), (a, b), (b, a), (a, b)], 4), None);
+    }
+}
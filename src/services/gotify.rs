@@ -0,0 +1,73 @@
+use crate::config::database::GotifySettings;
+use crate::services::notify::{NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// 校验Gotify设置
+pub fn validate(settings: &GotifySettings) -> Result<()> {
+    let parsed = reqwest::Url::parse(settings.server.trim()).map_err(|_| anyhow!("Gotify服务器地址无效: {}", settings.server))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("Gotify服务器地址只支持 http 和 https: {}", settings.server));
+    }
+    if settings.token.trim().is_empty() {
+        return Err(anyhow!("Gotify推送需要设置应用令牌"));
+    }
+    if settings.priority.is_some_and(|p| p > 10) {
+        return Err(anyhow!("Gotify优先级需在 0-10 之间"));
+    }
+    Ok(())
+}
+
+/// 消息接口地址，服务器可以部署在子路径下
+fn message_url(settings: &GotifySettings) -> String {
+    format!("{}/message", settings.server.trim().trim_end_matches('/'))
+}
+
+fn payload(settings: &GotifySettings, event: &NotifyEvent) -> Value {
+    let default_priority = if matches!(event.kind, NotifyKind::UpdateFailed | NotifyKind::IpMismatch) { 8 } else { 5 };
+    let lines = event.lines();
+    json!({
+        "title": event.title(),
+        "message": if lines.is_empty() { event.title() } else { lines.join("\n") },
+        "priority": settings.priority.unwrap_or(default_priority),
+    })
+}
+
+/// 通过应用令牌发送消息
+pub async fn send(client: &Client, settings: &GotifySettings, event: &NotifyEvent) -> Result<()> {
+    client
+        .post(message_url(settings))
+        .header("X-Gotify-Key", settings.token.trim())
+        .json(&payload(settings, event))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_gotify_payload() {
+        let mut settings = GotifySettings {
+            server: "https://push.example.com/gotify/".to_string(),
+            token: "AbCdEf".to_string(),
+            priority: None,
+        };
+        assert!(validate(&settings).is_ok());
+        assert_eq!(message_url(&settings), "https://push.example.com/gotify/message");
+
+        let event = NotifyEvent::new(NotifyKind::Recovered, "example.com", Utc::now());
+        assert_eq!(
+            payload(&settings, &event),
+            json!({"title": "✅ example.com DNS更新已恢复", "message": "✅ example.com DNS更新已恢复", "priority": 5})
+        );
+
+        settings.priority = Some(11);
+        assert!(validate(&settings).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::services::cloudflare::DnsRecord;
+use serde::{Deserialize, Serialize};
+
+/// HTTPS/SVCB 记录的结构化数据（Cloudflare API中的 data 字段）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SvcbData {
+    /// 优先级，0为别名模式（不携带地址提示）
+    pub priority: u16,
+    /// 目标名称，"." 表示记录所在的域名本身
+    pub target: String,
+    /// SvcParams，如 `alpn="h2,h3" ipv6hint="2001:db8::1"`
+    #[serde(default)]
+    pub value: String,
+}
+
+impl SvcbData {
+    /// 新建HTTPS记录使用的数据：服务模式，目标为自身，只携带ipv6hint
+    pub fn for_ip(ip: &str) -> Self {
+        Self {
+            priority: 1,
+            target: ".".to_string(),
+            value: format!("ipv6hint=\"{}\"", ip),
+        }
+    }
+
+    /// 从Cloudflare返回的记录中解析结构化数据
+    pub fn from_record(record: &DnsRecord) -> Option<Self> {
+        record
+            .data
+            .clone()
+            .and_then(|data| serde_json::from_value(data).ok())
+    }
+
+    /// 别名模式的记录不携带地址提示
+    pub fn is_alias(&self) -> bool {
+        self.priority == 0
+    }
+
+    /// 记录内容的文本形式，与Cloudflare展示的content一致
+    pub fn content(&self) -> String {
+        format!("{} {} {}", self.priority, self.target, self.value)
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// 按空白拆分SvcParams，引号内的空白不拆分
+fn split_params(value: &str) -> Vec<&str> {
+    let mut params = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                start.get_or_insert(i);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if let Some(s) = start.take() {
+                    params.push(&value[s..i]);
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(s) = start {
+        params.push(&value[s..]);
+    }
+    params
+}
+
+/// 读取SvcParams中的ipv6hint（去掉引号）
+pub fn ipv6hint(value: &str) -> Option<&str> {
+    split_params(value)
+        .into_iter()
+        .find_map(|param| param.strip_prefix("ipv6hint="))
+        .map(|hint| hint.trim_matches('"'))
+}
+
+/// 将SvcParams中的ipv6hint设置为指定地址，保留其他参数及顺序，没有ipv6hint时追加到末尾
+pub fn set_ipv6hint(value: &str, ip: &str) -> String {
+    let hint = format!("ipv6hint=\"{}\"", ip);
+    let mut replaced = false;
+    let mut params: Vec<String> = split_params(value)
+        .into_iter()
+        .map(|param| {
+            if param.starts_with("ipv6hint=") {
+                replaced = true;
+                hint.clone()
+            } else {
+                param.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        params.push(hint);
+    }
+    params.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_ipv6hint_keeps_other_params() {
+        let value = r#"alpn="h2,h3" ipv6hint="2001:db8::1" ech="abc def""#;
+        assert_eq!(ipv6hint(value), Some("2001:db8::1"));
+        assert_eq!(
+            set_ipv6hint(value, "2001:db8::2"),
+            r#"alpn="h2,h3" ipv6hint="2001:db8::2" ech="abc def""#
+        );
+        assert_eq!(set_ipv6hint(r#"alpn="h2""#, "2001:db8::2"), r#"alpn="h2" ipv6hint="2001:db8::2""#);
+        assert_eq!(set_ipv6hint("", "2001:db8::2"), r#"ipv6hint="2001:db8::2""#);
+        assert_eq!(SvcbData::for_ip("2001:db8::2").content(), r#"1 . ipv6hint="2001:db8::2""#);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::config::database::ChangeDetection;
+use crate::services::propagation::{self, DOH_RESOLVERS};
+use anyhow::{Result, anyhow};
+use ipnetwork::Ipv6Network;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
+
+/// Cloudflare代理使用的IPv6网段，代理的记录解析到这些地址，无法与本机IP比对
+const CLOUDFLARE_RANGES: [&str; 7] = [
+    "2400:cb00::/32",
+    "2606:4700::/32",
+    "2803:f800::/32",
+    "2405:b500::/32",
+    "2405:8100::/32",
+    "2a06:98c0::/29",
+    "2c0f:f248::/32",
+];
+
+/// 域名当前对外解析到的AAAA地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiveAnswer {
+    /// 解析结果包含本机IP
+    Current,
+    /// 解析结果不含本机IP（包括没有AAAA记录）
+    Stale(Vec<Ipv6Addr>),
+    /// 解析到Cloudflare代理的地址，无法比对
+    Proxied,
+}
+
+/// 按解析结果判断域名是否已指向本机IP
+pub fn classify(addresses: Vec<Ipv6Addr>, current_ip: Ipv6Addr) -> LiveAnswer {
+    if addresses.contains(&current_ip) {
+        LiveAnswer::Current
+    } else if !addresses.is_empty() && addresses.iter().all(is_cloudflare) {
+        LiveAnswer::Proxied
+    } else {
+        LiveAnswer::Stale(addresses)
+    }
+}
+
+fn is_cloudflare(ip: &Ipv6Addr) -> bool {
+    CLOUDFLARE_RANGES
+        .iter()
+        .filter_map(|range| range.parse::<Ipv6Network>().ok())
+        .any(|network| network.contains(*ip))
+}
+
+/// 按配置的方式解析域名的AAAA地址，last_ip 模式不解析
+pub async fn resolve(domain: &str, mode: ChangeDetection) -> Result<Vec<Ipv6Addr>> {
+    match mode {
+        ChangeDetection::LastIp => Err(anyhow!("当前的变化检测方式不解析域名")),
+        ChangeDetection::Resolver => {
+            let addresses = tokio::net::lookup_host((domain, 0)).await?;
+            Ok(addresses
+                .filter_map(|addr| match addr.ip() {
+                    IpAddr::V6(ip) => Some(ip),
+                    IpAddr::V4(_) => None,
+                })
+                .collect())
+        }
+        ChangeDetection::Doh => {
+            let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+            let mut last_error = anyhow!("没有可用的DoH解析器");
+            for (_, url) in DOH_RESOLVERS {
+                match propagation::lookup(&client, url, domain).await {
+                    Ok(addresses) => return Ok(addresses),
+                    Err(e) => last_error = e,
+                }
+            }
+            Err(last_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_live_answers() {
+        let current: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let old: Ipv6Addr = "2001:db8::2".parse().unwrap();
+        let proxy: Ipv6Addr = "2606:4700:3030::6815:1001".parse().unwrap();
+
+        assert_eq!(classify(vec![old, current], current), LiveAnswer::Current);
+        assert_eq!(classify(vec![old], current), LiveAnswer::Stale(vec![old]));
+        assert_eq!(classify(vec![], current), LiveAnswer::Stale(vec![]));
+        assert_eq!(classify(vec![proxy], current), LiveAnswer::Proxied);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::config::database::{DomainState, UpdateTotals};
+use crate::config::write_queue::WriteStatsSnapshot;
+use chrono::{DateTime, Utc};
+use std::fmt::{Display, Write};
+
+/// 指标名称前缀
+const PREFIX: &str = "cloudflare_auto";
+
+/// 指标的输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Prometheus文本格式 0.0.4
+    Prometheus,
+    /// OpenMetrics 1.0，额外输出链接到更新记录的示例（exemplar）
+    OpenMetrics,
+}
+
+impl Format {
+    /// 按抓取请求的Accept头选择格式，声明支持OpenMetrics时才使用
+    pub fn from_accept(accept: Option<&str>) -> Self {
+        match accept {
+            Some(accept) if accept.contains("application/openmetrics-text") => Format::OpenMetrics,
+            _ => Format::Prometheus,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Format::Prometheus => "text/plain; version=0.0.4; charset=utf-8",
+            Format::OpenMetrics => "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        }
+    }
+}
+
+/// 生成指标所需的数据
+pub struct Snapshot<'a> {
+    pub instance: &'a str,         // 实例ID，多个实例写入同一Prometheus时区分来源
+    pub zone: &'a str,             // 根域名，尚未配置时为空
+    pub totals: &'a UpdateTotals,
+    pub writes: &'a WriteStatsSnapshot,
+    pub domains: &'a [DomainState], // 选中域名的同步状态
+}
+
+/// 输出指标，所有指标都带 instance 和 zone 标签，域名相关的指标另带 domain 标签
+pub fn render(snapshot: &Snapshot, format: Format) -> String {
+    let mut out = Output {
+        text: String::new(),
+        labels: format!("instance=\"{}\",zone=\"{}\"", escape(snapshot.instance), escape(snapshot.zone)),
+        format,
+    };
+    let totals = snapshot.totals;
+    let writes = snapshot.writes;
+
+    out.family("update_runs_total", "counter", "已记录的更新次数");
+    out.sample("update_runs_total", &[], totals.runs, None);
+
+    out.family("domain_actions_total", "counter", "按处理结果统计的域名次数");
+    for (action, count) in [
+        ("created", totals.created),
+        ("updated", totals.updated),
+        ("unchanged", totals.unchanged),
+    ] {
+        out.sample("domain_actions_total", &[("action", action)], count, None);
+    }
+    // 失败计数链接到最近一条有失败的更新记录，Grafana可据此跳转到Web界面的更新记录
+    out.sample("domain_actions_total", &[("action", "failed")], totals.failed, totals.last_failure);
+
+    if let Some(last_run) = totals.last_run {
+        out.family("last_update_timestamp_seconds", "gauge", "最近一次更新的时间");
+        out.sample("last_update_timestamp_seconds", &[], last_run.timestamp(), None);
+    }
+
+    if !snapshot.domains.is_empty() {
+        out.family("domain_last_success_timestamp_seconds", "gauge", "各域名最后一次成功同步的时间，从未成功时为0");
+        for state in snapshot.domains {
+            let time = state.last_success_at.map(|t| t.timestamp()).unwrap_or(0);
+            out.sample("domain_last_success_timestamp_seconds", &[("domain", &state.domain)], time, None);
+        }
+
+        out.family("domain_failing", "gauge", "各域名最近一次处理是否失败");
+        for state in snapshot.domains {
+            let failing = match (state.last_error_at, state.last_success_at) {
+                (Some(error), Some(success)) => error > success,
+                (Some(_), None) => true,
+                (None, _) => false,
+            };
+            out.sample("domain_failing", &[("domain", &state.domain)], failing as u8, None);
+        }
+    }
+
+    out.family("db_write_retries_total", "counter", "数据库繁忙时重试写入的次数");
+    out.sample("db_write_retries_total", &[], writes.retried, None);
+
+    out.family("db_writes_queued", "gauge", "暂存在内存中等待补写的数据库写入数");
+    out.sample("db_writes_queued", &[], writes.queued, None);
+
+    out.family("db_writes_dropped_total", "counter", "暂存队列已满而丢弃的数据库写入数");
+    out.sample("db_writes_dropped_total", &[], writes.dropped, None);
+
+    if format == Format::OpenMetrics {
+        out.text.push_str("# EOF\n");
+    }
+    out.text
+}
+
+struct Output {
+    text: String,
+    labels: String, // 公共标签
+    format: Format,
+}
+
+impl Output {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        // OpenMetrics中计数器的指标族名称不含 _total 后缀
+        let family = match self.format {
+            Format::OpenMetrics if kind == "counter" => name.trim_end_matches("_total"),
+            _ => name,
+        };
+        let _ = writeln!(self.text, "# HELP {}_{} {}", PREFIX, family, help);
+        let _ = writeln!(self.text, "# TYPE {}_{} {}", PREFIX, family, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display, exemplar: Option<(i64, DateTime<Utc>)>) {
+        let _ = write!(self.text, "{}_{}{{{}", PREFIX, name, self.labels);
+        for (key, value) in labels {
+            let _ = write!(self.text, ",{}=\"{}\"", key, escape(value));
+        }
+        let _ = write!(self.text, "}} {}", value);
+        if let (Format::OpenMetrics, Some((history_id, time))) = (self.format, exemplar) {
+            let _ = write!(self.text, " # {{history_id=\"{}\"}} 1 {}", history_id, time.timestamp());
+        }
+        self.text.push('\n');
+    }
+}
+
+/// 转义标签值中的反斜杠、引号和换行
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_action_counters() {
+        let totals = UpdateTotals { runs: 3, created: 1, updated: 2, unchanged: 4, failed: 1, last_run: None, last_failure: None };
+        let writes = WriteStatsSnapshot { retried: 2, queued: 1, dropped: 0 };
+        let snapshot = Snapshot { instance: "a1b2", zone: "example.com", totals: &totals, writes: &writes, domains: &[] };
+        let text = render(&snapshot, Format::Prometheus);
+        assert!(text.contains("cloudflare_auto_update_runs_total{instance=\"a1b2\",zone=\"example.com\"} 3\n"));
+        assert!(text.contains("cloudflare_auto_domain_actions_total{instance=\"a1b2\",zone=\"example.com\",action=\"created\"} 1\n"));
+        assert!(text.contains("cloudflare_auto_domain_actions_total{instance=\"a1b2\",zone=\"example.com\",action=\"unchanged\"} 4\n"));
+        assert!(!text.contains("last_update_timestamp_seconds"));
+        assert!(text.contains("cloudflare_auto_db_writes_queued{instance=\"a1b2\",zone=\"example.com\"} 1\n"));
+        assert!(text.contains("cloudflare_auto_db_writes_dropped_total{instance=\"a1b2\",zone=\"example.com\"} 0\n"));
+    }
+
+    #[test]
+    fn test_openmetrics_exemplar_and_domain_labels() {
+        let failed_at = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let totals = UpdateTotals { failed: 2, last_failure: Some((42, failed_at)), ..Default::default() };
+        let writes = WriteStatsSnapshot::default();
+        let domain = DomainState {
+            domain: "home.example.com".to_string(),
+            record_id: None,
+            content: None,
+            cf_created_on: None,
+            cf_modified_on: None,
+            updated_at: failed_at,
+            warning: None,
+            last_success_at: None,
+            last_error: Some("timeout".to_string()),
+            last_error_at: Some(failed_at),
+            cf_modified_on_display: None,
+            updated_at_display: None,
+        };
+        let snapshot = Snapshot { instance: "a1b2", zone: "example.com", totals: &totals, writes: &writes, domains: &[domain] };
+
+        let text = render(&snapshot, Format::OpenMetrics);
+        assert!(text.contains("# TYPE cloudflare_auto_domain_actions counter\n"));
+        assert!(text.contains("action=\"failed\"} 2 # {history_id=\"42\"} 1 1704067200\n"));
+        assert!(text.contains("cloudflare_auto_domain_failing{instance=\"a1b2\",zone=\"example.com\",domain=\"home.example.com\"} 1\n"));
+        assert!(text.ends_with("# EOF\n"));
+
+        // Prometheus文本格式不支持示例
+        let text = render(&snapshot, Format::Prometheus);
+        assert!(!text.contains("history_id"));
+        assert!(text.contains("# TYPE cloudflare_auto_domain_actions_total counter\n"));
+    }
+}
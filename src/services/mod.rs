@@ -1,3 +1,41 @@
+pub mod acme;
+pub mod api_tokens;
+pub mod backup;
+pub mod backup_hostname;
+pub mod bark;
+pub mod chatops;
 pub mod cloudflare;
+pub mod cloudflare_error;
+pub mod dingtalk;
+pub mod discord;
 pub mod config_service;
-pub mod monitor_service;
\ No newline at end of file
+pub mod domain_settings;
+pub mod drift;
+pub mod dyndns;
+pub mod events;
+pub mod fault_injection;
+pub mod flapping;
+pub mod gotify;
+pub mod https_record;
+pub mod live_dns;
+pub mod metrics;
+pub mod monitor_service;
+pub mod mqtt;
+pub mod network_watcher;
+pub mod notify;
+pub mod notify_webhook;
+pub mod ntfy;
+pub mod operations;
+pub mod ownership;
+pub mod propagation;
+pub mod pushplus;
+pub mod schedule;
+pub mod scheduler;
+pub mod serverchan;
+pub mod slack;
+pub mod status;
+pub mod telegram;
+#[cfg(test)]
+pub mod test_support;
+pub mod uptime_kuma;
+pub mod wecom;
\ No newline at end of file
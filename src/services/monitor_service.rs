@@ -1,190 +1,528 @@
-use tokio_cron_scheduler::{JobScheduler, Job};
 use crate::{
     services::{
         config_service::ConfigService,
-        cloudflare::{CloudflareClient, CloudflareConfig},
+        cloudflare::CloudflareConfig,
+        schedule::BurstSettings,
+        scheduler::{CronScheduler, RepeatedTask, Scheduler},
+        status::ServiceStatus,
     },
-    utils::network::get_preferred_ipv6,
-    config::database::Database,
 };
 use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, Mutex};
 use tracing::{info, error, warn, debug};
 
 pub struct MonitorService {
     config_service: ConfigService,
-    scheduler: JobScheduler,
+    jobs: Arc<Mutex<ScheduledJobs>>,
+    listening: bool, // 调度器已启动，且已在监听配置保存
+}
+
+/// 调度器及当前定时任务使用的检查参数
+struct ScheduledJobs {
+    scheduler: Box<dyn Scheduler>,
+    active: Option<BurstSettings>, // 未创建定时任务时为None
+    stopped: bool,                 // 已通过stop停止，保存配置时不重建定时任务
+}
+
+/// 监控服务的运行状态
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorStatus {
+    pub running: bool,               // 定时任务是否在运行
+    pub check_interval: Option<u64>, // 当前定时任务的检查间隔（秒），未运行时为None
+    pub burst_interval: Option<u64>, // 启用突发检查时的突发间隔（秒）
+    pub paused: bool,                // 是否处于持久化的暂停状态
+}
+
+/// 发送给监控服务的命令，每个命令通过oneshot返回结果
+enum MonitorCommand {
+    Start(oneshot::Sender<Result<()>>),
+    Stop(oneshot::Sender<Result<()>>),
+    Trigger(oneshot::Sender<Result<bool>>),
+    Status(oneshot::Sender<MonitorStatus>),
+}
+
+/// 监控服务的控制句柄，可在API处理器之间共享
+#[derive(Clone)]
+pub struct MonitorHandle {
+    commands: mpsc::Sender<MonitorCommand>,
+}
+
+impl MonitorHandle {
+    /// 启动（或重新启动）定时检查
+    pub async fn start(&self) -> Result<()> {
+        self.request(MonitorCommand::Start).await?
+    }
+
+    /// 停止定时检查，只影响本次运行，重启程序后恢复
+    pub async fn stop(&self) -> Result<()> {
+        self.request(MonitorCommand::Stop).await?
+    }
+
+    /// 立即执行一次与定时任务相同的检查，返回是否更新了记录
+    pub async fn trigger(&self) -> Result<bool> {
+        self.request(MonitorCommand::Trigger).await?
+    }
+
+    pub async fn status(&self) -> Result<MonitorStatus> {
+        self.request(MonitorCommand::Status).await
+    }
+
+    async fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> MonitorCommand) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(command(reply))
+            .await
+            .map_err(|_| anyhow!("监控服务已退出"))?;
+        response.await.map_err(|_| anyhow!("监控服务已退出"))
+    }
 }
 
 impl MonitorService {
     pub async fn new(config_service: ConfigService) -> Result<Self> {
-        let scheduler = JobScheduler::new().await?;
-        Ok(Self {
+        let scheduler = CronScheduler::new().await?;
+        Ok(Self::with_scheduler(config_service, scheduler))
+    }
+
+    /// 使用指定的调度器创建监控服务，测试中可传入手动触发的调度器
+    pub fn with_scheduler(config_service: ConfigService, scheduler: impl Scheduler + 'static) -> Self {
+        Self {
             config_service,
-            scheduler,
-        })
+            jobs: Arc::new(Mutex::new(ScheduledJobs {
+                scheduler: Box::new(scheduler),
+                active: None,
+                stopped: false,
+            })),
+            listening: false,
+        }
+    }
+
+    /// 将监控服务移入后台任务，返回用于控制它的句柄
+    pub fn spawn(mut self) -> MonitorHandle {
+        let (commands, mut receiver) = mpsc::channel(16);
+        tokio::spawn(async move {
+            while let Some(command) = receiver.recv().await {
+                match command {
+                    MonitorCommand::Start(reply) => {
+                        let _ = reply.send(self.start().await);
+                    }
+                    MonitorCommand::Stop(reply) => {
+                        let _ = reply.send(self.stop().await);
+                    }
+                    MonitorCommand::Trigger(reply) => {
+                        // 检查耗时较长，在单独的任务中执行，不阻塞其他命令
+                        let config_service = self.config_service.clone();
+                        tokio::spawn(async move {
+                            let _ = reply.send(Self::check_and_update(&config_service).await);
+                        });
+                    }
+                    MonitorCommand::Status(reply) => {
+                        let _ = reply.send(self.status().await);
+                    }
+                }
+            }
+        });
+        MonitorHandle { commands }
     }
 
-    /// 启动监控服务
+    /// 启动监控服务，之后每次保存配置都会按新的检查间隔重建定时任务
     pub async fn start(&mut self) -> Result<()> {
-        if !self.config_service.has_configuration() {
-            warn!("⚠️ 没有找到配置，监控服务未启动");
+        self.jobs.lock().await.stopped = false;
+        Self::apply_schedule(&self.config_service, &self.jobs).await?;
+        if self.listening {
             return Ok(());
         }
+        self.jobs.lock().await.scheduler.start().await?;
+        self.listening = true;
+        
+        let mut changes = self.config_service.subscribe_config_changes();
+        let config_service = self.config_service.clone();
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            while changes.changed().await.is_ok() {
+                if let Err(e) = Self::apply_schedule(&config_service, &jobs).await {
+                    error!("❌ 重新加载定时任务失败: {}", e);
+                }
+            }
+        });
+        
+        Ok(())
+    }
 
-        let config = self.config_service.load_configuration()?;
-        let config_service_clone = self.config_service.clone();
+    /// 按当前配置创建定时任务，检查参数未变化时保留现有任务
+    async fn apply_schedule(config_service: &ConfigService, jobs: &Mutex<ScheduledJobs>) -> Result<()> {
+        if !config_service.has_configuration() {
+            warn!("⚠️ 没有找到配置，保存配置后将自动启动监控任务");
+            return Ok(());
+        }
+
+        let config = config_service.load_configuration()?;
+        let burst = BurstSettings::from_config(&config);
+        let mut jobs = jobs.lock().await;
+        if jobs.stopped || jobs.active == Some(burst) {
+            return Ok(());
+        }
+        if jobs.active.is_some() {
+            info!("🔁 检查参数已变化，重新创建监控任务");
+            jobs.scheduler.clear().await?;
+        }
         
         info!("🔍 配置监控任务，检查间隔: {}秒", config.check_interval);
+        if burst.enabled() {
+            info!(
+                "⚡ 已启用突发检查: IP变化后 {} 分钟内每 {} 秒检查一次",
+                config.burst_duration, config.burst_interval
+            );
+        }
         info!("📋 监控域名数量: {}", config.selected_subdomains.len());
         
-        // 创建定时任务
-        let job = Job::new_repeated_async(
-            Duration::from_secs(config.check_interval), 
-            move |_uuid, _l| {
-                let config_service = config_service_clone.clone();
-                Box::pin(async move {
-                    debug!("🔄 开始执行监控任务");
-                    if let Err(e) = Self::check_and_update(&config_service).await {
-                        error!("❌ 监控任务执行失败: {}", e);
-                    } else {
-                        debug!("✅ 监控任务执行完成");
-                    }
-                })
-            }
-        )?;
+        // 创建定时任务，启用突发检查时按突发间隔触发，由调度状态决定是否真正检查
+        let config_service = config_service.clone();
+        let task: RepeatedTask = Arc::new(move || {
+            let config_service = config_service.clone();
+            Box::pin(async move {
+                if !config_service.take_scheduled_check(&burst) {
+                    return;
+                }
+                debug!("🔄 开始执行监控任务");
+                if let Err(e) = Self::check_and_update(&config_service).await {
+                    error!("❌ 监控任务执行失败: {}", e);
+                } else {
+                    debug!("✅ 监控任务执行完成");
+                }
+            })
+        });
 
-        self.scheduler.add(job).await?;
-        self.scheduler.start().await?;
+        jobs.scheduler.add_repeated(burst.tick(), task).await?;
+        jobs.active = Some(burst);
         
-        info!("✅ 监控服务已启动，检查间隔: {}秒", config.check_interval);
+        info!("✅ 监控任务已启动，检查间隔: {}秒", config.check_interval);
         
         Ok(())
     }
 
-    /// 停止监控服务
+    /// 停止监控服务，移除定时任务，可再次调用start恢复
     pub async fn stop(&mut self) -> Result<()> {
-        self.scheduler.shutdown().await?;
+        let mut jobs = self.jobs.lock().await;
+        jobs.scheduler.clear().await?;
+        jobs.active = None;
+        jobs.stopped = true;
         info!("🛑 监控服务已停止");
         Ok(())
     }
 
+    /// 当前运行状态
+    pub async fn status(&self) -> MonitorStatus {
+        let active = self.jobs.lock().await.active;
+        MonitorStatus {
+            running: active.is_some(),
+            check_interval: active.map(|burst| burst.base.as_secs()),
+            burst_interval: active.filter(BurstSettings::enabled).map(|burst| burst.burst.as_secs()),
+            paused: self.config_service.pause_state().is_some(),
+        }
+    }
+
+    /// 按配置执行程序启动时的首次检查
+    /// 可以关闭首次检查，或延迟一段时间后在后台执行（适用于网络启动较慢的环境）
+    pub async fn run_startup_check(config_service: &ConfigService) {
+        let config = match config_service.load_configuration() {
+            Ok(config) => config,
+            Err(_) => return,
+        };
+        
+        if !config.startup_check {
+            info!("⏭️ 已关闭启动时首次检查，等待定时任务执行");
+            return;
+        }
+        
+        if config.startup_delay > 0 {
+            info!("⏳ 首次IP检查将在 {} 秒后执行", config.startup_delay);
+            let config_service = config_service.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(config.startup_delay)).await;
+                Self::startup_check(&config_service).await;
+            });
+        } else {
+            Self::startup_check(config_service).await;
+        }
+    }
+
+    async fn startup_check(config_service: &ConfigService) {
+        info!("🔍 程序启动，执行首次IP检查...");
+        if let Err(e) = Self::check_and_update(config_service).await {
+            warn!("⚠️ 首次IP检查失败: {}", e);
+        } else {
+            info!("✅ 首次IP检查完成");
+        }
+    }
+
     /// 立即执行一次检查更新
     pub async fn check_and_update_now(&self) -> Result<bool> {
         Self::check_and_update(&self.config_service).await
     }
 
-    /// 检查IP变化并更新
+    /// 检查IP变化并更新，暂停期间直接跳过，其余检查都记录结果
     async fn check_and_update(config_service: &ConfigService) -> Result<bool> {
+        if config_service.pause_state().is_some() {
+            debug!("⏸️ 定时检查已暂停，跳过本次检查");
+            return Ok(false);
+        }
+        
+        let result = Self::update_if_changed(config_service).await;
+        config_service.note_check_result(&result);
+        Self::reconcile_if_due(config_service).await;
+        config_service.send_daily_summary_if_due();
+        // 本周期没有写入时也尝试补写之前因数据库繁忙暂存的写入
+        if let Err(e) = config_service.database().flush_pending_writes() {
+            warn!("⚠️ 补写暂存的数据库写入失败: {}", e);
+        }
+        result
+    }
+
+    /// 到了对账时间时读取所有记录并修复漂移，对账失败不影响本次检查的结果
+    async fn reconcile_if_due(config_service: &ConfigService) {
+        let Ok(config) = config_service.load_configuration() else { return };
+        if !config_service.take_reconcile_due(&config) {
+            return;
+        }
+        if let Err(e) = config_service.reconcile().await {
+            error!("❌ 对账失败: {}", e);
+        }
+    }
+
+    /// 检查IP变化并更新
+    async fn update_if_changed(config_service: &ConfigService) -> Result<bool> {
+        // 整个周期持有锁，期间保存的配置在本周期结束后才生效
+        let _cycle = config_service.lock_cycle().await;
         let config = config_service.load_configuration()?;
+        let faults = config_service.fault_injector().take_cycle();
         
         // 获取当前IP
-        let current_ip = match get_preferred_ipv6() {
-            Ok(ip) => ip.to_string(),
+        let current_ip = match config_service.detect_ipv6_for_cycle(&config, faults).await {
+            Ok(ip) => ip,
             Err(e) => {
                 error!("❌ 获取当前IP失败: {}", e);
+                // 持续无法获取IP时视为离线，延长检查间隔
+                let status = config_service.note_unreachable(&config);
+                config_service.report_status(&config, status, Some(format!("获取当前IP失败: {}", e)));
                 return Ok(false);
             }
         };
         
         debug!("🌐 当前检测到的IPv6地址: {}", current_ip);
+        config_service.verify_external_ip(&config, &current_ip).await;
         
-        // 检查IP是否变化
-        let last_ip = config_service.get_last_ip()?;
-        if let Some(ref last_ip) = last_ip {
-            if *last_ip == current_ip {
-                debug!("✅ IP地址未变化: {}", current_ip);
-                return Ok(false);
+        // 检查IP是否变化以及各域名最后写入的地址，按配置还会比对各域名的实际解析结果
+        let last_ip = config.last_ip.clone();
+        let behind = config_service.domains_behind(&config, &current_ip);
+        // 发布所有地址时，其他地址的增减也需要更新
+        let additional = config_service.additional_addresses(&config, &current_ip)?;
+        let published = config_service.published_additional_ips();
+        let unchanged = last_ip.as_deref() == Some(current_ip.as_str()) && behind.is_empty() && additional == published;
+        let stale = match unchanged {
+            true => config_service.stale_live_domains(&config, &current_ip).await,
+            false => Vec::new(),
+        };
+        if unchanged && stale.is_empty() {
+            debug!("✅ IP地址未变化: {}", current_ip);
+            config_service.note_reachable();
+            // 上次发布备用主机名失败时单独重试
+            if config_service.backup_hostname_pending(&config, &current_ip) {
+                config_service.publish_backup_hostname(&config, &current_ip, false).await;
             }
+            config_service.check_flapping(&config);
+            // 部分域名失败的降级状态需等到下次实际更新才能确认恢复
+            let status = match config_service.current_status() {
+                ServiceStatus::Degraded => ServiceStatus::Degraded,
+                _ => ServiceStatus::InSync,
+            };
+            config_service.report_status(&config, status, None);
+            return Ok(false);
         }
         
-        info!("🔄 检测到IP地址变化: {} -> {}", last_ip.as_ref().unwrap_or(&"无".to_string()), current_ip);
+        // 演练模式不写入最后记录的IP，同一IP只演练一次
+        if config.is_dry_run() && config_service.dry_run_previewed(&current_ip) {
+            debug!("🧪 演练模式：IP {} 已演练过，跳过本次检查", current_ip);
+            return Ok(false);
+        }
         
-        // 创建Cloudflare客户端
-        let cf_config = CloudflareConfig {
-            api_key: config.cloudflare_api_key,
-            zone_id: config.cloudflare_zone_id,
-            root_domain: config.root_domain.clone(),
-        };
+        if !stale.is_empty() {
+            info!("🔎 域名解析结果与当前IP不一致，开始更新: {}", stale.join(", "));
+        } else if last_ip.as_deref() == Some(current_ip.as_str()) && !behind.is_empty() {
+            info!("➕ 部分域名尚未指向当前IP，开始更新: {}", behind.join(", "));
+        } else if last_ip.as_deref() == Some(current_ip.as_str()) {
+            info!("🔢 其他地址变化，开始更新: [{}] -> [{}]", published.join(", "), additional.join(", "));
+        } else {
+            info!("🔄 检测到IP地址变化: {} -> {}", last_ip.as_ref().unwrap_or(&"无".to_string()), current_ip);
+            config_service.note_ip_change(&config, last_ip.as_deref(), &current_ip);
+        }
         
-        let client = CloudflareClient::new(cf_config);
+        // 创建Cloudflare客户端
+        let cf_config = CloudflareConfig::from_app_config(&config);
+        let client = config_service.cloudflare_client_for_cycle(cf_config, faults);
         
         // 更新选中的子域名
-        let mut success_count = 0;
-        let mut total_count = 0;
-        let mut error_message = None;
-        
-        info!("📝 开始更新 {} 个域名记录", config.selected_subdomains.len());
-        
-        for subdomain in &config.selected_subdomains {
-            total_count += 1;
-            
-            let full_domain = if subdomain.is_empty() {
-                config.root_domain.clone()
-            } else {
-                format!("{}.{}", subdomain, config.root_domain)
-            };
-            
-            debug!("🔍 处理域名: {}", full_domain);
-            
-            match client.get_aaaa_records(&full_domain).await {
-                Ok(records) => {
-                    if let Some(record) = records.first() {
-                        // 检查IP是否真的发生了变化
-                        if record.content == current_ip {
-                            debug!("✅ IP地址未变化，跳过更新: {} -> {}", full_domain, current_ip);
-                            success_count += 1; // 这种情况也算成功
-                            continue;
-                        }
-                        
-                        // 更新现有记录
-                        debug!("📝 更新现有DNS记录: {} -> {}", full_domain, current_ip);
-                        if let Ok(true) = client.update_dns_record(&record.id, current_ip.parse()?).await {
-                            success_count += 1;
-                            info!("✅ 成功更新域名: {} -> {}", full_domain, current_ip);
-                        } else {
-                            error!("❌ 更新域名失败: {}", full_domain);
-                            error_message = Some(format!("更新域名失败: {}", full_domain));
-                        }
-                    } else {
-                        // 创建新记录
-                        debug!("➕ 创建新DNS记录: {} -> {}", full_domain, current_ip);
-                        if let Ok(true) = client.create_aaaa_record(subdomain, current_ip.parse()?).await {
-                            success_count += 1;
-                            info!("✅ 成功创建域名: {} -> {}", full_domain, current_ip);
-                        } else {
-                            error!("❌ 创建域名失败: {}", full_domain);
-                            error_message = Some(format!("创建域名失败: {}", full_domain));
-                        }
-                    }
-                }
-                Err(e) => {
-                    error!("❌ 获取域名记录失败 {}: {}", full_domain, e);
-                    error_message = Some(format!("获取域名记录失败 {}: {}", full_domain, e));
-                }
-            }
-        }
+        let mut summary = config_service.update_selected_domains(&config, &client, &current_ip, false).await?;
+        // 主区域的接口故障时备用主机名仍会发布，客户端可改用备用名称访问
+        summary.backup_hostname = config_service.publish_backup_hostname(&config, &current_ip, summary.dry_run).await;
         
         // 记录DNS更新记录
-        let db = Database::new()?;
-        if let Err(e) = db.add_dns_update_record(
-            last_ip.clone(),
-            &current_ip,
-            total_count as i32,
-            success_count as i32,
-            error_message.clone(),
-        ) {
-            error!("❌ 记录DNS更新记录失败: {}", e);
-        }
-        
-        // 更新最后记录的IP
-        if success_count > 0 {
-            config_service.update_last_ip(&current_ip)?;
-            info!("🎉 IP更新完成: 成功 {}/{} 个域名", success_count, total_count);
+        config_service.record_update(&config, last_ip.clone(), &current_ip, &summary);
+        config_service.check_flapping(&config);
+        if summary.success_count == 0 && summary.unreachable {
+            let status = config_service.note_unreachable(&config);
+            config_service.report_status(&config, status, summary.error_message.clone());
+        } else {
+            config_service.note_reachable();
+            config_service.report_summary_status(&config, &summary);
+        }
+        
+        if summary.dry_run {
+            config_service.mark_dry_run(&current_ip);
+        }
+        
+        // 更新最后记录的IP，演练模式下保持不变
+        if summary.success_count > 0 {
+            if !summary.dry_run {
+                config_service.update_last_ip(&current_ip)?;
+                // 有记录处理失败时不保存，下个周期重试
+                if summary.error_message.is_none() {
+                    config_service.update_additional_ips(&additional)?;
+                }
+            }
+            info!(
+                "🎉 IP更新完成: 成功 {}/{} 个域名（新建 {}，更新 {}，未变化 {}）",
+                summary.success_count,
+                summary.total_count,
+                summary.actions.created,
+                summary.actions.updated,
+                summary.actions.unchanged
+            );
             Ok(true)
         } else {
             error!("❌ 所有域名更新都失败了");
             Err(anyhow!("所有域名更新都失败了"))
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::database::{AppConfig, Database};
+    use crate::services::scheduler::ManualScheduler;
+    use crate::utils::clock::{Clock, ManualClock};
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_scheduled_checks_follow_manual_clock() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = ManualClock::new(start);
+        let service = ConfigService::with_clock(Database::new(":memory:").unwrap(), Arc::new(clock.clone()));
+        let config: AppConfig = serde_json::from_value(json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["home"],
+            "check_interval": 600
+        }))
+        .unwrap();
+        service.database().save_config(&config).unwrap();
+        // 暂停后触发的检查直接返回，不访问网络
+        service.pause(Some(clock.utc_now() + chrono::Duration::hours(1)), None).unwrap();
+
+        let scheduler = ManualScheduler::new();
+        let mut monitor = MonitorService::with_scheduler(service.clone(), scheduler.clone());
+        monitor.start().await.unwrap();
+        assert_eq!(scheduler.intervals(), vec![Duration::from_secs(600)]);
+
+        // 首次触发即执行检查，之后需等到下一个检查间隔
+        scheduler.fire().await;
+        assert_eq!(service.next_scheduled_check(&config), Some(start + chrono::Duration::seconds(600)));
+        // 暂停期间跳过的检查不记录结果
+        assert!(service.last_check().is_none());
+        let settings = BurstSettings::from_config(&config);
+        assert!(!service.take_scheduled_check(&settings));
+        clock.advance(Duration::from_secs(590));
+        assert!(!service.take_scheduled_check(&settings));
+        clock.advance(Duration::from_secs(10));
+        assert!(service.take_scheduled_check(&settings));
+
+        // 暂停到期后自动恢复
+        assert!(service.pause_state().is_some());
+        clock.advance(Duration::from_secs(3600));
+        assert!(service.pause_state().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_handle_stops_and_restarts_schedule() {
+        let service = ConfigService::with_clock(Database::new(":memory:").unwrap(), Arc::new(ManualClock::new(Utc::now())));
+        let config: AppConfig = serde_json::from_value(json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["home"],
+            "check_interval": 300,
+            "ip_source": "dyndns"
+        }))
+        .unwrap();
+        service.database().save_config(&config).unwrap();
+        // 地址来自路由器推送，保存配置时不检测本机网络
+        service.database().update_pushed_ip("fd00::1").unwrap();
+
+        let scheduler = ManualScheduler::new();
+        let mut monitor = MonitorService::with_scheduler(service.clone(), scheduler.clone());
+        monitor.start().await.unwrap();
+        let handle = monitor.spawn();
+        assert_eq!(handle.status().await.unwrap().check_interval, Some(300));
+
+        handle.stop().await.unwrap();
+        assert!(scheduler.intervals().is_empty());
+        assert!(!handle.status().await.unwrap().running);
+
+        // 停止期间保存配置不会重建定时任务
+        service.save_configuration(config.clone()).await.unwrap();
+        assert_eq!(service.load_configuration().unwrap().last_ip.as_deref(), Some("fd00::1"));
+        tokio::task::yield_now().await;
+        assert!(scheduler.intervals().is_empty());
+
+        handle.start().await.unwrap();
+        assert_eq!(scheduler.intervals(), vec![Duration::from_secs(300)]);
+    }
+
+    #[tokio::test]
+    async fn test_saving_config_reloads_schedule() {
+        let service = ConfigService::with_clock(Database::new(":memory:").unwrap(), Arc::new(ManualClock::new(Utc::now())));
+        let scheduler = ManualScheduler::new();
+        let mut monitor = MonitorService::with_scheduler(service.clone(), scheduler.clone());
+        monitor.start().await.unwrap();
+        assert!(scheduler.intervals().is_empty());
+
+        let mut config: AppConfig = serde_json::from_value(json!({
+            "cloudflare_api_key": "token",
+            "cloudflare_zone_id": "zone",
+            "root_domain": "example.com",
+            "selected_subdomains": ["home"],
+            "check_interval": 600,
+            "ip_source": "dyndns"
+        }))
+        .unwrap();
+        // 地址来自路由器推送，保存配置时不检测本机网络
+        service.database().save_config(&config).unwrap();
+        service.database().update_pushed_ip("fd00::1").unwrap();
+        for interval in [600, 120] {
+            config.check_interval = interval;
+            service.save_configuration(config.clone()).await.unwrap();
+            for _ in 0..100 {
+                if scheduler.intervals() == vec![Duration::from_secs(interval)] {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+            assert_eq!(scheduler.intervals(), vec![Duration::from_secs(interval)]);
+        }
+    }
+}
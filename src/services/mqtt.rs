@@ -0,0 +1,281 @@
+use crate::config::database::{DnsUpdateRecord, MqttSettings};
+use crate::services::status::ServiceStatus;
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// MQTT默认端口（不加密），一次连接、握手和发布的总超时时间
+const DEFAULT_MQTT_PORT: u16 = 1883;
+const MQTT_TIMEOUT: Duration = Duration::from_secs(5);
+const KEEP_ALIVE_SECS: u16 = 30;
+
+/// 要发布的一条消息，主题为完整主题
+#[derive(Debug, Clone)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+impl MqttMessage {
+    /// 发布到设置的主题前缀下的子主题
+    pub fn under(settings: &MqttSettings, subtopic: &str, payload: String) -> Self {
+        Self {
+            topic: format!("{}/{}", settings.topic.trim_end_matches('/'), subtopic),
+            payload,
+            retain: settings.retain,
+        }
+    }
+}
+
+/// 校验MQTT设置
+pub fn validate(settings: &MqttSettings) -> Result<()> {
+    parse_broker(&settings.broker)?;
+    for topic in [&settings.topic, &settings.discovery_prefix] {
+        let topic = topic.trim();
+        if topic.is_empty() || topic.contains(['+', '#']) {
+            return Err(anyhow!("MQTT主题前缀不能为空，也不能包含通配符 + 或 #"));
+        }
+    }
+    if settings.password.is_some() && settings.username.is_none() {
+        return Err(anyhow!("设置MQTT密码时需要同时设置用户名"));
+    }
+    Ok(())
+}
+
+/// 解析代理地址：host、host:port、[IPv6]:port，可带 mqtt:// 前缀，未指定端口时使用1883
+pub fn parse_broker(broker: &str) -> Result<(String, u16)> {
+    let broker = broker.trim();
+    let broker = broker.strip_prefix("mqtt://").unwrap_or(broker);
+    if let Some((scheme, _)) = broker.split_once("://") {
+        return Err(match scheme.to_lowercase().as_str() {
+            "mqtts" | "ssl" | "tls" => anyhow!("不支持加密的MQTT连接: {}，只能使用不加密的 mqtt:// 或 host:port", broker),
+            _ => anyhow!("不支持的MQTT代理协议 {}://，应为 mqtt://host:port 或 host:port", scheme),
+        });
+    }
+    if let Ok(addr) = broker.parse::<SocketAddr>() {
+        return Ok((addr.ip().to_string(), addr.port()));
+    }
+    if let Ok(ip) = broker.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+        return Ok((ip.to_string(), DEFAULT_MQTT_PORT));
+    }
+    let (host, port) = match broker.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse::<u16>().map_err(|_| anyhow!("MQTT代理端口无效: {}", broker))?),
+        None => (broker, DEFAULT_MQTT_PORT),
+    };
+    if host.is_empty() || host.contains(['/', ':', ' ']) || port == 0 {
+        return Err(anyhow!("MQTT代理地址无效: {}，应为 host:port", broker));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// Home Assistant传感器的状态，以保留消息发布到 主题前缀/state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HomeAssistantState {
+    pub current_ip: Option<String>,
+    pub status: &'static str,
+    pub last_update: Option<DateTime<Utc>>,
+    pub last_change: Option<DateTime<Utc>>,
+}
+
+impl HomeAssistantState {
+    /// 由更新历史（按时间倒序）得出：当前地址和上次变化取实际写入成功的记录，不含演练记录
+    pub fn from_history(records: &[DnsUpdateRecord]) -> Self {
+        let mut applied = records.iter().filter(|r| !r.dry_run && r.success_count > 0);
+        let latest = records.iter().find(|r| !r.dry_run);
+        Self {
+            current_ip: applied.clone().next().map(|r| r.new_ip.clone()),
+            status: latest
+                .map(|r| ServiceStatus::from_counts(r.success_count, r.domain_count))
+                .unwrap_or(ServiceStatus::Unknown)
+                .as_str(),
+            last_update: latest.map(|r| r.timestamp),
+            last_change: applied.find(|r| r.old_ip.as_deref() != Some(r.new_ip.as_str())).map(|r| r.timestamp),
+        }
+    }
+}
+
+/// 设备标识，同一个代理下区分管理不同根域名的实例
+fn node_id(root_domain: &str) -> String {
+    let name: String = root_domain
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    format!("cloudflare_auto_{}", name)
+}
+
+/// 按Home Assistant的MQTT发现约定生成三个传感器（当前IPv6、上次更新状态、上次地址变化时间）的配置和当前状态，
+/// 都以保留消息发布，Home Assistant重启后仍能恢复
+pub fn home_assistant_messages(settings: &MqttSettings, root_domain: &str, state: &HomeAssistantState) -> Vec<MqttMessage> {
+    let node = node_id(root_domain);
+    let state_topic = format!("{}/state", settings.topic.trim_end_matches('/'));
+    let device = json!({
+        "identifiers": [node],
+        "name": format!("cloudflare-auto {}", root_domain),
+        "manufacturer": "cloudflare-auto",
+        "sw_version": env!("CARGO_PKG_VERSION"),
+    });
+    let sensors = [
+        ("current_ip", "IPv6 address", json!({"icon": "mdi:ip-network"})),
+        ("status", "Last update status", json!({"icon": "mdi:dns"})),
+        ("last_change", "Last IP change", json!({"device_class": "timestamp"})),
+    ];
+    let mut messages: Vec<MqttMessage> = sensors
+        .into_iter()
+        .map(|(key, name, extra)| {
+            let mut config = json!({
+                "name": name,
+                "unique_id": format!("{}_{}", node, key),
+                "state_topic": state_topic,
+                "value_template": format!("{{{{ value_json.{} }}}}", key),
+                "device": device,
+            });
+            if let (Some(config), Some(extra)) = (config.as_object_mut(), extra.as_object()) {
+                config.extend(extra.clone());
+            }
+            MqttMessage {
+                topic: format!("{}/sensor/{}/{}/config", settings.discovery_prefix.trim_end_matches('/'), node, key),
+                payload: config.to_string(),
+                retain: true,
+            }
+        })
+        .collect();
+    messages.push(MqttMessage {
+        topic: state_topic,
+        payload: serde_json::to_string(state).unwrap_or_default(),
+        retain: true,
+    });
+    messages
+}
+
+/// 连接代理并以QoS 0发布消息，发布完成后断开连接；整个过程超过 MQTT_TIMEOUT 即放弃
+pub async fn publish(settings: &MqttSettings, messages: &[MqttMessage]) -> Result<()> {
+    let (host, port) = parse_broker(&settings.broker)?;
+    tokio::time::timeout(MQTT_TIMEOUT, exchange(settings, &host, port, messages))
+        .await
+        .map_err(|_| anyhow!("向MQTT代理 {} 发布消息超时", settings.broker))?
+}
+
+async fn exchange(settings: &MqttSettings, host: &str, port: u16, messages: &[MqttMessage]) -> Result<()> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let client_id = format!("cloudflare-auto-{:08x}", OsRng.next_u32());
+    stream
+        .write_all(&connect_packet(&client_id, settings.username.as_deref(), settings.password.as_deref()))
+        .await?;
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack).await?;
+    check_connack(&connack)?;
+
+    for message in messages {
+        stream.write_all(&publish_packet(&message.topic, message.payload.as_bytes(), message.retain)).await?;
+    }
+    stream.write_all(&[0xe0, 0]).await?; // DISCONNECT
+    stream.flush().await?;
+    Ok(())
+}
+
+/// 剩余长度按每字节7位编码，最高位表示后面还有字节
+fn encode_remaining_length(mut len: usize, out: &mut Vec<u8>) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn encode_string(value: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet = vec![header];
+    encode_remaining_length(body.len(), &mut packet);
+    packet.extend(body);
+    packet
+}
+
+/// MQTT 3.1.1 CONNECT，使用干净会话
+fn connect_packet(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut flags = 0x02;
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+    let mut body = Vec::new();
+    encode_string(b"MQTT", &mut body);
+    body.push(4); // 协议级别 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    encode_string(client_id.as_bytes(), &mut body);
+    for value in [username, password].into_iter().flatten() {
+        encode_string(value.as_bytes(), &mut body);
+    }
+    packet(0x10, body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    encode_string(topic.as_bytes(), &mut body);
+    body.extend_from_slice(payload);
+    packet(0x30 | retain as u8, body)
+}
+
+fn check_connack(connack: &[u8; 4]) -> Result<()> {
+    if connack[0] != 0x20 || connack[1] != 2 {
+        return Err(anyhow!("MQTT代理返回了无效的CONNACK"));
+    }
+    match connack[3] {
+        0 => Ok(()),
+        1 => Err(anyhow!("MQTT代理不支持协议版本3.1.1")),
+        4 => Err(anyhow!("MQTT用户名或密码错误")),
+        5 => Err(anyhow!("MQTT代理拒绝连接：未授权")),
+        code => Err(anyhow!("MQTT代理拒绝连接，返回码 {}", code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mqtt_packets() {
+        let mut out = Vec::new();
+        encode_remaining_length(321, &mut out);
+        assert_eq!(out, [0xc1, 0x02]);
+
+        let connect = connect_packet("c", Some("u"), Some("p"));
+        assert_eq!(connect, [0x10, 19, 0, 4, b'M', b'Q', b'T', b'T', 4, 0xc2, 0, 30, 0, 1, b'c', 0, 1, b'u', 0, 1, b'p']);
+        assert_eq!(publish_packet("a/b", b"hi", true), [0x31, 7, 0, 3, b'a', b'/', b'b', b'h', b'i']);
+        assert!(check_connack(&[0x20, 2, 0, 0]).is_ok() && check_connack(&[0x20, 2, 0, 4]).is_err());
+
+        assert_eq!(parse_broker("mqtt://broker.lan").unwrap(), ("broker.lan".to_string(), 1883));
+        assert_eq!(parse_broker("[fd00::1]:8883").unwrap(), ("fd00::1".to_string(), 8883));
+        assert!(parse_broker("broker:x").is_err());
+        assert!(parse_broker("mqtts://broker.lan:8883").unwrap_err().to_string().contains("不支持加密"));
+
+        let settings: MqttSettings = serde_json::from_str(r#"{"broker": "broker.lan", "home_assistant": true}"#).unwrap();
+        let state = HomeAssistantState { current_ip: Some("2001:db8::1".to_string()), status: "in_sync", last_update: None, last_change: None };
+        let messages = home_assistant_messages(&settings, "home.example.com", &state);
+        assert_eq!(messages[0].topic, "homeassistant/sensor/cloudflare_auto_home_example_com/current_ip/config");
+        assert!(messages[0].payload.contains(r#""value_template":"{{ value_json.current_ip }}""#));
+        assert!(messages[2].payload.contains(r#""device_class":"timestamp""#));
+        assert_eq!(messages[3].topic, "cloudflare-auto/state");
+        assert!(messages.iter().all(|m| m.retain));
+    }
+}
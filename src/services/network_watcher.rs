@@ -0,0 +1,139 @@
+use crate::services::monitor_service::MonitorHandle;
+use tracing::debug;
+
+/// 监听系统网络变化，默认路由或接口地址变化时立即触发一次检查
+/// 目前支持macOS（通过 `route -n monitor`），其他平台不启动；设置 NETWORK_WATCH=off 可关闭
+pub fn spawn(monitor: MonitorHandle) {
+    if std::env::var("NETWORK_WATCH").is_ok_and(|v| v.eq_ignore_ascii_case("off") || v == "0") {
+        debug!("🔕 已通过 NETWORK_WATCH 关闭网络变化监听");
+        return;
+    }
+    start(monitor);
+}
+
+#[cfg(target_os = "macos")]
+fn start(monitor: MonitorHandle) {
+    tokio::spawn(macos::watch(monitor));
+}
+
+#[cfg(not(target_os = "macos"))]
+fn start(_monitor: MonitorHandle) {
+    debug!("当前平台不支持网络变化监听，仅按检查间隔定时检查");
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::RouteMonitorParser;
+    use crate::services::monitor_service::MonitorHandle;
+    use std::process::Stdio;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::process::Command;
+    use tracing::{info, warn};
+
+    /// 变化通常成批出现（删除旧地址、添加新地址、更新路由），安静一段时间后再检查
+    const DEBOUNCE: Duration = Duration::from_secs(3);
+    /// route monitor 意外退出后重新启动的等待时间
+    const RESTART_DELAY: Duration = Duration::from_secs(30);
+
+    pub async fn watch(monitor: MonitorHandle) {
+        loop {
+            if let Err(e) = run(&monitor).await {
+                warn!("⚠️ 网络变化监听出错: {}", e);
+            }
+            tokio::time::sleep(RESTART_DELAY).await;
+        }
+    }
+
+    async fn run(monitor: &MonitorHandle) -> anyhow::Result<()> {
+        let mut child = Command::new("/sbin/route")
+            .args(["-n", "monitor"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow::anyhow!("无法读取 route monitor 的输出"))?;
+        let mut lines = BufReader::new(stdout).lines();
+        let mut parser = RouteMonitorParser::default();
+        info!("👀 已开始监听网络变化（route monitor）");
+
+        loop {
+            let Some(line) = lines.next_line().await? else {
+                return Err(anyhow::anyhow!("route monitor 已退出"));
+            };
+            if !parser.feed(&line) {
+                continue;
+            }
+            // 合并短时间内的连续变化
+            while let Ok(Ok(Some(line))) = tokio::time::timeout(DEBOUNCE, lines.next_line()).await {
+                parser.feed(&line);
+            }
+            info!("🔌 检测到默认路由或接口地址变化，立即检查IP");
+            let monitor = monitor.clone();
+            tokio::spawn(async move {
+                if let Err(e) = monitor.trigger().await {
+                    warn!("⚠️ 网络变化后的检查失败: {}", e);
+                }
+            });
+        }
+    }
+}
+
+/// 解析 `route -n monitor` 的输出：地址增删总是需要检查，路由变化只关心默认路由
+#[cfg(any(target_os = "macos", test))]
+#[derive(Debug, Default)]
+struct RouteMonitorParser {
+    route_message: bool, // 正在读取路由增删消息的地址部分
+}
+
+#[cfg(any(target_os = "macos", test))]
+impl RouteMonitorParser {
+    /// 读入一行输出，返回这一行是否表示需要检查的变化
+    fn feed(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        if let Some(kind) = line.split(':').next().filter(|kind| kind.starts_with("RTM_")) {
+            self.route_message = matches!(kind, "RTM_ADD" | "RTM_DELETE" | "RTM_CHANGE");
+            return matches!(kind, "RTM_NEWADDR" | "RTM_DELADDR");
+        }
+        if line.starts_with("got message") || line.is_empty() {
+            self.route_message = false;
+            return false;
+        }
+        // 地址部分的第一项为目的地址，默认路由显示为 default
+        if self.route_message && line.split_whitespace().next() == Some("default") {
+            self.route_message = false;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_monitor_parser() {
+        let output = [
+            ("got message of size 192 on Thu Oct 16 10:00:00 2026", false),
+            ("RTM_ADD: Add Route: len 192, pid: 0, seq 0, errno 0, flags:<UP,GATEWAY,DONE>", false),
+            ("locks:  inits:", false),
+            ("sockaddrs: <DST,GATEWAY,NETMASK>", false),
+            ("2001:db8::10 fe80::1%en0 (255) ffff:ffff:ffff:ffff::", false),
+            ("", false),
+            ("got message of size 180 on Thu Oct 16 10:00:01 2026", false),
+            ("RTM_DELETE: Delete Route: len 180, pid: 0, seq 0, errno 0, flags:<UP,GATEWAY,DONE>", false),
+            ("sockaddrs: <DST,GATEWAY,NETMASK>", false),
+            ("default fe80::1%en0 default", true),
+            ("", false),
+            ("RTM_NEWADDR: address being added to iface: len 140, metric 0, flags:<UP>", true),
+            ("sockaddrs: <NETMASK,IFP,IFA,BRD>", false),
+            ("RTM_IFINFO: iface status change: len 148, if# 6, link: up, flags:<UP>", false),
+            ("default", false),
+        ];
+        let mut parser = RouteMonitorParser::default();
+        for (line, expected) in output {
+            assert_eq!(parser.feed(line), expected, "{}", line);
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::config::database::DnsUpdateRecord;
+use crate::services::config_service::{DomainOutcome, DomainResult};
+use crate::services::status::{ServiceStatus, StatusTransition};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// 通知事件的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyKind {
+    IpChanged,       // 地址变化并已写入DNS记录
+    UpdateSucceeded, // 地址未变化，补写或修复了记录且没有失败，只发送到事件Webhook
+    UpdateFailed,    // 服务状态变为降级、失败或离线
+    Recovered,       // 从上述状态恢复为已同步
+    DailySummary,    // 每日更新统计
+    IpMismatch,      // 本地选择的地址与外部服务看到的地址变为不一致
+}
+
+impl NotifyKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotifyKind::IpChanged => "ip_changed",
+            NotifyKind::UpdateSucceeded => "update_succeeded",
+            NotifyKind::UpdateFailed => "update_failed",
+            NotifyKind::Recovered => "recovered",
+            NotifyKind::DailySummary => "daily_summary",
+            NotifyKind::IpMismatch => "ip_mismatch",
+        }
+    }
+}
+
+/// 发送到各通知渠道的事件，渠道按各自的格式排版
+#[derive(Debug, Clone, Serialize)]
+pub struct NotifyEvent {
+    pub kind: NotifyKind,
+    pub root_domain: String,
+    pub old_ip: Option<String>,
+    pub new_ip: Option<String>,
+    pub message: Option<String>, // 失败原因或统计内容
+    pub domains: Vec<DomainResult>, // 地址变化时各域名的处理结果
+    pub timestamp: DateTime<Utc>,
+}
+
+impl NotifyEvent {
+    pub fn new(kind: NotifyKind, root_domain: &str, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            kind,
+            root_domain: root_domain.to_string(),
+            old_ip: None,
+            new_ip: None,
+            message: None,
+            domains: Vec::new(),
+            timestamp,
+        }
+    }
+
+    /// 标题，例如 "🔄 example.com IPv6地址已变化"
+    pub fn title(&self) -> String {
+        let (emoji, text) = match self.kind {
+            NotifyKind::IpChanged => ("🔄", "IPv6地址已变化"),
+            NotifyKind::UpdateSucceeded => ("✅", "DNS记录已更新"),
+            NotifyKind::UpdateFailed => ("❌", "DNS更新失败"),
+            NotifyKind::Recovered => ("✅", "DNS更新已恢复"),
+            NotifyKind::DailySummary => ("📊", "每日更新统计"),
+            NotifyKind::IpMismatch => ("⚠️", "本地与外部IPv6不一致"),
+        };
+        format!("{} {} {}", emoji, self.root_domain, text)
+    }
+
+    /// 正文各行：新旧地址、原因和各域名的结果
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(old_ip) = &self.old_ip {
+            lines.push(format!("旧地址: {}", old_ip));
+        }
+        if let Some(new_ip) = &self.new_ip {
+            let label = if self.kind == NotifyKind::IpChanged { "新地址" } else { "当前地址" };
+            lines.push(format!("{}: {}", label, new_ip));
+        }
+        if let Some(message) = &self.message {
+            lines.push(message.clone());
+        }
+        lines.extend(self.domains.iter().map(domain_line));
+        lines
+    }
+
+    /// 纯文本内容，标题和正文之间空一行
+    pub fn text(&self) -> String {
+        let lines = self.lines();
+        if lines.is_empty() {
+            return self.title();
+        }
+        format!("{}\n\n{}", self.title(), lines.join("\n"))
+    }
+
+    /// 模板占位符的值，没有的值为空字符串
+    fn placeholder(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "event" => self.kind.as_str().to_string(),
+            "title" => self.title(),
+            "text" => self.text(),
+            "root_domain" => self.root_domain.clone(),
+            "old_ip" => self.old_ip.clone().unwrap_or_default(),
+            "new_ip" => self.new_ip.clone().unwrap_or_default(),
+            "message" => self.message.clone().unwrap_or_default(),
+            "domains" => self.domains.iter().map(domain_line).collect::<Vec<_>>().join("\n"),
+            "timestamp" => self.timestamp.to_rfc3339(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// 按模板生成内容：{event}、{title}、{text}、{root_domain}、{old_ip}、{new_ip}、{message}、{domains}、{timestamp}
+    /// 替换为经 escape 处理后的值，其他花括号原样保留
+    pub fn render(&self, template: &str, escape: impl Fn(&str) -> String) -> String {
+        let mut output = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            let tail = &rest[start..];
+            match tail.find('}').and_then(|end| Some((end, self.placeholder(&tail[1..end])?))) {
+                Some((end, value)) => {
+                    output.push_str(&escape(&value));
+                    rest = &tail[end + 1..];
+                }
+                None => {
+                    output.push('{');
+                    rest = &tail[1..];
+                }
+            }
+        }
+        output.push_str(rest);
+        output
+    }
+}
+
+/// 状态变为降级、失败或离线时通知失败，从这些状态恢复为已同步时通知恢复，其他转换不通知
+pub fn from_transition(root_domain: &str, transition: &StatusTransition, current_ip: Option<String>) -> Option<NotifyEvent> {
+    let failed = |status| matches!(status, ServiceStatus::Degraded | ServiceStatus::Failing | ServiceStatus::Offline);
+    let kind = if failed(transition.to) {
+        NotifyKind::UpdateFailed
+    } else if transition.to == ServiceStatus::InSync && failed(transition.from) {
+        NotifyKind::Recovered
+    } else {
+        return None;
+    };
+    let mut event = NotifyEvent::new(kind, root_domain, transition.timestamp);
+    event.new_ip = current_ip;
+    if kind == NotifyKind::UpdateFailed {
+        event.message = Some(format!(
+            "状态: {}{}",
+            transition.to.as_str(),
+            transition.reason.as_deref().map(|r| format!("，{}", r)).unwrap_or_default()
+        ));
+    }
+    Some(event)
+}
+
+/// 统计 now 之前24小时的更新记录（不含演练记录）
+pub fn daily_summary(
+    root_domain: &str,
+    records: &[DnsUpdateRecord],
+    now: DateTime<Utc>,
+    current_ip: Option<String>,
+    status: ServiceStatus,
+) -> NotifyEvent {
+    let since = now - chrono::Duration::hours(24);
+    let recent: Vec<&DnsUpdateRecord> = records.iter().filter(|r| !r.dry_run && r.timestamp >= since).collect();
+    let failed = recent.iter().filter(|r| r.error_message.is_some()).count();
+    let changes = recent
+        .iter()
+        .filter(|r| r.success_count > 0 && r.old_ip.as_deref() != Some(r.new_ip.as_str()))
+        .count();
+    let mut event = NotifyEvent::new(NotifyKind::DailySummary, root_domain, now);
+    event.new_ip = current_ip;
+    event.message = Some(format!(
+        "过去24小时: 更新 {} 次，失败 {} 次，地址变化 {} 次\n当前状态: {}",
+        recent.len(),
+        failed,
+        changes,
+        status.as_str()
+    ));
+    event
+}
+
+/// 单个域名结果的图标和说明，失败时说明为错误信息
+pub fn domain_outcome(domain: &DomainResult) -> (&'static str, &str) {
+    match domain.outcome {
+        DomainOutcome::Created => ("✅", "created"),
+        DomainOutcome::Updated => ("✅", "updated"),
+        DomainOutcome::Unchanged => ("➖", "unchanged"),
+        DomainOutcome::Failed => ("❌", domain.error.as_deref().unwrap_or("failed")),
+    }
+}
+
+/// 单个域名的结果，例如 "✅ www.example.com: updated"
+pub fn domain_line(domain: &DomainResult) -> String {
+    let (emoji, outcome) = domain_outcome(domain);
+    format!("{} {}: {}", emoji, domain.domain, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_event_text() {
+        let mut event = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        event.old_ip = Some("2001:db8::1".to_string());
+        event.new_ip = Some("2001:db8::2".to_string());
+        event.domains.push(DomainResult {
+            subdomain: "www".to_string(),
+            domain: "www.example.com".to_string(),
+            outcome: DomainOutcome::Failed,
+            error: Some("权限不足".to_string()),
+            propagation: None,
+        });
+        assert_eq!(
+            event.text(),
+            "🔄 example.com IPv6地址已变化\n\n旧地址: 2001:db8::1\n新地址: 2001:db8::2\n❌ www.example.com: 权限不足"
+        );
+        assert_eq!(NotifyEvent::new(NotifyKind::Recovered, "example.com", Utc::now()).text(), "✅ example.com DNS更新已恢复");
+        assert_eq!(
+            event.render("{event} {old_ip}->{new_ip} {message}{unknown} {\"a\":1}", str::to_string),
+            "ip_changed 2001:db8::1->2001:db8::2 {unknown} {\"a\":1}"
+        );
+
+        let mut transition = StatusTransition {
+            from: ServiceStatus::InSync,
+            to: ServiceStatus::Failing,
+            reason: Some("获取当前IP失败".to_string()),
+            timestamp: Utc::now(),
+        };
+        let failed = from_transition("example.com", &transition, None).unwrap();
+        assert_eq!((failed.kind, failed.message.as_deref()), (NotifyKind::UpdateFailed, Some("状态: failing，获取当前IP失败")));
+        (transition.from, transition.to) = (ServiceStatus::Failing, ServiceStatus::InSync);
+        assert_eq!(from_transition("example.com", &transition, None).unwrap().kind, NotifyKind::Recovered);
+        transition.from = ServiceStatus::Unknown;
+        assert!(from_transition("example.com", &transition, None).is_none());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::config::database::NotifyWebhook;
+use crate::services::notify::{NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use reqwest::header::{HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Client, Method};
+
+/// 最多可配置的Webhook数量
+pub const MAX_NOTIFY_WEBHOOKS: usize = 10;
+
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+/// Webhook可订阅的事件，每日统计只发送到聊天渠道
+const EVENTS: [NotifyKind; 5] = [
+    NotifyKind::IpChanged,
+    NotifyKind::UpdateSucceeded,
+    NotifyKind::UpdateFailed,
+    NotifyKind::Recovered,
+    NotifyKind::IpMismatch,
+];
+
+/// 校验Webhook设置，请求体模板用示例事件渲染后必须是合法的JSON
+pub fn validate(webhook: &NotifyWebhook) -> Result<()> {
+    let parsed = reqwest::Url::parse(webhook.url.trim()).map_err(|_| anyhow!("Webhook地址无效: {}", webhook.url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("Webhook地址只支持 http 和 https: {}", webhook.url));
+    }
+    if !METHODS.contains(&webhook.method.to_ascii_uppercase().as_str()) {
+        return Err(anyhow!("Webhook请求方法无效: {}，可选 {}", webhook.method, METHODS.join("、")));
+    }
+    for (name, value) in &webhook.headers {
+        HeaderName::from_bytes(name.as_bytes()).map_err(|_| anyhow!("Webhook请求头名称无效: {}", name))?;
+        HeaderValue::from_str(value).map_err(|_| anyhow!("Webhook请求头 {} 的值无效", name))?;
+    }
+    if let Some(event) = webhook.events.iter().find(|e| !EVENTS.iter().any(|k| k.as_str() == e.as_str())) {
+        return Err(anyhow!(
+            "未知的Webhook事件: {}，可选 {}",
+            event,
+            EVENTS.map(|k| k.as_str()).join("、")
+        ));
+    }
+    if let Some(template) = &webhook.body_template {
+        let mut sample = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        sample.old_ip = Some("2001:db8::1".to_string());
+        sample.new_ip = Some("2001:db8::2".to_string());
+        sample.message = Some("\"引号\"\n换行".to_string());
+        serde_json::from_str::<serde_json::Value>(&sample.render(template, json_escape))
+            .map_err(|e| anyhow!("Webhook请求体模板不是合法的JSON: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 该Webhook是否订阅了事件，未指定事件时订阅全部
+pub fn wants(webhook: &NotifyWebhook, kind: NotifyKind) -> bool {
+    if !EVENTS.contains(&kind) {
+        return false;
+    }
+    webhook.events.is_empty() || webhook.events.iter().any(|e| e == kind.as_str())
+}
+
+/// 占位符的值按JSON字符串内容转义，模板中写作 "{new_ip}"
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+fn body(webhook: &NotifyWebhook, event: &NotifyEvent) -> Result<String> {
+    match &webhook.body_template {
+        Some(template) => Ok(event.render(template, json_escape)),
+        None => Ok(serde_json::to_string(event)?),
+    }
+}
+
+/// 发送请求，返回非2xx状态码时视为失败
+pub async fn send(client: &Client, webhook: &NotifyWebhook, event: &NotifyEvent) -> Result<()> {
+    let method = Method::from_bytes(webhook.method.to_ascii_uppercase().as_bytes())?;
+    let mut request = client.request(method.clone(), webhook.url.trim());
+    if method != Method::GET {
+        request = request.header(CONTENT_TYPE, "application/json").body(body(webhook, event)?);
+    }
+    // 自定义的请求头（包括 Content-Type）覆盖默认值
+    for (name, value) in &webhook.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notify_webhook() {
+        let mut webhook: NotifyWebhook = serde_json::from_str(r#"{"url": "https://hooks.example.com/ddns"}"#).unwrap();
+        assert_eq!(webhook.method, "POST");
+        assert!(validate(&webhook).is_ok());
+        assert!(wants(&webhook, NotifyKind::UpdateSucceeded) && !wants(&webhook, NotifyKind::DailySummary));
+
+        webhook.events = vec!["ip_changed".to_string()];
+        assert!(wants(&webhook, NotifyKind::IpChanged) && !wants(&webhook, NotifyKind::UpdateFailed));
+
+        webhook.body_template = Some(r#"{"ip": "{new_ip}", "note": "{message}"}"#.to_string());
+        let mut event = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        event.new_ip = Some("2001:db8::2".to_string());
+        event.message = Some("a \"b\"".to_string());
+        assert_eq!(body(&webhook, &event).unwrap(), r#"{"ip": "2001:db8::2", "note": "a \"b\""}"#);
+
+        webhook.body_template = Some(r#"{"ip": {new_ip}}"#.to_string());
+        assert!(validate(&webhook).is_err());
+        webhook.body_template = None;
+        webhook.events = vec!["daily_summary".to_string()];
+        assert!(validate(&webhook).is_err());
+    }
+}
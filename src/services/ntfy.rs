@@ -0,0 +1,78 @@
+use crate::config::database::NtfySettings;
+use crate::services::notify::{NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// 校验ntfy设置
+pub fn validate(settings: &NtfySettings) -> Result<()> {
+    let parsed = reqwest::Url::parse(settings.server.trim()).map_err(|_| anyhow!("ntfy服务器地址无效: {}", settings.server))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("ntfy服务器地址只支持 http 和 https: {}", settings.server));
+    }
+    let topic = settings.topic.trim();
+    if topic.is_empty() || topic.len() > 64 || !topic.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(anyhow!("ntfy主题无效: {}，只能包含字母、数字、- 和 _，最长64个字符", topic));
+    }
+    if settings.priority.is_some_and(|p| !(1..=5).contains(&p)) {
+        return Err(anyhow!("ntfy优先级需在 1-5 之间"));
+    }
+    Ok(())
+}
+
+/// 以JSON方式发布，标题和正文不受请求头编码的限制
+fn payload(settings: &NtfySettings, event: &NotifyEvent) -> Value {
+    let (default_priority, tag) = match event.kind {
+        NotifyKind::UpdateFailed | NotifyKind::IpMismatch => (4, "warning"),
+        NotifyKind::Recovered | NotifyKind::UpdateSucceeded => (3, "white_check_mark"),
+        NotifyKind::IpChanged => (3, "arrows_counterclockwise"),
+        NotifyKind::DailySummary => (3, "bar_chart"),
+    };
+    let lines = event.lines();
+    json!({
+        "topic": settings.topic.trim(),
+        "title": event.title(),
+        "message": if lines.is_empty() { event.title() } else { lines.join("\n") },
+        "priority": settings.priority.unwrap_or(default_priority),
+        "tags": [tag],
+    })
+}
+
+/// 发布到服务器根地址，主题在请求体中指定
+pub async fn send(client: &Client, settings: &NtfySettings, event: &NotifyEvent) -> Result<()> {
+    let mut request = client.post(settings.server.trim()).json(&payload(settings, event));
+    if let Some(token) = &settings.token {
+        request = request.bearer_auth(token.trim());
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_ntfy_payload() {
+        let mut settings: NtfySettings = serde_json::from_str(r#"{"topic": "home-ddns"}"#).unwrap();
+        assert_eq!(settings.server, "https://ntfy.sh");
+        assert!(validate(&settings).is_ok());
+
+        let mut event = NotifyEvent::new(NotifyKind::UpdateFailed, "example.com", Utc::now());
+        event.message = Some("状态: failing".to_string());
+        assert_eq!(
+            payload(&settings, &event),
+            json!({
+                "topic": "home-ddns",
+                "title": "❌ example.com DNS更新失败",
+                "message": "状态: failing",
+                "priority": 4,
+                "tags": ["warning"],
+            })
+        );
+
+        settings.topic = "home/ddns".to_string();
+        assert!(validate(&settings).is_err());
+    }
+}
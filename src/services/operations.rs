@@ -0,0 +1,116 @@
+use crate::config::database::{Operation, OperationError};
+use crate::services::config_service::{normalize_subdomain, ConfigService};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+/// 操作类型：强制同步
+pub const KIND_FORCE_SYNC: &str = "force_sync";
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+pub const STATUS_FAILED: &str = "failed";
+
+/// 强制同步操作的参数，subdomains为None表示全部受管子域名
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ForceSyncParams {
+    pub subdomains: Option<Vec<String>>,
+}
+
+/// 创建强制同步操作并在后台执行，立即返回操作ID
+pub fn start_force_sync(service: &ConfigService, subdomains: Option<Vec<String>>) -> Result<String> {
+    let config = service.load_configuration()?;
+    let subdomains = subdomains
+        .map(|list| {
+            list.iter()
+                .map(|name| normalize_subdomain(name, &config.root_domain))
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+    if let Some(unknown) = subdomains
+        .iter()
+        .flatten()
+        .find(|s| !config.selected_subdomains.contains(s))
+    {
+        return Err(anyhow!("子域名未被管理: {}", config.full_domain(unknown)));
+    }
+
+    let total = subdomains
+        .as_ref()
+        .map_or(config.selected_subdomains.len(), Vec::len);
+    let params = serde_json::to_value(ForceSyncParams { subdomains })?;
+    let id = service
+        .database()
+        .create_operation(KIND_FORCE_SYNC, &params, total as i64)?;
+    info!("🗂️ 已创建强制同步操作 {}（{} 个域名）", id, total);
+
+    spawn(service.clone(), id.clone());
+    Ok(id)
+}
+
+/// 重新执行程序退出前未完成的操作，启动时调用
+pub fn resume_unfinished(service: &ConfigService) {
+    match service.database().get_unfinished_operations() {
+        Ok(operations) => {
+            for operation in operations {
+                info!("🔁 继续执行未完成的操作 {} ({})", operation.id, operation.kind);
+                spawn(service.clone(), operation.id);
+            }
+        }
+        Err(e) => warn!("⚠️ 读取未完成的操作失败: {}", e),
+    }
+}
+
+fn spawn(service: ConfigService, id: String) {
+    tokio::spawn(async move {
+        if let Err(e) = run(&service, &id).await {
+            error!("❌ 操作 {} 执行失败: {}", id, e);
+            // 保留已经记录的进度，只标记失败
+            let db = service.database();
+            let (done, errors) = match db.get_operation(&id) {
+                Ok(Some(operation)) => (operation.done, operation.errors),
+                _ => (0, Vec::new()),
+            };
+            if let Err(e) = db.update_operation(&id, STATUS_FAILED, done, &errors, Some(&e.to_string())) {
+                error!("❌ 保存操作状态失败 {}: {}", id, e);
+            }
+        }
+    });
+}
+
+async fn run(service: &ConfigService, id: &str) -> Result<()> {
+    let db = service.database();
+    let operation: Operation = db
+        .get_operation(id)?
+        .ok_or_else(|| anyhow!("操作不存在: {}", id))?;
+    if operation.kind != KIND_FORCE_SYNC {
+        return Err(anyhow!("未知的操作类型: {}", operation.kind));
+    }
+    let params: ForceSyncParams = serde_json::from_value(operation.params)?;
+
+    // 重新执行时从头开始，进度清零
+    db.update_operation(id, STATUS_RUNNING, 0, &[], None)?;
+    let mut done = 0;
+    let mut errors = Vec::new();
+    let summary = service
+        .force_sync(params.subdomains, |domain, error| {
+            done += 1;
+            if let Some(message) = error {
+                errors.push(OperationError {
+                    domain: domain.to_string(),
+                    message: message.to_string(),
+                });
+            }
+            if let Err(e) = db.update_operation(id, STATUS_RUNNING, done, &errors, None) {
+                error!("❌ 保存操作进度失败 {}: {}", id, e);
+            }
+        })
+        .await?;
+
+    let status = if errors.is_empty() { STATUS_SUCCEEDED } else { STATUS_FAILED };
+    let message = format!("成功 {}/{} 个域名", summary.success_count, summary.total_count);
+    db.update_operation(id, status, done, &errors, Some(&message))?;
+    info!("🗂️ 操作 {} 完成: {}", id, message);
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::services::cloudflare::DnsRecord;
+
+/// 写入记录备注中的归属标记前缀，完整标记为 "cf-auto-owner=<实例ID>"
+pub const OWNER_MARKER_PREFIX: &str = "cf-auto-owner=";
+
+/// 归属状态：记录带有归属标记，由标记中的实例维护
+pub const STATUS_OWNED: &str = "owned";
+/// 归属状态：受管域名的记录属于其他实例，本实例不做修改
+pub const STATUS_CONFLICT: &str = "conflict";
+
+/// 从记录备注中解析归属实例，没有标记时返回None
+pub fn owner_of(record: &DnsRecord) -> Option<&str> {
+    record
+        .comment
+        .as_deref()?
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix(OWNER_MARKER_PREFIX))
+        .filter(|owner| !owner.is_empty())
+}
+
+/// 把同名记录分为本实例可维护的记录（带有本实例标记或没有标记）和属于其他实例的记录，未启用归属登记时全部可维护
+pub fn partition_foreign(records: Vec<DnsRecord>, instance_id: Option<&str>) -> (Vec<DnsRecord>, Vec<DnsRecord>) {
+    let Some(id) = instance_id else { return (records, Vec::new()) };
+    records
+        .into_iter()
+        .partition(|record| owner_of(record).is_none_or(|owner| owner == id))
+}
+
+/// 计算写入归属标记后的备注，已带有本实例标记时返回None
+/// 保留记录已有的备注，没有备注时使用配置的标记备注
+pub fn claim_comment(existing: Option<&str>, default: Option<&str>, instance_id: &str) -> Option<String> {
+    let existing = existing.map(str::trim).filter(|c| !c.is_empty());
+    let marker = format!("{}{}", OWNER_MARKER_PREFIX, instance_id);
+    if existing.is_some_and(|c| c.split_whitespace().any(|word| word == marker)) {
+        return None;
+    }
+
+    match existing.or(default.map(str::trim).filter(|c| !c.is_empty())) {
+        Some(base) => Some(format!("{} {}", base, marker)),
+        None => Some(marker),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(comment: Option<&str>) -> DnsRecord {
+        serde_json::from_value(serde_json::json!({
+            "id": "r1", "name": "home.example.com", "type": "AAAA",
+            "content": "2001:db8::1", "proxied": false, "ttl": 1, "comment": comment
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_owner_marker_roundtrip() {
+        let comment = claim_comment(Some("nas"), Some("managed by cloudflare-auto"), "a1b2").unwrap();
+        assert_eq!(comment, "nas cf-auto-owner=a1b2");
+        assert_eq!(claim_comment(Some(&comment), None, "a1b2"), None);
+        assert_eq!(claim_comment(None, Some("managed"), "a1b2").unwrap(), "managed cf-auto-owner=a1b2");
+
+        let records = [record(Some(&comment)), record(Some("cf-auto-owner=c3d4")), record(None)];
+        assert_eq!(owner_of(&records[0]), Some("a1b2"));
+        assert_eq!(owner_of(&records[2]), None);
+        let (own, foreign) = partition_foreign(records.to_vec(), Some("a1b2"));
+        assert_eq!(own.len(), 2);
+        assert_eq!(foreign.iter().filter_map(owner_of).collect::<Vec<_>>(), vec!["c3d4"]);
+        assert_eq!(partition_foreign(records.to_vec(), None).1.len(), 0);
+    }
+}
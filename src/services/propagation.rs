@@ -0,0 +1,140 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::net::Ipv6Addr;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// 确认记录生效时查询的公共DoH解析器（JSON格式接口）
+pub const DOH_RESOLVERS: [(&str, &str); 2] = [
+    ("1.1.1.1", "https://1.1.1.1/dns-query"),
+    ("8.8.8.8", "https://8.8.8.8/resolve"),
+];
+/// 写入后最多等待多久确认新地址生效
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(60);
+/// 两轮查询之间的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// AAAA记录的类型编号
+const TYPE_AAAA: u16 = 28;
+
+/// 单个域名写入后的生效确认结果
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Propagation {
+    pub verified: bool,           // 公共解析器是否已返回新地址
+    pub latency_ms: u64,          // 从写入完成到确认生效（或放弃）的毫秒数
+    pub resolver: Option<String>, // 首个返回新地址的解析器
+}
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+impl DohResponse {
+    /// 应答中的AAAA地址，域名不存在（NXDOMAIN）时为空，其他错误状态返回错误
+    fn addresses(&self) -> Result<Vec<Ipv6Addr>> {
+        match self.status {
+            0 | 3 => Ok(self
+                .answer
+                .iter()
+                .filter(|a| a.record_type == TYPE_AAAA)
+                .filter_map(|a| a.data.parse().ok())
+                .collect()),
+            status => Err(anyhow!("DoH查询返回错误状态: {}", status)),
+        }
+    }
+}
+
+/// 轮询公共DoH解析器，直到任一解析器返回新地址或超时
+pub async fn verify(domain: &str, ip: Ipv6Addr, written_at: Instant) -> Propagation {
+    let client = match reqwest::Client::builder().timeout(Duration::from_secs(5)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            debug!("创建DoH客户端失败: {}", e);
+            return Propagation { verified: false, latency_ms: 0, resolver: None };
+        }
+    };
+
+    loop {
+        for (name, url) in DOH_RESOLVERS {
+            match lookup(&client, url, domain).await {
+                Ok(addresses) if addresses.contains(&ip) => {
+                    return Propagation {
+                        verified: true,
+                        latency_ms: written_at.elapsed().as_millis() as u64,
+                        resolver: Some(name.to_string()),
+                    };
+                }
+                Ok(_) => debug!("{} 尚未返回 {} 的新地址", name, domain),
+                Err(e) => debug!("通过 {} 查询 {} 失败: {}", name, domain, e),
+            }
+        }
+        if written_at.elapsed() + POLL_INTERVAL > VERIFY_TIMEOUT {
+            return Propagation {
+                verified: false,
+                latency_ms: written_at.elapsed().as_millis() as u64,
+                resolver: None,
+            };
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// 通过指定的DoH解析器查询域名的AAAA地址
+pub async fn lookup(client: &reqwest::Client, url: &str, domain: &str) -> Result<Vec<Ipv6Addr>> {
+    let response: DohResponse = client
+        .get(url)
+        .query(&[("name", domain), ("type", "AAAA")])
+        .header("accept", "application/dns-json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    response.addresses()
+}
+
+/// 汇总一次更新中各域名的确认结果：全部生效时为已确认，延迟取最慢的一个；没有确认过的域名时返回None
+pub fn summarize<'a>(results: impl IntoIterator<Item = &'a Propagation>) -> Option<(bool, u64)> {
+    results.into_iter().fold(None, |acc, p| {
+        let (verified, latency) = acc.unwrap_or((true, 0));
+        Some((verified && p.verified, latency.max(p.latency_ms)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doh_answer_and_summary() {
+        let response: DohResponse = serde_json::from_str(
+            r#"{"Status":0,"Answer":[
+                {"name":"home.example.com","type":5,"TTL":300,"data":"other.example.com."},
+                {"name":"home.example.com","type":28,"TTL":300,"data":"2001:db8:0:0::1"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(response.addresses().unwrap(), vec!["2001:db8::1".parse::<Ipv6Addr>().unwrap()]);
+
+        let nxdomain: DohResponse = serde_json::from_str(r#"{"Status":3}"#).unwrap();
+        assert!(nxdomain.addresses().unwrap().is_empty());
+        let servfail: DohResponse = serde_json::from_str(r#"{"Status":2}"#).unwrap();
+        assert!(servfail.addresses().is_err());
+
+        let fast = Propagation { verified: true, latency_ms: 800, resolver: Some("1.1.1.1".to_string()) };
+        let slow = Propagation { verified: false, latency_ms: 60000, resolver: None };
+        assert_eq!(summarize([&fast]), Some((true, 800)));
+        assert_eq!(summarize([&fast, &slow]), Some((false, 60000)));
+        assert_eq!(summarize([]), None);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::services::notify::NotifyEvent;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const SEND_URL: &str = "https://www.pushplus.plus/send";
+
+/// 接口的响应，code 为200表示发送成功
+#[derive(Debug, Deserialize)]
+struct PushPlusResponse {
+    code: i64,
+    #[serde(default)]
+    msg: String,
+}
+
+/// 校验令牌
+pub fn validate(token: &str) -> Result<()> {
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(anyhow!("PushPlus令牌无效，请填写个人中心中的用户token"));
+    }
+    Ok(())
+}
+
+/// 以Markdown模板发送，正文逐段列出
+fn payload(token: &str, event: &NotifyEvent) -> Value {
+    let lines = event.lines();
+    json!({
+        "token": token,
+        "title": event.title(),
+        "content": if lines.is_empty() { event.title() } else { lines.join("\n\n") },
+        "template": "markdown",
+    })
+}
+
+/// 推送到微信公众号
+pub async fn send(client: &Client, token: &str, event: &NotifyEvent) -> Result<()> {
+    let response: PushPlusResponse = client
+        .post(SEND_URL)
+        .json(&payload(token, event))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if response.code != 200 {
+        return Err(anyhow!("PushPlus返回错误 {}: {}", response.code, response.msg));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notify::NotifyKind;
+    use chrono::Utc;
+
+    #[test]
+    fn test_pushplus_payload() {
+        assert!(validate("0123456789abcdef0123456789abcdef").is_ok());
+        assert!(validate("token with spaces").is_err());
+
+        let event = NotifyEvent::new(NotifyKind::Recovered, "example.com", Utc::now());
+        assert_eq!(
+            payload("abc", &event),
+            json!({
+                "token": "abc",
+                "title": "✅ example.com DNS更新已恢复",
+                "content": "✅ example.com DNS更新已恢复",
+                "template": "markdown",
+            })
+        );
+    }
+}
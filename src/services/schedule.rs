@@ -0,0 +1,211 @@
+use crate::config::database::AppConfig;
+use std::time::{Duration, Instant};
+
+/// 判断检查是否到期时允许的定时误差
+const TICK_TOLERANCE: Duration = Duration::from_secs(1);
+/// 连续多少次检查无法连接网络后判定为离线
+pub const OFFLINE_AFTER_FAILURES: u32 = 3;
+/// 离线期间检查间隔的上限，常规间隔更长时使用常规间隔
+const MAX_OFFLINE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 突发检查参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurstSettings {
+    /// 常规检查间隔
+    pub base: Duration,
+    /// 突发期间的检查间隔，为0或不小于常规间隔时不启用突发模式
+    pub burst: Duration,
+    /// IP变化后保持突发检查的时长
+    pub window: Duration,
+}
+
+impl BurstSettings {
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self {
+            base: Duration::from_secs(config.check_interval),
+            burst: Duration::from_secs(config.burst_interval),
+            window: Duration::from_secs(config.burst_duration * 60),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.burst.is_zero() && self.burst < self.base && !self.window.is_zero()
+    }
+
+    /// 定时任务的触发间隔：启用突发模式时按突发间隔触发，由调度状态决定是否真正检查
+    pub fn tick(&self) -> Duration {
+        if self.enabled() {
+            self.burst
+        } else {
+            self.base
+        }
+    }
+}
+
+/// 自适应检查调度：平时按常规间隔检查，检测到IP变化后在窗口期内高频检查，
+/// 窗口结束后间隔逐次翻倍，逐步回落到常规间隔；
+/// 持续无法连接网络时判定为离线，检查间隔逐次翻倍直到上限，恢复连接后回到正常节奏
+#[derive(Debug, Default)]
+pub struct AdaptiveSchedule {
+    last_check: Option<Instant>,
+    burst_until: Option<Instant>,
+    interval: Option<Duration>,
+    unreachable_count: u32,            // 连续无法连接网络的检查次数
+    offline_interval: Option<Duration>, // 离线期间的检查间隔，未离线时为None
+}
+
+impl AdaptiveSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前生效的检查间隔
+    pub fn current_interval(&self, settings: &BurstSettings) -> Duration {
+        if let Some(offline) = self.offline_interval {
+            return offline;
+        }
+        match self.interval {
+            Some(interval) if settings.enabled() => interval.min(settings.base),
+            _ => settings.base,
+        }
+    }
+
+    /// 按当前间隔计算的下一次检查时间，尚未检查过时为None
+    pub fn next_due(&self, settings: &BurstSettings) -> Option<Instant> {
+        self.last_check.map(|last| last + self.current_interval(settings))
+    }
+
+    /// 本次触发是否需要执行检查
+    pub fn is_due(&self, now: Instant, settings: &BurstSettings) -> bool {
+        match self.last_check {
+            Some(last) => now.saturating_duration_since(last) + TICK_TOLERANCE >= self.current_interval(settings),
+            None => true,
+        }
+    }
+
+    /// 记录一次检查，突发窗口结束后每次检查将间隔翻倍
+    pub fn mark_checked(&mut self, now: Instant, settings: &BurstSettings) {
+        self.last_check = Some(now);
+
+        let (Some(interval), Some(until)) = (self.interval, self.burst_until) else {
+            return;
+        };
+        if now < until {
+            return;
+        }
+
+        let next = interval * 2;
+        if next >= settings.base {
+            self.interval = None;
+            self.burst_until = None;
+        } else {
+            self.interval = Some(next);
+        }
+    }
+
+    /// 检测到IP变化，进入突发检查
+    pub fn note_change(&mut self, now: Instant, settings: &BurstSettings) {
+        if !settings.enabled() {
+            return;
+        }
+        self.burst_until = Some(now + settings.window);
+        self.interval = Some(settings.burst);
+    }
+
+    /// 是否处于突发窗口或回落阶段
+    pub fn is_bursting(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// 记录一次无法连接网络的检查，连续达到阈值后进入离线状态，之后每次将检查间隔翻倍
+    /// 返回本次是否刚进入离线状态
+    pub fn note_unreachable(&mut self, settings: &BurstSettings) -> bool {
+        self.unreachable_count += 1;
+        if self.unreachable_count < OFFLINE_AFTER_FAILURES {
+            return false;
+        }
+
+        let limit = MAX_OFFLINE_INTERVAL.max(settings.base);
+        let entering = self.offline_interval.is_none();
+        let next = self.offline_interval.unwrap_or(settings.base) * 2;
+        self.offline_interval = Some(next.min(limit));
+        entering
+    }
+
+    /// 记录一次网络正常的检查，返回此前是否处于离线状态
+    pub fn note_reachable(&mut self) -> bool {
+        self.unreachable_count = 0;
+        self.offline_interval.take().is_some()
+    }
+
+    /// 是否处于离线退避状态
+    pub fn is_offline(&self) -> bool {
+        self.offline_interval.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_decay_to_base() {
+        let settings = BurstSettings {
+            base: Duration::from_secs(600),
+            burst: Duration::from_secs(30),
+            window: Duration::from_secs(120),
+        };
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let mut schedule = AdaptiveSchedule::new();
+
+        assert!(schedule.is_due(at(0), &settings));
+        schedule.mark_checked(at(0), &settings);
+        assert!(!schedule.is_due(at(30), &settings));
+
+        schedule.note_change(at(0), &settings);
+        assert!(schedule.is_due(at(30), &settings));
+
+        // 窗口内保持突发间隔
+        schedule.mark_checked(at(90), &settings);
+        assert_eq!(schedule.current_interval(&settings), Duration::from_secs(30));
+
+        // 窗口结束后逐次翻倍：60 -> 120 -> 240 -> 480 -> 常规间隔
+        let mut now = 120;
+        for expected in [60, 120, 240, 480] {
+            schedule.mark_checked(at(now), &settings);
+            assert_eq!(schedule.current_interval(&settings), Duration::from_secs(expected));
+            now += expected;
+        }
+        schedule.mark_checked(at(now), &settings);
+        assert_eq!(schedule.current_interval(&settings), settings.base);
+        assert!(!schedule.is_bursting());
+    }
+
+    #[test]
+    fn test_offline_backoff_and_recovery() {
+        let settings = BurstSettings {
+            base: Duration::from_secs(600),
+            burst: Duration::ZERO,
+            window: Duration::ZERO,
+        };
+        let mut schedule = AdaptiveSchedule::new();
+
+        // 偶尔一两次失败不会延长间隔
+        assert!(!schedule.note_unreachable(&settings));
+        assert!(!schedule.note_unreachable(&settings));
+        assert_eq!(schedule.current_interval(&settings), settings.base);
+
+        assert!(schedule.note_unreachable(&settings));
+        assert_eq!(schedule.current_interval(&settings), Duration::from_secs(1200));
+        assert!(!schedule.note_unreachable(&settings));
+        assert_eq!(schedule.current_interval(&settings), Duration::from_secs(2400));
+        schedule.note_unreachable(&settings);
+        assert_eq!(schedule.current_interval(&settings), MAX_OFFLINE_INTERVAL);
+
+        assert!(schedule.note_reachable());
+        assert!(!schedule.is_offline());
+        assert_eq!(schedule.current_interval(&settings), settings.base);
+        assert!(!schedule.note_reachable());
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+/// 定时任务每次触发时执行的Future
+pub type TaskFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// 周期执行的任务
+pub type RepeatedTask = Arc<dyn Fn() -> TaskFuture + Send + Sync>;
+
+/// 调度器操作返回的Future
+pub type SchedulerFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// 定时任务调度器，监控服务通过它注册周期检查任务，测试中可替换为手动触发的调度器
+pub trait Scheduler: Send {
+    /// 注册按固定间隔重复执行的任务
+    fn add_repeated(&mut self, interval: Duration, task: RepeatedTask) -> SchedulerFuture<'_>;
+
+    /// 开始调度已注册的任务
+    fn start(&mut self) -> SchedulerFuture<'_>;
+
+    /// 移除所有已注册的任务，调度器保持运行，之后注册的任务立即生效
+    fn clear(&mut self) -> SchedulerFuture<'_>;
+
+    /// 停止调度
+    fn shutdown(&mut self) -> SchedulerFuture<'_>;
+}
+
+/// 基于 tokio-cron-scheduler 的调度器
+pub struct CronScheduler {
+    inner: JobScheduler,
+    started: bool,
+}
+
+impl CronScheduler {
+    pub async fn new() -> Result<Self> {
+        Ok(Self {
+            inner: JobScheduler::new().await?,
+            started: false,
+        })
+    }
+}
+
+impl Scheduler for CronScheduler {
+    fn add_repeated(&mut self, interval: Duration, task: RepeatedTask) -> SchedulerFuture<'_> {
+        Box::pin(async move {
+            let job = Job::new_repeated_async(interval, move |_uuid, _l| task())?;
+            self.inner.add(job).await?;
+            Ok(())
+        })
+    }
+
+    fn start(&mut self) -> SchedulerFuture<'_> {
+        Box::pin(async move {
+            self.inner.start().await?;
+            self.started = true;
+            Ok(())
+        })
+    }
+
+    fn clear(&mut self) -> SchedulerFuture<'_> {
+        // 停止旧的调度器并换成新的，避免逐个记录任务ID
+        Box::pin(async move {
+            self.inner.shutdown().await?;
+            self.inner = JobScheduler::new().await?;
+            if self.started {
+                self.inner.start().await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn shutdown(&mut self) -> SchedulerFuture<'_> {
+        Box::pin(async move {
+            self.inner.shutdown().await?;
+            self.started = false;
+            Ok(())
+        })
+    }
+}
+
+/// 手动触发的调度器，只记录注册的任务，由调用方决定何时执行，用于测试
+#[derive(Clone, Default)]
+pub struct ManualScheduler {
+    tasks: Arc<Mutex<Vec<(Duration, RepeatedTask)>>>,
+}
+
+impl ManualScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已注册任务的触发间隔
+    pub fn intervals(&self) -> Vec<Duration> {
+        self.tasks.lock().unwrap().iter().map(|(interval, _)| *interval).collect()
+    }
+
+    /// 依次执行所有已注册的任务一次
+    pub async fn fire(&self) {
+        let tasks: Vec<RepeatedTask> = self.tasks.lock().unwrap().iter().map(|(_, task)| task.clone()).collect();
+        for task in tasks {
+            task().await;
+        }
+    }
+}
+
+impl Scheduler for ManualScheduler {
+    fn add_repeated(&mut self, interval: Duration, task: RepeatedTask) -> SchedulerFuture<'_> {
+        self.tasks.lock().unwrap().push((interval, task));
+        Box::pin(async { Ok(()) })
+    }
+
+    fn start(&mut self) -> SchedulerFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+
+    fn clear(&mut self) -> SchedulerFuture<'_> {
+        self.tasks.lock().unwrap().clear();
+        Box::pin(async { Ok(()) })
+    }
+
+    fn shutdown(&mut self) -> SchedulerFuture<'_> {
+        self.tasks.lock().unwrap().clear();
+        Box::pin(async { Ok(()) })
+    }
+}
@@ -0,0 +1,85 @@
+use crate::services::notify::NotifyEvent;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde::Deserialize;
+
+/// 消息标题最长32个字符
+const MAX_TITLE_CHARS: usize = 32;
+
+/// 接口的响应，code 为0表示发送成功
+#[derive(Debug, Deserialize)]
+struct ServerChanResponse {
+    code: i64,
+    #[serde(default)]
+    message: String,
+}
+
+/// 校验SendKey
+pub fn validate(key: &str) -> Result<()> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(anyhow!("Server酱SendKey无效，请填写形如 SCT... 或 sctp... 的SendKey"));
+    }
+    Ok(())
+}
+
+/// 发送地址：Server酱³的Key形如 sctp<uid>t...，发送到用户专属域名；其他为Turbo版
+fn send_url(key: &str) -> String {
+    if let Some(rest) = key.strip_prefix("sctp") {
+        let uid: String = rest.chars().take_while(char::is_ascii_digit).collect();
+        if !uid.is_empty() {
+            return format!("https://{}.push.ft07.com/send/{}.send", uid, key);
+        }
+    }
+    format!("https://sctapi.ftqq.com/{}.send", key)
+}
+
+/// 标题和Markdown正文
+fn message(event: &NotifyEvent) -> (String, String) {
+    let title: String = event.title().chars().take(MAX_TITLE_CHARS).collect();
+    let lines = event.lines();
+    let desp = if lines.is_empty() { event.title() } else { lines.join("\n\n") };
+    (title, desp)
+}
+
+/// 推送到微信
+pub async fn send(client: &Client, key: &str, event: &NotifyEvent) -> Result<()> {
+    let (title, desp) = message(event);
+    let response: ServerChanResponse = client
+        .post(send_url(key))
+        .form(&[("title", title), ("desp", desp)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if response.code != 0 {
+        return Err(anyhow!("Server酱返回错误 {}: {}", response.code, response.message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notify::NotifyKind;
+    use chrono::Utc;
+
+    #[test]
+    fn test_serverchan_message() {
+        assert!(validate("SCT12345TAbC").is_ok());
+        assert!(validate("https://sctapi.ftqq.com/SCT12345TAbC.send").is_err());
+        assert_eq!(send_url("SCT12345TAbC"), "https://sctapi.ftqq.com/SCT12345TAbC.send");
+        assert_eq!(send_url("sctp678tAbC"), "https://678.push.ft07.com/send/sctp678tAbC.send");
+
+        let mut event = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        event.old_ip = Some("2001:db8::1".to_string());
+        event.new_ip = Some("2001:db8::2".to_string());
+        assert_eq!(
+            message(&event),
+            (
+                "🔄 example.com IPv6地址已变化".to_string(),
+                "旧地址: 2001:db8::1\n\n新地址: 2001:db8::2".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::config::database::SlackSettings;
+use crate::services::notify::NotifyEvent;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde_json::{json, Value};
+
+/// 校验Slack设置
+pub fn validate(settings: &SlackSettings) -> Result<()> {
+    let url = settings.webhook_url.trim();
+    let parsed = reqwest::Url::parse(url).map_err(|_| anyhow!("Slack Webhook地址无效: {}", url))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(anyhow!("Slack Webhook地址应形如 https://hooks.slack.com/services/..."));
+    }
+    if let Some(channel) = &settings.channel {
+        if channel.trim().is_empty() || channel.contains(char::is_whitespace) {
+            return Err(anyhow!("Slack频道无效: {}", channel));
+        }
+    }
+    if settings.template.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        return Err(anyhow!("Slack消息模板不能为空"));
+    }
+    Ok(())
+}
+
+/// Slack的mrkdwn中 &、<、> 需要转义，否则会被当作链接或提及
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// 未设置模板时标题加粗、正文逐行列出；模板中的占位符值同样转义
+fn payload(settings: &SlackSettings, event: &NotifyEvent) -> Value {
+    let text = match &settings.template {
+        Some(template) => event.render(template, escape),
+        None => {
+            let mut text = format!("*{}*", escape(&event.title()));
+            for line in event.lines() {
+                text.push('\n');
+                text.push_str(&escape(&line));
+            }
+            text
+        }
+    };
+    let mut payload = json!({ "text": text });
+    if let Some(channel) = &settings.channel {
+        payload["channel"] = Value::String(channel.trim().to_string());
+    }
+    payload
+}
+
+/// 发送到Incoming Webhook，Slack成功时返回 ok
+pub async fn send(client: &Client, settings: &SlackSettings, event: &NotifyEvent) -> Result<()> {
+    client
+        .post(settings.webhook_url.trim())
+        .json(&payload(settings, event))
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::notify::NotifyKind;
+    use chrono::Utc;
+
+    #[test]
+    fn test_slack_payload() {
+        let mut settings = SlackSettings {
+            webhook_url: "https://hooks.slack.com/services/T0/B0/x".to_string(),
+            channel: Some("#ops".to_string()),
+            template: None,
+        };
+        assert!(validate(&settings).is_ok());
+
+        let mut event = NotifyEvent::new(NotifyKind::UpdateFailed, "example.com", Utc::now());
+        event.message = Some("状态: failing，<超时>".to_string());
+        assert_eq!(
+            payload(&settings, &event),
+            json!({"text": "*❌ example.com DNS更新失败*\n状态: failing，&lt;超时&gt;", "channel": "#ops"})
+        );
+
+        settings.channel = None;
+        settings.template = Some(":warning: {root_domain}: {message}".to_string());
+        assert_eq!(payload(&settings, &event), json!({"text": ":warning: example.com: 状态: failing，&lt;超时&gt;"}));
+
+        settings.channel = Some("ops team".to_string());
+        assert!(validate(&settings).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use anyhow::Result;
+use reqwest::Client;
+
+/// 服务整体同步状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    /// 程序启动后尚未完成任何检查
+    #[default]
+    Unknown,
+    /// 所有域名均已同步
+    InSync,
+    /// 部分域名更新失败
+    Degraded,
+    /// IP检测失败或所有域名更新失败
+    Failing,
+    /// 持续无法连接网络，已延长检查间隔
+    Offline,
+}
+
+impl ServiceStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceStatus::Unknown => "unknown",
+            ServiceStatus::InSync => "in_sync",
+            ServiceStatus::Degraded => "degraded",
+            ServiceStatus::Failing => "failing",
+            ServiceStatus::Offline => "offline",
+        }
+    }
+
+    /// 根据一次更新周期的成功数和总数计算状态
+    pub fn from_counts(success_count: i32, total_count: i32) -> Self {
+        if success_count >= total_count {
+            ServiceStatus::InSync
+        } else if success_count > 0 {
+            ServiceStatus::Degraded
+        } else {
+            ServiceStatus::Failing
+        }
+    }
+}
+
+/// 一次状态转换
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusTransition {
+    pub from: ServiceStatus,
+    pub to: ServiceStatus,
+    pub reason: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 状态机，只在状态发生变化时产生转换事件
+#[derive(Clone, Default)]
+pub struct StatusTracker {
+    current: Arc<Mutex<ServiceStatus>>,
+}
+
+impl StatusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 当前状态
+    pub fn current(&self) -> ServiceStatus {
+        *self.current.lock().unwrap()
+    }
+
+    /// 观察到新的状态，发生变化时返回转换事件
+    pub fn observe(&self, status: ServiceStatus, reason: Option<String>) -> Option<StatusTransition> {
+        let mut current = self.current.lock().unwrap();
+        if *current == status {
+            return None;
+        }
+
+        let transition = StatusTransition {
+            from: *current,
+            to: status,
+            reason,
+            timestamp: Utc::now(),
+        };
+        *current = status;
+        Some(transition)
+    }
+}
+
+/// Webhook推送的事件内容
+#[derive(Debug, Serialize)]
+struct StatusWebhookPayload<'a> {
+    event: &'static str,
+    root_domain: &'a str,
+    #[serde(flatten)]
+    transition: &'a StatusTransition,
+}
+
+/// 向配置的Webhook地址推送状态转换事件
+pub async fn send_status_webhook(client: &Client, url: &str, root_domain: &str, transition: &StatusTransition) -> Result<()> {
+    client
+        .post(url)
+        .json(&StatusWebhookPayload {
+            event: "status_changed",
+            root_domain,
+            transition,
+        })
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_transitions_emit_events() {
+        let tracker = StatusTracker::new();
+
+        let event = tracker.observe(ServiceStatus::InSync, None).unwrap();
+        assert_eq!((event.from, event.to), (ServiceStatus::Unknown, ServiceStatus::InSync));
+        assert!(tracker.observe(ServiceStatus::InSync, None).is_none());
+
+        let event = tracker.observe(ServiceStatus::from_counts(1, 2), Some("部分失败".to_string())).unwrap();
+        assert_eq!(event.to, ServiceStatus::Degraded);
+        assert_eq!(ServiceStatus::from_counts(0, 2), ServiceStatus::Failing);
+    }
+}
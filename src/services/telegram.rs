@@ -0,0 +1,82 @@
+use crate::config::database::TelegramSettings;
+use crate::services::notify::NotifyEvent;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+const DEFAULT_API_URL: &str = "https://api.telegram.org";
+
+/// Bot API的响应，失败时 description 为原因
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// 校验Telegram设置
+pub fn validate(settings: &TelegramSettings) -> Result<()> {
+    let token = settings.bot_token.trim();
+    let valid_token = token
+        .split_once(':')
+        .is_some_and(|(id, secret)| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) && !secret.is_empty());
+    if !valid_token {
+        return Err(anyhow!("Telegram机器人令牌格式无效，应形如 123456:ABC-DEF"));
+    }
+    if settings.chat_id.trim().is_empty() {
+        return Err(anyhow!("Telegram通知需要设置聊天ID"));
+    }
+    if let Some(api_url) = &settings.api_url {
+        reqwest::Url::parse(api_url).map_err(|_| anyhow!("Telegram API地址无效: {}", api_url))?;
+    }
+    Ok(())
+}
+
+/// sendMessage 的完整地址，令牌是路径的一部分
+fn send_message_url(settings: &TelegramSettings) -> String {
+    let api_url = settings.api_url.as_deref().unwrap_or(DEFAULT_API_URL).trim_end_matches('/');
+    format!("{}/bot{}/sendMessage", api_url, settings.bot_token.trim())
+}
+
+/// 以纯文本发送通知，不使用Markdown以免域名和错误信息中的符号需要转义
+pub async fn send(client: &Client, settings: &TelegramSettings, event: &NotifyEvent) -> Result<()> {
+    let response: TelegramResponse = client
+        .post(send_message_url(settings))
+        .json(&json!({
+            "chat_id": settings.chat_id.trim(),
+            "text": event.text(),
+            "disable_web_page_preview": true,
+        }))
+        .send()
+        .await?
+        .json()
+        .await?;
+    if !response.ok {
+        return Err(anyhow!("Telegram返回错误: {}", response.description.unwrap_or_default()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_telegram_settings() {
+        let mut settings = TelegramSettings {
+            bot_token: "123456:ABC-DEF".to_string(),
+            chat_id: "@home".to_string(),
+            daily_summary: false,
+            api_url: None,
+        };
+        assert!(validate(&settings).is_ok());
+        assert_eq!(send_message_url(&settings), "https://api.telegram.org/bot123456:ABC-DEF/sendMessage");
+
+        settings.api_url = Some("https://tg.example.com/".to_string());
+        assert_eq!(send_message_url(&settings), "https://tg.example.com/bot123456:ABC-DEF/sendMessage");
+
+        settings.bot_token = "ABC-DEF".to_string();
+        assert!(validate(&settings).is_err());
+    }
+}
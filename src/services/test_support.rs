@@ -0,0 +1,45 @@
+use crate::config::database::{AppConfig, Database};
+use crate::services::cloudflare::{CloudflareClient, CloudflareConfig};
+use crate::services::config_service::ConfigService;
+use crate::utils::clock::{ManualClock, SharedClock};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// 指向模拟Cloudflare API的客户端和服务，区域ID为 z，重试等待不实际休眠
+pub fn mock_cloudflare(server: &mockito::ServerGuard, subdomains: &[&str], dry_run: bool) -> (ConfigService, CloudflareClient, AppConfig) {
+    let clock: SharedClock = Arc::new(ManualClock::new(Utc::now()));
+    let config: AppConfig = serde_json::from_value(json!({
+        "cloudflare_api_key": "token", "cloudflare_zone_id": "z", "root_domain": "example.com",
+        "selected_subdomains": subdomains, "check_interval": 300, "dry_run": dry_run
+    }))
+    .unwrap();
+    let client = CloudflareClient::new(CloudflareConfig::from_app_config(&config))
+        .with_api_base(&server.url())
+        .with_clock(clock.clone());
+    (ConfigService::with_clock(Database::new(":memory:").unwrap(), clock), client, config)
+}
+
+/// Cloudflare API成功响应的响应体
+pub fn success_body(result: Value) -> String {
+    json!({ "success": true, "errors": [], "messages": [], "result": result }).to_string()
+}
+
+/// 模拟某个域名的AAAA记录列表
+pub async fn mock_records(server: &mut mockito::ServerGuard, name: &str, records: Value) -> mockito::Mock {
+    let count = records.as_array().map_or(0, Vec::len);
+    server
+        .mock("GET", "/zones/z/dns_records")
+        .match_query(mockito::Matcher::UrlEncoded("name".into(), name.into()))
+        .with_body(json!({
+            "success": true, "errors": [], "messages": [], "result": records,
+            "result_info": { "page": 1, "per_page": 100, "count": count, "total_count": count, "total_pages": 1 }
+        }).to_string())
+        .create_async()
+        .await
+}
+
+/// 一条AAAA记录的API表示
+pub fn record_json(id: &str, name: &str, content: &str) -> Value {
+    json!({ "id": id, "name": name, "type": "AAAA", "content": content, "proxied": false, "ttl": 1 })
+}
@@ -0,0 +1,28 @@
+use crate::services::status::ServiceStatus;
+use anyhow::Result;
+use reqwest::{Client, Url};
+
+/// 向Uptime Kuma推送监控心跳，降级状态仍视为在线
+pub async fn push(client: &Client, push_url: &str, status: ServiceStatus, message: &str) -> Result<()> {
+    let kuma_status = match status {
+        ServiceStatus::Failing | ServiceStatus::Offline => "down",
+        _ => "up",
+    };
+
+    // 推送地址通常自带 ?status=up&msg=OK&ping= 参数，这里替换为本次结果
+    let mut url = Url::parse(push_url)?;
+    let retained: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "status" && key != "msg" && key != "ping")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(retained)
+        .append_pair("status", kuma_status)
+        .append_pair("msg", message);
+
+    client.get(url).send().await?.error_for_status()?;
+
+    Ok(())
+}
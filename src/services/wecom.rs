@@ -0,0 +1,93 @@
+use crate::services::notify::{NotifyEvent, NotifyKind};
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Markdown消息内容最长4096字节
+const MAX_CONTENT_BYTES: usize = 4096;
+
+/// 接口的响应，HTTP状态码总是200，errcode 非0表示失败
+#[derive(Debug, Deserialize)]
+struct WecomResponse {
+    errcode: i64,
+    #[serde(default)]
+    errmsg: String,
+}
+
+/// 校验群机器人地址，形如 https://qyapi.weixin.qq.com/cgi-bin/webhook/send?key=<key>
+pub fn validate(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| anyhow!("企业微信机器人地址无效: {}", url))?;
+    let has_key = parsed.query_pairs().any(|(name, value)| name == "key" && !value.is_empty());
+    if !matches!(parsed.scheme(), "http" | "https") || !has_key {
+        return Err(anyhow!("企业微信机器人地址应形如 https://qyapi.weixin.qq.com/cgi-bin/webhook/send?key=<key>"));
+    }
+    Ok(())
+}
+
+/// 按字节截断到字符边界
+fn truncate_bytes(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Markdown消息：标题按事件着色，正文为引用块
+fn payload(event: &NotifyEvent) -> Value {
+    let color = match event.kind {
+        NotifyKind::UpdateFailed | NotifyKind::IpMismatch => "warning",
+        NotifyKind::DailySummary => "comment",
+        _ => "info",
+    };
+    let mut content = format!("<font color=\"{}\">**{}**</font>", color, event.title());
+    for line in event.lines() {
+        content.push_str("\n> ");
+        content.push_str(&line);
+    }
+    json!({
+        "msgtype": "markdown",
+        "markdown": { "content": truncate_bytes(&content, MAX_CONTENT_BYTES) },
+    })
+}
+
+/// 发送到群机器人
+pub async fn send(client: &Client, url: &str, event: &NotifyEvent) -> Result<()> {
+    let response: WecomResponse = client
+        .post(url)
+        .json(&payload(event))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if response.errcode != 0 {
+        return Err(anyhow!("企业微信返回错误 {}: {}", response.errcode, response.errmsg));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_wecom_payload() {
+        assert!(validate("https://qyapi.weixin.qq.com/cgi-bin/webhook/send?key=693a91f6").is_ok());
+        assert!(validate("https://qyapi.weixin.qq.com/cgi-bin/webhook/send").is_err());
+
+        let mut event = NotifyEvent::new(NotifyKind::IpChanged, "example.com", Utc::now());
+        event.old_ip = Some("2001:db8::1".to_string());
+        event.new_ip = Some("2001:db8::2".to_string());
+        assert_eq!(
+            payload(&event)["markdown"]["content"],
+            "<font color=\"info\">**🔄 example.com IPv6地址已变化**</font>\n> 旧地址: 2001:db8::1\n> 新地址: 2001:db8::2"
+        );
+        assert_eq!(truncate_bytes("地址", 4), "地");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::utils::network::{address_scope, prefer_stable, AddressInfo};
+use crate::utils::prefix_delegation;
+use anyhow::{Result, anyhow};
+use ipnetwork::Ipv6Network;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv6Addr};
+
+/// 有多个IPv6地址时选择发布哪一个
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// 系统访问公网时的首选源地址
+    #[default]
+    System,
+    /// 优先全局单播地址，没有时才使用其他候选地址
+    PreferGlobal,
+    /// 剩余有效期最长的全局单播地址，永久地址视为最长
+    LongestLifetime,
+}
+
+/// 地址选择策略：先按条件过滤候选地址，再按策略从中选择
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AddressSelection {
+    #[serde(default)]
+    pub strategy: SelectionStrategy,
+    #[serde(default)]
+    pub exclude_ula: bool,              // 排除唯一本地地址（fc00::/7）
+    #[serde(default, rename = "match")]
+    pub pattern: Option<String>,        // 只接受匹配的地址：CIDR前缀（如 2001:db8::/32）或正则表达式
+    #[serde(default = "default_skip_temporary")]
+    pub skip_temporary: bool,           // 优先使用稳定地址，跳过隐私扩展的临时地址和已弃用的地址
+    #[serde(default)]
+    pub suffix: Option<String>,         // 固定的接口标识（如 ::1234），发布 选中地址的/64前缀 + 该后缀
+}
+
+fn default_skip_temporary() -> bool {
+    true
+}
+
+impl Default for AddressSelection {
+    fn default() -> Self {
+        Self {
+            strategy: SelectionStrategy::default(),
+            exclude_ula: false,
+            pattern: None,
+            skip_temporary: default_skip_temporary(),
+            suffix: None,
+        }
+    }
+}
+
+/// 固定后缀替换的是地址的低64位（接口标识）
+const SUFFIX_PREFIX_LEN: u8 = 64;
+
+/// 地址匹配条件
+enum Matcher {
+    Prefix(Ipv6Network),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn parse(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        if let Ok(network) = pattern.parse::<Ipv6Network>() {
+            return Ok(Matcher::Prefix(network));
+        }
+        Regex::new(pattern)
+            .map(Matcher::Regex)
+            .map_err(|e| anyhow!("地址匹配条件既不是IPv6前缀也不是有效的正则表达式: {}", e))
+    }
+
+    fn matches(&self, ip: &Ipv6Addr) -> bool {
+        match self {
+            Matcher::Prefix(network) => network.contains(*ip),
+            Matcher::Regex(regex) => regex.is_match(&ip.to_string()),
+        }
+    }
+}
+
+impl AddressSelection {
+    /// 校验匹配条件
+    pub fn validate(&self) -> Result<()> {
+        if let Some(pattern) = self.pattern.as_deref().filter(|p| !p.trim().is_empty()) {
+            Matcher::parse(pattern)?;
+        }
+        if let Some(suffix) = self.pinned_suffix() {
+            prefix_delegation::parse_host_suffix(suffix, SUFFIX_PREFIX_LEN)?;
+        }
+        Ok(())
+    }
+
+    fn pinned_suffix(&self) -> Option<&str> {
+        self.suffix.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    /// 配置了固定后缀时，用地址的/64前缀拼接该后缀；前缀变化后发布的地址随之变化而后缀保持不变
+    pub fn apply_suffix(&self, ip: Ipv6Addr) -> Result<Ipv6Addr> {
+        let Some(suffix) = self.pinned_suffix() else {
+            return Ok(ip);
+        };
+        let suffix = prefix_delegation::parse_host_suffix(suffix, SUFFIX_PREFIX_LEN)?;
+        Ok(prefix_delegation::combine(ip, SUFFIX_PREFIX_LEN, suffix))
+    }
+
+    /// 符合过滤条件（排除ULA、匹配条件、跳过临时地址）的候选地址，保持原有顺序
+    fn accepted(&self, candidates: &[AddressInfo]) -> Result<Vec<AddressInfo>> {
+        let matcher = match self.pattern.as_deref().filter(|p| !p.trim().is_empty()) {
+            Some(pattern) => Some(Matcher::parse(pattern)?),
+            None => None,
+        };
+        let accepted: Vec<AddressInfo> = candidates
+            .iter()
+            .filter(|info| !(self.exclude_ula && scope(&info.address) == "unique_local"))
+            .filter(|info| matcher.as_ref().is_none_or(|m| m.matches(&info.address)))
+            .cloned()
+            .collect();
+        // 只有临时地址时仍然使用临时地址
+        Ok(if self.skip_temporary { prefer_stable(accepted) } else { accepted })
+    }
+
+    /// 发布所有地址时使用：所有符合过滤条件的地址（已拼接固定后缀并去重），不按策略挑选
+    pub fn qualifying(&self, candidates: &[AddressInfo]) -> Result<Vec<Ipv6Addr>> {
+        let mut addresses: Vec<Ipv6Addr> = Vec::new();
+        for info in self.accepted(candidates)? {
+            let ip = self.apply_suffix(info.address)?;
+            if !addresses.contains(&ip) {
+                addresses.push(ip);
+            }
+        }
+        Ok(addresses)
+    }
+
+    /// 从候选地址中选择要发布的地址，preferred为系统首选地址
+    pub fn select(&self, candidates: &[AddressInfo], preferred: Option<Ipv6Addr>) -> Result<Ipv6Addr> {
+        if candidates.is_empty() {
+            return Err(anyhow!("没有可发布的IPv6地址（全局单播或唯一本地地址）"));
+        }
+        let accepted = self.accepted(candidates)?;
+        let accepted: Vec<&AddressInfo> = accepted.iter().collect();
+        let global: Vec<&AddressInfo> = accepted
+            .iter()
+            .copied()
+            .filter(|info| scope(&info.address) == "global")
+            .collect();
+
+        // 首选地址在范围内时优先使用，否则使用范围内的第一个
+        let pick = |pool: &[&AddressInfo]| {
+            pool.iter()
+                .find(|info| Some(info.address) == preferred)
+                .or(pool.first())
+                .map(|info| info.address)
+        };
+        let selected = match self.strategy {
+            SelectionStrategy::System => pick(&accepted),
+            SelectionStrategy::PreferGlobal => pick(&global).or_else(|| pick(&accepted)),
+            SelectionStrategy::LongestLifetime => global
+                .iter()
+                .max_by_key(|info| (info.valid_lifetime.unwrap_or(u64::MAX), Some(info.address) == preferred))
+                .map(|info| info.address),
+        };
+        selected.ok_or_else(|| {
+            let listed: Vec<String> = candidates.iter().map(|info| info.address.to_string()).collect();
+            anyhow!("没有符合地址选择策略的IPv6地址（候选地址: {}）", listed.join(", "))
+        })
+    }
+}
+
+fn scope(ip: &Ipv6Addr) -> &'static str {
+    address_scope(&IpAddr::V6(*ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(address: &str, valid_lifetime: Option<u64>) -> AddressInfo {
+        AddressInfo {
+            interface: "eth0".to_string(),
+            address: address.parse().unwrap(),
+            valid_lifetime,
+            temporary: false,
+            deprecated: false,
+        }
+    }
+
+    #[test]
+    fn test_address_selection() {
+        let ula = info("fd00::2", None);
+        let short = info("2001:db8:1::10", Some(3600));
+        let long = info("2001:db8:2::10", Some(86400));
+        let candidates = [ula.clone(), short.clone(), long.clone()];
+        let preferred = Some(ula.address);
+
+        let select = |selection: AddressSelection| selection.select(&candidates, preferred).unwrap();
+        let temporary = AddressInfo { temporary: true, ..info("2001:db8:1::beef", Some(600)) };
+        assert_eq!(select(AddressSelection::default()), ula.address);
+        assert_eq!(select(AddressSelection { exclude_ula: true, ..Default::default() }), short.address);
+        assert_eq!(
+            select(AddressSelection { strategy: SelectionStrategy::PreferGlobal, ..Default::default() }),
+            short.address
+        );
+        assert_eq!(
+            select(AddressSelection { strategy: SelectionStrategy::LongestLifetime, ..Default::default() }),
+            long.address
+        );
+        assert_eq!(
+            select(AddressSelection { pattern: Some("2001:db8:2::/48".to_string()), ..Default::default() }),
+            long.address
+        );
+        assert_eq!(
+            select(AddressSelection { pattern: Some("^2001:db8:1:".to_string()), ..Default::default() }),
+            short.address
+        );
+
+        // 系统首选临时地址时改用同一网段的稳定地址，关闭后照常使用临时地址
+        let with_temporary = [temporary.clone(), short.clone()];
+        assert_eq!(AddressSelection::default().select(&with_temporary, Some(temporary.address)).unwrap(), short.address);
+        let keep = AddressSelection { skip_temporary: false, ..Default::default() };
+        assert_eq!(keep.select(&with_temporary, Some(temporary.address)).unwrap(), temporary.address);
+        assert_eq!(AddressSelection::default().select(std::slice::from_ref(&temporary), None).unwrap(), temporary.address);
+
+        let pinned = AddressSelection { suffix: Some("::1234".to_string()), ..Default::default() };
+        assert_eq!(pinned.apply_suffix(temporary.address).unwrap(), "2001:db8:1::1234".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(AddressSelection::default().apply_suffix(temporary.address).unwrap(), temporary.address);
+        assert!(AddressSelection { suffix: Some("2001:db8::1".to_string()), ..Default::default() }.validate().is_err());
+        assert!(AddressSelection { suffix: Some("1234".to_string()), ..Default::default() }.validate().is_err());
+
+        // 发布所有地址：不按策略挑选，同一/64内的地址拼接固定后缀后合并为一个
+        let excluding_ula = AddressSelection { exclude_ula: true, ..Default::default() };
+        assert_eq!(excluding_ula.qualifying(&candidates).unwrap(), vec![short.address, long.address]);
+        assert_eq!(AddressSelection::default().qualifying(&with_temporary).unwrap(), vec![short.address]);
+        let pinned_all = AddressSelection { skip_temporary: false, ..pinned.clone() };
+        assert_eq!(pinned_all.qualifying(&with_temporary).unwrap(), vec!["2001:db8:1::1234".parse::<Ipv6Addr>().unwrap()]);
+
+        let none = AddressSelection { pattern: Some("2001:db8:9::/48".to_string()), ..Default::default() };
+        assert!(none.select(&candidates, preferred).is_err());
+        assert!(AddressSelection { pattern: Some("(".to_string()), ..Default::default() }.validate().is_err());
+    }
+}
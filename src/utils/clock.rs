@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 等待结束的Future
+pub type SleepFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// 共享的时钟实例
+pub type SharedClock = Arc<dyn Clock>;
+
+/// 时间来源，冷却、重试、调度等依赖时间的逻辑通过它读取时间和等待，
+/// 测试中可替换为手动时钟，无需真实等待
+pub trait Clock: Send + Sync {
+    /// 单调时间，用于计算间隔
+    fn now(&self) -> Instant;
+
+    /// 当前UTC时间，用于记录和比较时间点
+    fn utc_now(&self) -> DateTime<Utc>;
+
+    /// 等待指定时长
+    fn sleep(&self, duration: Duration) -> SleepFuture;
+}
+
+/// 系统时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// 默认使用的系统时钟
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// 手动推进的时钟，用于测试：时间只在调用 advance 或 sleep 时前进，sleep 立即返回
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new(start_utc: DateTime<Utc>) -> Self {
+        Self {
+            start: Instant::now(),
+            start_utc,
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// 时间前进指定时长
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    /// 自创建以来经过的时长（包含 sleep 推进的时间）
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.start_utc + chrono::Duration::from_std(self.elapsed()).unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) -> SleepFuture {
+        self.advance(duration);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_manual_clock_advances_without_waiting() {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let clock = ManualClock::new(start);
+        let before = clock.now();
+
+        clock.advance(Duration::from_secs(30));
+        clock.sleep(Duration::from_secs(3600)).await;
+
+        assert_eq!(clock.now() - before, Duration::from_secs(3630));
+        assert_eq!(clock.utc_now(), start + chrono::Duration::seconds(3630));
+    }
+}
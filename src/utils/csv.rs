@@ -0,0 +1,78 @@
+use anyhow::{Result, anyhow};
+
+/// 解析CSV文本：逗号分隔，双引号包裹的字段可包含逗号和换行，字段内的 "" 表示一个引号。
+/// 兼容Excel导出的BOM和CRLF换行，跳过空行
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                push_row(&mut rows, std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(anyhow!("CSV中的引号没有闭合"));
+    }
+    row.push(field);
+    push_row(&mut rows, row);
+    Ok(rows)
+}
+
+fn push_row(rows: &mut Vec<Vec<String>>, row: Vec<String>) {
+    if row.iter().any(|field| !field.trim().is_empty()) {
+        rows.push(row);
+    }
+}
+
+/// 将一行字段写为CSV，包含逗号、引号或换行的字段加引号
+pub fn write_row(fields: &[&str]) -> String {
+    let mut line = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        let text = write_row(&["name", "note"]) + &write_row(&["home", "a, \"b\"\nc"]) + "\r\n,\r\n";
+        let rows = parse(&format!("\u{feff}{}", text)).unwrap();
+        assert_eq!(rows, vec![vec!["name", "note"], vec!["home", "a, \"b\"\nc"]]);
+        assert!(parse("name\n\"home").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::config::data_dir::data_dir;
+use crate::utils::logger;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 单次抓取的最长时间（分钟）
+pub const MAX_CAPTURE_MINUTES: u64 = 60;
+/// 单次抓取最多记录的请求数，超出后不再写入
+const MAX_CAPTURE_ENTRIES: usize = 2000;
+/// 响应体最多保留的字符数
+const MAX_BODY_CHARS: usize = 8192;
+/// 抓取期间使用的日志级别
+const CAPTURE_LOG_FILTER: &str = "debug";
+/// 脱敏时替换的字段名（小写匹配）
+const SENSITIVE_KEYS: &[&str] = &["email", "token", "key", "secret", "password", "authorization"];
+
+#[derive(Default)]
+struct CaptureState {
+    until: Option<DateTime<Utc>>,
+    generation: u64,
+    entries: usize,
+}
+
+/// 调试抓取状态
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureStatus {
+    pub active: bool,
+    pub until: Option<DateTime<Utc>>,
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+/// 单条抓取记录
+#[derive(Debug, Serialize)]
+struct CaptureEntry {
+    timestamp: DateTime<Utc>,
+    method: String,
+    url: String,
+    request_body: Option<Value>,
+    status: Option<u16>,
+    response_body: Option<Value>,
+    error: Option<String>,
+    elapsed_ms: u128,
+}
+
+fn state() -> &'static Mutex<CaptureState> {
+    static STATE: OnceLock<Mutex<CaptureState>> = OnceLock::new();
+    STATE.get_or_init(|| Mutex::new(CaptureState::default()))
+}
+
+/// 抓取文件路径
+pub fn capture_path() -> PathBuf {
+    data_dir().join("debug-capture.jsonl")
+}
+
+/// 当前抓取状态
+pub fn status() -> CaptureStatus {
+    let state = state().lock().unwrap();
+    CaptureStatus {
+        active: is_active_locked(&state),
+        until: state.until,
+        entries: state.entries,
+        max_entries: MAX_CAPTURE_ENTRIES,
+    }
+}
+
+fn is_active_locked(state: &CaptureState) -> bool {
+    state.until.is_some_and(|until| Utc::now() < until)
+}
+
+/// 开始抓取：提高日志级别并记录所有Cloudflare请求，到期后自动恢复
+/// 同一时间只允许一个抓取窗口
+pub fn start(minutes: u64) -> Result<CaptureStatus> {
+    if minutes == 0 || minutes > MAX_CAPTURE_MINUTES {
+        return Err(anyhow!("抓取时长需在 1-{} 分钟之间", MAX_CAPTURE_MINUTES));
+    }
+
+    let generation = {
+        let mut state = state().lock().unwrap();
+        if is_active_locked(&state) {
+            return Err(anyhow!("已有正在进行的抓取，将在 {} 结束", state.until.unwrap().to_rfc3339()));
+        }
+
+        std::fs::write(capture_path(), b"")?;
+        state.generation += 1;
+        state.entries = 0;
+        state.until = Some(Utc::now() + chrono::Duration::minutes(minutes as i64));
+        state.generation
+    };
+
+    if let Err(e) = logger::set_log_filter(CAPTURE_LOG_FILTER) {
+        warn!("⚠️ 提高日志级别失败: {}", e);
+    }
+    info!("🔬 调试抓取已开始，持续 {} 分钟", minutes);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+        stop_generation(generation);
+    });
+
+    Ok(status())
+}
+
+/// 提前结束抓取
+pub fn stop() -> CaptureStatus {
+    let generation = state().lock().unwrap().generation;
+    stop_generation(generation);
+    status()
+}
+
+fn stop_generation(generation: u64) {
+    {
+        let mut state = state().lock().unwrap();
+        // 到期任务属于已被提前结束的旧抓取时不做处理
+        if state.generation != generation || state.until.is_none() {
+            return;
+        }
+        state.until = None;
+    }
+
+    if let Err(e) = logger::reset_log_filter() {
+        warn!("⚠️ 恢复日志级别失败: {}", e);
+    }
+    info!("🔬 调试抓取已结束，抓取文件: {}", capture_path().display());
+}
+
+/// 发送请求，抓取窗口内同时记录脱敏后的请求与响应
+pub async fn send(builder: RequestBuilder) -> reqwest::Result<Response> {
+    if !status().active {
+        return builder.send().await;
+    }
+
+    let (client, request) = builder.build_split();
+    let request = request?;
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let request_body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(sanitize_body);
+
+    let started = std::time::Instant::now();
+    let response = match client.execute(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            record(CaptureEntry {
+                timestamp: Utc::now(),
+                method,
+                url,
+                request_body,
+                status: None,
+                response_body: None,
+                error: Some(e.to_string()),
+                elapsed_ms: started.elapsed().as_millis(),
+            });
+            return Err(e);
+        }
+    };
+
+    // 读取响应体后重新组装响应，调用方不受影响
+    let status = response.status();
+    let headers = response.headers().clone();
+    let bytes = response.bytes().await?;
+    record(CaptureEntry {
+        timestamp: Utc::now(),
+        method,
+        url,
+        request_body,
+        status: Some(status.as_u16()),
+        response_body: Some(sanitize_body(&bytes)),
+        error: None,
+        elapsed_ms: started.elapsed().as_millis(),
+    });
+
+    let mut rebuilt = http::Response::new(bytes);
+    *rebuilt.status_mut() = status;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+fn record(entry: CaptureEntry) {
+    let mut state = state().lock().unwrap();
+    if !is_active_locked(&state) || state.entries >= MAX_CAPTURE_ENTRIES {
+        return;
+    }
+
+    let result = serde_json::to_string(&entry).map_err(anyhow::Error::from).and_then(|line| {
+        let mut file = std::fs::OpenOptions::new().append(true).create(true).open(capture_path())?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    });
+    match result {
+        Ok(()) => state.entries += 1,
+        Err(e) => warn!("⚠️ 写入调试抓取文件失败: {}", e),
+    }
+}
+
+/// 解析并脱敏请求/响应体，非JSON内容按文本截断保存
+fn sanitize_body(bytes: &[u8]) -> Value {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            redact(&mut value);
+            value
+        }
+        Err(_) => Value::String(String::from_utf8_lossy(bytes).chars().take(MAX_BODY_CHARS).collect()),
+    }
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS.iter().any(|sensitive| key.contains(sensitive)) && !value.is_null() {
+                    *value = Value::String("***".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_body_redacts_secrets() {
+        let body = br#"{"result":{"name":"example.com","owner":{"email":"me@example.com"},"api_key":"abc"}}"#;
+        let value = sanitize_body(body);
+        assert_eq!(value["result"]["name"], "example.com");
+        assert_eq!(value["result"]["owner"]["email"], "***");
+        assert_eq!(value["result"]["api_key"], "***");
+    }
+}
@@ -0,0 +1,312 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 默认界面语言
+pub const DEFAULT_LANGUAGE: &str = "zh-CN";
+
+/// 支持的界面语言：代码与显示名称
+pub const LANGUAGES: [(&str, &str); 2] = [("zh-CN", "简体中文"), ("en", "English")];
+
+/// 单条界面文字的各语言翻译
+struct Message {
+    key: &'static str,
+    zh: &'static str,
+    en: &'static str,
+}
+
+const fn m(key: &'static str, zh: &'static str, en: &'static str) -> Message {
+    Message { key, zh, en }
+}
+
+/// 界面文字目录，前端通过 /api/v1/i18n/{lang}.json 获取，文字中的 {name} 由前端替换
+const MESSAGES: &[Message] = &[
+    m("app.title", "🌐 Cloudflare IPv6自动更新", "🌐 Cloudflare IPv6 Auto Update"),
+    m("app.subtitle", "监控本地IPv6地址变化并自动更新到Cloudflare DNS", "Watches the local IPv6 address and keeps Cloudflare DNS up to date"),
+    m("language.label", "界面语言:", "Language:"),
+    // 配置状态
+    m("config_status.title", "配置状态", "Configuration status"),
+    m("config_status.checking", "正在检查配置状态...", "Checking configuration..."),
+    m("config_status.saved", "✅ 配置已保存", "✅ Configuration saved"),
+    m("pause.placeholder", "暂停时长，如 30m、2h，留空则一直暂停", "Pause duration, e.g. 30m or 2h; leave empty to pause until resumed"),
+    m("pause.button", "⏸️ 暂停定时检查", "⏸️ Pause scheduled checks"),
+    m("resume.button", "▶️ 恢复定时检查", "▶️ Resume scheduled checks"),
+    // 配置表单
+    m("config.title", "Cloudflare配置", "Cloudflare settings"),
+    m("config.auth_type", "认证方式:", "Authentication:"),
+    m("config.auth_type.api_token", "API令牌 (推荐)", "API token (recommended)"),
+    m("config.auth_type.global_key", "Global API Key + 邮箱", "Global API Key + email"),
+    m("config.auth_email", "账户邮箱:", "Account email:"),
+    m("config.auth_email.placeholder", "Global API Key 对应的Cloudflare账户邮箱", "Cloudflare account email for the Global API Key"),
+    m("config.api_key", "API密钥:", "API key:"),
+    m("config.api_key.placeholder", "输入Cloudflare API密钥", "Cloudflare API key"),
+    m("config.zone_id", "区域ID:", "Zone ID:"),
+    m("config.zone_id.placeholder", "输入区域ID", "Zone ID"),
+    m("config.root_domain", "根域名:", "Root domain:"),
+    m("config.root_domain.placeholder", "例如: example.com", "e.g. example.com"),
+    m("config.check_interval", "检查间隔(秒):", "Check interval (seconds):"),
+    m("config.check_interval.placeholder", "默认300秒(5分钟)", "Default 300 seconds (5 minutes)"),
+    m("config.burst_interval", "IP变化后突发检查间隔(秒):", "Burst check interval after an IP change (seconds):"),
+    m("config.burst_interval.placeholder", "0表示不启用，例如30", "0 disables, e.g. 30"),
+    m("config.burst_duration", "突发检查持续时间(分钟):", "Burst check duration (minutes):"),
+    m("config.burst_duration.placeholder", "默认10分钟", "Default 10 minutes"),
+    m("config.flap_threshold", "IP抖动告警次数:", "IP flapping alert threshold:"),
+    m("config.flap_threshold.placeholder", "在同两个地址间来回切换的次数，0表示不检测", "Switches between the same two addresses; 0 disables"),
+    m("config.flap_window_hours", "IP抖动统计窗口(小时):", "IP flapping window (hours):"),
+    m("config.flap_window_hours.placeholder", "默认24小时", "Default 24 hours"),
+    m("config.reconcile_interval", "对账间隔(分钟):", "Reconciliation interval (minutes):"),
+    m("config.update_parallelism", "同时处理的域名数:", "Domains updated in parallel:"),
+    m("config.update_parallelism.placeholder", "默认5，域名较多时可适当调大，最多20", "Default 5; raise it for many domains, at most 20"),
+    m("config.proxy_url", "代理地址(可选):", "Proxy URL (optional):"),
+    m("config.proxy_url.placeholder", "例如: socks5h://127.0.0.1:1080，留空时使用环境变量中的代理", "e.g. socks5h://127.0.0.1:1080; leave empty to use the proxy from environment variables"),
+    m("config.ca_cert_path", "根证书文件(可选):", "CA certificate file (optional):"),
+    m("config.ca_cert_path.placeholder", "服务器上PEM格式根证书的路径，用于TLS拦截或私有API网关", "Path to a PEM root certificate on the server, for TLS interception or private API gateways"),
+    m("config.http_connect_timeout", "连接超时(秒):", "Connect timeout (seconds):"),
+    m("config.http_connect_timeout.placeholder", "连接Cloudflare API的超时，默认10，最多60", "Timeout for connecting to the Cloudflare API; default 10, at most 60"),
+    m("config.http_request_timeout", "请求超时(秒):", "Request timeout (seconds):"),
+    m("config.http_request_timeout.placeholder", "单次请求的总超时，默认30，最多300", "Total timeout per request; default 30, at most 300"),
+    m("config.reconcile_interval.placeholder", "定期检查并修复被外部修改的记录，0表示不启用，建议60", "Periodically repair records edited elsewhere; 0 disables, 60 recommended"),
+    m("config.startup_check", "程序启动时执行首次检查", "Run a check on startup"),
+    m("config.startup_delay", "首次检查延迟(秒):", "Startup check delay (seconds):"),
+    m("config.startup_delay.placeholder", "网络启动较慢时可设置等待时间", "Wait for a slow network to come up"),
+    m("config.ip_source", "IP来源:", "IP source:"),
+    m("config.ip_source.local", "本机网卡地址", "Local interface address"),
+    m("config.ip_source.external", "外部HTTP服务（公网视角）", "External HTTP service (public view)"),
+    m("config.external_ip_services", "外部IP检测服务(可选，每行一个，按顺序尝试):", "External IP services (optional, one per line, tried in order):"),
+    m("config.external_ip_services.placeholder", "留空使用内置列表（api6.ipify.org、v6.ident.me、ifconfig.co）", "Leave empty to use the built-in list (api6.ipify.org, v6.ident.me, ifconfig.co)"),
+    m("config.ip_source.stun", "STUN服务器（公网视角）", "STUN server (public view)"),
+    m("config.ip_source.command", "自定义命令（IP_COMMAND 环境变量）", "Custom command (IP_COMMAND environment variable)"),
+    m("config.ip_source.dyndns", "路由器推送（DynDNS2 /nic/update）", "Pushed by router (DynDNS2 /nic/update)"),
+    m("config.stun_servers", "STUN服务器(可选，每行一个 host:port，按顺序尝试):", "STUN servers (optional, one host:port per line, tried in order):"),
+    m("config.stun_servers.placeholder", "留空使用内置列表（stun.cloudflare.com:3478、stun.l.google.com:19302）", "Leave empty to use the built-in list (stun.cloudflare.com:3478, stun.l.google.com:19302)"),
+    m("config.verify_external_ip", "通过外部服务校验公网IPv6", "Verify the public IPv6 with an external service"),
+    m("config.interface", "检测地址的网卡:", "Detect address on interface:"),
+    m("config.interface.auto", "自动（系统首选地址）", "Automatic (system preferred address)"),
+    m("config.allowed_prefix", "只发布该前缀内的地址(可选):", "Only publish addresses within this prefix (optional):"),
+    m("config.allowed_prefix.placeholder", "例如 2400:cb00::/32，留空不限制", "e.g. 2400:cb00::/32, leave empty for no restriction"),
+    m("config.address_strategy", "地址选择策略:", "Address selection:"),
+    m("config.address_strategy.system", "系统首选地址", "System preferred address"),
+    m("config.address_strategy.prefer_global", "优先全局单播地址", "Prefer global unicast"),
+    m("config.address_strategy.longest_lifetime", "有效期最长的全局地址", "Global address with the longest lifetime"),
+    m("config.address_exclude_ula", "排除唯一本地地址（fc00::/7）", "Exclude unique local addresses (fc00::/7)"),
+    m("config.address_skip_temporary", "跳过临时地址（隐私扩展），优先使用稳定地址", "Skip temporary (privacy extension) addresses and prefer stable ones"),
+    m("config.address_match", "地址匹配条件(可选):", "Address filter (optional):"),
+    m("config.address_match.placeholder", "IPv6前缀（如 2001:db8::/32）或正则表达式", "IPv6 prefix (e.g. 2001:db8::/32) or regular expression"),
+    m("config.address_suffix", "固定后缀(可选):", "Pinned suffix (optional):"),
+    m("config.address_suffix.placeholder", "如 ::1234，发布 /64前缀 + 该后缀", "e.g. ::1234, publishes the /64 prefix + this suffix"),
+    m("config.delegated_prefix_len", "委派前缀长度:", "Delegated prefix length:"),
+    m("lan_hosts.title", "局域网主机(发布 委派前缀 + 主机后缀):", "LAN hosts (published as delegated prefix + host suffix):"),
+    m("lan_hosts.name.placeholder", "子域名，如 nas", "Subdomain, e.g. nas"),
+    m("lan_hosts.suffix.placeholder", "后缀，如 ::211:32ff:fe12:3456", "Suffix, e.g. ::211:32ff:fe12:3456"),
+    m("lan_hosts.description.placeholder", "备注(可选)", "Description (optional)"),
+    m("lan_hosts.save", "💾 保存", "💾 Save"),
+    m("lan_hosts.empty", "没有局域网主机", "No LAN hosts"),
+    m("lan_hosts.delete", "🗑️ 删除", "🗑️ Delete"),
+    m("lan_hosts.delete_confirm", "确定删除局域网主机 {name}？子域名将恢复发布本机地址", "Delete LAN host {name}? The subdomain will publish this machine's address again"),
+    m("lan_hosts.save_failed", "保存局域网主机失败", "Failed to save LAN host"),
+    m("lan_hosts.delete_failed", "删除局域网主机失败", "Failed to delete LAN host"),
+    m("lan_hosts.discover", "🔍 发现", "🔍 Discover"),
+    m("lan_hosts.discovering", "正在扫描IPv6邻居...", "Scanning IPv6 neighbors..."),
+    m("lan_hosts.discover_empty", "邻居表中没有全局IPv6邻居", "No global IPv6 neighbors in the neighbor table"),
+    m("lan_hosts.discover_failed", "发现局域网主机失败", "Failed to discover LAN hosts"),
+    m("lan_hosts.map", "➕ 映射", "➕ Map"),
+    m("lan_hosts.map_prompt", "将 {address} 映射到子域名:", "Map {address} to subdomain:"),
+    m("lan_hosts.suffix", "后缀 {suffix}", "Suffix {suffix}"),
+    m("lan_hosts.eui64", "稳定（由MAC生成）", "Stable (derived from MAC)"),
+    m("lan_hosts.outside_prefix", "不在当前委派前缀内", "Outside the current delegated prefix"),
+    m("config.change_detection", "变化检测方式:", "Change detection:"),
+    m("config.change_detection.last_ip", "比对最后记录的IP", "Compare with the last recorded IP"),
+    m("config.change_detection.resolver", "解析域名（系统解析器）", "Resolve names (system resolver)"),
+    m("config.change_detection.doh", "解析域名（公共DoH）", "Resolve names (public DoH)"),
+    m("config.verify_propagation", "写入记录后通过公共DNS（1.1.1.1、8.8.8.8）确认新地址已生效", "Confirm new addresses via public DNS (1.1.1.1, 8.8.8.8) after writing"),
+    m("config.backup_hostname", "备用主机名:", "Backup hostname:"),
+    m("config.backup_hostname.placeholder", "可选，主区域接口故障时客户端可改用的完整域名", "Optional, a fallback name clients can use while the primary zone's API is down"),
+    m("config.backup_zone_id.placeholder", "备用主机名所在区域的ID", "Zone ID of the backup hostname"),
+    m("config.backup_api_key.placeholder", "可选，该区域的API令牌，留空使用上面的凭据", "Optional API token for that zone; leave empty to reuse the credentials above"),
+    m("config.backup_ttl.placeholder", "TTL(秒)，默认60", "TTL (seconds), default 60"),
+    m("config.status_webhook", "状态变化Webhook:", "Status change webhook:"),
+    m("config.status_webhook.placeholder", "可选，服务状态变化时推送事件", "Optional, receives an event when the service status changes"),
+    m("config.uptime_kuma", "Uptime Kuma Push地址:", "Uptime Kuma push URL:"),
+    m("config.uptime_kuma.placeholder", "可选，每个周期结束后推送心跳", "Optional, receives a heartbeat after every cycle"),
+    m("config.mqtt", "MQTT代理:", "MQTT broker:"),
+    m("config.mqtt.placeholder", "可选，host:port，发布IP变化和更新结果", "Optional, host:port, receives IP changes and update results"),
+    m("config.mqtt_topic.placeholder", "主题前缀，默认 cloudflare-auto", "Topic prefix, default cloudflare-auto"),
+    m("config.mqtt_username.placeholder", "可选，用户名", "Optional, username"),
+    m("config.mqtt_password.placeholder", "可选，密码", "Optional, password"),
+    m("config.mqtt_retain", "发布保留消息", "Publish retained messages"),
+    m("config.mqtt_home_assistant", "Home Assistant自动发现", "Home Assistant discovery"),
+    m("config.mqtt_discovery_prefix.placeholder", "发现主题前缀，默认 homeassistant", "Discovery prefix, default homeassistant"),
+    m("config.telegram", "Telegram通知:", "Telegram notifications:"),
+    m("config.telegram_bot_token.placeholder", "可选，机器人令牌，如 123456:ABC-DEF", "Optional, bot token such as 123456:ABC-DEF"),
+    m("config.telegram_chat_id.placeholder", "聊天ID或 @频道用户名", "Chat ID or @channel username"),
+    m("config.telegram_api_url.placeholder", "可选，自建Bot API或反向代理地址", "Optional, self-hosted Bot API or reverse proxy URL"),
+    m("config.telegram_daily_summary", "每日发送更新统计", "Send a daily update summary"),
+    m("config.discord", "Discord Webhook:", "Discord webhook:"),
+    m("config.discord.placeholder", "可选，IP变化、更新失败和恢复时推送消息", "Optional, receives IP changes, failures and recoveries"),
+    m("config.slack", "Slack Webhook:", "Slack webhook:"),
+    m("config.slack.placeholder", "可选，Incoming Webhook地址", "Optional, incoming webhook URL"),
+    m("config.slack_channel.placeholder", "可选，频道，如 #ops", "Optional, channel such as #ops"),
+    m("config.slack_template.placeholder", "可选，消息模板，如 :warning: {title} {new_ip}", "Optional, message template such as :warning: {title} {new_ip}"),
+    m("config.ntfy", "ntfy推送:", "ntfy push:"),
+    m("config.ntfy_topic.placeholder", "可选，主题，如 home-ddns", "Optional, topic such as home-ddns"),
+    m("config.ntfy_server.placeholder", "服务器，默认 https://ntfy.sh", "Server, default https://ntfy.sh"),
+    m("config.ntfy_token.placeholder", "可选，访问令牌", "Optional, access token"),
+    m("config.ntfy_priority.placeholder", "可选，优先级 1-5", "Optional, priority 1-5"),
+    m("config.gotify", "Gotify推送:", "Gotify push:"),
+    m("config.gotify_server.placeholder", "可选，服务器地址，如 https://gotify.example.com", "Optional, server URL such as https://gotify.example.com"),
+    m("config.gotify_token.placeholder", "应用令牌", "Application token"),
+    m("config.gotify_priority.placeholder", "可选，优先级 0-10", "Optional, priority 0-10"),
+    m("config.wecom", "企业微信机器人:", "WeCom group robot:"),
+    m("config.dingtalk", "钉钉机器人:", "DingTalk robot:"),
+    m("config.dingtalk.placeholder", "可选，自定义机器人Webhook地址，IP变化、更新失败和恢复时推送消息", "Optional, custom robot webhook URL for IP changes, failures and recoveries"),
+    m("config.dingtalk_secret.placeholder", "可选，加签密钥（SEC开头）", "Optional, signing secret (starts with SEC)"),
+    m("config.bark", "Bark推送:", "Bark push:"),
+    m("config.bark_key.placeholder", "可选，设备Key，IP变化、更新失败和恢复时推送到iPhone", "Optional, device key; pushes IP changes, failures and recoveries to your iPhone"),
+    m("config.bark_server.placeholder", "服务器地址，默认 https://api.day.app", "Server URL, defaults to https://api.day.app"),
+    m("config.bark_sound.placeholder", "可选，提示音，如 alarm", "Optional, sound such as alarm"),
+    m("config.serverchan", "Server酱:", "ServerChan:"),
+    m("config.serverchan.placeholder", "可选，SendKey，IP变化、更新失败和恢复时推送到微信", "Optional, SendKey; pushes IP changes, failures and recoveries to WeChat"),
+    m("config.pushplus", "PushPlus:", "PushPlus:"),
+    m("config.pushplus.placeholder", "可选，用户token，IP变化、更新失败和恢复时推送到微信", "Optional, user token; pushes IP changes, failures and recoveries to WeChat"),
+    m("config.wecom.placeholder", "可选，群机器人Webhook地址，IP变化、更新失败和恢复时推送消息", "Optional, group robot webhook URL for IP changes, failures and recoveries"),
+    m("config.record_comment", "记录备注:", "Record comment:"),
+    m("config.record_comment.placeholder", "可选，例如 managed by cloudflare-auto", "Optional, e.g. managed by cloudflare-auto"),
+    m("config.cleanup_duplicates", "清理同名的多余AAAA记录（只保留指向当前IP的一条）", "Delete duplicate AAAA records (keep only the one pointing at the current IP)"),
+    m("config.proxied_default", "新建记录默认通过Cloudflare代理（域名列表中单独设置的优先）", "Proxy new records through Cloudflare by default (per-domain settings take precedence)"),
+    m("config.https_records", "同时维护HTTPS记录（ipv6hint与当前IP保持一致）", "Also maintain HTTPS records (keep ipv6hint on the current IP)"),
+    m("config.dry_run", "演练模式（只记录将要做的修改，不修改Cloudflare记录）", "Dry run (log planned changes without touching Cloudflare)"),
+    m("config.round_robin", "轮询模式（同名的其他AAAA记录属于其他主机，只更新本机的记录）", "Round robin (other AAAA records with the same name belong to other hosts)"),
+    m("config.multi_address", "发布所有地址（每个符合条件的本机地址一条AAAA记录，消失的地址会被删除）", "Publish all addresses (one AAAA record per qualifying local address; records for vanished addresses are deleted)"),
+    m("config.ownership", "登记记录归属（同一区域有多个实例时，不修改其他实例的记录）", "Record ownership (leave records owned by other instances alone)"),
+    m("config.ui_language", "默认界面语言:", "Default UI language:"),
+    m("config.test", "🧪 测试配置", "🧪 Test configuration"),
+    m("config.save", "💾 保存配置", "💾 Save configuration"),
+    // 域名选择
+    m("domains.title", "域名选择", "Domains"),
+    m("domains.hint", "请先测试配置以获取域名列表...", "Test the configuration to load the domain list..."),
+    m("domains.new.placeholder", "新增子域名，例如 nas（Cloudflare中尚不存在也可以）", "New subdomain, e.g. nas (may not exist in Cloudflare yet)"),
+    m("domains.add", "➕ 添加", "➕ Add"),
+    m("domains.select_all", "📋 全选", "📋 Select all"),
+    m("domains.save_selection", "💾 保存选择", "💾 Save selection"),
+    // 当前状态
+    m("status.title", "当前状态", "Current status"),
+    m("status.current_ip", "当前IPv6:", "Current IPv6:"),
+    m("status.last_ip", "最后记录IP:", "Last recorded IP:"),
+    m("status.monitor", "监控状态:", "Monitor:"),
+    m("status.last_check", "上次检查:", "Last check:"),
+    m("status.next_check", "下次检查:", "Next check:"),
+    m("status.last_reconcile", "上次对账:", "Last reconciliation:"),
+    m("status.loading", "获取中...", "Loading..."),
+    m("status.detect_failed", "获取失败", "Detection failed"),
+    m("status.export", "📤 导出域名设置CSV", "📤 Export domain settings (CSV)"),
+    m("status.import", "📥 导入CSV", "📥 Import CSV"),
+    m("status.update_now", "🔄 立即更新", "🔄 Update now"),
+    m("status.force_update", "强制重写（内容一致的记录也重新写入）", "Force rewrite (also rewrite records that are already up to date)"),
+    m("monitor.paused", "已暂停", "Paused"),
+    m("monitor.running", "运行中（每{interval}秒）", "Running (every {interval}s)"),
+    m("monitor.stopped", "未启动", "Stopped"),
+    m("monitor.offline", "网络不可达（每{interval}秒重试）", "Offline (retrying every {interval}s)"),
+    // 更新记录
+    m("records.title", "📊 DNS更新记录", "📊 DNS update history"),
+    m("records.recent", "最近更新记录", "Recent updates"),
+    m("records.refresh", "🔄 刷新", "🔄 Refresh"),
+    m("records.loading", "正在加载更新记录...", "Loading update history..."),
+    // DNS记录管理
+    m("dns.title", "🗂️ DNS记录管理", "🗂️ DNS records"),
+    m("dns.admin_token.placeholder", "管理令牌 ADMIN_TOKEN", "Admin token (ADMIN_TOKEN)"),
+    m("dns.all_types", "全部类型", "All types"),
+    m("dns.load", "🔄 加载", "🔄 Load"),
+    m("dns.name.placeholder", "名称，例如 www 或 @", "Name, e.g. www or @"),
+    m("dns.content.placeholder", "内容", "Content"),
+    m("dns.priority.placeholder", "MX优先级", "MX priority"),
+    m("dns.proxied", "代理", "Proxied"),
+    m("dns.create", "➕ 创建", "➕ Create"),
+    m("tokens.title", "🔑 API令牌", "🔑 API tokens"),
+    m("tokens.hint", "为自动化脚本生成只能调用指定接口、会过期的令牌，无需交出管理令牌。每行一个接口，例如 POST /domains/home/sync", "Mint expiring tokens that can only call the listed endpoints, so automation never holds the admin token. One endpoint per line, e.g. POST /domains/home/sync"),
+    m("tokens.name.placeholder", "名称，例如 CI部署", "Name, e.g. CI deploy"),
+    m("tokens.ttl.placeholder", "有效期(小时)", "Lifetime (hours)"),
+    m("tokens.scopes.placeholder", "POST /domains/home/sync", "POST /domains/home/sync"),
+    m("tokens.create", "➕ 生成", "➕ Create"),
+    m("tokens.load", "🔄 加载", "🔄 Load"),
+    m("tokens.empty", "没有API令牌", "No API tokens"),
+    m("tokens.expired", "（已过期）", "(expired)"),
+    m("tokens.expires", "有效期至 {time}", "Expires {time}"),
+    m("tokens.last_used", "最近使用 {time}", "Last used {time}"),
+    m("tokens.revoke", "🗑️ 吊销", "🗑️ Revoke"),
+    m("tokens.revoke_confirm", "确定吊销令牌 {name}？", "Revoke token {name}?"),
+    m("tokens.load_failed", "获取API令牌失败", "Failed to load API tokens"),
+    m("tokens.create_failed", "生成API令牌失败", "Failed to create API token"),
+    m("tokens.revoke_failed", "吊销API令牌失败", "Failed to revoke API token"),
+    // 提示消息
+    m("toast.config_saved", "配置保存并更新成功！", "Configuration saved and update started!"),
+    m("toast.save_failed", "保存失败", "Save failed"),
+    m("toast.network_error", "网络错误: {error}", "Network error: {error}"),
+    m("toast.update_started", "开始手动更新...", "Starting manual update..."),
+];
+
+/// 语言目录的信息
+#[derive(Debug, Serialize)]
+pub struct LanguageInfo {
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+/// 所有支持的语言
+pub fn languages() -> Vec<LanguageInfo> {
+    LANGUAGES.iter().map(|(code, name)| LanguageInfo { code, name }).collect()
+}
+
+/// 将语言代码规范为支持的语言，例如 zh、zh-cn、zh_CN 都对应 zh-CN，en-US 对应 en
+pub fn normalize_language(lang: &str) -> Result<&'static str> {
+    let lang = lang.trim().to_ascii_lowercase().replace('_', "-");
+    let primary = lang.split('-').next().unwrap_or_default();
+    LANGUAGES
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| code.to_ascii_lowercase() == lang || code.split('-').next() == Some(primary))
+        .ok_or_else(|| anyhow!("不支持的语言: {}", lang))
+}
+
+/// 单条文字的翻译，未知的键原样返回
+pub fn translate(lang: &str, key: &'static str) -> &'static str {
+    MESSAGES
+        .iter()
+        .find(|message| message.key == key)
+        .map(|message| message.text(lang))
+        .unwrap_or(key)
+}
+
+/// 某种语言的全部文字
+pub fn bundle(lang: &str) -> BTreeMap<&'static str, &'static str> {
+    MESSAGES.iter().map(|message| (message.key, message.text(lang))).collect()
+}
+
+impl Message {
+    fn text(&self, lang: &str) -> &'static str {
+        match lang {
+            "en" => self.en,
+            _ => self.zh,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_catalog_is_complete() {
+        let mut keys = HashSet::new();
+        for message in MESSAGES {
+            assert!(keys.insert(message.key), "重复的键: {}", message.key);
+            assert!(!message.zh.is_empty() && !message.en.is_empty(), "缺少翻译: {}", message.key);
+        }
+
+        assert_eq!(normalize_language("zh_cn").unwrap(), "zh-CN");
+        assert_eq!(normalize_language("en-US").unwrap(), "en");
+        assert!(normalize_language("fr").is_err());
+        assert_eq!(translate("en", "status.title"), "Current status");
+        assert_eq!(bundle(DEFAULT_LANGUAGE)["status.title"], "当前状态");
+    }
+}
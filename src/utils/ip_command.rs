@@ -0,0 +1,94 @@
+use crate::utils::network::address_scope;
+use anyhow::{Result, anyhow};
+use std::net::{IpAddr, Ipv6Addr};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::debug;
+
+/// 命令默认的超时时间（秒），可通过 IP_COMMAND_TIMEOUT 调整
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+/// 获取IP的自定义命令，只能通过 IP_COMMAND 环境变量设置，避免通过Web界面执行任意命令
+pub fn configured_command() -> Option<String> {
+    std::env::var("IP_COMMAND").ok().filter(|command| !command.trim().is_empty())
+}
+
+fn command_timeout() -> Duration {
+    let secs = std::env::var("IP_COMMAND_TIMEOUT")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    Duration::from_secs(secs.min(MAX_TIMEOUT_SECS))
+}
+
+/// 解析命令输出：第一行非空内容为IPv6地址，可带前缀长度（如 2001:db8::1/64），只接受全局或唯一本地地址
+pub fn parse_command_output(stdout: &str) -> Result<Ipv6Addr> {
+    let line = stdout
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .ok_or_else(|| anyhow!("命令没有输出"))?;
+    let text = line.split('/').next().unwrap_or(line);
+    let ip: Ipv6Addr = text
+        .parse()
+        .map_err(|_| anyhow!("命令输出不是IPv6地址: {}", line.chars().take(64).collect::<String>()))?;
+    match address_scope(&IpAddr::V6(ip)) {
+        "global" | "unique_local" => Ok(ip),
+        scope => Err(anyhow!("命令输出的 {} 不能发布（{}）", ip, scope)),
+    }
+}
+
+/// 执行 IP_COMMAND 并解析输出的IPv6地址，超时或退出码非0时返回错误
+pub async fn run_ip_command() -> Result<Ipv6Addr> {
+    let command = configured_command().ok_or_else(|| anyhow!("IP来源为command，但没有设置 IP_COMMAND 环境变量"))?;
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", &command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", &command]);
+        cmd
+    };
+    let child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow!("无法执行 IP_COMMAND: {}", e))?;
+
+    let timeout = command_timeout();
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow!("IP_COMMAND 执行超时（{}秒）", timeout.as_secs()))??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "IP_COMMAND 执行失败（{}）: {}",
+            output.status,
+            stderr.trim().chars().take(200).collect::<String>()
+        ));
+    }
+    let ip = parse_command_output(&String::from_utf8_lossy(&output.stdout))?;
+    debug!("🔧 IP_COMMAND 返回: {}", ip);
+    Ok(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command_output() {
+        let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        assert_eq!(parse_command_output("\n  2001:db8::1/64\nfd00::1\n").unwrap(), ip);
+        assert_eq!(parse_command_output("2001:db8::1").unwrap(), ip);
+        assert!(parse_command_output("").is_err());
+        assert!(parse_command_output("fe80::1").is_err());
+        assert!(parse_command_output("192.0.2.1").is_err());
+    }
+}
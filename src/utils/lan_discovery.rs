@@ -0,0 +1,215 @@
+use crate::utils::network::address_scope;
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::process::Command;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+/// mDNS端口，对该端口的单播查询按RFC 6762的传统单播方式直接回复给查询方
+const MDNS_PORT: u16 = 5353;
+/// 等待单个主机回复mDNS反向查询的时间，没有运行mDNS服务的主机不会回复
+const MDNS_TIMEOUT: Duration = Duration::from_millis(800);
+const DNS_HEADER_LEN: usize = 12;
+const TYPE_PTR: u16 = 12;
+const CLASS_IN: u16 = 1;
+
+/// 邻居表中的一个IPv6邻居
+#[derive(Debug, Clone, Serialize)]
+pub struct Neighbor {
+    pub interface: String,
+    pub address: Ipv6Addr,
+    pub mac: Option<String>,
+    pub state: String,
+    /// 接口标识由MAC地址生成（EUI-64），不会像隐私扩展的临时地址那样轮换
+    pub eui64: bool,
+    /// 通过mDNS反向查询得到的主机名
+    pub hostname: Option<String>,
+}
+
+/// 通过 `ip -6 neigh` 读取邻居表，只保留全局和唯一本地地址，忽略解析失败的条目
+pub async fn read_neighbors() -> Result<Vec<Neighbor>> {
+    if !cfg!(target_os = "linux") {
+        return Err(anyhow!("局域网主机发现只支持Linux（需要 ip 命令）"));
+    }
+    let output = Command::new("ip")
+        .args(["-6", "neigh", "show"])
+        .output()
+        .await
+        .map_err(|e| anyhow!("无法执行 ip -6 neigh: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!("ip -6 neigh 执行失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(parse_ip_neigh_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `ip -6 neigh show` 的输出，每行形如
+/// `2001:db8::5 dev br0 lladdr 00:11:32:12:34:56 router REACHABLE`
+fn parse_ip_neigh_output(output: &str) -> Vec<Neighbor> {
+    let mut neighbors: Vec<Neighbor> = output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let address: Ipv6Addr = tokens.first()?.parse().ok()?;
+            let value_of = |key: &str| tokens.iter().position(|t| *t == key).and_then(|i| tokens.get(i + 1)).map(|v| v.to_string());
+            let state = tokens.last()?.to_string();
+            if matches!(state.as_str(), "FAILED" | "INCOMPLETE") {
+                return None;
+            }
+            if !matches!(address_scope(&IpAddr::V6(address)), "global" | "unique_local") {
+                return None;
+            }
+            let mac = value_of("lladdr").map(|mac| mac.to_lowercase());
+            Some(Neighbor {
+                interface: value_of("dev").unwrap_or_default(),
+                eui64: mac.as_deref().is_some_and(|mac| is_eui64(address, mac)),
+                address,
+                mac,
+                state,
+                hostname: None,
+            })
+        })
+        .collect();
+    neighbors.sort_by_key(|n| n.address);
+    neighbors.dedup_by_key(|n| n.address);
+    neighbors
+}
+
+/// 地址的低64位是否由该MAC地址按EUI-64生成（插入 ff:fe 并翻转U/L位）
+fn is_eui64(address: Ipv6Addr, mac: &str) -> bool {
+    let bytes: Vec<u8> = mac.split(':').filter_map(|b| u8::from_str_radix(b, 16).ok()).collect();
+    if bytes.len() != 6 {
+        return false;
+    }
+    let octets = address.octets();
+    octets[8..] == [bytes[0] ^ 0x02, bytes[1], bytes[2], 0xff, 0xfe, bytes[3], bytes[4], bytes[5]]
+}
+
+/// 反向查询使用的域名，如 ...8.b.d.0.1.0.0.2.ip6.arpa
+fn reverse_name(ip: Ipv6Addr) -> String {
+    let mut labels: Vec<String> = ip
+        .octets()
+        .iter()
+        .rev()
+        .flat_map(|b| [format!("{:x}", b & 0x0f), format!("{:x}", b >> 4)])
+        .collect();
+    labels.push("ip6.arpa".to_string());
+    labels.join(".")
+}
+
+fn ptr_query(id: u16, name: &str) -> Vec<u8> {
+    let mut query = Vec::with_capacity(DNS_HEADER_LEN + name.len() + 6);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 0, 0, 0]); // 标志为0，一个问题
+    for label in name.split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    query.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    query.extend_from_slice(&CLASS_IN.to_be_bytes());
+    query
+}
+
+/// 读取pos处的域名（支持压缩指针），返回域名及其后的位置
+fn read_name(message: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..64 {
+        let len = *message.get(pos)? as usize;
+        if len & 0xc0 == 0xc0 {
+            end.get_or_insert(pos + 2);
+            pos = ((len & 0x3f) << 8) | *message.get(pos + 1)? as usize;
+            continue;
+        }
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        labels.push(String::from_utf8_lossy(message.get(pos + 1..pos + 1 + len)?).into_owned());
+        pos += 1 + len;
+    }
+    None
+}
+
+/// 从响应中读取第一个PTR记录指向的主机名
+fn parse_ptr_response(response: &[u8], id: u16) -> Option<String> {
+    if response.len() < DNS_HEADER_LEN || response[..2] != id.to_be_bytes() || response[2] & 0x80 == 0 {
+        return None;
+    }
+    let questions = u16::from_be_bytes([response[4], response[5]]);
+    let answers = u16::from_be_bytes([response[6], response[7]]);
+    let mut pos = DNS_HEADER_LEN;
+    for _ in 0..questions {
+        pos = read_name(response, pos)?.1 + 4;
+    }
+    for _ in 0..answers {
+        pos = read_name(response, pos)?.1;
+        let header = response.get(pos..pos + 10)?;
+        let record_type = u16::from_be_bytes([header[0], header[1]]);
+        let length = u16::from_be_bytes([header[8], header[9]]) as usize;
+        if record_type == TYPE_PTR {
+            return read_name(response, pos + 10).map(|(name, _)| name);
+        }
+        pos += 10 + length;
+    }
+    None
+}
+
+/// 向主机的mDNS端口发送反向查询，没有回复时返回None
+async fn mdns_hostname(ip: Ipv6Addr) -> Option<String> {
+    let socket = UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).await.ok()?;
+    socket.connect((ip, MDNS_PORT)).await.ok()?;
+    let id = OsRng.next_u32() as u16;
+    socket.send(&ptr_query(id, &reverse_name(ip))).await.ok()?;
+    let mut buf = [0u8; 1500];
+    let received = tokio::time::timeout(MDNS_TIMEOUT, socket.recv(&mut buf)).await.ok()?.ok()?;
+    parse_ptr_response(&buf[..received], id)
+}
+
+/// 并发查询所有邻居的mDNS主机名
+pub async fn resolve_hostnames(neighbors: &mut [Neighbor]) {
+    let mut tasks = JoinSet::new();
+    for (index, neighbor) in neighbors.iter().enumerate() {
+        let ip = neighbor.address;
+        tasks.spawn(async move { (index, mdns_hostname(ip).await) });
+    }
+    while let Some(Ok((index, hostname))) = tasks.join_next().await {
+        if let Some(hostname) = hostname {
+            debug!("🔍 mDNS: {} -> {}", neighbors[index].address, hostname);
+            neighbors[index].hostname = Some(hostname);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lan_discovery() {
+        let output = "\
+2001:db8::211:32ff:fe12:3456 dev br0 lladdr 00:11:32:12:34:56 REACHABLE
+2001:db8::8c3a:1b2c:3d4e:5f60 dev br0 lladdr 00:11:32:12:34:56 STALE
+fe80::1 dev br0 lladdr 00:11:32:aa:bb:cc router STALE
+2001:db8::99 dev br0 FAILED
+";
+        let neighbors = parse_ip_neigh_output(output);
+        assert_eq!(neighbors.len(), 2);
+        assert!(neighbors[0].eui64 && !neighbors[1].eui64);
+        assert_eq!((neighbors[0].interface.as_str(), neighbors[0].state.as_str()), ("br0", "REACHABLE"));
+
+        let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        assert!(reverse_name(ip).starts_with("1.0.0.0.0.0.0.0.") && reverse_name(ip).ends_with(".8.b.d.0.1.0.0.2.ip6.arpa"));
+
+        // 回显问题并用压缩指针引用 local 的响应
+        let mut response = ptr_query(7, "a.b.local");
+        response[2] = 0x84;
+        response[7] = 1;
+        response.extend_from_slice(&[0xc0, 12, 0, 12, 0, 1, 0, 0, 0, 10, 0, 6, 3, b'n', b'a', b's', 0xc0, 16]);
+        assert_eq!(parse_ptr_response(&response, 7).as_deref(), Some("nas.local"));
+        assert_eq!(parse_ptr_response(&response, 8), None);
+    }
+}
@@ -1,63 +1,166 @@
 use std::path::Path;
 use std::fs;
+use std::sync::OnceLock;
 use tracing_subscriber::{
     fmt,
-    layer::SubscriberExt,
+    layer::{Layered, SubscriberExt},
+    reload,
     util::SubscriberInitExt,
     EnvFilter,
+    Layer,
+    Registry,
 };
 use tracing_appender::{
     non_blocking::WorkerGuard,
     rolling::{RollingFileAppender, Rotation},
 };
+use crate::utils::syslog::SyslogMakeWriter;
 
-/// 初始化日志系统
-/// 支持控制台和文件同步输出，自动日志轮转
-pub fn init_logger() -> anyhow::Result<WorkerGuard> {
-    // 创建日志目录
-    let log_dir = "logs";
-    if !Path::new(log_dir).exists() {
-        fs::create_dir_all(log_dir)?;
+/// 运行时调整日志级别的句柄，以及启动时的过滤规则
+static FILTER_HANDLE: OnceLock<(reload::Handle<EnvFilter, Registry>, String)> = OnceLock::new();
+
+/// 临时修改日志过滤规则（例如 "debug"）
+pub fn set_log_filter(directives: &str) -> anyhow::Result<()> {
+    let (handle, _) = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("日志系统尚未初始化"))?;
+    handle.reload(EnvFilter::try_new(directives)?)?;
+    Ok(())
+}
+
+/// 恢复启动时的日志过滤规则
+pub fn reset_log_filter() -> anyhow::Result<()> {
+    let (handle, initial) = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("日志系统尚未初始化"))?;
+    handle.reload(EnvFilter::try_new(initial)?)?;
+    Ok(())
+}
+
+type FilteredRegistry = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+type BoxedLayer = Box<dyn Layer<FilteredRegistry> + Send + Sync>;
+
+/// 日志输出目标，通过环境变量 LOG_TARGET 选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogTarget {
+    /// 默认：彩色控制台输出，同时写入 logs 目录下的JSON日志文件
+    Default,
+    /// 只输出到标准输出，不带颜色，不写日志文件（适用于由procd/systemd收集输出的环境）
+    Stdout,
+    /// 写入systemd-journald
+    Journald,
+    /// 以RFC5424格式发送到syslog，SYSLOG_ADDR 指定远程地址，默认本地 /dev/log
+    Syslog,
+}
+
+impl LogTarget {
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "default" | "file" => Ok(Self::Default),
+            "stdout" => Ok(Self::Stdout),
+            "journald" => Ok(Self::Journald),
+            "syslog" => Ok(Self::Syslog),
+            other => Err(anyhow::anyhow!("无效的 LOG_TARGET: {}（可选 stdout、journald、syslog）", other)),
+        }
     }
 
-    // 配置日志轮转 - 每天轮转一次，保留7天
-    let file_appender = RollingFileAppender::new(
-        Rotation::DAILY,
-        log_dir,
-        "cloudflare-auto.log",
-    );
+    fn from_env() -> anyhow::Result<Self> {
+        Self::parse(&std::env::var("LOG_TARGET").unwrap_or_default())
+    }
+}
 
-    // 创建非阻塞写入器
-    let (non_blocking_appender, guard) = tracing_appender::non_blocking(file_appender);
+/// 初始化日志系统
+/// 默认支持控制台和文件同步输出，自动日志轮转；可通过 LOG_TARGET 改为标准输出、journald或syslog
+/// console_to_stderr 为 true 时控制台日志改写到标准错误，让标准输出只留给 --print-addr 的地址
+pub fn init_logger(console_to_stderr: bool) -> anyhow::Result<Option<WorkerGuard>> {
+    let target = LogTarget::from_env()?;
 
     // 配置环境过滤器 - 默认INFO级别，可通过RUST_LOG环境变量调整
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("info"));
+    let initial_filter = env_filter.to_string();
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+    let _ = FILTER_HANDLE.set((filter_handle, initial_filter));
+
+    let mut layers: Vec<BoxedLayer> = Vec::new();
+    let mut guard = None;
+    match target {
+        LogTarget::Default | LogTarget::Stdout => {
+            // 配置控制台输出格式，设置 NO_COLOR 时也不输出颜色
+            let ansi = target == LogTarget::Default && std::env::var_os("NO_COLOR").is_none();
+            let console = fmt::layer()
+                .with_ansi(ansi)
+                .with_target(false)
+                .with_thread_ids(false)
+                .with_thread_names(false)
+                .with_file(false)
+                .with_line_number(false)
+                .compact();
+            if console_to_stderr {
+                layers.push(console.with_writer(std::io::stderr).boxed());
+            } else {
+                layers.push(console.boxed());
+            }
+        }
+        LogTarget::Journald => {
+            #[cfg(target_os = "linux")]
+            layers.push(tracing_journald::layer()?.boxed());
+            #[cfg(not(target_os = "linux"))]
+            return Err(anyhow::anyhow!("journald 仅支持Linux"));
+        }
+        LogTarget::Syslog => {
+            let addr = std::env::var("SYSLOG_ADDR").ok().filter(|addr| !addr.is_empty());
+            let writer = SyslogMakeWriter::connect(addr.as_deref())?;
+            // 时间和级别由syslog消息头携带
+            layers.push(
+                fmt::layer()
+                    .with_writer(writer)
+                    .with_ansi(false)
+                    .without_time()
+                    .with_level(false)
+                    .with_target(false)
+                    .compact()
+                    .boxed(),
+            );
+        }
+    }
 
-    // 配置控制台输出格式
-    let console_layer = fmt::layer()
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_thread_names(false)
-        .with_file(false)
-        .with_line_number(false)
-        .compact();
-
-    // 配置文件输出格式
-    let file_layer = fmt::layer()
-        .with_writer(non_blocking_appender)
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_file(true)
-        .with_line_number(true)
-        .json();
+    if target == LogTarget::Default {
+        // 创建日志目录
+        let log_dir = "logs";
+        if !Path::new(log_dir).exists() {
+            fs::create_dir_all(log_dir)?;
+        }
+
+        // 配置日志轮转 - 每天轮转一次，保留7天
+        let file_appender = RollingFileAppender::new(
+            Rotation::DAILY,
+            log_dir,
+            "cloudflare-auto.log",
+        );
+
+        // 创建非阻塞写入器
+        let (non_blocking_appender, file_guard) = tracing_appender::non_blocking(file_appender);
+        guard = Some(file_guard);
+
+        // 配置文件输出格式
+        layers.push(
+            fmt::layer()
+                .with_writer(non_blocking_appender)
+                .with_target(true)
+                .with_thread_ids(true)
+                .with_thread_names(true)
+                .with_file(true)
+                .with_line_number(true)
+                .json()
+                .boxed(),
+        );
+    }
 
     // 初始化订阅者
     tracing_subscriber::registry()
         .with(env_filter)
-        .with(console_layer)
-        .with(file_layer)
+        .with(layers)
         .init();
 
     Ok(guard)
@@ -1,2 +1,15 @@
+pub mod address_selection;
+pub mod clock;
+pub mod csv;
+pub mod debug_capture;
 pub mod network;
-pub mod logger;
\ No newline at end of file
+pub mod prefix_delegation;
+pub mod logger;
+pub mod rate_limiter;
+pub mod router_ipv4;
+pub mod stun;
+pub mod syslog;
+pub mod time_format;
+pub mod i18n;
+pub mod ip_command;
+pub mod lan_discovery;
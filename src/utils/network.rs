@@ -1,5 +1,18 @@
-use std::net::{IpAddr, UdpSocket};
+use std::net::{IpAddr, Ipv6Addr, UdpSocket};
 use anyhow::{Result, anyhow};
+use ipnetwork::Ipv6Network;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// 内置的外部IPv6检测服务，未配置服务列表时按顺序尝试
+pub const EXTERNAL_IPV6_SERVICES: &[&str] = &[
+    "https://api6.ipify.org",
+    "https://v6.ident.me",
+    "https://ifconfig.co/ip",
+];
+
+/// 外部服务返回的内容超过该长度时视为无效（正常响应只有一个地址）
+const MAX_EXTERNAL_RESPONSE_LEN: usize = 1024;
 
 /// 获取本机IPv6地址
 pub fn get_local_ipv6() -> Result<IpAddr> {
@@ -17,9 +30,352 @@ pub fn get_local_ipv6() -> Result<IpAddr> {
     }
 }
 
-/// 获取首选IPv6地址（使用UDP连接方法）
+/// 获取首选IPv6地址（使用UDP连接方法），首选地址不是可发布的全局单播地址时返回错误
 pub fn get_preferred_ipv6() -> Result<IpAddr> {
-    get_local_ipv6()
+    let ip = get_local_ipv6()?;
+    if let IpAddr::V6(ipv6) = ip {
+        check_publishable(ipv6, false).map_err(|e| anyhow!("系统首选的IPv6地址不能发布: {}", e))?;
+    }
+    Ok(ip)
+}
+
+/// 检查地址能否发布到DNS：拒绝未指定、回环、组播、链路本地和文档示例地址，allow_unique_local为false时也拒绝唯一本地地址
+pub fn check_publishable(ip: Ipv6Addr, allow_unique_local: bool) -> Result<()> {
+    let [first, second, ..] = ip.segments();
+    // 文档示例地址 2001:db8::/32 和 3fff::/20（RFC 3849、RFC 9637）
+    let documentation = (first == 0x2001 && second == 0x0db8) || (first == 0x3fff && second & 0xf000 == 0);
+    let problem = if ip.is_unspecified() {
+        "未指定地址 ::"
+    } else if ip.is_loopback() {
+        "回环地址 ::1"
+    } else if ip.is_multicast() {
+        "组播地址"
+    } else if (first & 0xffc0) == 0xfe80 {
+        "链路本地地址（fe80::/10），只在本网段有效"
+    } else if documentation {
+        "文档示例地址（2001:db8::/32、3fff::/20）"
+    } else if !allow_unique_local && (first & 0xfe00) == 0xfc00 {
+        "唯一本地地址（fc00::/7），只在内网可达"
+    } else {
+        return Ok(());
+    };
+    Err(anyhow!("{} 是{}", ip, problem))
+}
+
+/// 检查地址是否在允许发布的前缀内，prefix为None时不限制
+pub fn check_allowed_prefix(ip: Ipv6Addr, prefix: Option<&str>) -> Result<()> {
+    let Some(prefix) = prefix else { return Ok(()) };
+    let network: Ipv6Network = prefix.parse().map_err(|e| anyhow!("无效的允许前缀 {}: {}", prefix, e))?;
+    if network.contains(ip) {
+        Ok(())
+    } else {
+        Err(anyhow!("检测到的地址 {} 不在允许的前缀 {} 内，拒绝发布", ip, network))
+    }
+}
+
+/// 多线路场景下接受的IPv6前缀，priority越小越优先
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct PrefixRule {
+    pub label: String,  // 线路标签，例如 ISP-A
+    pub prefix: String, // CIDR格式的前缀，例如 2001:db8:1::/48
+    #[serde(default)]
+    pub priority: u32,
+}
+
+impl PrefixRule {
+    /// 解析前缀
+    pub fn network(&self) -> Result<Ipv6Network> {
+        self.prefix
+            .parse::<Ipv6Network>()
+            .map_err(|e| anyhow!("无效的IPv6前缀 {}: {}", self.prefix, e))
+    }
+}
+
+/// 是否为可发布到公网的全局单播IPv6地址
+pub fn is_global_ipv6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !ip.is_loopback()
+        && !ip.is_unspecified()
+        && !ip.is_multicast()
+        && (first & 0xffc0) != 0xfe80 // 链路本地 fe80::/10
+        && (first & 0xfe00) != 0xfc00 // 唯一本地 fc00::/7
+}
+
+/// 地址的类别：ipv4 / global / link_local / unique_local / loopback / multicast
+pub fn address_scope(ip: &IpAddr) -> &'static str {
+    let IpAddr::V6(ip) = ip else { return "ipv4" };
+    let first = ip.segments()[0];
+    if ip.is_loopback() {
+        "loopback"
+    } else if ip.is_multicast() {
+        "multicast"
+    } else if (first & 0xffc0) == 0xfe80 {
+        "link_local"
+    } else if (first & 0xfe00) == 0xfc00 {
+        "unique_local"
+    } else {
+        "global"
+    }
+}
+
+/// 网卡及其上的地址
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub loopback: bool,
+    pub addresses: Vec<InterfaceAddress>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceAddress {
+    pub address: IpAddr,
+    pub scope: &'static str,
+    pub preferred: bool, // 是否为系统访问公网时的首选源地址
+}
+
+/// 列出所有网卡及其地址，按系统返回的顺序
+pub fn list_interfaces() -> Result<Vec<NetworkInterface>> {
+    let preferred = get_local_ipv6().ok();
+    let mut interfaces: Vec<NetworkInterface> = Vec::new();
+    for iface in if_addrs::get_if_addrs()? {
+        let ip = iface.ip();
+        let address = InterfaceAddress {
+            address: ip,
+            scope: address_scope(&ip),
+            preferred: Some(ip) == preferred,
+        };
+        match interfaces.iter_mut().find(|existing| existing.name == iface.name) {
+            Some(existing) => existing.addresses.push(address),
+            None => interfaces.push(NetworkInterface {
+                name: iface.name.clone(),
+                loopback: iface.is_loopback(),
+                addresses: vec![address],
+            }),
+        }
+    }
+    Ok(interfaces)
+}
+
+/// 本机IPv6地址及其属性
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressInfo {
+    pub interface: String,
+    pub address: Ipv6Addr,
+    pub valid_lifetime: Option<u64>, // 剩余有效期（秒），None表示永久或无法获取
+    pub temporary: bool,             // RFC 4941 隐私扩展生成的临时地址
+    pub deprecated: bool,            // 首选有效期已过，不再用于新连接
+}
+
+/// 获取可发布的IPv6地址（全局单播和唯一本地地址）及其属性，interface为None时包括所有网卡
+/// Linux上通过 `ip -6 addr` 读取有效期和地址标志，其他平台或读取失败时只有地址本身
+pub fn get_ipv6_address_info(interface: Option<&str>) -> Result<Vec<AddressInfo>> {
+    let mut addresses = match read_ip_addr_output() {
+        Some(output) => parse_ip_addr_output(&output),
+        None => if_addrs::get_if_addrs()?
+            .into_iter()
+            .filter_map(|iface| match iface.ip() {
+                IpAddr::V6(address) => Some(AddressInfo {
+                    interface: iface.name,
+                    address,
+                    valid_lifetime: None,
+                    temporary: false,
+                    deprecated: false,
+                }),
+                _ => None,
+            })
+            .collect(),
+    };
+    if let Some(name) = interface {
+        addresses.retain(|info| info.interface == name);
+        if addresses.is_empty() {
+            return Err(anyhow!("找不到网卡 {}，或网卡上没有IPv6地址", name));
+        }
+    }
+    addresses.retain(|info| matches!(address_scope(&IpAddr::V6(info.address)), "global" | "unique_local"));
+    Ok(addresses)
+}
+
+/// 优先使用稳定地址：有非临时且未弃用的地址时只保留这些地址，否则原样返回
+pub fn prefer_stable(candidates: Vec<AddressInfo>) -> Vec<AddressInfo> {
+    let is_stable = |info: &AddressInfo| !info.temporary && !info.deprecated;
+    if candidates.iter().any(is_stable) {
+        candidates.into_iter().filter(is_stable).collect()
+    } else {
+        candidates
+    }
+}
+
+fn read_ip_addr_output() -> Option<String> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let output = std::process::Command::new("ip").args(["-o", "-6", "addr", "show"]).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 解析 `ip -o -6 addr show` 的输出，每行形如
+/// `2: eth0    inet6 2001:db8::1/64 scope global temporary dynamic \       valid_lft 86393sec preferred_lft 14393sec`
+fn parse_ip_addr_output(output: &str) -> Vec<AddressInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let interface = tokens.get(1)?.split('@').next()?.to_string();
+            let inet6 = tokens.iter().position(|t| *t == "inet6")?;
+            let address = tokens.get(inet6 + 1)?.split('/').next()?.parse().ok()?;
+            let valid_lifetime = tokens
+                .iter()
+                .position(|t| *t == "valid_lft")
+                .and_then(|i| tokens.get(i + 1))
+                .and_then(|value| value.strip_suffix("sec"))
+                .and_then(|secs| secs.parse().ok());
+            Some(AddressInfo {
+                interface,
+                address,
+                valid_lifetime,
+                temporary: tokens.contains(&"temporary"),
+                deprecated: tokens.contains(&"deprecated"),
+            })
+        })
+        .collect()
+}
+
+/// 按线路策略从候选地址中选择：只接受匹配规则的地址，优先级相同时优先使用系统首选地址
+pub fn select_by_prefix_rules<'a>(
+    candidates: &[Ipv6Addr],
+    rules: &'a [PrefixRule],
+    preferred: Option<Ipv6Addr>,
+) -> Option<(Ipv6Addr, &'a PrefixRule)> {
+    let mut best: Option<(Ipv6Addr, &PrefixRule)> = None;
+    for ip in candidates {
+        let rule = rules
+            .iter()
+            .filter(|rule| rule.network().map(|net| net.contains(*ip)).unwrap_or(false))
+            .min_by_key(|rule| rule.priority);
+        let Some(rule) = rule else { continue };
+        
+        let better = match best {
+            None => true,
+            Some((best_ip, best_rule)) => {
+                rule.priority < best_rule.priority
+                    || (rule.priority == best_rule.priority && Some(*ip) == preferred && best_ip != *ip)
+            }
+        };
+        if better {
+            best = Some((*ip, rule));
+        }
+    }
+    best
+}
+
+/// 按线路策略获取IPv6地址，指定网卡时只考虑该网卡上的地址，没有任何地址匹配已接受的前缀时返回错误
+/// skip_temporary为true时优先使用稳定地址
+pub fn get_ipv6_by_prefix_rules(rules: &[PrefixRule], interface: Option<&str>, skip_temporary: bool) -> Result<(IpAddr, String)> {
+    let mut candidates = get_ipv6_address_info(interface)?;
+    candidates.retain(|info| is_global_ipv6(&info.address));
+    if skip_temporary {
+        candidates = prefer_stable(candidates);
+    }
+    let candidates: Vec<Ipv6Addr> = candidates.into_iter().map(|info| info.address).collect();
+    let preferred = match get_local_ipv6() {
+        Ok(IpAddr::V6(ip)) => Some(ip),
+        _ => None,
+    };
+    
+    select_by_prefix_rules(&candidates, rules, preferred)
+        .map(|(ip, rule)| (IpAddr::V6(ip), rule.label.clone()))
+        .ok_or_else(|| anyhow!(
+            "没有匹配已接受前缀的IPv6地址，拒绝发布（候选地址: {:?}）",
+            candidates
+        ))
+}
+
+/// 校验外部IP检测服务地址，只接受 http/https
+pub fn validate_ip_service(url: &str) -> Result<()> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| anyhow!("外部IP检测服务地址无效: {} ({})", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(anyhow!("外部IP检测服务地址需以 http:// 或 https:// 开头: {}", url));
+    }
+    Ok(())
+}
+
+/// 解析外部服务的响应：纯文本地址，或带 ip 字段的JSON；只接受全局单播IPv6地址
+pub fn parse_external_response(body: &str) -> Result<Ipv6Addr> {
+    let body = body.trim();
+    if body.len() > MAX_EXTERNAL_RESPONSE_LEN {
+        return Err(anyhow!("响应内容过长，不是IP地址"));
+    }
+    let text = if body.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(body)?;
+        json.get("ip")
+            .and_then(|ip| ip.as_str())
+            .ok_or_else(|| anyhow!("JSON响应中没有 ip 字段"))?
+            .trim()
+            .to_string()
+    } else {
+        body.to_string()
+    };
+    match text.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) if is_global_ipv6(&ip) => Ok(ip),
+        Ok(IpAddr::V6(ip)) => Err(anyhow!("返回的 {} 不是全局单播IPv6地址", ip)),
+        Ok(IpAddr::V4(ip)) => Err(anyhow!("返回的是IPv4地址 {}", ip)),
+        Err(_) => Err(anyhow!("响应不是IP地址: {}", text.chars().take(64).collect::<String>())),
+    }
+}
+
+/// 从指定网卡发出请求时使用的源地址：该网卡上的全局单播地址，优先使用稳定地址
+pub fn interface_source_address(interface: &str) -> Result<Ipv6Addr> {
+    let candidates = get_ipv6_address_info(Some(interface))?
+        .into_iter()
+        .filter(|info| is_global_ipv6(&info.address))
+        .collect();
+    prefer_stable(candidates)
+        .first()
+        .map(|info| info.address)
+        .ok_or_else(|| anyhow!("网卡 {} 上没有全局IPv6地址，无法从该网卡发出请求", interface))
+}
+
+/// 通过外部HTTP服务获取公网视角下的IPv6地址，按顺序尝试，services为空时使用内置列表；
+/// builder带有应用配置中的超时、代理和根证书；source为请求绑定的源地址，用于从指定网卡发出
+pub async fn get_external_ipv6(builder: reqwest::ClientBuilder, services: &[String], source: Option<Ipv6Addr>) -> Result<Ipv6Addr> {
+    // 绑定IPv6本地地址，确保请求通过IPv6发出
+    let client = builder
+        .local_address(IpAddr::V6(source.unwrap_or(Ipv6Addr::UNSPECIFIED)))
+        .build()?;
+    
+    let services: Vec<&str> = if services.is_empty() {
+        EXTERNAL_IPV6_SERVICES.to_vec()
+    } else {
+        services.iter().map(|s| s.trim()).collect()
+    };
+    let mut last_error = anyhow!("没有可用的外部IP检测服务");
+    for service in services {
+        let result = async {
+            let text = client
+                .get(service)
+                .header(reqwest::header::ACCEPT, "text/plain, application/json")
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            parse_external_response(&text)
+        }.await;
+        
+        match result {
+            Ok(ip) => {
+                debug!("🌐 外部IP检测服务 {} 返回: {}", service, ip);
+                return Ok(ip);
+            }
+            Err(e) => {
+                debug!("外部IP检测服务 {} 失败: {}", service, e);
+                let message = format!("外部IP检测服务 {} 失败: {}", service, e);
+                last_error = e.context(message);
+            }
+        }
+    }
+    
+    Err(last_error)
 }
 
 #[cfg(test)]
@@ -27,8 +383,77 @@ mod tests {
     use super::*;
     
     #[test]
-    fn test_get_all_ipv6_addresses() {
-        let result = get_all_ipv6_addresses();
+    fn test_select_by_prefix_rules() {
+        let rules = vec![
+            PrefixRule { label: "ISP-A".to_string(), prefix: "2001:db8:a::/48".to_string(), priority: 0 },
+            PrefixRule { label: "ISP-B".to_string(), prefix: "2001:db8:b::/48".to_string(), priority: 1 },
+        ];
+        let a: Ipv6Addr = "2001:db8:a::1".parse().unwrap();
+        let b: Ipv6Addr = "2001:db8:b::1".parse().unwrap();
+        let other: Ipv6Addr = "2001:db8:c::1".parse().unwrap();
+
+        // 系统首选ISP-B时仍按策略选择ISP-A
+        let (ip, rule) = select_by_prefix_rules(&[b, a], &rules, Some(b)).unwrap();
+        assert_eq!((ip, rule.label.as_str()), (a, "ISP-A"));
+
+        // ISP-A故障后切换到ISP-B
+        let (ip, _) = select_by_prefix_rules(&[b, other], &rules, Some(other)).unwrap();
+        assert_eq!(ip, b);
+
+        // 没有匹配的前缀时不发布
+        assert!(select_by_prefix_rules(&[other], &rules, Some(other)).is_none());
+    }
+
+    #[test]
+    fn test_address_scope() {
+        let scope = |ip: &str| address_scope(&ip.parse().unwrap());
+        assert_eq!(scope("2001:db8::1"), "global");
+        assert_eq!(scope("fe80::1"), "link_local");
+        assert_eq!(scope("fd00::1"), "unique_local");
+        assert_eq!(scope("::1"), "loopback");
+        assert_eq!(scope("192.168.1.1"), "ipv4");
+        assert!(get_ipv6_address_info(Some("no-such-iface0")).is_err());
+    }
+
+    #[test]
+    fn test_parse_ip_addr_output() {
+        let output = "1: lo    inet6 ::1/128 scope host \\       valid_lft forever preferred_lft forever\n\
+            2: eth0    inet6 2001:db8::abcd/64 scope global temporary dynamic \\       valid_lft 86393sec preferred_lft 14393sec\n\
+            3: br0@if5    inet6 2001:db8::1/64 scope global deprecated \\       valid_lft forever preferred_lft 0sec\n";
+        let addresses = parse_ip_addr_output(output);
+        assert_eq!(addresses.len(), 3);
+        assert_eq!(addresses[1].interface, "eth0");
+        assert!(addresses[1].temporary);
+        assert_eq!(addresses[1].valid_lifetime, Some(86393));
+        assert_eq!(addresses[2].interface, "br0");
+        assert!(addresses[2].deprecated && !addresses[2].temporary);
+        assert_eq!(addresses[2].valid_lifetime, None);
+    }
+
+    #[test]
+    fn test_parse_external_response() {
+        let ip: Ipv6Addr = "2001:db8::10".parse().unwrap();
+        assert_eq!(parse_external_response("2001:db8::10\n").unwrap(), ip);
+        assert_eq!(parse_external_response(r#"{"ip": "2001:db8::10", "country": "CN"}"#).unwrap(), ip);
+        assert!(parse_external_response("203.0.113.1").is_err());
+        assert!(parse_external_response("fd00::1").is_err());
+        assert!(parse_external_response("<html>rate limited</html>").is_err());
+        assert!(validate_ip_service("https://ifconfig.co/ip").is_ok());
+        assert!(validate_ip_service("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn test_get_preferred_ipv6() {
+        let result = get_preferred_ipv6();
         assert!(result.is_ok() || result.is_err());
+        assert!(interface_source_address("no-such-iface0").is_err());
+
+        let check = |ip: &str, allow_unique_local| check_publishable(ip.parse().unwrap(), allow_unique_local).is_ok();
+        assert!(check("2400:cb00::1", false));
+        assert!(!check("fe80::1", true) && !check("::1", true) && !check("2001:db8::1", true) && !check("3fff::1", true));
+        assert!(check("fd00::1", true) && !check("fd00::1", false));
+        let ip: Ipv6Addr = "2400:cb00:1::5".parse().unwrap();
+        assert!(check_allowed_prefix(ip, None).is_ok() && check_allowed_prefix(ip, Some("2400:cb00::/32")).is_ok());
+        assert!(check_allowed_prefix(ip, Some("2001:db8::/32")).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+use anyhow::{Result, anyhow};
+use ipnetwork::Ipv6Network;
+use std::net::Ipv6Addr;
+
+/// 委派前缀长度的允许范围，常见为运营商委派的 /48、/56 或 /60，以及单个 /64
+pub const MIN_PREFIX_LEN: u8 = 48;
+pub const MAX_PREFIX_LEN: u8 = 64;
+
+fn host_mask(prefix_len: u8) -> u128 {
+    u128::MAX >> prefix_len
+}
+
+/// 校验委派前缀长度
+pub fn validate_prefix_len(prefix_len: u8) -> Result<()> {
+    if (MIN_PREFIX_LEN..=MAX_PREFIX_LEN).contains(&prefix_len) {
+        Ok(())
+    } else {
+        Err(anyhow!("委派前缀长度需在 {}-{} 之间", MIN_PREFIX_LEN, MAX_PREFIX_LEN))
+    }
+}
+
+/// 解析主机后缀（如 ::1234 或 ::1:211:32ff:fe12:3456），后缀只能占用前缀之后的位
+pub fn parse_host_suffix(suffix: &str, prefix_len: u8) -> Result<u128> {
+    let ip: Ipv6Addr = suffix
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("后缀格式无效: {}，应为类似 ::1234 的IPv6地址", suffix))?;
+    let bits = u128::from(ip);
+    if bits & !host_mask(prefix_len) != 0 {
+        return Err(anyhow!("后缀 {} 超出了 /{} 前缀之后的范围", suffix, prefix_len));
+    }
+    if bits == 0 {
+        return Err(anyhow!("后缀不能为 ::"));
+    }
+    Ok(bits)
+}
+
+/// 取地址的前 prefix_len 位，与后缀拼接成新地址
+pub fn combine(ip: Ipv6Addr, prefix_len: u8, suffix: u128) -> Ipv6Addr {
+    Ipv6Addr::from((u128::from(ip) & !host_mask(prefix_len)) | suffix)
+}
+
+/// 地址在委派前缀之后的部分，即作为主机后缀发布的值
+pub fn suffix_of(ip: Ipv6Addr, prefix_len: u8) -> Ipv6Addr {
+    Ipv6Addr::from(u128::from(ip) & host_mask(prefix_len))
+}
+
+/// 地址所在的委派前缀
+pub fn prefix_of(ip: Ipv6Addr, prefix_len: u8) -> Ipv6Network {
+    let network = Ipv6Addr::from(u128::from(ip) & !host_mask(prefix_len));
+    Ipv6Network::new(network, prefix_len).expect("前缀长度不超过128")
+}
+
+/// 两个地址的接口标识相同而委派前缀不同时（只有前缀变化），返回新旧前缀
+pub fn prefix_only_change(old: Ipv6Addr, new: Ipv6Addr, prefix_len: u8) -> Option<(Ipv6Network, Ipv6Network)> {
+    let mask = host_mask(prefix_len);
+    let (old_prefix, new_prefix) = (prefix_of(old, prefix_len), prefix_of(new, prefix_len));
+    (u128::from(old) & mask == u128::from(new) & mask && old_prefix != new_prefix).then_some((old_prefix, new_prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_delegation() {
+        let router: Ipv6Addr = "2001:db8:aa00:1::1".parse().unwrap();
+        let nas = parse_host_suffix("::1:211:32ff:fe12:3456", 56).unwrap();
+        assert_eq!(combine(router, 56, nas), "2001:db8:aa00:1:211:32ff:fe12:3456".parse::<Ipv6Addr>().unwrap());
+        assert_eq!(combine(router, 64, parse_host_suffix("::10", 64).unwrap()), "2001:db8:aa00:1::10".parse::<Ipv6Addr>().unwrap());
+        // /64 时子网号属于前缀，不能出现在后缀中
+        assert!(parse_host_suffix("::1:211:32ff:fe12:3456", 64).is_err());
+        assert!(parse_host_suffix("::", 64).is_err());
+        assert_eq!(suffix_of("2001:db8:aa00:1:211:32ff:fe12:3456".parse().unwrap(), 56).to_string(), "::1:211:32ff:fe12:3456");
+        assert!(validate_prefix_len(56).is_ok() && validate_prefix_len(80).is_err());
+
+        let renumbered: Ipv6Addr = "2001:db8:bb00:1::1".parse().unwrap();
+        let (old, new) = prefix_only_change(router, renumbered, 56).unwrap();
+        assert_eq!((old.to_string(), new.to_string()), ("2001:db8:aa00::/56".to_string(), "2001:db8:bb00::/56".to_string()));
+        assert!(prefix_only_change(router, "2001:db8:bb00:1::2".parse().unwrap(), 56).is_none());
+        assert!(prefix_only_change(router, router, 56).is_none());
+    }
+}
@@ -0,0 +1,62 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep, Instant};
+
+/// Cloudflare API限制为每5分钟1200次请求，这里保守地限制为每秒3次
+const CLOUDFLARE_REQUESTS_PER_SECOND: f64 = 3.0;
+/// 允许的突发请求数
+const CLOUDFLARE_BURST: f64 = 10.0;
+
+/// 令牌桶限流器
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    /// 创建限流器，rate为每秒补充的令牌数，capacity为桶容量
+    pub fn new(rate: f64, capacity: f64) -> Self {
+        Self {
+            rate,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// 获取一个令牌，令牌不足时等待
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.rate).min(self.capacity);
+                state.last_refill = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    return;
+                }
+
+                Duration::from_secs_f64((1.0 - state.tokens) / self.rate)
+            };
+            sleep(wait).await;
+        }
+    }
+}
+
+/// 所有Cloudflare客户端共享的全局限流器
+pub fn cloudflare_rate_limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(|| RateLimiter::new(CLOUDFLARE_REQUESTS_PER_SECOND, CLOUDFLARE_BURST))
+}
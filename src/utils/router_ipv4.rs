@@ -0,0 +1,224 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+/// NAT-PMP（RFC 6886）网关端口，首次等待250毫秒，之后每次加倍
+const NATPMP_PORT: u16 = 5351;
+const NATPMP_ATTEMPTS: u32 = 4;
+const NATPMP_INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+/// SSDP组播地址及等待路由器响应的时间
+const SSDP_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::new(239, 255, 255, 250), 1900);
+const SSDP_TIMEOUT: Duration = Duration::from_secs(3);
+const UPNP_HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+const IGD_DEVICE: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+
+/// 路由器报告的外部IPv4地址
+#[derive(Debug, Clone, Serialize)]
+pub struct RouterIpv4 {
+    pub address: Ipv4Addr,
+    pub method: &'static str, // nat_pmp 或 upnp
+    /// 是否为公网地址；路由器本身位于运营商级NAT（100.64.0.0/10）或上级路由之后时为false，此时不能发布
+    pub public: bool,
+}
+
+/// 依次通过NAT-PMP和UPnP IGD向本地路由器查询外部IPv4地址，gateway为None时使用默认网关
+pub async fn get_router_ipv4(gateway: Option<Ipv4Addr>) -> Result<RouterIpv4> {
+    let result = |address: Ipv4Addr, method| RouterIpv4 { address, method, public: is_public_ipv4(address) };
+    let natpmp_error = match gateway.map(Ok).unwrap_or_else(default_gateway) {
+        Ok(gateway) => match natpmp_external_ipv4(gateway).await {
+            Ok(address) => return Ok(result(address, "nat_pmp")),
+            Err(e) => e,
+        },
+        Err(e) => e,
+    };
+    debug!("NAT-PMP查询失败，改用UPnP: {}", natpmp_error);
+    match upnp_external_ipv4().await {
+        Ok(address) => Ok(result(address, "upnp")),
+        Err(e) => Err(anyhow!("NAT-PMP: {}；UPnP: {}", natpmp_error, e)),
+    }
+}
+
+/// 从 /proc/net/route 读取IPv4默认网关
+fn default_gateway() -> Result<Ipv4Addr> {
+    let routes = std::fs::read_to_string("/proc/net/route")
+        .map_err(|_| anyhow!("无法读取默认网关，请指定路由器地址"))?;
+    parse_default_gateway(&routes).ok_or_else(|| anyhow!("没有IPv4默认路由"))
+}
+
+/// 解析 /proc/net/route，默认路由的目标和掩码为0，网关为按本机字节序打印的十六进制
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+    routes.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.get(1) != Some(&"00000000") || fields.get(7) != Some(&"00000000") {
+            return None;
+        }
+        let gateway = u32::from_str_radix(fields.get(2)?, 16).ok()?;
+        Some(Ipv4Addr::from(gateway.to_ne_bytes())).filter(|ip| !ip.is_unspecified())
+    })
+}
+
+async fn natpmp_external_ipv4(gateway: Ipv4Addr) -> Result<Ipv4Addr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.connect((gateway, NATPMP_PORT)).await?;
+    let mut timeout = NATPMP_INITIAL_TIMEOUT;
+    let mut buf = [0u8; 16];
+    for _ in 0..NATPMP_ATTEMPTS {
+        // 版本0，操作码0：查询外部地址
+        socket.send(&[0, 0]).await?;
+        match tokio::time::timeout(timeout, socket.recv(&mut buf)).await {
+            Ok(received) => return parse_natpmp_response(&buf[..received?]),
+            Err(_) => timeout *= 2,
+        }
+    }
+    Err(anyhow!("网关 {} 没有响应NAT-PMP请求", gateway))
+}
+
+/// 外部地址响应：版本、操作码128、2字节结果码、4字节时间、4字节地址
+fn parse_natpmp_response(response: &[u8]) -> Result<Ipv4Addr> {
+    if response.len() < 12 || response[0] != 0 || response[1] != 128 {
+        return Err(anyhow!("NAT-PMP响应格式不正确"));
+    }
+    match u16::from_be_bytes([response[2], response[3]]) {
+        0 => Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11])),
+        code => Err(anyhow!("NAT-PMP返回错误码 {}", code)),
+    }
+}
+
+/// 通过SSDP找到路由器的描述文件，再调用WAN连接服务的 GetExternalIPAddress
+async fn upnp_external_ipv4() -> Result<Ipv4Addr> {
+    let location = discover_igd().await?;
+    let client = reqwest::Client::builder().no_proxy().timeout(UPNP_HTTP_TIMEOUT).build()?;
+    let description = client.get(&location).send().await?.error_for_status()?.text().await?;
+    let (service_type, control_url) = find_wan_service(&description, &location)
+        .ok_or_else(|| anyhow!("路由器 {} 没有WAN连接服务", location))?;
+
+    let body = format!(
+        "<?xml version=\"1.0\"?>\
+         <s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\">\
+         <s:Body><u:GetExternalIPAddress xmlns:u=\"{}\"/></s:Body></s:Envelope>",
+        service_type
+    );
+    let response = client
+        .post(&control_url)
+        .header(reqwest::header::CONTENT_TYPE, "text/xml; charset=\"utf-8\"")
+        .header("SOAPAction", format!("\"{}#GetExternalIPAddress\"", service_type))
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let text = xml_text(&response, "NewExternalIPAddress").ok_or_else(|| anyhow!("UPnP响应中没有外部地址"))?;
+    text.parse().map_err(|_| anyhow!("UPnP返回的外部地址无效: {}", text))
+}
+
+async fn discover_igd() -> Result<String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    let request = format!(
+        "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: {}\r\n\r\n",
+        IGD_DEVICE
+    );
+    socket.send_to(request.as_bytes(), SSDP_ADDR).await?;
+    let mut buf = [0u8; 2048];
+    let deadline = tokio::time::Instant::now() + SSDP_TIMEOUT;
+    loop {
+        let (received, from) = tokio::time::timeout_at(deadline, socket.recv_from(&mut buf))
+            .await
+            .map_err(|_| anyhow!("局域网内没有响应的UPnP路由器"))??;
+        if let Some(location) = parse_location(&String::from_utf8_lossy(&buf[..received])) {
+            debug!("🔍 UPnP路由器 {}: {}", from, location);
+            return Ok(location);
+        }
+    }
+}
+
+/// 读取SSDP响应中的 LOCATION 头
+fn parse_location(response: &str) -> Option<String> {
+    response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim().eq_ignore_ascii_case("location").then(|| value.trim().to_string())
+    })
+}
+
+/// 在设备描述中查找 WANIPConnection 或 WANPPPConnection 服务，返回服务类型和完整的控制地址
+fn find_wan_service(description: &str, location: &str) -> Option<(String, String)> {
+    description.split("<service>").skip(1).find_map(|service| {
+        let service_type = xml_text(service, "serviceType")?;
+        if !service_type.contains(":WANIPConnection:") && !service_type.contains(":WANPPPConnection:") {
+            return None;
+        }
+        let control_url = xml_text(service, "controlURL")?;
+        Some((service_type.to_string(), resolve_url(location, control_url)))
+    })
+}
+
+/// 控制地址可以是相对路径，相对于描述文件所在的主机
+fn resolve_url(location: &str, path: &str) -> String {
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return path.to_string();
+    }
+    let scheme_end = location.find("://").map(|i| i + 3).unwrap_or(0);
+    let host_end = location[scheme_end..].find('/').map(|i| scheme_end + i).unwrap_or(location.len());
+    format!("{}/{}", &location[..host_end], path.trim_start_matches('/'))
+}
+
+/// 取第一个该标签（可带命名空间前缀）的文本内容
+fn xml_text<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = xml.find(&format!("{}>", tag))?;
+    let start = open + tag.len() + 1;
+    let end = start + xml[start..].find("</")?;
+    Some(xml[start..end].trim())
+}
+
+/// 可以作为A记录发布的公网地址：排除私有、回环、链路本地、运营商级NAT（100.64.0.0/10）、文档和保留地址
+pub fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    let shared = octets[0] == 100 && octets[1] & 0xc0 == 64;
+    let reserved = octets[0] >= 240;
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || shared
+        || reserved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_router_ipv4_parsing() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+                      eth0\t0001A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+                      eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+        let expected = Ipv4Addr::from(u32::from_str_radix("0101A8C0", 16).unwrap().to_ne_bytes());
+        assert_eq!(parse_default_gateway(routes), Some(expected));
+
+        let response = [0, 128, 0, 0, 0, 0, 0, 9, 203, 0, 113, 7];
+        assert_eq!(parse_natpmp_response(&response).unwrap(), Ipv4Addr::new(203, 0, 113, 7));
+        assert!(parse_natpmp_response(&[0, 128, 0, 3, 0, 0, 0, 9, 0, 0, 0, 0]).is_err());
+
+        let description = "<root><device><serviceList>\
+            <service><serviceType>urn:schemas-upnp-org:service:Layer3Forwarding:1</serviceType><controlURL>/l3f</controlURL></service>\
+            <service><serviceType>urn:schemas-upnp-org:service:WANIPConnection:1</serviceType><controlURL>/ctl/IPConn</controlURL></service>\
+            </serviceList></device></root>";
+        let (service_type, url) = find_wan_service(description, "http://192.168.1.1:5000/rootDesc.xml").unwrap();
+        assert_eq!(service_type, "urn:schemas-upnp-org:service:WANIPConnection:1");
+        assert_eq!(url, "http://192.168.1.1:5000/ctl/IPConn");
+        assert_eq!(parse_location("HTTP/1.1 200 OK\r\nLocation: http://192.168.1.1:5000/rootDesc.xml\r\n").as_deref(), Some("http://192.168.1.1:5000/rootDesc.xml"));
+
+        let soap = "<s:Body><u:GetExternalIPAddressResponse><NewExternalIPAddress>198.51.100.20</NewExternalIPAddress></u:GetExternalIPAddressResponse></s:Body>";
+        assert_eq!(xml_text(soap, "NewExternalIPAddress"), Some("198.51.100.20"));
+
+        assert!(is_public_ipv4(Ipv4Addr::new(8, 8, 8, 8)));
+        assert!(!is_public_ipv4(Ipv4Addr::new(100, 72, 1, 1)));
+        assert!(!is_public_ipv4(Ipv4Addr::new(192, 168, 1, 1)));
+    }
+}
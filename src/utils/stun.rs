@@ -0,0 +1,183 @@
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use anyhow::{Result, anyhow};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tracing::debug;
+
+/// 内置的STUN服务器，未配置服务器列表时按顺序尝试
+pub const DEFAULT_STUN_SERVERS: &[&str] = &[
+    "stun.cloudflare.com:3478",
+    "stun.l.google.com:19302",
+];
+
+const DEFAULT_STUN_PORT: u16 = 3478;
+/// RFC 5389 的固定魔数
+const MAGIC_COOKIE: u32 = 0x2112_A442;
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS: u16 = 0x0101;
+const ATTR_MAPPED_ADDRESS: u16 = 0x0001;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+const FAMILY_IPV6: u8 = 0x02;
+const HEADER_LEN: usize = 20;
+/// 每个服务器发送请求的次数及每次等待响应的时间（UDP可能丢包）
+const ATTEMPTS: u32 = 2;
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 解析STUN服务器地址：host、host:port、[IPv6]:port，可带 stun: 前缀，未指定端口时使用3478
+pub fn parse_server(server: &str) -> Result<(String, u16)> {
+    let server = server.trim();
+    let server = server.strip_prefix("stun:").unwrap_or(server);
+    if let Ok(addr) = server.parse::<SocketAddr>() {
+        return Ok((addr.ip().to_string(), addr.port()));
+    }
+    if let Ok(ip) = server.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+        return Ok((ip.to_string(), DEFAULT_STUN_PORT));
+    }
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse::<u16>().map_err(|_| anyhow!("STUN服务器端口无效: {}", server))?;
+            (host, port)
+        }
+        None => (server, DEFAULT_STUN_PORT),
+    };
+    if host.is_empty() || host.contains(['/', ':', ' ']) || port == 0 {
+        return Err(anyhow!("STUN服务器地址无效: {}，应为 host:port", server));
+    }
+    Ok((host.to_string(), port))
+}
+
+/// 构造Binding请求
+fn binding_request(transaction_id: &[u8; 12]) -> Vec<u8> {
+    let mut request = Vec::with_capacity(HEADER_LEN);
+    request.extend_from_slice(&BINDING_REQUEST.to_be_bytes());
+    request.extend_from_slice(&0u16.to_be_bytes()); // 没有属性
+    request.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    request.extend_from_slice(transaction_id);
+    request
+}
+
+/// 从Binding成功响应中读取映射地址，优先 XOR-MAPPED-ADDRESS，只接受IPv6
+fn parse_binding_response(response: &[u8], transaction_id: &[u8; 12]) -> Result<Ipv6Addr> {
+    if response.len() < HEADER_LEN {
+        return Err(anyhow!("STUN响应过短"));
+    }
+    let message_type = u16::from_be_bytes([response[0], response[1]]);
+    let length = u16::from_be_bytes([response[2], response[3]]) as usize;
+    if response[4..8] != MAGIC_COOKIE.to_be_bytes() || &response[8..20] != transaction_id {
+        return Err(anyhow!("STUN响应与请求不匹配"));
+    }
+    if message_type != BINDING_SUCCESS {
+        return Err(anyhow!("STUN服务器返回错误（消息类型 {:#06x}）", message_type));
+    }
+    let body = response
+        .get(HEADER_LEN..HEADER_LEN + length)
+        .ok_or_else(|| anyhow!("STUN响应长度不正确"))?;
+
+    let mut mapped = None;
+    let mut offset = 0;
+    while offset + 4 <= body.len() {
+        let attr_type = u16::from_be_bytes([body[offset], body[offset + 1]]);
+        let attr_len = u16::from_be_bytes([body[offset + 2], body[offset + 3]]) as usize;
+        let value = body
+            .get(offset + 4..offset + 4 + attr_len)
+            .ok_or_else(|| anyhow!("STUN属性长度不正确"))?;
+        // 地址属性：保留1字节、地址族1字节、端口2字节、地址
+        if (attr_type == ATTR_XOR_MAPPED_ADDRESS || attr_type == ATTR_MAPPED_ADDRESS) && value.len() >= 4 {
+            if value[1] != FAMILY_IPV6 || value.len() < 20 {
+                return Err(anyhow!("STUN服务器返回的不是IPv6地址"));
+            }
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&value[4..20]);
+            if attr_type == ATTR_XOR_MAPPED_ADDRESS {
+                // IPv6地址与魔数和事务ID依次异或
+                let key: Vec<u8> = MAGIC_COOKIE.to_be_bytes().into_iter().chain(transaction_id.iter().copied()).collect();
+                octets.iter_mut().zip(key).for_each(|(b, k)| *b ^= k);
+                return Ok(Ipv6Addr::from(octets));
+            }
+            mapped = Some(Ipv6Addr::from(octets));
+        }
+        // 属性按4字节对齐
+        offset += 4 + attr_len.div_ceil(4) * 4;
+    }
+    mapped.ok_or_else(|| anyhow!("STUN响应中没有映射地址"))
+}
+
+/// 向单个STUN服务器发送Binding请求，返回服务器看到的IPv6地址
+async fn query(server: &str, source: Option<Ipv6Addr>) -> Result<Ipv6Addr> {
+    let (host, port) = parse_server(server)?;
+    let target = tokio::net::lookup_host((host.as_str(), port))
+        .await?
+        .find(SocketAddr::is_ipv6)
+        .ok_or_else(|| anyhow!("STUN服务器 {} 没有IPv6地址", host))?;
+    let socket = UdpSocket::bind((source.unwrap_or(Ipv6Addr::UNSPECIFIED), 0)).await?;
+    socket.connect(target).await?;
+
+    let mut transaction_id = [0u8; 12];
+    OsRng.fill_bytes(&mut transaction_id);
+    let request = binding_request(&transaction_id);
+    let mut buf = [0u8; 512];
+    for _ in 0..ATTEMPTS {
+        socket.send(&request).await?;
+        match tokio::time::timeout(ATTEMPT_TIMEOUT, socket.recv(&mut buf)).await {
+            Ok(received) => return parse_binding_response(&buf[..received?], &transaction_id),
+            Err(_) => continue,
+        }
+    }
+    Err(anyhow!("等待STUN响应超时"))
+}
+
+/// 通过STUN获取公网视角下的IPv6地址，按顺序尝试，servers为空时使用内置列表；source为绑定的源地址
+pub async fn get_stun_ipv6(servers: &[String], source: Option<Ipv6Addr>) -> Result<Ipv6Addr> {
+    let servers: Vec<&str> = if servers.is_empty() {
+        DEFAULT_STUN_SERVERS.to_vec()
+    } else {
+        servers.iter().map(|s| s.trim()).collect()
+    };
+    let mut last_error = anyhow!("没有可用的STUN服务器");
+    for server in servers {
+        match query(server, source).await {
+            Ok(ip) => {
+                debug!("🌐 STUN服务器 {} 返回: {}", server, ip);
+                return Ok(ip);
+            }
+            Err(e) => {
+                debug!("STUN服务器 {} 失败: {}", server, e);
+                let message = format!("STUN服务器 {} 失败: {}", server, e);
+                last_error = e.context(message);
+            }
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_binding_response() {
+        let transaction_id = [7u8; 12];
+        let ip: Ipv6Addr = "2001:db8:1234:5678:11:2233:4455:6677".parse().unwrap();
+        let key: Vec<u8> = MAGIC_COOKIE.to_be_bytes().into_iter().chain(transaction_id).collect();
+        let xored: Vec<u8> = ip.octets().iter().zip(&key).map(|(b, k)| b ^ k).collect();
+
+        let mut response = Vec::new();
+        response.extend_from_slice(&BINDING_SUCCESS.to_be_bytes());
+        response.extend_from_slice(&24u16.to_be_bytes());
+        response.extend_from_slice(&MAGIC_COOKIE.to_be_bytes());
+        response.extend_from_slice(&transaction_id);
+        response.extend_from_slice(&ATTR_XOR_MAPPED_ADDRESS.to_be_bytes());
+        response.extend_from_slice(&20u16.to_be_bytes());
+        response.extend_from_slice(&[0, FAMILY_IPV6, 0x80, 0x55]);
+        response.extend_from_slice(&xored);
+
+        assert_eq!(parse_binding_response(&response, &transaction_id).unwrap(), ip);
+        assert!(parse_binding_response(&response, &[0u8; 12]).is_err());
+        assert_eq!(binding_request(&transaction_id).len(), HEADER_LEN);
+
+        assert_eq!(parse_server("stun:stun.example.com").unwrap(), ("stun.example.com".to_string(), 3478));
+        assert_eq!(parse_server("[2001:db8::1]:19302").unwrap(), ("2001:db8::1".to_string(), 19302));
+        assert!(parse_server("stun.example.com:abc").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{SecondsFormat, Utc};
+use std::io::{self, Write};
+use std::net::UdpSocket;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// 本地syslog套接字
+#[cfg(unix)]
+const LOCAL_SOCKET: &str = "/dev/log";
+/// 设施：系统守护进程（daemon）
+const FACILITY_DAEMON: u8 = 3;
+const APP_NAME: &str = "cloudflare-auto";
+
+enum Transport {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+/// 以RFC5424格式发送日志的写入器，每条日志作为一个数据报发送
+pub struct SyslogMakeWriter {
+    transport: Transport,
+    hostname: String,
+    pid: u32,
+}
+
+impl SyslogMakeWriter {
+    /// 连接syslog：指定地址（host:port）时使用UDP，否则使用本地 /dev/log
+    pub fn connect(addr: Option<&str>) -> io::Result<Self> {
+        let transport = match addr {
+            Some(addr) => {
+                let socket = UdpSocket::bind(if addr.starts_with('[') { "[::]:0" } else { "0.0.0.0:0" })?;
+                socket.connect(addr)?;
+                Transport::Udp(socket)
+            }
+            #[cfg(unix)]
+            None => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(LOCAL_SOCKET)?;
+                Transport::Unix(socket)
+            }
+            #[cfg(not(unix))]
+            None => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "当前平台需通过 SYSLOG_ADDR 指定syslog地址"));
+            }
+        };
+        Ok(Self {
+            transport,
+            hostname: local_hostname(),
+            pid: std::process::id(),
+        })
+    }
+
+    fn send(&self, severity: u8, message: &str) {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let line = format_message(severity, &timestamp, &self.hostname, self.pid, message);
+        // 发送失败时无处可报，直接丢弃
+        let _ = match &self.transport {
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.send(line.as_bytes()),
+            Transport::Udp(socket) => socket.send(line.as_bytes()),
+        };
+    }
+}
+
+/// 单条日志的写入器，格式化完成（被丢弃）时发送
+pub struct SyslogWriter<'a> {
+    sink: &'a SyslogMakeWriter,
+    severity: u8,
+    buffer: Vec<u8>,
+}
+
+impl Write for SyslogWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for SyslogWriter<'_> {
+    fn drop(&mut self) {
+        let message = String::from_utf8_lossy(&self.buffer);
+        let message = message.trim_end();
+        if !message.is_empty() {
+            self.sink.send(self.severity, message);
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for SyslogMakeWriter {
+    type Writer = SyslogWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.writer(Level::INFO)
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        self.writer(*meta.level())
+    }
+}
+
+impl SyslogMakeWriter {
+    fn writer(&self, level: Level) -> SyslogWriter<'_> {
+        SyslogWriter {
+            sink: self,
+            severity: severity(level),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// 日志级别对应的syslog严重程度
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+/// 按RFC5424组装一条syslog消息：<PRI>1 时间 主机名 应用名 进程号 - - 消息
+fn format_message(severity: u8, timestamp: &str, hostname: &str, pid: u32, message: &str) -> String {
+    format!(
+        "<{}>1 {} {} {} {} - - {}",
+        FACILITY_DAEMON * 8 + severity,
+        timestamp,
+        hostname,
+        APP_NAME,
+        pid,
+        message
+    )
+}
+
+/// 本机主机名，获取失败时使用RFC5424的空值 "-"
+fn local_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && !name.contains(' '))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_rfc5424() {
+        let line = format_message(severity(Level::WARN), "2024-01-01T00:00:00.000Z", "router", 42, "⚠️ IP检测失败");
+        assert_eq!(line, "<28>1 2024-01-01T00:00:00.000Z router cloudflare-auto 42 - - ⚠️ IP检测失败");
+    }
+}
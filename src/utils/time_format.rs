@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use anyhow::{Result, anyhow};
+
+/// 默认显示语言
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+/// 时间显示格式化器，按配置的时区和语言生成展示字符串
+#[derive(Debug, Clone)]
+pub struct TimeFormatter {
+    tz: Tz,
+    locale: String,
+}
+
+impl Default for TimeFormatter {
+    fn default() -> Self {
+        Self {
+            tz: parse_timezone(&default_timezone()).unwrap_or(Tz::UTC),
+            locale: DEFAULT_LOCALE.to_string(),
+        }
+    }
+}
+
+/// 默认显示时区：优先使用 TZ 环境变量，否则为UTC
+pub fn default_timezone() -> String {
+    std::env::var("TZ")
+        .ok()
+        .filter(|tz| parse_timezone(tz).is_ok())
+        .unwrap_or_else(|| "UTC".to_string())
+}
+
+impl TimeFormatter {
+    /// 创建格式化器，时区使用IANA名称（例如 Asia/Shanghai、UTC）
+    pub fn new(timezone: &str, locale: &str) -> Result<Self> {
+        let tz = parse_timezone(timezone)?;
+        Ok(Self { tz, locale: locale.to_string() })
+    }
+
+    /// 格式化为展示字符串
+    pub fn format(&self, time: &DateTime<Utc>) -> String {
+        let local = time.with_timezone(&self.tz);
+        let pattern = match self.locale.as_str() {
+            "en-US" => "%m/%d/%Y %I:%M:%S %p",
+            "en-GB" => "%d/%m/%Y %H:%M:%S",
+            _ => "%Y-%m-%d %H:%M:%S",
+        };
+        format!("{} ({})", local.format(pattern), self.tz.name())
+    }
+}
+
+/// 校验并解析IANA时区名称
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone
+        .parse::<Tz>()
+        .map_err(|_| anyhow!("无效的时区: {}", timezone))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_with_timezone_and_locale() {
+        let time = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+
+        let zh = TimeFormatter::new("Asia/Shanghai", "zh-CN").unwrap();
+        assert_eq!(zh.format(&time), "2024-01-02 11:04:05 (Asia/Shanghai)");
+
+        let en = TimeFormatter::new("UTC", "en-US").unwrap();
+        assert_eq!(en.format(&time), "01/02/2024 03:04:05 AM (UTC)");
+
+        assert!(TimeFormatter::new("Mars/Base", "zh-CN").is_err());
+    }
+}
@@ -1,18 +1,75 @@
 class CloudflareManager {
     constructor() {
         this.selectedDomains = new Set();
+        this.domainProxied = {};
+        this.messages = {};
         this.init();
     }
 
     async init() {
+        await this.loadTranslations();
         this.bindEvents();
+        await this.loadInterfaces();
         await this.loadConfigStatus();
-        await this.updateCurrentIp();
+        await this.loadLanHosts();
+        await this.loadStatus();
         await this.loadDnsUpdateRecords();
-        setInterval(() => this.updateCurrentIp(), 30000); // 每30秒更新IP
+        await this.loadDomainStates();
+        setInterval(() => this.loadStatus(), 30000); // 每30秒刷新运行状态
+    }
+
+    // 界面文字由后端的文字目录提供，浏览器中选择过的语言优先于配置的默认语言
+    async loadTranslations(lang) {
+        try {
+            const result = await (await fetch('/api/v1/i18n')).json();
+            if (!result.success) return;
+            const { default_language, languages } = result.data;
+            lang = lang || localStorage.getItem('ui-language') || default_language;
+            this.messages = await (await fetch(`/api/v1/i18n/${lang}.json`)).json();
+
+            for (const id of ['ui-language-switch', 'ui-language']) {
+                const select = document.getElementById(id);
+                select.innerHTML = languages.map(l => `<option value="${l.code}">${l.name}</option>`).join('');
+                select.value = id === 'ui-language' ? default_language : lang;
+            }
+            document.documentElement.lang = lang;
+            this.applyTranslations();
+        } catch (error) {
+            console.error('获取界面文字失败:', error);
+        }
+    }
+
+    applyTranslations() {
+        document.querySelectorAll('[data-i18n]').forEach(el => {
+            el.textContent = this.t(el.dataset.i18n, {}, el.textContent.trim());
+        });
+        document.querySelectorAll('[data-i18n-placeholder]').forEach(el => {
+            el.placeholder = this.t(el.dataset.i18nPlaceholder, {}, el.placeholder);
+        });
+    }
+
+    // 取翻译后的文字，{name} 替换为 vars 中的值
+    t(key, vars = {}, fallback = key) {
+        const text = this.messages[key] || fallback;
+        return text.replace(/\{(\w+)\}/g, (_, name) => vars[name] ?? '');
     }
 
     bindEvents() {
+        // 切换界面语言，只保存在当前浏览器
+        document.getElementById('ui-language-switch').addEventListener('change', async (e) => {
+            localStorage.setItem('ui-language', e.target.value);
+            await this.loadTranslations(e.target.value);
+            await this.loadStatus();
+        });
+
+        // 从指标示例跳转到更新记录
+        window.addEventListener('hashchange', () => this.focusHistoryEntry());
+
+        // 认证方式切换
+        document.getElementById('auth-type').addEventListener('change', () => {
+            this.toggleAuthEmail();
+        });
+
         // 测试配置按钮
         document.getElementById('test-btn').addEventListener('click', () => {
             this.testConfig();
@@ -29,6 +86,11 @@ class CloudflareManager {
             this.selectAllDomains();
         });
 
+        // 新增子域名
+        document.getElementById('add-domain-btn').addEventListener('click', () => {
+            this.addNewDomain();
+        });
+
         // 保存选择按钮
         document.getElementById('save-selection-btn').addEventListener('click', () => {
             this.saveDomainSelection();
@@ -39,10 +101,52 @@ class CloudflareManager {
             this.manualUpdate();
         });
 
+        // 暂停/恢复定时检查
+        document.getElementById('pause-monitor-btn').addEventListener('click', () => {
+            this.setMonitorPaused(true);
+        });
+        document.getElementById('resume-monitor-btn').addEventListener('click', () => {
+            this.setMonitorPaused(false);
+        });
+
+        // 域名设置CSV导入
+        document.getElementById('import-domains-btn').addEventListener('click', () => {
+            this.importDomainSettings();
+        });
+
         // 刷新记录按钮
         document.getElementById('refresh-records-btn').addEventListener('click', () => {
             this.loadDnsUpdateRecords();
         });
+
+        // DNS记录管理
+        const adminToken = document.getElementById('admin-token');
+        adminToken.value = localStorage.getItem('adminToken') || '';
+        adminToken.addEventListener('change', () => {
+            localStorage.setItem('adminToken', adminToken.value.trim());
+        });
+        document.getElementById('load-dns-records-btn').addEventListener('click', () => {
+            this.loadDnsRecords();
+        });
+        document.getElementById('create-record-btn').addEventListener('click', () => {
+            this.createDnsRecord();
+        });
+
+        // API令牌
+        document.getElementById('load-tokens-btn').addEventListener('click', () => {
+            this.loadApiTokens();
+        });
+        document.getElementById('create-token-btn').addEventListener('click', () => {
+            this.createApiToken();
+        });
+
+        // 局域网主机
+        document.getElementById('save-lan-host-btn').addEventListener('click', () => {
+            this.saveLanHost();
+        });
+        document.getElementById('discover-lan-hosts-btn').addEventListener('click', () => {
+            this.discoverLanHosts();
+        });
     }
 
     async testConfig() {
@@ -52,7 +156,7 @@ class CloudflareManager {
         this.showLoading(true);
         
         try {
-            const response = await fetch('/api/test-config', {
+            const response = await fetch('/api/v1/test-config', {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(formData)
@@ -61,13 +165,13 @@ class CloudflareManager {
             const result = await response.json();
             
             if (result.success) {
-                this.showToast('配置测试成功！', 'success');
+                this.showToast(result.message || '配置测试成功！', 'success');
                 await this.loadDomainList(formData);
             } else {
                 this.showToast(result.message || '配置测试失败', 'error');
             }
         } catch (error) {
-            this.showToast('网络错误: ' + error.message, 'error');
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
         } finally {
             this.showLoading(false);
         }
@@ -75,7 +179,7 @@ class CloudflareManager {
 
     async loadDomainList(formData) {
         try {
-            const response = await fetch('/api/domain-list', {
+            const response = await fetch('/api/v1/domain-list', {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(formData)
@@ -90,7 +194,7 @@ class CloudflareManager {
                 this.showToast(result.message || '获取域名列表失败', 'error');
             }
         } catch (error) {
-            this.showToast('网络错误: ' + error.message, 'error');
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
         }
     }
 
@@ -104,16 +208,18 @@ class CloudflareManager {
 
         let html = `<div class="domain-list">
             <div class="domain-item">
-                <input type="checkbox" id="root-domain" value="" checked>
+                <input type="checkbox" class="domain-checkbox" id="root-domain" value="" checked>
                 <label for="root-domain">${document.getElementById('root-domain').value} (根域名)</label>
+                ${this.proxiedToggle('')}
             </div>`;
 
         domains.forEach(domain => {
             const fullDomain = `${domain}.${document.getElementById('root-domain').value}`;
             html += `
             <div class="domain-item">
-                <input type="checkbox" id="domain-${domain}" value="${domain}">
+                <input type="checkbox" class="domain-checkbox" id="domain-${domain}" value="${domain}">
                 <label for="domain-${domain}">${fullDomain}</label>
+                ${this.proxiedToggle(domain)}
             </div>`;
         });
 
@@ -121,7 +227,7 @@ class CloudflareManager {
         domainListDiv.innerHTML = html;
 
         // 绑定复选框事件
-        document.querySelectorAll('.domain-item input[type="checkbox"]').forEach(checkbox => {
+        document.querySelectorAll('.domain-item .domain-checkbox').forEach(checkbox => {
             checkbox.addEventListener('change', (e) => {
                 if (e.target.checked) {
                     this.selectedDomains.add(e.target.value);
@@ -135,8 +241,62 @@ class CloudflareManager {
         this.selectAllDomains();
     }
 
+    // 添加Cloudflare中尚不存在的子域名，保存后将在下一次更新时创建记录
+    addNewDomain() {
+        const input = document.getElementById('new-domain-input');
+        const rootDomain = document.getElementById('root-domain').value.trim();
+        let domain = input.value.trim().toLowerCase().replace(/\.$/, '');
+        if (domain.endsWith('.' + rootDomain)) {
+            domain = domain.slice(0, -(rootDomain.length + 1));
+        }
+        if (!domain || !/^(\*\.)?[a-z0-9_-]+(\.[a-z0-9_-]+)*$/.test(domain)) {
+            this.showToast('请输入有效的子域名', 'error');
+            return;
+        }
+        if (document.querySelector(`.domain-checkbox[value="${domain}"]`)) {
+            this.showToast('该子域名已在列表中', 'info');
+            return;
+        }
+
+        let list = document.querySelector('#domain-list .domain-list');
+        if (!list) {
+            document.getElementById('domain-list').innerHTML = '<div class="domain-list"></div>';
+            list = document.querySelector('#domain-list .domain-list');
+        }
+        const item = document.createElement('div');
+        item.className = 'domain-item';
+        item.innerHTML = `
+                <input type="checkbox" class="domain-checkbox" id="domain-${domain}" value="${domain}" checked>
+                <label for="domain-${domain}">${domain}.${rootDomain} (新建)</label>
+                ${this.proxiedToggle(domain)}`;
+        list.appendChild(item);
+
+        const checkbox = item.querySelector('.domain-checkbox');
+        checkbox.addEventListener('change', (e) => {
+            if (e.target.checked) {
+                this.selectedDomains.add(e.target.value);
+            } else {
+                this.selectedDomains.delete(e.target.value);
+            }
+        });
+        this.selectedDomains.add(domain);
+        input.value = '';
+        this.showToast('已添加，保存选择后将在下一次更新时创建记录', 'success');
+    }
+
+    proxiedToggle(domain) {
+        // 没有单独设置的子域名显示全局默认值
+        const proxied = domain in this.domainProxied
+            ? this.domainProxied[domain]
+            : document.getElementById('proxied-default').checked;
+        const checked = proxied ? 'checked' : '';
+        return `<label class="proxied-toggle" title="通过Cloudflare代理（橙色云朵）">
+                    <input type="checkbox" class="proxied-checkbox" data-domain="${domain}" ${checked}> 代理
+                </label>`;
+    }
+
     selectAllDomains() {
-        document.querySelectorAll('.domain-item input[type="checkbox"]').forEach(checkbox => {
+        document.querySelectorAll('.domain-item .domain-checkbox').forEach(checkbox => {
             checkbox.checked = true;
             this.selectedDomains.add(checkbox.value);
         });
@@ -145,10 +305,10 @@ class CloudflareManager {
     async saveDomainSelection() {
         const formData = this.getFormData();
         formData.selected_subdomains = Array.from(this.selectedDomains);
-        formData.check_interval = parseInt(document.getElementById('check-interval').value) || 300;
+        this.collectSettings(formData);
 
         try {
-            const response = await fetch('/api/save-config', {
+            const response = await fetch('/api/v1/save-config', {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(formData)
@@ -159,11 +319,12 @@ class CloudflareManager {
             if (result.success) {
                 this.showToast('配置保存成功！', 'success');
                 await this.loadConfigStatus();
+                await this.loadStatus();
             } else {
-                this.showToast(result.message || '保存失败', 'error');
+                this.showToast(result.message || this.t('toast.save_failed'), 'error');
             }
         } catch (error) {
-            this.showToast('网络错误: ' + error.message, 'error');
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
         }
     }
 
@@ -172,10 +333,10 @@ class CloudflareManager {
         if (!this.validateForm(formData)) return;
 
         formData.selected_subdomains = Array.from(this.selectedDomains);
-        formData.check_interval = parseInt(document.getElementById('check-interval').value) || 300;
+        this.collectSettings(formData);
 
         try {
-            const response = await fetch('/api/save-config', {
+            const response = await fetch('/api/v1/save-config', {
                 method: 'POST',
                 headers: { 'Content-Type': 'application/json' },
                 body: JSON.stringify(formData)
@@ -184,20 +345,39 @@ class CloudflareManager {
             const result = await response.json();
             
             if (result.success) {
-                this.showToast('配置保存并更新成功！', 'success');
+                this.showToast(this.t('toast.config_saved'), 'success');
                 await this.loadConfigStatus();
+                await this.loadStatus();
                 await this.loadDnsUpdateRecords(); // 刷新更新记录
             } else {
-                this.showToast(result.message || '保存失败', 'error');
+                this.showToast(result.message || this.t('toast.save_failed'), 'error');
             }
         } catch (error) {
-            this.showToast('网络错误: ' + error.message, 'error');
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+        }
+    }
+
+    // 网卡选项后附上该网卡的全局IPv6地址，便于辨认
+    async loadInterfaces() {
+        try {
+            const result = await (await fetch('/api/v1/interfaces')).json();
+            if (!result.success) return;
+            const select = document.getElementById('interface');
+            for (const iface of result.data.filter(i => !i.loopback)) {
+                const global = iface.addresses.filter(a => a.scope === 'global').map(a => a.address);
+                const option = document.createElement('option');
+                option.value = iface.name;
+                option.textContent = global.length ? `${iface.name} (${global.join(', ')})` : iface.name;
+                select.appendChild(option);
+            }
+        } catch (error) {
+            console.error('获取网卡列表失败:', error);
         }
     }
 
     async loadConfigStatus() {
         try {
-            const response = await fetch('/api/config-status');
+            const response = await fetch('/api/v1/config-status');
             const result = await response.json();
             
             if (result.success) {
@@ -215,12 +395,15 @@ class CloudflareManager {
             const config = status.current_config;
             statusDiv.innerHTML = `
                 <div style="color: #48bb78; margin-bottom: 10px;">
-                    ✅ 配置已保存
+                    ${this.t('config_status.saved')}
                 </div>
                 <div style="font-size: 0.9em; color: #666;">
                     <div>根域名: ${config.root_domain}</div>
                     <div>已选子域名: ${config.selected_subdomains.join(', ') || '无'}</div>
                     <div>检查间隔: ${config.check_interval}秒</div>
+                    ${status.dry_run ? '<div>🧪 演练模式：只记录将要做的修改</div>' : ''}
+                    ${status.flapping ? `<div>🔀 IP在 ${status.flapping.addresses.join(' 与 ')} 之间反复切换，请检查IP来源配置（已暂停推送更新通知）</div>` : ''}
+                    ${status.pause ? `<div>⏸️ 定时检查已暂停${status.pause.until ? '，至 ' + new Date(status.pause.until).toLocaleString() : '，直到手动恢复'}${status.pause.reason ? '（' + status.pause.reason + '）' : ''}</div>` : ''}
                 </div>
             `;
             
@@ -229,47 +412,342 @@ class CloudflareManager {
             document.getElementById('zone-id').value = config.cloudflare_zone_id;
             document.getElementById('root-domain').value = config.root_domain;
             document.getElementById('check-interval').value = config.check_interval;
-            
-            // 更新监控状态
-            document.getElementById('monitor-status').textContent = '运行中';
-            document.getElementById('monitor-status').style.color = '#48bb78';
-            
+            document.getElementById('startup-check').checked = config.startup_check !== false;
+            document.getElementById('startup-delay').value = config.startup_delay || 0;
+            document.getElementById('burst-interval').value = config.burst_interval || 0;
+            document.getElementById('burst-duration').value = config.burst_duration || 10;
+            document.getElementById('flap-threshold').value = config.flap_threshold ?? 4;
+            document.getElementById('flap-window-hours').value = config.flap_window_hours || 24;
+            document.getElementById('reconcile-interval').value = config.reconcile_interval || 0;
+            document.getElementById('update-parallelism').value = config.update_parallelism || 5;
+            document.getElementById('http-connect-timeout').value = config.http_connect_timeout || 10;
+            document.getElementById('http-request-timeout').value = config.http_request_timeout || 30;
+            document.getElementById('ui-language').value = config.ui_language || 'zh-CN';
+            document.getElementById('verify-external-ip').checked = !!config.verify_external_ip;
+            document.getElementById('verify-propagation').checked = !!config.verify_propagation;
+            document.getElementById('change-detection').value = config.change_detection || 'last_ip';
+            document.getElementById('ip-source').value = config.ip_source || 'local';
+            document.getElementById('external-ip-services').value = (config.external_ip_services || []).join('\n');
+            document.getElementById('stun-servers').value = (config.stun_servers || []).join('\n');
+            const interfaceSelect = document.getElementById('interface');
+            // 已配置的网卡当前不存在时也保留在选项中，避免保存时被清除
+            if (config.interface && ![...interfaceSelect.options].some(o => o.value === config.interface)) {
+                interfaceSelect.add(new Option(config.interface, config.interface));
+            }
+            interfaceSelect.value = config.interface || '';
+            document.getElementById('allowed-prefix').value = config.allowed_prefix || '';
+            const selection = config.address_selection || {};
+            document.getElementById('address-strategy').value = selection.strategy || 'system';
+            document.getElementById('address-exclude-ula').checked = !!selection.exclude_ula;
+            document.getElementById('address-skip-temporary').checked = selection.skip_temporary !== false;
+            document.getElementById('address-match').value = selection.match || '';
+            document.getElementById('address-suffix').value = selection.suffix || '';
+            document.getElementById('delegated-prefix-len').value = config.delegated_prefix_len || 64;
+            const backup = config.backup_hostname || {};
+            document.getElementById('backup-hostname').value = backup.name || '';
+            document.getElementById('backup-zone-id').value = backup.zone_id || '';
+            document.getElementById('backup-api-key').value = backup.api_key || '';
+            document.getElementById('backup-ttl').value = backup.ttl || '';
+            document.getElementById('status-webhook-url').value = config.status_webhook_url || '';
+            document.getElementById('uptime-kuma-push-url').value = config.uptime_kuma_push_url || '';
+            const mqtt = config.mqtt || {};
+            document.getElementById('mqtt-broker').value = mqtt.broker || '';
+            document.getElementById('mqtt-topic').value = mqtt.topic || '';
+            document.getElementById('mqtt-username').value = mqtt.username || '';
+            this.fillSecret('mqtt-password', mqtt.password_configured);
+            document.getElementById('mqtt-retain').checked = !!mqtt.retain;
+            document.getElementById('mqtt-home-assistant').checked = !!mqtt.home_assistant;
+            document.getElementById('mqtt-discovery-prefix').value = mqtt.discovery_prefix || '';
+            const telegram = config.telegram || {};
+            this.fillSecret('telegram-bot-token', telegram.bot_token_configured);
+            document.getElementById('telegram-chat-id').value = telegram.chat_id || '';
+            document.getElementById('telegram-api-url').value = telegram.api_url || '';
+            document.getElementById('telegram-daily-summary').checked = !!telegram.daily_summary;
+            document.getElementById('discord-webhook-url').value = config.discord_webhook_url || '';
+            const slack = config.slack || {};
+            document.getElementById('slack-webhook-url').value = slack.webhook_url || '';
+            document.getElementById('slack-channel').value = slack.channel || '';
+            document.getElementById('slack-template').value = slack.template || '';
+            const ntfy = config.ntfy || {};
+            document.getElementById('ntfy-topic').value = ntfy.topic || '';
+            document.getElementById('ntfy-server').value = ntfy.server || '';
+            document.getElementById('ntfy-token').value = ntfy.token || '';
+            document.getElementById('ntfy-priority').value = ntfy.priority || '';
+            const gotify = config.gotify || {};
+            document.getElementById('gotify-server').value = gotify.server || '';
+            document.getElementById('gotify-token').value = gotify.token || '';
+            document.getElementById('gotify-priority').value = gotify.priority ?? '';
+            document.getElementById('wecom-webhook-url').value = config.wecom_webhook_url || '';
+            const dingtalk = config.dingtalk || {};
+            document.getElementById('dingtalk-webhook-url').value = dingtalk.webhook_url || '';
+            this.fillSecret('dingtalk-secret', dingtalk.secret_configured);
+            const bark = config.bark || {};
+            document.getElementById('bark-device-key').value = bark.device_key || '';
+            document.getElementById('bark-server').value = bark.server || '';
+            document.getElementById('bark-sound').value = bark.sound || '';
+            this.fillSecret('serverchan-key', config.serverchan_key_configured);
+            this.fillSecret('pushplus-token', config.pushplus_token_configured);
+            document.getElementById('record-comment').value = config.record_comment || '';
+            document.getElementById('cleanup-duplicates').checked = !!config.cleanup_duplicates;
+            document.getElementById('round-robin').checked = !!config.round_robin;
+            document.getElementById('multi-address').checked = !!config.multi_address;
+            document.getElementById('ownership-enabled').checked = !!config.ownership_enabled;
+            document.getElementById('proxied-default').checked = !!config.proxied_default;
+            document.getElementById('dry-run').checked = !!config.dry_run;
+            document.getElementById('https-records').checked = !!config.https_records;
+            this.domainProxied = config.domain_proxied || {};
+            document.getElementById('auth-type').value = config.auth_type || 'api_token';
+            document.getElementById('auth-email').value = config.auth_email || '';
+            document.getElementById('proxy-url').value = config.proxy_url || '';
+            document.getElementById('ca-cert-path').value = config.ca_cert_path || '';
+            this.toggleAuthEmail();
         } else {
             statusDiv.innerHTML = '<div style="color: #e53e3e;">❌ 未配置</div>';
-            document.getElementById('monitor-status').textContent = '未启动';
-            document.getElementById('monitor-status').style.color = '#e53e3e';
         }
     }
 
-    async updateCurrentIp() {
+    // 一次获取监控状态、当前IP、最近一次和下一次检查
+    async loadStatus() {
         try {
-            const response = await fetch('/api/current-ip');
+            const response = await fetch('/api/v1/status');
             const result = await response.json();
-            
             if (result.success) {
-                document.getElementById('current-ip').textContent = result.data;
+                this.displayStatus(result.data);
             }
         } catch (error) {
-            console.error('获取当前IP失败:', error);
+            console.error('获取运行状态失败:', error);
+        }
+    }
+
+    // 状态接口不返回密钥本身，已设置时填入占位符，原样保存会沿用已保存的值
+    fillSecret(id, configured) {
+        document.getElementById(id).value = configured ? '********' : '';
+    }
+
+    displayStatus(status) {
+        const formatTime = time => time ? new Date(time).toLocaleString() : '-';
+        document.getElementById('current-ip').textContent = status.current_ip || this.t('status.detect_failed');
+        document.getElementById('last-ip').textContent = status.last_ip || '-';
+
+        const monitorStatus = document.getElementById('monitor-status');
+        if (status.pause) {
+            monitorStatus.textContent = this.t('monitor.paused');
+            monitorStatus.style.color = '#dd6b20';
+        } else if (status.monitor.running && status.status === 'offline') {
+            monitorStatus.textContent = this.t('monitor.offline', { interval: status.effective_check_interval });
+            monitorStatus.style.color = '#dd6b20';
+        } else if (status.monitor.running) {
+            monitorStatus.textContent = this.t('monitor.running', { interval: status.effective_check_interval });
+            monitorStatus.style.color = '#48bb78';
+        } else {
+            monitorStatus.textContent = this.t('monitor.stopped');
+            monitorStatus.style.color = '#e53e3e';
         }
+
+        const lastCheck = status.last_check;
+        let lastCheckText = '-';
+        if (lastCheck) {
+            const outcome = lastCheck.error ? `失败: ${lastCheck.error}` : (lastCheck.updated ? '已更新' : '无需更新');
+            lastCheckText = `${formatTime(lastCheck.finished_at)}（${outcome}）`;
+        }
+        document.getElementById('last-check').textContent = lastCheckText;
+        document.getElementById('next-check').textContent = formatTime(status.next_check);
+
+        const reconcile = status.reconcile;
+        let reconcileText = reconcile.next_due ? `下次 ${formatTime(reconcile.next_due)}` : '未启用';
+        if (reconcile.last) {
+            const last = reconcile.last;
+            reconcileText = `${formatTime(last.finished_at)}（修复 ${last.repaired} 个，失败 ${last.failed} 个），${reconcileText}`;
+        }
+        document.getElementById('last-reconcile').textContent = reconcileText;
     }
 
     async manualUpdate() {
-        this.showToast('开始手动更新...', 'info');
+        this.showToast(this.t('toast.update_started'), 'info');
+        this.showLoading(true);
         
         try {
-            // 这里需要实现手动更新逻辑
-            this.showToast('手动更新功能待实现', 'info');
+            const result = await this.adminFetch('/api/v1/update-now', {
+                method: 'POST',
+                body: JSON.stringify({ force: document.getElementById('force-update').checked })
+            });
+            
+            if (result.data) {
+                this.displayUpdateResult(result.data);
+            }
+            this.showToast(result.message || '更新完成', result.success ? 'success' : 'error');
+            await this.loadDnsUpdateRecords();
+            await this.loadDomainStates();
+            await this.loadConfigStatus();
+            await this.loadStatus();
         } catch (error) {
             this.showToast('更新失败: ' + error.message, 'error');
+        } finally {
+            this.showLoading(false);
+        }
+    }
+
+    async setMonitorPaused(paused) {
+        try {
+            const options = { method: 'POST' };
+            if (paused) {
+                options.body = JSON.stringify({ duration: document.getElementById('pause-duration').value.trim() || null });
+            }
+            const result = await this.adminFetch(paused ? '/api/v1/monitor/pause' : '/api/v1/monitor/resume', options);
+            this.showToast(result.message, result.success ? 'success' : 'error');
+            await this.loadConfigStatus();
+            await this.loadStatus();
+        } catch (error) {
+            this.showToast('操作失败: ' + error.message, 'error');
+        }
+    }
+
+    displayUpdateResult(report) {
+        const labels = { created: '➕ 新建', updated: '✏️ 更新', unchanged: '✅ 未变化', failed: '❌ 失败' };
+        let html = '<div class="records-list">';
+        report.domains.forEach(item => {
+            html += `
+                <div class="record-item">
+                    <div class="record-header">
+                        <span class="record-time">${item.domain}</span>
+                        <span class="value">${labels[item.outcome] || item.outcome}${report.dry_run ? ' 🧪 演练' : ''}</span>
+                    </div>
+                    ${item.error ? `
+                    <div class="record-error">
+                        <span class="label">错误:</span>
+                        <span class="value">${item.error}</span>
+                    </div>
+                    ` : ''}
+                </div>
+            `;
+        });
+        html += '</div>';
+        document.getElementById('update-result').innerHTML = html;
+    }
+
+    collectSettings(formData) {
+        formData.check_interval = parseInt(document.getElementById('check-interval').value) || 300;
+        formData.startup_check = document.getElementById('startup-check').checked;
+        formData.startup_delay = parseInt(document.getElementById('startup-delay').value) || 0;
+        formData.burst_interval = parseInt(document.getElementById('burst-interval').value) || 0;
+        formData.burst_duration = parseInt(document.getElementById('burst-duration').value) || 10;
+        const flapThreshold = parseInt(document.getElementById('flap-threshold').value);
+        formData.flap_threshold = isNaN(flapThreshold) ? 4 : flapThreshold;
+        formData.flap_window_hours = parseInt(document.getElementById('flap-window-hours').value) || 24;
+        formData.reconcile_interval = parseInt(document.getElementById('reconcile-interval').value) || 0;
+        formData.update_parallelism = parseInt(document.getElementById('update-parallelism').value) || 5;
+        formData.http_connect_timeout = parseInt(document.getElementById('http-connect-timeout').value) || 10;
+        formData.http_request_timeout = parseInt(document.getElementById('http-request-timeout').value) || 30;
+        formData.ui_language = document.getElementById('ui-language').value || 'zh-CN';
+        formData.verify_external_ip = document.getElementById('verify-external-ip').checked;
+        formData.verify_propagation = document.getElementById('verify-propagation').checked;
+        formData.change_detection = document.getElementById('change-detection').value;
+        formData.ip_source = document.getElementById('ip-source').value;
+        formData.external_ip_services = document.getElementById('external-ip-services').value
+            .split('\n').map(s => s.trim()).filter(s => s);
+        formData.stun_servers = document.getElementById('stun-servers').value
+            .split('\n').map(s => s.trim()).filter(s => s);
+        formData.interface = document.getElementById('interface').value;
+        formData.allowed_prefix = document.getElementById('allowed-prefix').value.trim();
+        formData.address_selection = {
+            strategy: document.getElementById('address-strategy').value,
+            exclude_ula: document.getElementById('address-exclude-ula').checked,
+            skip_temporary: document.getElementById('address-skip-temporary').checked,
+            match: document.getElementById('address-match').value.trim() || null,
+            suffix: document.getElementById('address-suffix').value.trim() || null
+        };
+        formData.delegated_prefix_len = parseInt(document.getElementById('delegated-prefix-len').value) || 64;
+        // 主机名留空时清除备用主机名
+        formData.backup_hostname = {
+            name: document.getElementById('backup-hostname').value.trim(),
+            zone_id: document.getElementById('backup-zone-id').value.trim(),
+            api_key: document.getElementById('backup-api-key').value.trim() || null,
+            ttl: parseInt(document.getElementById('backup-ttl').value) || 60
+        };
+        formData.status_webhook_url = document.getElementById('status-webhook-url').value.trim();
+        formData.uptime_kuma_push_url = document.getElementById('uptime-kuma-push-url').value.trim();
+        // 代理地址留空时关闭MQTT发布
+        formData.mqtt = {
+            broker: document.getElementById('mqtt-broker').value.trim(),
+            topic: document.getElementById('mqtt-topic').value.trim() || 'cloudflare-auto',
+            username: document.getElementById('mqtt-username').value.trim() || null,
+            password: document.getElementById('mqtt-password').value || null,
+            retain: document.getElementById('mqtt-retain').checked,
+            home_assistant: document.getElementById('mqtt-home-assistant').checked,
+            discovery_prefix: document.getElementById('mqtt-discovery-prefix').value.trim() || 'homeassistant'
+        };
+        formData.telegram = {
+            bot_token: document.getElementById('telegram-bot-token').value.trim(),
+            chat_id: document.getElementById('telegram-chat-id').value.trim(),
+            api_url: document.getElementById('telegram-api-url').value.trim() || null,
+            daily_summary: document.getElementById('telegram-daily-summary').checked
+        };
+        formData.discord_webhook_url = document.getElementById('discord-webhook-url').value.trim();
+        formData.slack = {
+            webhook_url: document.getElementById('slack-webhook-url').value.trim(),
+            channel: document.getElementById('slack-channel').value.trim() || null,
+            template: document.getElementById('slack-template').value.trim() || null
+        };
+        formData.ntfy = {
+            topic: document.getElementById('ntfy-topic').value.trim(),
+            server: document.getElementById('ntfy-server').value.trim() || 'https://ntfy.sh',
+            token: document.getElementById('ntfy-token').value.trim() || null,
+            priority: parseInt(document.getElementById('ntfy-priority').value) || null
+        };
+        formData.gotify = {
+            server: document.getElementById('gotify-server').value.trim(),
+            token: document.getElementById('gotify-token').value.trim(),
+            priority: parseInt(document.getElementById('gotify-priority').value) || null
+        };
+        formData.wecom_webhook_url = document.getElementById('wecom-webhook-url').value.trim();
+        formData.dingtalk = {
+            webhook_url: document.getElementById('dingtalk-webhook-url').value.trim(),
+            secret: document.getElementById('dingtalk-secret').value.trim() || null
+        };
+        formData.bark = {
+            device_key: document.getElementById('bark-device-key').value.trim(),
+            server: document.getElementById('bark-server').value.trim() || 'https://api.day.app',
+            sound: document.getElementById('bark-sound').value.trim() || null
+        };
+        formData.serverchan_key = document.getElementById('serverchan-key').value.trim();
+        formData.pushplus_token = document.getElementById('pushplus-token').value.trim();
+        formData.record_comment = document.getElementById('record-comment').value.trim();
+        formData.cleanup_duplicates = document.getElementById('cleanup-duplicates').checked;
+        formData.round_robin = document.getElementById('round-robin').checked;
+        formData.multi_address = document.getElementById('multi-address').checked;
+        formData.ownership_enabled = document.getElementById('ownership-enabled').checked;
+        formData.proxied_default = document.getElementById('proxied-default').checked;
+        formData.dry_run = document.getElementById('dry-run').checked;
+        formData.https_records = document.getElementById('https-records').checked;
+
+        // 只有显示了域名列表时才提交代理设置，否则保留已保存的配置
+        // 没有单独设置且与全局默认值相同的子域名不写入，保留记录原有的代理状态
+        const proxiedBoxes = document.querySelectorAll('.proxied-checkbox');
+        if (proxiedBoxes.length > 0) {
+            formData.domain_proxied = {};
+            proxiedBoxes.forEach(box => {
+                const domain = box.dataset.domain;
+                if (domain in this.domainProxied || box.checked !== formData.proxied_default) {
+                    formData.domain_proxied[domain] = box.checked;
+                }
+            });
         }
     }
 
+    toggleAuthEmail() {
+        const isGlobalKey = document.getElementById('auth-type').value === 'global_key';
+        document.getElementById('auth-email-group').style.display = isGlobalKey ? 'block' : 'none';
+    }
+
     getFormData() {
         return {
             api_key: document.getElementById('api-key').value,
             zone_id: document.getElementById('zone-id').value,
-            root_domain: document.getElementById('root-domain').value
+            root_domain: document.getElementById('root-domain').value,
+            auth_type: document.getElementById('auth-type').value,
+            auth_email: document.getElementById('auth-email').value || null,
+            proxy_url: document.getElementById('proxy-url').value.trim(),
+            ca_cert_path: document.getElementById('ca-cert-path').value.trim()
         };
     }
 
@@ -278,6 +756,10 @@ class CloudflareManager {
             this.showToast('请输入API密钥', 'error');
             return false;
         }
+        if (data.auth_type === 'global_key' && !data.auth_email) {
+            this.showToast('Global API Key 模式需要填写账户邮箱', 'error');
+            return false;
+        }
         if (!data.zone_id) {
             this.showToast('请输入区域ID', 'error');
             return false;
@@ -310,12 +792,132 @@ class CloudflareManager {
         });
     }
 
+    async loadDomainStates() {
+        try {
+            const response = await fetch('/api/v1/domains/status');
+            const result = await response.json();
+
+            if (result.success) {
+                this.displayDomainStates(result.data);
+            }
+        } catch (error) {
+            console.error('获取域名状态失败:', error);
+        }
+    }
+
+    displayDomainStates(statuses) {
+        const statesDiv = document.getElementById('domain-states');
+
+        if (statuses.length === 0) {
+            statesDiv.innerHTML = '';
+            return;
+        }
+
+        const rows = statuses.map(status => {
+            let error = '';
+            if (status.warning) {
+                error = `<div class="error-text">警告: ${status.warning}</div>`;
+            }
+            if (status.last_error) {
+                const style = status.failing ? 'error-text' : '';
+                error += `<div class="${style}">${status.last_error_at_display || ''} ${status.last_error}</div>`;
+            }
+            return `
+                <tr class="${status.failing ? 'failing' : ''}">
+                    <td>${status.domain}</td>
+                    <td class="mono">${status.record_id || '-'}</td>
+                    <td class="mono">${status.content || '-'}</td>
+                    <td>${status.last_success_at_display || '-'}</td>
+                    <td>${error || '-'}</td>
+                    <td><button class="btn btn-secondary btn-sm" data-sync="${status.domain}">🔄 同步</button></td>
+                </tr>
+            `;
+        }).join('');
+
+        statesDiv.innerHTML = `
+            <table class="domain-table">
+                <thead>
+                    <tr><th>域名</th><th>记录ID</th><th>当前内容</th><th>最后成功同步</th><th>最近错误</th><th></th></tr>
+                </thead>
+                <tbody>${rows}</tbody>
+            </table>
+        `;
+
+        statesDiv.querySelectorAll('[data-sync]').forEach(button => {
+            button.addEventListener('click', () => this.syncDomain(button.dataset.sync));
+        });
+    }
+
+    async importDomainSettings() {
+        const file = document.getElementById('import-domains-file').files[0];
+        if (!file) {
+            this.showToast('请先选择CSV文件', 'error');
+            return;
+        }
+
+        try {
+            const result = await this.adminFetch('/api/v1/domains/import', {
+                method: 'POST',
+                headers: { 'Content-Type': 'text/csv' },
+                body: await file.text(),
+            });
+            if (!result.success) {
+                this.showToast(result.message || '导入域名设置失败', 'error');
+                return;
+            }
+
+            const report = result.data;
+            let message = `已导入 ${report.total} 个子域名：新增 ${report.enabled.length}，停用 ${report.disabled.length}，修改代理状态 ${report.proxied_changed.length}`;
+            if (report.ignored_columns.length > 0) {
+                message += `（已忽略不支持的列: ${report.ignored_columns.join(', ')}）`;
+            }
+            this.showToast(message, 'success');
+            this.loadConfigStatus();
+            this.loadDomainStates();
+        } catch (error) {
+            this.showToast('导入域名设置失败: ' + error.message, 'error');
+        }
+    }
+
+    // 先展示将要做的修改，确认后再同步单个域名
+    async syncDomain(domain) {
+        try {
+            const response = await fetch(`/api/v1/domains/${encodeURIComponent(domain)}/diff?force=true`);
+            const result = await response.json();
+            if (!result.success) {
+                this.showToast(result.message || '计算域名差异失败', 'error');
+                return;
+            }
+
+            const diff = result.data;
+            if (diff.action === 'skip') {
+                this.showToast(diff.reason || `${domain} 已跳过`, 'error');
+                return;
+            }
+            const format = value => value === null || value === undefined ? '-' : value;
+            const lines = diff.changes.map(change => `${change.field}: ${format(change.from)} → ${format(change.to)}`);
+            if (diff.delete_duplicates) {
+                lines.push(`删除 ${diff.duplicates.length} 条多余记录`);
+            }
+            const detail = lines.length > 0 ? lines.join('\n') : '记录已是最新，将重新写入当前内容';
+            if (!confirm(`同步 ${diff.domain}（检测到的IP: ${diff.detected_ip}）\n\n${detail}`)) return;
+
+            const syncResult = await this.adminFetch('/api/v1/operations/sync', {
+                method: 'POST',
+                body: JSON.stringify({ subdomains: [diff.subdomain || '@'] }),
+            });
+            this.showToast(syncResult.message || (syncResult.success ? '同步操作已开始' : '启动同步失败'), syncResult.success ? 'success' : 'error');
+        } catch (error) {
+            this.showToast('同步失败: ' + error.message, 'error');
+        }
+    }
+
     async loadDnsUpdateRecords() {
         const recordsContent = document.getElementById('records-content');
         recordsContent.innerHTML = '<p>正在加载更新记录...</p>';
         
         try {
-            const response = await fetch('/api/dns-update-records');
+            const response = await fetch('/api/v1/dns-update-records');
             const result = await response.json();
             
             if (result.success) {
@@ -336,42 +938,378 @@ class CloudflareManager {
             return;
         }
 
-        let html = '<div class="records-list">';
+        recordsContent.innerHTML = `<div class="records-list">${records.map(record => this.renderUpdateRecord(record)).join('')}</div>`;
+        this.focusHistoryEntry();
+    }
+
+    renderUpdateRecord(record) {
+        const timestamp = record.timestamp_display || new Date(record.timestamp).toLocaleString('zh-CN');
+        const successRate = record.domain_count > 0 
+            ? Math.round((record.success_count / record.domain_count) * 100) 
+            : 0;
+        const statusClass = record.success_count === record.domain_count ? 'success' : 
+                          record.success_count > 0 ? 'warning' : 'error';
         
-        records.forEach(record => {
-            const timestamp = new Date(record.timestamp).toLocaleString('zh-CN');
-            const successRate = record.domain_count > 0 
-                ? Math.round((record.success_count / record.domain_count) * 100) 
-                : 0;
-            const statusClass = record.success_count === record.domain_count ? 'success' : 
-                              record.success_count > 0 ? 'warning' : 'error';
-            
-            html += `
-                <div class="record-item">
-                    <div class="record-header">
-                        <span class="record-time">${timestamp}</span>
-                        <span class="record-status ${statusClass}">
-                            ${record.success_count}/${record.domain_count} (${successRate}%)
-                        </span>
+        return `
+            <div class="record-item" id="history-${record.id}">
+                <div class="record-header">
+                    <span class="record-time">${timestamp}${record.dry_run ? ' 🧪 演练' : ''}</span>
+                    <span class="record-status ${statusClass}">
+                        ${record.success_count}/${record.domain_count} (${successRate}%)
+                    </span>
+                </div>
+                <div class="record-details">
+                    <div class="record-ip">
+                        <span class="label">IP变化:</span>
+                        <span class="value">${record.old_ip || '无'} → ${record.new_ip}</span>
+                    </div>
+                    ${record.actions ? `
+                    <div class="record-ip">
+                        <span class="label">处理结果:</span>
+                        <span class="value">新建 ${record.actions.created} / 更新 ${record.actions.updated} / 未变化 ${record.actions.unchanged}</span>
                     </div>
-                    <div class="record-details">
-                        <div class="record-ip">
-                            <span class="label">IP变化:</span>
-                            <span class="value">${record.old_ip || '无'} → ${record.new_ip}</span>
-                        </div>
-                        ${record.error_message ? `
-                        <div class="record-error">
-                            <span class="label">错误:</span>
-                            <span class="value">${record.error_message}</span>
-                        </div>
-                        ` : ''}
+                    ` : ''}
+                    ${record.verified != null ? `
+                    <div class="record-ip">
+                        <span class="label">生效确认:</span>
+                        <span class="value">${record.verified ? '✅ 公共DNS已返回新地址' : '⚠️ 公共DNS未返回新地址'}（${(record.verify_latency_ms / 1000).toFixed(1)}秒）</span>
                     </div>
+                    ` : ''}
+                    ${record.error_message ? `
+                    <div class="record-error">
+                        <span class="label">错误:</span>
+                        <span class="value">${record.error_message}</span>
+                    </div>
+                    ` : ''}
                 </div>
-            `;
+            </div>
+        `;
+    }
+
+    // 地址为 #history-<ID> 时定位到该条更新记录（Grafana通过指标示例跳转），不在最近记录中时单独获取
+    async focusHistoryEntry() {
+        const match = location.hash.match(/^#history-(\d+)$/);
+        if (!match) return;
+
+        let item = document.getElementById(`history-${match[1]}`);
+        if (!item) {
+            try {
+                const result = await (await fetch(`/api/v1/dns-update-records/${match[1]}`)).json();
+                if (!result.success) {
+                    this.showToast(result.message || '没有找到更新记录', 'error');
+                    return;
+                }
+                const list = document.querySelector('#records-content .records-list');
+                if (!list) return;
+                list.insertAdjacentHTML('afterbegin', this.renderUpdateRecord(result.data));
+                item = list.firstElementChild;
+            } catch (error) {
+                this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+                return;
+            }
+        }
+        document.querySelectorAll('.record-item.highlight').forEach(el => el.classList.remove('highlight'));
+        item.classList.add('highlight');
+        item.scrollIntoView({ behavior: 'smooth', block: 'center' });
+    }
+
+    // 调用需要 ADMIN_TOKEN 的管理接口
+    async adminFetch(url, options = {}) {
+        const token = document.getElementById('admin-token').value.trim();
+        const headers = { 'Content-Type': 'application/json', ...options.headers, 'Authorization': `Bearer ${token}` };
+        const response = await fetch(url, { ...options, headers });
+        return response.json();
+    }
+
+    async loadDnsRecords() {
+        const type = document.getElementById('dns-type-filter').value;
+        const query = type ? `?type=${encodeURIComponent(type)}` : '';
+        try {
+            const result = await this.adminFetch(`/api/v1/dns-records${query}`);
+            if (result.success) {
+                this.displayDnsRecords(result.data);
+            } else {
+                this.showToast(result.message || '获取DNS记录失败', 'error');
+            }
+        } catch (error) {
+            this.showToast('获取DNS记录失败: ' + error.message, 'error');
+        }
+    }
+
+    displayDnsRecords(records) {
+        const content = document.getElementById('dns-records-content');
+        if (records.length === 0) {
+            content.innerHTML = '<p>没有记录</p>';
+            return;
+        }
+
+        content.innerHTML = records.map(record => `
+            <div class="record-item">
+                <div class="record-header">
+                    <span class="record-time">${record.type} ${record.name}</span>
+                    <span>
+                        <button class="btn btn-secondary btn-sm" data-edit="${record.id}">✏️ 修改</button>
+                        <button class="btn btn-secondary btn-sm" data-delete="${record.id}">🗑️ 删除</button>
+                    </span>
+                </div>
+                <div class="record-details">
+                    <span class="value">${record.priority != null ? record.priority + ' ' : ''}${record.content}${record.proxied ? '（已代理）' : ''}</span>
+                </div>
+            </div>
+        `).join('');
+
+        content.querySelectorAll('[data-edit]').forEach(button => {
+            const record = records.find(r => r.id === button.dataset.edit);
+            button.addEventListener('click', () => this.editDnsRecord(record));
         });
-        
-        html += '</div>';
-        recordsContent.innerHTML = html;
+        content.querySelectorAll('[data-delete]').forEach(button => {
+            const record = records.find(r => r.id === button.dataset.delete);
+            button.addEventListener('click', () => this.deleteDnsRecord(record));
+        });
+    }
+
+    async createDnsRecord() {
+        const priority = document.getElementById('new-record-priority').value;
+        const body = {
+            type: document.getElementById('new-record-type').value,
+            name: document.getElementById('new-record-name').value.trim(),
+            content: document.getElementById('new-record-content').value.trim(),
+            proxied: document.getElementById('new-record-proxied').checked,
+            priority: priority === '' ? null : parseInt(priority),
+        };
+        const result = await this.adminFetch('/api/v1/dns-records', { method: 'POST', body: JSON.stringify(body) });
+        this.showToast(result.message || (result.success ? 'DNS记录已创建' : '创建DNS记录失败'), result.success ? 'success' : 'error');
+        if (result.success) {
+            this.loadDnsRecords();
+        }
+    }
+
+    async editDnsRecord(record) {
+        const content = prompt(`修改 ${record.type} ${record.name} 的内容`, record.content);
+        if (content === null || content === record.content) return;
+
+        const result = await this.adminFetch(`/api/v1/dns-records/${record.id}`, {
+            method: 'PUT',
+            body: JSON.stringify({ content: content.trim() }),
+        });
+        this.showToast(result.message || (result.success ? 'DNS记录已更新' : '更新DNS记录失败'), result.success ? 'success' : 'error');
+        if (result.success) {
+            this.loadDnsRecords();
+        }
+    }
+
+    async deleteDnsRecord(record) {
+        if (!confirm(`确定删除 ${record.type} ${record.name} -> ${record.content}？`)) return;
+
+        const result = await this.adminFetch(`/api/v1/dns-records/${record.id}`, { method: 'DELETE' });
+        this.showToast(result.message || (result.success ? 'DNS记录已删除' : '删除DNS记录失败'), result.success ? 'success' : 'error');
+        if (result.success) {
+            this.loadDnsRecords();
+        }
+    }
+
+    async loadApiTokens() {
+        try {
+            const result = await this.adminFetch('/api/v1/admin/tokens');
+            if (result.success) {
+                this.displayApiTokens(result.data);
+            } else {
+                this.showToast(result.message || this.t('tokens.load_failed'), 'error');
+            }
+        } catch (error) {
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+        }
+    }
+
+    displayApiTokens(tokens) {
+        const content = document.getElementById('api-tokens-content');
+        if (tokens.length === 0) {
+            content.innerHTML = `<p>${this.t('tokens.empty')}</p>`;
+            return;
+        }
+
+        const formatTime = time => time ? new Date(time).toLocaleString() : '-';
+        const now = new Date();
+        content.innerHTML = tokens.map(token => `
+            <div class="record-item">
+                <div class="record-header">
+                    <span class="record-time">${token.name}${new Date(token.expires_at) <= now ? ' ' + this.t('tokens.expired') : ''}</span>
+                    <button class="btn btn-secondary btn-sm" data-revoke="${token.id}">${this.t('tokens.revoke')}</button>
+                </div>
+                <div class="record-details">
+                    <span class="value">${token.scopes.join('<br>')}</span>
+                    <span>${this.t('tokens.expires', { time: formatTime(token.expires_at) })}</span>
+                    <span>${this.t('tokens.last_used', { time: formatTime(token.last_used_at) })}</span>
+                </div>
+            </div>
+        `).join('');
+
+        content.querySelectorAll('[data-revoke]').forEach(button => {
+            const token = tokens.find(t => t.id === button.dataset.revoke);
+            button.addEventListener('click', () => this.revokeApiToken(token));
+        });
+    }
+
+    async createApiToken() {
+        const body = {
+            name: document.getElementById('new-token-name').value.trim(),
+            scopes: document.getElementById('new-token-scopes').value.split('\n').map(s => s.trim()).filter(s => s),
+            ttl_hours: parseInt(document.getElementById('new-token-ttl').value) || 24,
+        };
+        const result = await this.adminFetch('/api/v1/admin/tokens', { method: 'POST', body: JSON.stringify(body) });
+        this.showToast(result.message || this.t('tokens.create_failed'), result.success ? 'success' : 'error');
+        if (result.success) {
+            // 令牌明文只返回这一次
+            const value = document.getElementById('new-token-value');
+            value.textContent = result.data.token;
+            value.hidden = false;
+            this.loadApiTokens();
+        }
+    }
+
+    async revokeApiToken(token) {
+        if (!confirm(this.t('tokens.revoke_confirm', { name: token.name }))) return;
+
+        const result = await this.adminFetch(`/api/v1/admin/tokens/${token.id}`, { method: 'DELETE' });
+        this.showToast(result.message || this.t('tokens.revoke_failed'), result.success ? 'success' : 'error');
+        if (result.success) {
+            this.loadApiTokens();
+        }
+    }
+
+    async loadLanHosts() {
+        try {
+            const result = await (await fetch('/api/v1/lan-hosts')).json();
+            if (result.success) {
+                this.displayLanHosts(result.data);
+            }
+        } catch (error) {
+            console.error('加载局域网主机失败:', error);
+        }
+    }
+
+    displayLanHosts(hosts) {
+        const content = document.getElementById('lan-hosts-content');
+        if (hosts.length === 0) {
+            content.innerHTML = `<p>${this.t('lan_hosts.empty')}</p>`;
+            return;
+        }
+
+        content.innerHTML = hosts.map(host => `
+            <div class="record-item">
+                <div class="record-header">
+                    <span class="record-time">${host.subdomain} → ${host.suffix}</span>
+                    <button type="button" class="btn btn-secondary btn-sm" data-delete-lan-host="${host.subdomain}">${this.t('lan_hosts.delete')}</button>
+                </div>
+                ${host.description ? `<div class="record-details"><span>${host.description}</span></div>` : ''}
+            </div>
+        `).join('');
+
+        content.querySelectorAll('[data-delete-lan-host]').forEach(button => {
+            button.addEventListener('click', () => this.deleteLanHost(button.dataset.deleteLanHost));
+        });
+    }
+
+    async saveLanHost() {
+        const name = document.getElementById('new-lan-host-name').value.trim() || '@';
+        const body = {
+            suffix: document.getElementById('new-lan-host-suffix').value.trim(),
+            description: document.getElementById('new-lan-host-description').value.trim() || null,
+        };
+        try {
+            const result = await this.adminFetch(`/api/v1/lan-hosts/${encodeURIComponent(name)}`, {
+                method: 'PUT',
+                body: JSON.stringify(body),
+            });
+            this.showToast(result.message || this.t('lan_hosts.save_failed'), result.success ? 'success' : 'error');
+            if (result.success) {
+                ['new-lan-host-name', 'new-lan-host-suffix', 'new-lan-host-description']
+                    .forEach(id => document.getElementById(id).value = '');
+                this.loadLanHosts();
+            }
+            return result.success ? result.data : null;
+        } catch (error) {
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+            return null;
+        }
+    }
+
+    async discoverLanHosts() {
+        const content = document.getElementById('lan-discovery-content');
+        content.innerHTML = `<p>${this.t('lan_hosts.discovering')}</p>`;
+        try {
+            const result = await (await fetch('/api/v1/lan-hosts/discover')).json();
+            if (result.success) {
+                this.displayDiscoveredHosts(result.data);
+            } else {
+                content.innerHTML = '';
+                this.showToast(result.message || this.t('lan_hosts.discover_failed'), 'error');
+            }
+        } catch (error) {
+            content.innerHTML = '';
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+        }
+    }
+
+    displayDiscoveredHosts(hosts) {
+        const content = document.getElementById('lan-discovery-content');
+        if (hosts.length === 0) {
+            content.innerHTML = `<p>${this.t('lan_hosts.discover_empty')}</p>`;
+            return;
+        }
+
+        content.innerHTML = hosts.map((host, index) => `
+            <div class="record-item">
+                <div class="record-header">
+                    <span class="record-time">${host.hostname || host.address}</span>
+                    ${host.subdomain
+                        ? `<span>→ ${host.subdomain}</span>`
+                        : `<button type="button" class="btn btn-secondary btn-sm" data-map-host="${index}">${this.t('lan_hosts.map')}</button>`}
+                </div>
+                <div class="record-details">
+                    <span class="value">${host.address}</span>
+                    <span>${this.t('lan_hosts.suffix', { suffix: host.suffix })}</span>
+                    <span>${host.interface}${host.mac ? ' · ' + host.mac : ''} · ${host.state}</span>
+                    ${host.eui64 ? `<span>${this.t('lan_hosts.eui64')}</span>` : ''}
+                    ${host.in_prefix ? '' : `<span>${this.t('lan_hosts.outside_prefix')}</span>`}
+                </div>
+            </div>
+        `).join('');
+
+        content.querySelectorAll('[data-map-host]').forEach(button => {
+            button.addEventListener('click', () => this.mapDiscoveredHost(hosts, hosts[button.dataset.mapHost]));
+        });
+    }
+
+    async mapDiscoveredHost(hosts, host) {
+        // 以mDNS主机名的第一段作为默认子域名
+        const suggested = (host.hostname || '').split('.')[0].toLowerCase().replace(/[^a-z0-9-]/g, '');
+        const name = prompt(this.t('lan_hosts.map_prompt', { address: host.address }), suggested);
+        if (!name || !name.trim()) return;
+
+        document.getElementById('new-lan-host-name').value = name.trim();
+        document.getElementById('new-lan-host-suffix').value = host.suffix;
+        document.getElementById('new-lan-host-description').value = host.hostname || host.mac || '';
+        const saved = await this.saveLanHost();
+        if (saved) {
+            host.subdomain = saved.subdomain;
+            this.displayDiscoveredHosts(hosts);
+        }
+    }
+
+    async deleteLanHost(name) {
+        if (!confirm(this.t('lan_hosts.delete_confirm', { name }))) return;
+
+        try {
+            const result = await this.adminFetch(`/api/v1/lan-hosts/${encodeURIComponent(name)}`, { method: 'DELETE' });
+            this.showToast(result.message || this.t('lan_hosts.delete_failed'), result.success ? 'success' : 'error');
+            if (result.success) {
+                this.loadLanHosts();
+            }
+        } catch (error) {
+            this.showToast(this.t('toast.network_error', { error: error.message }), 'error');
+        }
+    }
     }
 }
 